@@ -16,8 +16,12 @@ use std::path::PathBuf;
 /// 面板类型及其参数
 #[derive(Clone, PartialEq, Deserialize)]
 pub enum PanelKind {
-    /// 对话面板，可选 session_id
-    Conversation { session_id: Option<String> },
+    /// 对话面板，可选 session_id，以及打开后要定位到的历史消息序号
+    Conversation {
+        session_id: Option<String>,
+        #[serde(default)]
+        message_index: Option<usize>,
+    },
     /// 终端面板，可选工作目录
     Terminal {
         #[serde(skip)]
@@ -35,6 +39,16 @@ pub enum PanelKind {
         tool_call_id: String,
         tool_call: Box<ToolCall>,
     },
+    /// Agent 标准错误日志面板
+    AgentLogs,
+    /// ACP 协议流量检查面板
+    ProtocolInspector,
+    /// 待处理权限请求队列面板
+    PermissionQueue,
+    /// 权限决策审计日志面板
+    PermissionAudit,
+    /// 会话历史全文搜索面板
+    SessionSearch,
 }
 
 /// 面板操作（添加/展示）
@@ -56,7 +70,10 @@ pub struct PanelAction(pub PanelCommand);
 impl PanelAction {
     pub fn add_conversation(placement: DockPlacement) -> Self {
         Self(PanelCommand::Add {
-            panel: PanelKind::Conversation { session_id: None },
+            panel: PanelKind::Conversation {
+                session_id: None,
+                message_index: None,
+            },
             placement,
         })
     }
@@ -65,6 +82,7 @@ impl PanelAction {
         Self(PanelCommand::Add {
             panel: PanelKind::Conversation {
                 session_id: Some(session_id),
+                message_index: None,
             },
             placement,
         })
@@ -96,7 +114,38 @@ impl PanelAction {
     }
 
     pub fn show_conversation(session_id: Option<String>) -> Self {
-        Self(PanelCommand::Show(PanelKind::Conversation { session_id }))
+        Self(PanelCommand::Show(PanelKind::Conversation {
+            session_id,
+            message_index: None,
+        }))
+    }
+
+    /// 打开会话并滚动到第 message_index 条历史消息
+    pub fn show_conversation_message(session_id: String, message_index: usize) -> Self {
+        Self(PanelCommand::Show(PanelKind::Conversation {
+            session_id: Some(session_id),
+            message_index: Some(message_index),
+        }))
+    }
+
+    pub fn show_agent_logs() -> Self {
+        Self(PanelCommand::Show(PanelKind::AgentLogs))
+    }
+
+    pub fn show_protocol_inspector() -> Self {
+        Self(PanelCommand::Show(PanelKind::ProtocolInspector))
+    }
+
+    pub fn show_permission_queue() -> Self {
+        Self(PanelCommand::Show(PanelKind::PermissionQueue))
+    }
+
+    pub fn show_permission_audit() -> Self {
+        Self(PanelCommand::Show(PanelKind::PermissionAudit))
+    }
+
+    pub fn show_session_search() -> Self {
+        Self(PanelCommand::Show(PanelKind::SessionSearch))
     }
 
     pub fn show_tool_call_detail(tool_call_id: String, tool_call: ToolCall) -> Self {
@@ -232,6 +281,21 @@ pub struct CancelSession {
     /// 会话唯一标识符
     pub session_id: String,
 }
+
+/// 从指定消息处分叉会话
+///
+/// 由 ConversationPanel 中用户消息和 Agent 消息的分叉按钮触发，
+/// 新会话使用相同的 Agent 和工作区，实际的分叉逻辑在 workspace/actions.rs 中实现
+#[derive(Action, Clone, Debug, PartialEq, Deserialize)]
+#[action(namespace = agentx, no_json)]
+pub struct ForkSession {
+    /// 被分叉的会话唯一标识符
+    pub session_id: String,
+    /// 开始计数的会话文件位置（面板中最早加载的历史消息）
+    pub history_start: usize,
+    /// 从 history_start 起算的对话消息数量，分叉包含第 message_count 条消息
+    pub message_count: usize,
+}
 /// 显示会话对话面板
 ///
 #[derive(Action, Clone, PartialEq, Deserialize)]
@@ -343,6 +407,9 @@ pub struct AddAgent {
     pub args: Vec<String>,
     /// Environment variables / 环境变量
     pub env: std::collections::HashMap<String, String>,
+    /// Connection to the agent / 连接方式
+    #[serde(default)]
+    pub transport: crate::core::config::AgentTransport,
 }
 
 /// 更新现有 Agent 的配置
@@ -359,6 +426,9 @@ pub struct UpdateAgent {
     pub args: Vec<String>,
     /// Environment variables / 环境变量
     pub env: std::collections::HashMap<String, String>,
+    /// Connection to the agent / 连接方式
+    #[serde(default)]
+    pub transport: crate::core::config::AgentTransport,
 }
 
 /// 移除 Agent
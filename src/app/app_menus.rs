@@ -3,7 +3,7 @@ use gpui_component::{ThemeMode, ThemeRegistry};
 use rust_i18n::t;
 
 use crate::{
-    About, CloseWindow, Open, Quit, SelectLocale, ToggleSearch,
+    About, CloseWindow, Open, PanelAction, Quit, SelectLocale, ToggleSearch,
     app::actions::{SwitchTheme, SwitchThemeMode},
 };
 
@@ -87,6 +87,26 @@ pub fn init(title: impl Into<SharedString>, cx: &mut App) {
                 MenuItem::action(t!("menu.window.close").to_string(), CloseWindow),
                 MenuItem::separator(),
                 MenuItem::action(t!("menu.window.toggle_search").to_string(), ToggleSearch),
+                MenuItem::action(
+                    t!("menu.window.agent_logs").to_string(),
+                    PanelAction::show_agent_logs(),
+                ),
+                MenuItem::action(
+                    t!("menu.window.protocol_inspector").to_string(),
+                    PanelAction::show_protocol_inspector(),
+                ),
+                MenuItem::action(
+                    t!("menu.window.permission_queue").to_string(),
+                    PanelAction::show_permission_queue(),
+                ),
+                MenuItem::action(
+                    t!("menu.window.permission_audit").to_string(),
+                    PanelAction::show_permission_audit(),
+                ),
+                MenuItem::action(
+                    t!("menu.window.session_search").to_string(),
+                    PanelAction::show_session_search(),
+                ),
             ],
         },
         Menu {
@@ -3,15 +3,21 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::{
-    core::agent::{AgentManager, PermissionStore},
+    core::agent::{
+        AgentLogStore, AgentManager, ExtensionRegistry, OpenBufferRegistry, PermissionAuditLog,
+        PermissionRuleStore, PermissionStore, ProtocolTraceStore, TerminalRegistry,
+        register_builtin_handlers,
+    },
     core::config::DEFAULT_TOOL_CALL_PREVIEW_MAX_LINES,
     core::event_bus::{
-        AgentConfigBusContainer, CodeSelectionBusContainer, PermissionBusContainer,
-        SessionUpdateBusContainer, WorkspaceUpdateBusContainer,
+        AgentConfigBusContainer, AgentConfigEvent, AgentLogBusContainer, CodeSelectionBusContainer,
+        ExtensionBusContainer, FileChangeBusContainer, PermissionBusContainer,
+        PermissionStatusEvent, ProtocolTraceBusContainer, SessionUpdateBusContainer,
+        TerminalBusContainer, WorkspaceUpdateBusContainer,
     },
     core::services::{
-        AgentConfigService, AgentService, AiService, MessageService, PersistenceService,
-        WorkspaceService,
+        AgentConfigService, AgentService, AiService, MessageService, PROVISIONAL_FLUSH_INTERVAL,
+        PersistenceService, WorkspaceService,
     },
 };
 
@@ -28,9 +34,29 @@ pub struct AppState {
     permission_store: Option<Arc<PermissionStore>>,
     pub session_bus: SessionUpdateBusContainer,
     pub permission_bus: PermissionBusContainer,
+    /// Rules answering permission requests without asking
+    pub permission_rules: PermissionRuleStore,
+    /// Record of answered permission requests, per workspace
+    pub permission_audit: PermissionAuditLog,
     pub workspace_bus: WorkspaceUpdateBusContainer,
     pub code_selection_bus: CodeSelectionBusContainer,
     pub agent_config_bus: AgentConfigBusContainer,
+    pub file_change_bus: FileChangeBusContainer,
+    /// Editor buffers shared with agents for `fs/read_text_file`
+    pub open_buffers: OpenBufferRegistry,
+    pub terminal_bus: TerminalBusContainer,
+    /// Output of terminals created by agents, rendered in tool calls
+    pub terminals: TerminalRegistry,
+    pub agent_log_bus: AgentLogBusContainer,
+    /// Captured stderr of agent processes, shown in the Agent Logs panel
+    pub agent_logs: AgentLogStore,
+    pub protocol_trace_bus: ProtocolTraceBusContainer,
+    /// Recorded ACP traffic, shown in the Protocol Inspector panel
+    pub protocol_trace: ProtocolTraceStore,
+    /// ACP extensions no handler claimed
+    pub extension_bus: ExtensionBusContainer,
+    /// Handlers for ACP extensions, handed to the agent manager
+    pub extensions: ExtensionRegistry,
     /// Current welcome session - created when user selects an agent
     welcome_session: Option<WelcomeSession>,
     /// Service layer
@@ -60,12 +86,55 @@ impl AppState {
         // Create workspace bus
         let workspace_bus = WorkspaceUpdateBusContainer::new();
 
+        let sessions_dir = crate::core::config_manager::get_sessions_dir();
+        let persistence_service = Arc::new(PersistenceService::new(sessions_dir));
+        {
+            let persistence_service = persistence_service.clone();
+            smol::spawn(async move {
+                // Repair sessions left with unflushed state before indexing them
+                if let Err(e) = persistence_service.recover_sessions().await {
+                    log::error!("Failed to recover persisted sessions: {}", e);
+                }
+                if let Err(e) = persistence_service.rebuild_index().await {
+                    log::error!("Failed to index persisted sessions: {}", e);
+                }
+            })
+            .detach();
+        }
+        {
+            // Read the usage ledger in the background so rendering never waits on it
+            let usage_ledger = persistence_service.usage().clone();
+            smol::unblock(move || usage_ledger.load()).detach();
+        }
+        {
+            let persistence_service = persistence_service.clone();
+            smol::spawn(async move {
+                loop {
+                    smol::Timer::after(PROVISIONAL_FLUSH_INTERVAL).await;
+                    if let Err(e) = persistence_service.flush_provisional().await {
+                        log::warn!("Failed to write provisional session records: {}", e);
+                    }
+                }
+            })
+            .detach();
+        }
+
         // Create workspace service and set its bus
         let mut workspace_service = WorkspaceService::new(config_path);
         workspace_service.set_workspace_bus(workspace_bus.clone());
+        workspace_service.set_session_index(persistence_service.index().clone());
         let workspace_service = Arc::new(workspace_service);
-        let sessions_dir = crate::core::config_manager::get_sessions_dir();
-        let persistence_service = Arc::new(PersistenceService::new(sessions_dir));
+        let agent_log_bus = AgentLogBusContainer::new();
+        let agent_logs = AgentLogStore::new(agent_log_bus.clone());
+        let protocol_trace_bus = ProtocolTraceBusContainer::new();
+        let protocol_trace = ProtocolTraceStore::new(protocol_trace_bus.clone());
+        let extension_bus = ExtensionBusContainer::new();
+        let extensions = ExtensionRegistry::new();
+        register_builtin_handlers(&extensions, &extension_bus, &workspace_bus, &protocol_trace);
+        let permission_rules =
+            PermissionRuleStore::load(crate::core::config_manager::get_permission_rules_path());
+        let permission_audit =
+            PermissionAuditLog::new(crate::core::config_manager::get_permission_audit_dir());
 
         let state = Self {
             invisible_panels: cx.new(|_| Vec::new()),
@@ -73,11 +142,23 @@ impl AppState {
             permission_store: None,
             session_bus: SessionUpdateBusContainer::new(),
             permission_bus: PermissionBusContainer::new(),
+            permission_rules,
+            permission_audit,
             workspace_bus,
             code_selection_bus: Arc::new(std::sync::Mutex::new(
                 crate::core::event_bus::code_selection_bus::CodeSelectionBus::new(),
             )),
             agent_config_bus: AgentConfigBusContainer::new(),
+            file_change_bus: FileChangeBusContainer::new(),
+            open_buffers: OpenBufferRegistry::new(),
+            terminal_bus: TerminalBusContainer::new(),
+            terminals: TerminalRegistry::new(),
+            agent_log_bus,
+            agent_logs,
+            protocol_trace_bus,
+            protocol_trace,
+            extension_bus,
+            extensions,
             welcome_session: None,
             agent_service: None,
             message_service: None,
@@ -120,7 +201,44 @@ impl AppState {
         // Initialize services when agent_manager is set
         let mut agent_service = AgentService::new(manager.clone());
         agent_service.set_workspace_bus(self.workspace_bus.clone());
+        agent_service.set_session_bus(self.session_bus.clone());
+        agent_service.set_session_stats(persistence_service.stats().clone());
+        agent_service.set_usage_ledger(persistence_service.usage().clone());
+        agent_service.set_terminals(self.terminals.clone());
         let agent_service = Arc::new(agent_service);
+        agent_service.watch_agent_crashes();
+
+        // Price token usage with the configured models, following model edits
+        let usage_ledger = persistence_service.usage().clone();
+        usage_ledger.set_models(initial_config.models.clone());
+        self.agent_config_bus.subscribe_model_events({
+            let usage_ledger = usage_ledger.clone();
+            move |event| match event {
+                AgentConfigEvent::ModelAdded { name, config }
+                | AgentConfigEvent::ModelUpdated { name, config } => {
+                    usage_ledger.set_model(name, config.clone());
+                }
+                AgentConfigEvent::ModelRemoved { name } => usage_ledger.remove_model(name),
+                _ => {}
+            }
+        });
+
+        // Time how long permission requests wait for an answer
+        let stats = persistence_service.stats().clone();
+        self.permission_bus.subscribe(move |event| {
+            stats.record_permission_requested(&event.session_id, &event.permission_id);
+        });
+        let stats = persistence_service.stats().clone();
+        self.permission_bus.subscribe_status(move |event| {
+            if let PermissionStatusEvent::Resolved {
+                permission_id,
+                session_id,
+                ..
+            } = event
+            {
+                stats.record_permission_resolved(session_id, permission_id);
+            }
+        });
 
         let message_service = Arc::new(MessageService::new(
             self.session_bus.clone(),
@@ -128,6 +246,7 @@ impl AppState {
             persistence_service,
             self.workspace_bus.clone(),
         ));
+        message_service.watch_agent_restarts();
 
         // Initialize AgentConfigService if config_path is set
         let agent_config_service = if let Some(config_path) = &self.config_path {
@@ -150,10 +269,12 @@ impl AppState {
                 "Initializing AI Service with {} models",
                 initial_config.models.len()
             );
-            Some(Arc::new(AiService::new(
+            let mut ai_service = AiService::new(
                 initial_config.models.clone(),
                 initial_config.system_prompts.clone(),
-            )))
+            );
+            ai_service.set_usage_ledger(usage_ledger);
+            Some(Arc::new(ai_service))
         } else {
             log::warn!("No AI models configured in config.json");
             None
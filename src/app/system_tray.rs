@@ -7,6 +7,7 @@ use tray_icon::{
 
 // 定义菜单项ID常量
 const MENU_SHOW_ID: &str = "show_window";
+const MENU_PERMISSIONS_ID: &str = "pending_permissions";
 const MENU_QUIT_ID: &str = "quit_app";
 
 // 定义唯一的托盘图标 ID，避免与其他应用冲突
@@ -31,6 +32,8 @@ pub fn init_platform() -> Result<()> {
 pub struct SystemTray {
     tray_icon: TrayIcon,
     show_menu_id: MenuId,
+    permissions_menu_id: MenuId,
+    permissions_item: MenuItem,
     quit_menu_id: MenuId,
 }
 
@@ -45,16 +48,27 @@ impl SystemTray {
 
         // 创建菜单项ID
         let show_menu_id = MenuId::new(MENU_SHOW_ID);
+        let permissions_menu_id = MenuId::new(MENU_PERMISSIONS_ID);
         let quit_menu_id = MenuId::new(MENU_QUIT_ID);
 
         // 添加显示/隐藏窗口菜单项
         let show_item = MenuItem::with_id(show_menu_id.clone(), "显示主窗口", true, None);
+        // 待处理权限请求，点击打开权限队列面板
+        let permissions_item = MenuItem::with_id(
+            permissions_menu_id.clone(),
+            pending_permissions_label(0),
+            true,
+            None,
+        );
         let separator = PredefinedMenuItem::separator();
         let quit_item = MenuItem::with_id(quit_menu_id.clone(), "退出", true, None);
 
         tray_menu
             .append(&show_item)
             .context("Failed to append show item")?;
+        tray_menu
+            .append(&permissions_item)
+            .context("Failed to append permissions item")?;
         tray_menu
             .append(&separator)
             .context("Failed to append separator")?;
@@ -80,9 +94,34 @@ impl SystemTray {
         Ok(Self {
             tray_icon,
             show_menu_id,
+            permissions_menu_id,
+            permissions_item,
             quit_menu_id,
         })
     }
+
+    /// 在托盘图标上显示待处理权限请求的数量
+    ///
+    /// 标题仅在 macOS 上显示在图标旁边，其他平台通过提示文字和菜单项体现。
+    pub fn set_pending_permissions(&self, count: usize) {
+        let badge = (count > 0).then(|| count.to_string());
+        self.tray_icon.set_title(badge.as_deref());
+
+        let tooltip = if count > 0 {
+            format!("AgentX Studio - {} 个待处理权限请求", count)
+        } else {
+            "AgentX Studio".to_string()
+        };
+        if let Err(e) = self.tray_icon.set_tooltip(Some(tooltip)) {
+            log::warn!("Failed to update tray tooltip: {}", e);
+        }
+        self.permissions_item
+            .set_text(pending_permissions_label(count));
+    }
+}
+
+fn pending_permissions_label(count: usize) -> String {
+    format!("待处理权限 ({})", count)
 }
 
 /// 托盘事件类型
@@ -90,6 +129,8 @@ impl SystemTray {
 pub enum TrayEvent {
     /// 显示窗口
     Show,
+    /// 显示窗口并打开权限队列面板
+    ShowPermissions,
     /// 退出应用
     Quit,
 }
@@ -124,11 +165,13 @@ pub fn setup_tray_event_handler(tray: SystemTray, cx: &mut gpui::App) {
 
     // 提取菜单ID用于后台线程
     let show_menu_id = tray.show_menu_id.clone();
+    let permissions_menu_id = tray.permissions_menu_id.clone();
     let quit_menu_id = tray.quit_menu_id.clone();
 
     // 将 SystemTray 存储为 static，保持托盘图标的生命周期
     // 这样托盘图标就不会被销毁
-    let _tray = Box::leak(Box::new(tray));
+    let tray: &'static SystemTray = Box::leak(Box::new(tray));
+    watch_pending_permissions(tray, cx);
 
     // 创建通道用于跨线程通信
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<TrayEvent>();
@@ -177,6 +220,8 @@ pub fn setup_tray_event_handler(tray: SystemTray, cx: &mut gpui::App) {
                 // 根据菜单ID判断操作
                 let tray_event = if menu_id == &show_menu_id {
                     Some(TrayEvent::Show)
+                } else if menu_id == &permissions_menu_id {
+                    Some(TrayEvent::ShowPermissions)
                 } else if menu_id == &quit_menu_id {
                     Some(TrayEvent::Quit)
                 } else {
@@ -220,6 +265,20 @@ pub fn setup_tray_event_handler(tray: SystemTray, cx: &mut gpui::App) {
                         }
                     });
                 }
+                TrayEvent::ShowPermissions => {
+                    log::info!("Tray event: Show pending permissions");
+                    let _ = cx.update(|cx| {
+                        if let Some(window) = cx.windows().first() {
+                            let _ = window.update(cx, |_, window, cx| {
+                                window.activate_window();
+                                window.dispatch_action(
+                                    Box::new(crate::PanelAction::show_permission_queue()),
+                                    cx,
+                                );
+                            });
+                        }
+                    });
+                }
                 TrayEvent::Quit => {
                     // 退出应用
                     log::info!("Tray event: Quit application");
@@ -235,3 +294,45 @@ pub fn setup_tray_event_handler(tray: SystemTray, cx: &mut gpui::App) {
     })
     .detach();
 }
+
+/// 根据权限事件总线维护待处理权限请求数量，并更新托盘徽标
+fn watch_pending_permissions(tray: &'static SystemTray, cx: &mut gpui::App) {
+    use crate::core::event_bus::PermissionStatusEvent;
+    use gpui::AppContext;
+
+    /// 权限请求的新增或结束
+    enum PendingChange {
+        Added(String),
+        Removed(String),
+    }
+
+    let permission_bus = crate::AppState::global(cx).permission_bus.clone();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<PendingChange>();
+
+    let request_tx = tx.clone();
+    permission_bus.subscribe(move |event| {
+        let _ = request_tx.send(PendingChange::Added(event.permission_id.clone()));
+    });
+    permission_bus.subscribe_status(move |event| {
+        if let PermissionStatusEvent::Resolved { permission_id, .. } = event {
+            let _ = tx.send(PendingChange::Removed(permission_id.clone()));
+        }
+    });
+
+    // 托盘图标只能在主线程上更新
+    cx.spawn(async move |_cx| {
+        let mut pending = std::collections::HashSet::new();
+        while let Some(change) = rx.recv().await {
+            match change {
+                PendingChange::Added(id) => {
+                    pending.insert(id);
+                }
+                PendingChange::Removed(id) => {
+                    pending.remove(&id);
+                }
+            }
+            tray.set_pending_permissions(pending.len());
+        }
+    })
+    .detach();
+}
@@ -62,6 +62,7 @@ pub enum Icon {
     MoveRight,
     TextWrap,
     ArrowRightToLine,
+    GitFork,
 }
 
 impl IconNamed for Icon {
@@ -93,6 +94,7 @@ impl IconNamed for Icon {
             Icon::MoveRight => "icons2/move-right.svg",
             Icon::TextWrap => "icons2/text-wrap.svg",
             Icon::ArrowRightToLine => "icons2/arrow-right-to-line.svg",
+            Icon::GitFork => "icons2/git-fork.svg",
         }
         .into()
     }
@@ -5,7 +5,7 @@ use gpui::{
 use std::{rc::Rc, sync::Arc};
 
 use gpui_component::{
-    ActiveTheme, Disableable, Icon, IconName, Sizable,
+    ActiveTheme, Disableable, Icon, IconName, Selectable, Sizable,
     button::{Button, ButtonCustomVariant, ButtonVariants},
     h_flex,
     input::{Input, InputState},
@@ -15,6 +15,7 @@ use gpui_component::{
 };
 
 use agent_client_protocol::{AvailableCommand, ImageContent};
+use rust_i18n::t;
 
 use crate::app::actions::AddCodeSelection;
 use crate::components::{
@@ -104,6 +105,10 @@ pub struct ChatInputBox {
     selected_mcps: Vec<String>,
     /// Callback when MCP checkbox is clicked (passes (name, checked) tuple)
     on_mcp_toggle: Option<Rc<dyn Fn(&(String, bool), &mut Window, &mut App) + 'static>>,
+    /// Autopilot state of the session's task; `None` hides the toggle
+    autopilot: Option<bool>,
+    /// Callback when autopilot is toggled (passes the new state)
+    on_autopilot_toggle: Option<Rc<dyn Fn(&bool, &mut Window, &mut App) + 'static>>,
 }
 
 impl ChatInputBox {
@@ -135,6 +140,8 @@ impl ChatInputBox {
             available_mcps: Vec::new(),
             selected_mcps: Vec::new(),
             on_mcp_toggle: None,
+            autopilot: None,
+            on_autopilot_toggle: None,
         }
     }
 
@@ -302,6 +309,21 @@ impl ChatInputBox {
         self.on_mcp_toggle = Some(Rc::new(callback));
         self
     }
+
+    /// Show the autopilot toggle with the task's current state
+    pub fn autopilot(mut self, enabled: bool) -> Self {
+        self.autopilot = Some(enabled);
+        self
+    }
+
+    /// Set a callback for when autopilot is toggled
+    pub fn on_autopilot_toggle<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&bool, &mut Window, &mut App) + 'static,
+    {
+        self.on_autopilot_toggle = Some(Rc::new(callback));
+        self
+    }
 }
 
 impl RenderOnce for ChatInputBox {
@@ -731,9 +753,18 @@ impl RenderOnce for ChatInputBox {
 
                                                         content = content.child(
                                                             Checkbox::new(("mcp-cb", idx))
-                                                                .label(name.clone())
+                                                                .label(match config {
+                                                                    McpServerConfig::Stdio {
+                                                                        ..
+                                                                    } => name.clone(),
+                                                                    _ => format!(
+                                                                        "{} ({})",
+                                                                        name,
+                                                                        config.kind()
+                                                                    ),
+                                                                })
                                                                 .checked(is_selected)
-                                                                .disabled(!config.enabled)
+                                                                .disabled(!config.is_enabled())
                                                                 .on_click(
                                                                     move |checked, window, cx| {
                                                                         if let Some(cb) = &callback
@@ -756,6 +787,30 @@ impl RenderOnce for ChatInputBox {
 
                                                 content
                                             })
+                                    })
+                                    .when_some(self.autopilot, |this, enabled| {
+                                        let on_toggle = self.on_autopilot_toggle.clone();
+                                        this.child(
+                                            Button::new("autopilot")
+                                                .label(t!("chat_input.autopilot.label").to_string())
+                                                .icon(Icon::new(IconName::Bot))
+                                                .ghost()
+                                                .small()
+                                                .selected(enabled)
+                                                .tooltip(
+                                                    if enabled {
+                                                        t!("chat_input.autopilot.tooltip.on")
+                                                    } else {
+                                                        t!("chat_input.autopilot.tooltip.off")
+                                                    }
+                                                    .to_string(),
+                                                )
+                                                .on_click(move |_, window, cx| {
+                                                    if let Some(callback) = &on_toggle {
+                                                        callback(&!enabled, window, cx);
+                                                    }
+                                                }),
+                                        )
                                     }),
                             )
                             .child({
@@ -9,6 +9,7 @@ mod file_picker;
 mod input_suggestion;
 mod permission_request;
 mod select_items;
+mod session_export_button;
 mod status_indicator;
 // mod task_list_item;
 mod tool_call_item;
@@ -30,12 +31,16 @@ pub use input_suggestion::{InputSuggestion, InputSuggestionItem, InputSuggestion
 
 pub use file_picker::{FileItem, FilePickerDelegate};
 
-pub use tool_call_item::{ToolCallItem, ToolCallItemView};
+pub use tool_call_item::{ToolCallItem, ToolCallItemView, terminal_status_text};
 
 pub use user_message::{UserMessage, UserMessageData, UserMessageView};
 
-pub use permission_request::{PermissionRequest, PermissionRequestView};
+pub use permission_request::{
+    PermissionRequest, PermissionRequestView, permission_is_allow, permission_option_kind_to_icon,
+};
 
 pub use select_items::{ModeSelectItem, ModelSelectItem};
 
+pub use session_export_button::{SessionExportButton, export_session};
+
 pub use status_indicator::StatusIndicator;
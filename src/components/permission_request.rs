@@ -41,6 +41,8 @@ pub struct PermissionRequest {
     tool_kind: Option<String>,
     /// Available permission options
     options: Vec<PermissionOption>,
+    /// Why autopilot asked instead of approving
+    escalation: Option<String>,
     /// Whether the request has been responded to
     responded: bool,
 }
@@ -65,10 +67,30 @@ impl PermissionRequest {
             tool_title,
             tool_kind,
             options: options.into_iter().collect(),
+            escalation: None,
             responded: false,
         }
     }
 
+    /// Set the reason autopilot escalated this request to the user
+    pub fn escalation(mut self, reason: Option<String>) -> Self {
+        self.escalation = reason;
+        self
+    }
+
+    pub fn permission_id(&self) -> &str {
+        &self.permission_id
+    }
+
+    /// Show the request as answered, e.g. after it was resolved from the
+    /// permissions queue, timed out or was cancelled
+    pub fn mark_responded(&mut self, cx: &mut Context<Self>) {
+        if !self.responded {
+            self.responded = true;
+            cx.notify();
+        }
+    }
+
     /// Handle user selection of a permission option
     fn on_option_selected(
         &mut self,
@@ -182,6 +204,14 @@ impl Render for PermissionRequest {
                                 .text_color(cx.theme().muted_foreground)
                                 .child(format!("Kind: {}", kind)),
                         )
+                    })
+                    .when_some(self.escalation.as_ref(), |this, reason| {
+                        this.child(
+                            div()
+                                .text_size(px(12.))
+                                .text_color(cx.theme().warning)
+                                .child(format!("Autopilot paused: {}", reason)),
+                        )
                     }),
             )
             .when(!responded, |this| {
@@ -0,0 +1,154 @@
+use gpui::{App, ElementId, IntoElement, RenderOnce, Window};
+use std::rc::Rc;
+
+use gpui_component::{
+    IconName, Sizable,
+    button::{Button, ButtonVariants},
+    menu::{DropdownMenu as _, PopupMenuItem},
+};
+
+use crate::AppState;
+use crate::core::services::{SessionExportFormat, SessionExportOptions};
+
+/// Dropdown that exports a persisted session as Markdown, HTML or JSON
+#[derive(IntoElement)]
+pub struct SessionExportButton {
+    id: ElementId,
+    session_id: String,
+    options: SessionExportOptions,
+    /// Callback when an export option is toggled (passes the new options)
+    on_options_change: Option<Rc<dyn Fn(&SessionExportOptions, &mut Window, &mut App) + 'static>>,
+}
+
+impl SessionExportButton {
+    pub fn new(id: impl Into<ElementId>, session_id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            session_id: session_id.into(),
+            options: SessionExportOptions::default(),
+            on_options_change: None,
+        }
+    }
+
+    /// Current redaction and thought options
+    pub fn options(mut self, options: SessionExportOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set a callback for when an export option is toggled
+    pub fn on_options_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&SessionExportOptions, &mut Window, &mut App) + 'static,
+    {
+        self.on_options_change = Some(Rc::new(callback));
+        self
+    }
+}
+
+impl RenderOnce for SessionExportButton {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let session_id = self.session_id;
+        let options = self.options;
+        let on_options_change = self.on_options_change;
+
+        Button::new(self.id)
+            .icon(IconName::File)
+            .ghost()
+            .xsmall()
+            .tooltip("Export conversation")
+            .dropdown_menu(move |mut menu, _, _| {
+                for format in SessionExportFormat::ALL {
+                    let session_id = session_id.clone();
+                    menu = menu.item(
+                        PopupMenuItem::new(format!("Export as {}", format.label())).on_click(
+                            move |_, _, cx| {
+                                export_session(
+                                    session_id.clone(),
+                                    SessionExportOptions { format, ..options },
+                                    cx,
+                                );
+                            },
+                        ),
+                    );
+                }
+
+                let toggle = |label: &str, enabled: bool, toggled: SessionExportOptions| {
+                    let on_options_change = on_options_change.clone();
+                    let mut item =
+                        PopupMenuItem::new(label.to_string()).on_click(move |_, window, cx| {
+                            if let Some(callback) = &on_options_change {
+                                callback(&toggled, window, cx);
+                            }
+                        });
+                    if enabled {
+                        item = item.icon(IconName::Check);
+                    }
+                    item
+                };
+                menu.separator()
+                    .item(toggle(
+                        "Redact secrets",
+                        options.redact_secrets,
+                        SessionExportOptions {
+                            redact_secrets: !options.redact_secrets,
+                            ..options
+                        },
+                    ))
+                    .item(toggle(
+                        "Include thoughts",
+                        options.include_thoughts,
+                        SessionExportOptions {
+                            include_thoughts: !options.include_thoughts,
+                            ..options
+                        },
+                    ))
+            })
+    }
+}
+
+/// Render a session in the background and save it where the user picks
+pub fn export_session(session_id: String, options: SessionExportOptions, cx: &mut App) {
+    let app_state = AppState::global(cx);
+    let Some(persistence_service) = app_state.persistence_service().cloned() else {
+        log::error!("PersistenceService not initialized, cannot export session");
+        return;
+    };
+    let terminals = app_state.terminals.clone();
+
+    cx.spawn(async move |_cx| {
+        let contents = match persistence_service
+            .export_session(&session_id, &options, Some(&terminals))
+            .await
+        {
+            Ok(contents) => contents,
+            Err(e) => {
+                log::error!("Failed to export session {}: {}", session_id, e);
+                return;
+            }
+        };
+
+        let extension = options.format.extension();
+        let short_id: String = session_id.chars().take(8).collect();
+        let file = rfd::AsyncFileDialog::new()
+            .set_title("Export conversation")
+            .add_filter(options.format.label(), &[extension])
+            .set_file_name(format!("session-{}.{}", short_id, extension))
+            .save_file()
+            .await;
+        let Some(file) = file else {
+            return;
+        };
+        let path = file.path().to_path_buf();
+        match smol::unblock({
+            let path = path.clone();
+            move || std::fs::write(path, contents)
+        })
+        .await
+        {
+            Ok(()) => log::info!("Exported session {} to {}", session_id, path.display()),
+            Err(e) => log::error!("Failed to write {}: {}", path.display(), e),
+        }
+    })
+    .detach();
+}
@@ -39,6 +39,11 @@ impl StatusIndicator {
             SessionStatus::Completed => gpui::rgb(0x22c55e).into(),
             SessionStatus::Failed => gpui::rgb(0xef4444).into(),
             SessionStatus::Closed => gpui::rgb(0x6b7280).into(),
+            SessionStatus::Refused => gpui::rgb(0xef4444).into(),
+            SessionStatus::MaxTokens => gpui::rgb(0xf59e0b).into(),
+            SessionStatus::MaxTurnRequests => gpui::rgb(0xf59e0b).into(),
+            SessionStatus::Cancelled => gpui::rgb(0x6b7280).into(),
+            SessionStatus::TimedOut => gpui::rgb(0xef4444).into(),
         }
     }
 
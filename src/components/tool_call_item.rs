@@ -17,8 +17,12 @@ use similar::{ChangeTag, TextDiff};
 
 use crate::PanelAction;
 use crate::components::DiffView;
+use crate::core::agent::TerminalSnapshot;
 use crate::panels::conversation::types::{ToolCallStatusExt, ToolKindExt};
-use crate::utils::tool_call::{extract_terminal_output, extract_xml_content, truncate_lines};
+use crate::utils::tool_call::{
+    extract_terminal_output, extract_xml_content, tail_lines, tool_call_has_terminal,
+    truncate_lines,
+};
 
 /// Diff statistics
 #[derive(Debug, Clone, Default)]
@@ -67,6 +71,23 @@ fn extract_diff_stats_from_tool_call(tool_call: &ToolCall) -> Option<DiffStats>
     None
 }
 
+/// Short status line for an agent terminal
+pub fn terminal_status_text(snapshot: &TerminalSnapshot) -> String {
+    let status = match &snapshot.exit {
+        None => "Running".to_string(),
+        Some(exit) => match (exit.exit_code, &exit.signal) {
+            (Some(code), _) => format!("Exited with code {}", code),
+            (None, Some(signal)) => format!("Killed by {}", signal),
+            (None, None) => "Exited".to_string(),
+        },
+    };
+    if snapshot.truncated {
+        format!("{} (output truncated)", status)
+    } else {
+        status
+    }
+}
+
 /// Tool call item component based on ACP's ToolCall - stateful version
 pub struct ToolCallItem {
     tool_call: ToolCall,
@@ -149,6 +170,11 @@ impl ToolCallItem {
         !self.tool_call.content.is_empty()
     }
 
+    /// Whether this tool call shows the given agent terminal
+    pub fn has_terminal(&self, terminal_id: &str) -> bool {
+        tool_call_has_terminal(&self.tool_call, terminal_id)
+    }
+
     /// Get formatted display title for the tool call
     /// For Read tools, formats as: filename#L<offset>-<offset+limit>
     /// For other tools, returns the original title
@@ -221,25 +247,48 @@ impl ToolCallItem {
             },
             ToolCallContent::Terminal(terminal) => {
                 let max_lines = crate::AppState::global(cx).tool_call_preview_max_lines();
-                let output = extract_terminal_output(terminal).and_then(|text| {
-                    if text.trim().is_empty() {
-                        None
-                    } else {
-                        Some(text)
-                    }
-                });
-                let display_text = match output {
-                    Some(text) => {
-                        let truncated = truncate_lines(&text, max_lines);
-                        format!("Terminal: {}\n{}", terminal.terminal_id, truncated)
-                    }
+                let terminal_id = terminal.terminal_id.to_string();
+                let snapshot = crate::AppState::global(cx).terminals.snapshot(&terminal_id);
+
+                // Prefer live output from our own terminals, fall back to agent-provided meta
+                let output = snapshot
+                    .as_ref()
+                    .map(|snapshot| snapshot.output.clone())
+                    .or_else(|| extract_terminal_output(terminal))
+                    .and_then(|text| {
+                        if text.trim().is_empty() {
+                            None
+                        } else {
+                            Some(text)
+                        }
+                    });
+                let header = match &snapshot {
+                    Some(snapshot) => format!("$ {}", snapshot.command),
                     None => format!("Terminal: {}", terminal.terminal_id),
                 };
-                div()
-                    .text_size(px(12.))
-                    .text_color(cx.theme().muted_foreground)
-                    .line_height(px(18.))
-                    .child(display_text)
+                let display_text = match output {
+                    Some(text) => format!("{}\n{}", header, tail_lines(&text, max_lines)),
+                    None => header,
+                };
+                let status_text = snapshot.as_ref().map(terminal_status_text);
+
+                v_flex()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_size(px(12.))
+                            .text_color(cx.theme().muted_foreground)
+                            .line_height(px(18.))
+                            .child(display_text),
+                    )
+                    .when_some(status_text, |this, status| {
+                        this.child(
+                            div()
+                                .text_size(px(11.))
+                                .text_color(cx.theme().muted_foreground)
+                                .child(status),
+                        )
+                    })
                     .into_any_element()
             }
             _ => div()
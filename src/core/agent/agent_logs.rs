@@ -0,0 +1,300 @@
+//! Capture of agent process stderr.
+//!
+//! [`AgentLogStore`] keeps the most recent stderr lines of every agent in a
+//! bounded ring buffer and can mirror them to size-rotated files under the
+//! user data directory.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::core::config::AgentLogConfig;
+use crate::core::event_bus::{AgentLogBusContainer, AgentLogEvent};
+
+/// Lines kept per agent until a config is applied
+pub const DEFAULT_LOG_BUFFER_LINES: usize = 2000;
+
+/// Lines shown when reporting an initialization failure
+pub const STDERR_TAIL_LINES: usize = 10;
+
+/// One line written by an agent to stderr
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgentLogLine {
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+}
+
+/// Shared stderr buffers of all agents.
+///
+/// Agent workers push lines from their own threads; the UI reads snapshots
+/// and follows new lines through the agent log bus.
+#[derive(Clone)]
+pub struct AgentLogStore {
+    buffers: Arc<RwLock<LogBuffers>>,
+    files: Arc<Mutex<Option<RotatingLogFiles>>>,
+    log_bus: AgentLogBusContainer,
+}
+
+struct LogBuffers {
+    capacity: usize,
+    lines: HashMap<String, VecDeque<AgentLogLine>>,
+}
+
+impl AgentLogStore {
+    pub fn new(log_bus: AgentLogBusContainer) -> Self {
+        Self {
+            buffers: Arc::new(RwLock::new(LogBuffers {
+                capacity: DEFAULT_LOG_BUFFER_LINES,
+                lines: HashMap::new(),
+            })),
+            files: Arc::new(Mutex::new(None)),
+            log_bus,
+        }
+    }
+
+    /// Apply buffer size and file mirroring settings; `dir` receives the log files
+    pub fn configure(&self, config: &AgentLogConfig, dir: PathBuf) {
+        {
+            let mut buffers = self.buffers.write().unwrap();
+            buffers.capacity = config.buffer_lines.max(1);
+            let capacity = buffers.capacity;
+            for lines in buffers.lines.values_mut() {
+                while lines.len() > capacity {
+                    lines.pop_front();
+                }
+            }
+        }
+
+        *self.files.lock().unwrap() = config.write_to_file.then(|| {
+            log::info!("Mirroring agent stderr to {}", dir.display());
+            RotatingLogFiles::new(dir, config.max_file_bytes, config.max_files)
+        });
+    }
+
+    /// Record a stderr line of `agent_name`
+    pub fn push(&self, agent_name: &str, text: impl Into<String>) {
+        let line = AgentLogLine {
+            timestamp: Utc::now(),
+            text: text.into(),
+        };
+
+        {
+            let mut buffers = self.buffers.write().unwrap();
+            let capacity = buffers.capacity;
+            let lines = buffers.lines.entry(agent_name.to_string()).or_default();
+            if lines.len() >= capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.clone());
+        }
+
+        if let Some(files) = self.files.lock().unwrap().as_mut() {
+            if let Err(e) = files.write(agent_name, &line) {
+                log::warn!("Failed to write log file for agent {}: {}", agent_name, e);
+            }
+        }
+
+        self.log_bus.publish(AgentLogEvent {
+            agent_name: agent_name.to_string(),
+            line,
+        });
+    }
+
+    /// All buffered lines of an agent, oldest first
+    pub fn lines(&self, agent_name: &str) -> Vec<AgentLogLine> {
+        self.buffers
+            .read()
+            .unwrap()
+            .lines
+            .get(agent_name)
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Text of the last `count` lines of an agent
+    pub fn tail(&self, agent_name: &str, count: usize) -> Vec<String> {
+        self.buffers
+            .read()
+            .unwrap()
+            .lines
+            .get(agent_name)
+            .map(|lines| {
+                let skip = lines.len().saturating_sub(count);
+                lines
+                    .iter()
+                    .skip(skip)
+                    .map(|line| line.text.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Names of all agents that produced output, sorted
+    pub fn agents(&self) -> Vec<String> {
+        let mut agents: Vec<_> = self.buffers.read().unwrap().lines.keys().cloned().collect();
+        agents.sort();
+        agents
+    }
+
+    /// Drop the buffered lines of an agent (log files are kept)
+    pub fn clear(&self, agent_name: &str) {
+        self.buffers.write().unwrap().lines.remove(agent_name);
+    }
+}
+
+impl Default for AgentLogStore {
+    fn default() -> Self {
+        Self::new(AgentLogBusContainer::new())
+    }
+}
+
+/// Format a line the way it is written to log files and copied to the clipboard
+pub fn format_log_line(line: &AgentLogLine) -> String {
+    format!(
+        "{} {}",
+        line.timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S%.3f"),
+        line.text
+    )
+}
+
+/// Per-agent log files rotated by size
+struct RotatingLogFiles {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    open: HashMap<String, (File, u64)>,
+}
+
+impl RotatingLogFiles {
+    fn new(dir: PathBuf, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            max_files,
+            open: HashMap::new(),
+        }
+    }
+
+    fn write(&mut self, agent_name: &str, line: &AgentLogLine) -> std::io::Result<()> {
+        let entry = format!("{}\n", format_log_line(line));
+        let path = self.dir.join(log_file_name(agent_name));
+
+        let needs_rotation = self
+            .open
+            .get(agent_name)
+            .is_some_and(|(_, size)| *size > 0 && size + entry.len() as u64 > self.max_bytes);
+        if needs_rotation {
+            self.open.remove(agent_name);
+            rotate(&path, self.max_files)?;
+        }
+
+        if !self.open.contains_key(agent_name) {
+            fs::create_dir_all(&self.dir)?;
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            let size = file.metadata()?.len();
+            self.open.insert(agent_name.to_string(), (file, size));
+        }
+
+        let (file, size) = self.open.get_mut(agent_name).unwrap();
+        file.write_all(entry.as_bytes())?;
+        *size += entry.len() as u64;
+        Ok(())
+    }
+}
+
+/// File name for an agent, with characters unsafe in paths replaced
+fn log_file_name(agent_name: &str) -> String {
+    let name: String = agent_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.log", name)
+}
+
+/// Shift `<path>.1..N-1` to `<path>.2..N` and move `path` to `<path>.1`
+pub(super) fn rotate(path: &Path, max_files: usize) -> std::io::Result<()> {
+    let rotated = |index: usize| PathBuf::from(format!("{}.{}", path.display(), index));
+
+    if max_files == 0 {
+        return fs::remove_file(path);
+    }
+
+    let _ = fs::remove_file(rotated(max_files));
+    for index in (1..max_files).rev() {
+        let from = rotated(index);
+        if from.exists() {
+            fs::rename(&from, rotated(index + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_keeps_latest_lines() {
+        let store = AgentLogStore::default();
+        store.configure(
+            &AgentLogConfig {
+                buffer_lines: 3,
+                ..AgentLogConfig::default()
+            },
+            PathBuf::new(),
+        );
+
+        for i in 0..5 {
+            store.push("agent", format!("line {}", i));
+        }
+
+        assert_eq!(store.tail("agent", 10), vec!["line 2", "line 3", "line 4"]);
+        assert_eq!(store.tail("agent", 1), vec!["line 4"]);
+        assert!(store.tail("other", 5).is_empty());
+        assert_eq!(store.agents(), vec!["agent"]);
+    }
+
+    #[test]
+    fn test_log_files_rotate_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AgentLogStore::default();
+        store.configure(
+            &AgentLogConfig {
+                write_to_file: true,
+                max_file_bytes: 64,
+                max_files: 2,
+                ..AgentLogConfig::default()
+            },
+            dir.path().to_path_buf(),
+        );
+
+        for i in 0..10 {
+            store.push("my agent", format!("message number {}", i));
+        }
+
+        let current = dir.path().join("my_agent.log");
+        assert!(current.exists());
+        assert!(dir.path().join("my_agent.log.1").exists());
+        assert!(dir.path().join("my_agent.log.2").exists());
+        assert!(!dir.path().join("my_agent.log.3").exists());
+        assert!(
+            fs::read_to_string(current)
+                .unwrap()
+                .ends_with("message number 9\n")
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Unattended approval of permission requests for tasks in autopilot mode.
+//!
+//! While a task's autopilot flag is on, its session is listed in
+//! [`AutopilotSessions`] and `GuiClient` approves permission requests of that
+//! session without asking. An [`AutopilotPolicy`] built from the configured
+//! guardrails picks out the requests that still need a person: commands
+//! matching the deny list, paths outside the workspace and network access.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use agent_client_protocol as acp;
+use regex::Regex;
+use serde_json::Value;
+
+use super::file_system::normalize_path;
+use super::permission_rules::tool_command;
+use crate::core::config::AutopilotConfig;
+
+/// Commands that reach the network, escalated when `deny_network` is set
+const NETWORK_COMMAND_PATTERN: &str =
+    r"\b(curl|wget|ssh|scp|sftp|rsync|nc|ncat|telnet|ftp)\b|\bgit\s+(push|pull|fetch|clone)\b";
+
+/// Keys of a tool call's raw input that hold file system paths
+const PATH_INPUT_KEYS: [&str; 5] = ["path", "file_path", "filePath", "notebook_path", "cwd"];
+
+/// Sessions whose task runs in autopilot mode
+#[derive(Clone, Default)]
+pub struct AutopilotSessions {
+    sessions: Arc<RwLock<HashSet<String>>>,
+}
+
+impl AutopilotSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, session_id: &str, enabled: bool) {
+        let mut sessions = self.sessions.write().unwrap();
+        if enabled {
+            sessions.insert(session_id.to_string());
+        } else {
+            sessions.remove(session_id);
+        }
+    }
+
+    pub fn is_enabled(&self, session_id: &str) -> bool {
+        self.sessions.read().unwrap().contains(session_id)
+    }
+}
+
+/// What autopilot does with a permission request
+#[derive(Clone, Debug, PartialEq)]
+pub enum AutopilotVerdict {
+    /// Answer with this option without asking
+    Approve(acp::PermissionOptionId),
+    /// Ask the user, for the given reason
+    Escalate(String),
+}
+
+/// Guardrails compiled from [`AutopilotConfig`]
+#[derive(Clone, Debug)]
+pub struct AutopilotPolicy {
+    deny_commands: Vec<Regex>,
+    deny_outside_workspace: bool,
+    network_commands: Option<Regex>,
+}
+
+impl Default for AutopilotPolicy {
+    fn default() -> Self {
+        Self::from_config(&AutopilotConfig::default())
+    }
+}
+
+impl AutopilotPolicy {
+    /// Invalid deny patterns are logged and skipped
+    pub fn from_config(config: &AutopilotConfig) -> Self {
+        let deny_commands = config
+            .deny_commands
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    log::warn!(
+                        "Ignoring invalid autopilot deny pattern '{}': {}",
+                        pattern,
+                        e
+                    );
+                    None
+                }
+            })
+            .collect();
+        Self {
+            deny_commands,
+            deny_outside_workspace: config.deny_outside_workspace,
+            network_commands: config
+                .deny_network
+                .then(|| Regex::new(NETWORK_COMMAND_PATTERN).expect("valid network pattern")),
+        }
+    }
+
+    pub fn evaluate(
+        &self,
+        tool_call: &acp::ToolCallUpdate,
+        workspace: Option<&Path>,
+        options: &[acp::PermissionOption],
+    ) -> AutopilotVerdict {
+        match self.escalation_reason(tool_call, workspace) {
+            Some(reason) => AutopilotVerdict::Escalate(reason),
+            None => match options
+                .iter()
+                .find(|option| option.kind == acp::PermissionOptionKind::AllowOnce)
+            {
+                Some(option) => AutopilotVerdict::Approve(option.option_id.clone()),
+                // "Always" answers would outlive the task, so they are left to the user
+                None => AutopilotVerdict::Escalate("no allow-once option offered".to_string()),
+            },
+        }
+    }
+
+    fn escalation_reason(
+        &self,
+        tool_call: &acp::ToolCallUpdate,
+        workspace: Option<&Path>,
+    ) -> Option<String> {
+        let kind = tool_call.fields.kind.as_ref();
+
+        if self.network_commands.is_some() && kind == Some(&acp::ToolKind::Fetch) {
+            return Some("network access".to_string());
+        }
+
+        // Requests often come without a kind, so only kinds that cannot run a
+        // command skip the command checks
+        if !is_command_free(kind) {
+            // Agents without a structured command put it in the title
+            let command = tool_command(tool_call)
+                .or_else(|| tool_call.fields.title.clone())
+                .unwrap_or_default();
+            if let Some(regex) = self
+                .deny_commands
+                .iter()
+                .find(|regex| regex.is_match(&command))
+            {
+                return Some(format!("command matches deny pattern `{}`", regex.as_str()));
+            }
+            if self
+                .network_commands
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(&command))
+            {
+                return Some("command reaches the network".to_string());
+            }
+        }
+
+        if self.deny_outside_workspace {
+            let paths = tool_paths(tool_call);
+            match workspace {
+                None if !paths.is_empty() => {
+                    return Some("session has no known workspace".to_string());
+                }
+                Some(workspace) => {
+                    let root = normalize_path(workspace);
+                    if let Some(path) = paths
+                        .iter()
+                        .map(|path| normalize_path(&root.join(path)))
+                        .find(|path| !path.starts_with(&root))
+                    {
+                        return Some(format!("{} is outside the workspace", path.display()));
+                    }
+                }
+                None => {}
+            }
+        }
+
+        None
+    }
+}
+
+/// Whether a tool of this kind only touches files or thinks
+fn is_command_free(kind: Option<&acp::ToolKind>) -> bool {
+    matches!(
+        kind,
+        Some(
+            acp::ToolKind::Read
+                | acp::ToolKind::Edit
+                | acp::ToolKind::Delete
+                | acp::ToolKind::Move
+                | acp::ToolKind::Search
+                | acp::ToolKind::Think
+        )
+    )
+}
+
+/// Paths a tool call names, from its locations and raw input
+fn tool_paths(tool_call: &acp::ToolCallUpdate) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = tool_call
+        .fields
+        .locations
+        .iter()
+        .flatten()
+        .map(|location| location.path.clone())
+        .collect();
+    if let Some(Value::Object(input)) = &tool_call.fields.raw_input {
+        paths.extend(
+            PATH_INPUT_KEYS
+                .iter()
+                .filter_map(|key| input.get(*key)?.as_str())
+                .map(PathBuf::from),
+        );
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(value: serde_json::Value) -> acp::ToolCallUpdate {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn options() -> Vec<acp::PermissionOption> {
+        serde_json::from_value(serde_json::json!([
+            { "optionId": "allow", "name": "Allow", "kind": "allow_once" },
+            { "optionId": "always", "name": "Always", "kind": "allow_always" },
+            { "optionId": "reject", "name": "Reject", "kind": "reject_once" }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policy_approves_and_escalates() {
+        let policy = AutopilotPolicy::default();
+        let workspace = Some(Path::new("/work/project"));
+        let verdict =
+            |call: serde_json::Value| policy.evaluate(&tool_call(call), workspace, &options());
+
+        match verdict(serde_json::json!({
+            "toolCallId": "t1",
+            "kind": "execute",
+            "rawInput": { "command": "cargo test" }
+        })) {
+            AutopilotVerdict::Approve(option_id) => assert_eq!(option_id.to_string(), "allow"),
+            other => panic!("Expected approval, got {:?}", other),
+        }
+        assert!(matches!(
+            verdict(serde_json::json!({
+                "toolCallId": "t2",
+                "kind": "execute",
+                "title": "sudo rm -rf /"
+            })),
+            AutopilotVerdict::Escalate(_)
+        ));
+        assert!(matches!(
+            verdict(serde_json::json!({
+                "toolCallId": "t3",
+                "kind": "execute",
+                "rawInput": { "command": ["curl", "https://example.com"] }
+            })),
+            AutopilotVerdict::Escalate(_)
+        ));
+        assert!(matches!(
+            verdict(serde_json::json!({ "toolCallId": "t4", "kind": "fetch" })),
+            AutopilotVerdict::Escalate(_)
+        ));
+    }
+
+    #[test]
+    fn test_policy_checks_commands_without_kind() {
+        let policy = AutopilotPolicy::default();
+        let workspace = Some(Path::new("/work/project"));
+        let verdict =
+            |call: serde_json::Value| policy.evaluate(&tool_call(call), workspace, &options());
+
+        assert!(matches!(
+            verdict(serde_json::json!({ "toolCallId": "t1", "title": "sudo rm -rf /" })),
+            AutopilotVerdict::Escalate(_)
+        ));
+        assert!(matches!(
+            verdict(serde_json::json!({
+                "toolCallId": "t2",
+                "kind": "other",
+                "rawInput": { "command": "wget https://example.com" }
+            })),
+            AutopilotVerdict::Escalate(_)
+        ));
+        assert!(matches!(
+            verdict(serde_json::json!({ "toolCallId": "t3", "title": "List files" })),
+            AutopilotVerdict::Approve(_)
+        ));
+        // File tools are not mistaken for commands because of their title
+        assert!(matches!(
+            verdict(serde_json::json!({
+                "toolCallId": "t4",
+                "kind": "read",
+                "title": "Read scripts/curl.sh"
+            })),
+            AutopilotVerdict::Approve(_)
+        ));
+    }
+
+    #[test]
+    fn test_policy_checks_paths_against_workspace() {
+        let policy = AutopilotPolicy::default();
+        let workspace = Some(Path::new("/work/project"));
+        let edit = |path: &str| {
+            tool_call(serde_json::json!({
+                "toolCallId": "t1",
+                "kind": "edit",
+                "locations": [{ "path": path }]
+            }))
+        };
+
+        assert!(matches!(
+            policy.evaluate(&edit("/work/project/src/main.rs"), workspace, &options()),
+            AutopilotVerdict::Approve(_)
+        ));
+        assert!(matches!(
+            policy.evaluate(
+                &edit("/work/project/../other/secret"),
+                workspace,
+                &options()
+            ),
+            AutopilotVerdict::Escalate(_)
+        ));
+        assert!(matches!(
+            policy.evaluate(&edit("/work/project/src/main.rs"), None, &options()),
+            AutopilotVerdict::Escalate(_)
+        ));
+
+        let relaxed = AutopilotPolicy::from_config(&AutopilotConfig {
+            deny_outside_workspace: false,
+            ..Default::default()
+        });
+        assert!(matches!(
+            relaxed.evaluate(&edit("/etc/hosts"), workspace, &options()),
+            AutopilotVerdict::Approve(_)
+        ));
+    }
+
+    #[test]
+    fn test_sessions() {
+        let sessions = AutopilotSessions::new();
+        sessions.set("s1", true);
+        assert!(sessions.is_enabled("s1"));
+        assert!(!sessions.is_enabled("s2"));
+        sessions.set("s1", false);
+        assert!(!sessions.is_enabled("s1"));
+    }
+}
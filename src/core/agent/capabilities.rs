@@ -0,0 +1,165 @@
+//! What the client tells agents about itself during `initialize`.
+//!
+//! Capabilities start from what [`GuiClient`](super::client) implements and
+//! are narrowed by the per-agent [`ClientCapabilitiesConfig`]. The same
+//! [`ClientFeatures`] value gates the client methods, so an agent that was
+//! told a capability is off gets `method_not_found` if it calls it anyway.
+
+use serde_json::{Map, Value, json};
+
+use agent_client_protocol as acp;
+
+use super::extensions::ExtensionRegistry;
+use crate::core::config::ClientCapabilitiesConfig;
+
+/// Identifies the client in `initialize`
+pub const CLIENT_NAME: &str = "agentx";
+pub const CLIENT_TITLE: &str = "AgentX";
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Client methods offered to one agent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientFeatures {
+    pub read_text_file: bool,
+    pub write_text_file: bool,
+    pub terminal: bool,
+}
+
+impl ClientFeatures {
+    /// Everything the client implements
+    pub const IMPLEMENTED: Self = Self {
+        read_text_file: true,
+        write_text_file: true,
+        terminal: true,
+    };
+
+    /// Implemented features, minus the ones the agent's config turns off
+    pub fn for_agent(overrides: &ClientCapabilitiesConfig) -> Self {
+        let implemented = Self::IMPLEMENTED;
+        Self {
+            read_text_file: implemented.read_text_file && overrides.read_text_file.unwrap_or(true),
+            write_text_file: implemented.write_text_file
+                && overrides.write_text_file.unwrap_or(true),
+            terminal: implemented.terminal && overrides.terminal.unwrap_or(true),
+        }
+    }
+}
+
+/// `_meta` advertised to agents: the extension methods the client handles,
+/// with the agent's overrides merged in
+pub fn client_meta(
+    extensions: &ExtensionRegistry,
+    overrides: &ClientCapabilitiesConfig,
+) -> Map<String, Value> {
+    let mut meta = Map::new();
+    let methods = extensions.method_patterns();
+    let notifications = extensions.notification_patterns();
+    if !methods.is_empty() || !notifications.is_empty() {
+        meta.insert(
+            CLIENT_NAME.to_string(),
+            json!({
+                "extMethods": methods,
+                "extNotifications": notifications,
+            }),
+        );
+    }
+
+    for (key, value) in &overrides.meta {
+        if value.is_null() {
+            meta.remove(key);
+        } else {
+            meta.insert(key.clone(), value.clone());
+        }
+    }
+    meta
+}
+
+/// Capabilities sent in `initialize`
+pub fn client_capabilities(
+    features: ClientFeatures,
+    meta: Map<String, Value>,
+) -> acp::ClientCapabilities {
+    let mut value = json!({
+        "fs": {
+            "readTextFile": features.read_text_file,
+            "writeTextFile": features.write_text_file,
+        },
+        "terminal": features.terminal,
+    });
+    if !meta.is_empty() {
+        value["_meta"] = Value::Object(meta);
+    }
+
+    serde_json::from_value(value).unwrap_or_else(|e| {
+        log::error!("Failed to build client capabilities: {}", e);
+        let mut capabilities = acp::ClientCapabilities::default();
+        capabilities.fs.read_text_file = features.read_text_file;
+        capabilities.fs.write_text_file = features.write_text_file;
+        capabilities.terminal = features.terminal;
+        capabilities
+    })
+}
+
+/// Name, title and version of this client
+pub fn client_info() -> acp::Implementation {
+    let mut info = acp::Implementation::new(CLIENT_NAME, CLIENT_VERSION);
+    info.title = Some(CLIENT_TITLE.into());
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features_default_to_implemented() {
+        let features = ClientFeatures::for_agent(&ClientCapabilitiesConfig::default());
+        assert_eq!(features, ClientFeatures::IMPLEMENTED);
+    }
+
+    #[test]
+    fn test_overrides_turn_features_off() {
+        let overrides = ClientCapabilitiesConfig {
+            write_text_file: Some(false),
+            terminal: Some(false),
+            ..Default::default()
+        };
+        let features = ClientFeatures::for_agent(&overrides);
+
+        assert!(features.read_text_file);
+        assert!(!features.write_text_file);
+        assert!(!features.terminal);
+
+        let capabilities = client_capabilities(features, Map::new());
+        assert!(capabilities.fs.read_text_file);
+        assert!(!capabilities.fs.write_text_file);
+        assert!(!capabilities.terminal);
+    }
+
+    #[test]
+    fn test_meta_lists_extensions_and_merges_overrides() {
+        let extensions = ExtensionRegistry::new();
+        extensions.register_notification("_claude/status", |_, _| {});
+
+        let meta = client_meta(&extensions, &ClientCapabilitiesConfig::default());
+        assert_eq!(
+            meta[CLIENT_NAME]["extNotifications"],
+            json!(["claude/status"])
+        );
+
+        let mut overrides = ClientCapabilitiesConfig::default();
+        overrides.meta.insert("terminal_output".into(), json!(true));
+        overrides.meta.insert(CLIENT_NAME.into(), Value::Null);
+        let meta = client_meta(&extensions, &overrides);
+        assert_eq!(meta.get("terminal_output"), Some(&json!(true)));
+        assert!(!meta.contains_key(CLIENT_NAME));
+    }
+
+    #[test]
+    fn test_client_info_identifies_agentx() {
+        let info = client_info();
+        assert_eq!(info.name, "agentx");
+        assert_eq!(info.title.as_deref(), Some("AgentX"));
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    }
+}
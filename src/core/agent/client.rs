@@ -4,40 +4,79 @@
 //! which agent binaries to spawn, and provides a REPL to interact with them.
 
 use std::{
+    cell::RefCell,
     collections::HashMap,
+    path::PathBuf,
     rc::Rc,
     sync::{
-        Arc,
+        Arc, Weak,
         atomic::{AtomicU64, AtomicUsize, Ordering},
     },
     thread,
+    time::{Duration, Instant},
 };
 
 use agent_client_protocol::{self as acp, Agent as _};
 
 use anyhow::{Context, Result, anyhow};
+use futures::future::{Either, LocalBoxFuture, select};
 use log::{error, warn};
+use serde_json::Value;
 use tokio::{
     runtime::Builder as RuntimeBuilder,
     sync::{RwLock, mpsc, oneshot},
     task::LocalSet,
 };
 
-use crate::core::config::{AgentProcessConfig, ProxyConfig};
+use super::agent_logs::{AgentLogStore, STDERR_TAIL_LINES};
+use super::autopilot::{AutopilotPolicy, AutopilotSessions, AutopilotVerdict};
+use super::capabilities::{self, ClientFeatures};
+use super::extensions::{ExtensionContext, ExtensionKind, ExtensionRegistry};
+use super::file_system::{
+    OpenBufferRegistry, read_in_workspace, resolve_in_workspace, slice_lines,
+};
+use super::permission_audit::{AuditResolver, PermissionAuditEntry, PermissionAuditLog};
+use super::permission_rules::{self, PermissionRule, PermissionRuleStore, RuleRequest, RuleScope};
+use super::protocol_trace::{ProtocolTraceStore, TracedReader, TracedWriter};
+use super::supervisor::{AgentExit, RestartPolicy};
+use super::terminal::{LocalTerminals, TerminalExit, TerminalRegistry, TerminalSpawnRequest};
+use super::transport::{self, BoxedReader, BoxedWriter};
+use crate::core::config::{
+    AgentProcessConfig, AgentTransport, PermissionConfig, PermissionTimeoutAction, ProxyConfig,
+};
 use crate::core::event_bus::{
-    permission_bus::{PermissionBusContainer, PermissionRequestEvent},
+    extension_bus::{ExtensionBusContainer, ExtensionEvent},
+    file_change_bus::{FileChangeBusContainer, FileChangeEvent},
+    permission_bus::{
+        PermissionBusContainer, PermissionRequestEvent, PermissionResolution, PermissionStatusEvent,
+    },
     session_bus::{SessionUpdateBusContainer, SessionUpdateEvent},
+    terminal_bus::TerminalBusContainer,
+    workspace_bus::{WorkspaceUpdateBusContainer, WorkspaceUpdateEvent},
 };
 
 use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 
+/// How long a failed initialization waits for the agent's remaining stderr
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
 #[derive(Clone)]
 pub struct AgentManager {
     agents: Arc<RwLock<HashMap<String, Arc<AgentHandle>>>>,
     permission_store: Arc<PermissionStore>,
     session_bus: SessionUpdateBusContainer,
     permission_bus: PermissionBusContainer,
+    workspace_bus: WorkspaceUpdateBusContainer,
+    file_change_bus: FileChangeBusContainer,
+    open_buffers: OpenBufferRegistry,
+    terminal_bus: TerminalBusContainer,
+    terminals: TerminalRegistry,
+    agent_logs: AgentLogStore,
+    protocol_trace: ProtocolTraceStore,
+    extensions: ExtensionRegistry,
+    extension_bus: ExtensionBusContainer,
     proxy_config: Arc<RwLock<ProxyConfig>>,
+    restart_policy: RestartPolicy,
 }
 
 impl AgentManager {
@@ -46,6 +85,15 @@ impl AgentManager {
         permission_store: Arc<PermissionStore>,
         session_bus: SessionUpdateBusContainer,
         permission_bus: PermissionBusContainer,
+        workspace_bus: WorkspaceUpdateBusContainer,
+        file_change_bus: FileChangeBusContainer,
+        open_buffers: OpenBufferRegistry,
+        terminal_bus: TerminalBusContainer,
+        terminals: TerminalRegistry,
+        agent_logs: AgentLogStore,
+        protocol_trace: ProtocolTraceStore,
+        extensions: ExtensionRegistry,
+        extension_bus: ExtensionBusContainer,
         proxy_config: ProxyConfig,
     ) -> Result<Arc<Self>> {
         if configs.is_empty() {
@@ -57,7 +105,17 @@ impl AgentManager {
             permission_store,
             session_bus,
             permission_bus,
+            workspace_bus,
+            file_change_bus,
+            open_buffers,
+            terminal_bus,
+            terminals,
+            agent_logs,
+            protocol_trace,
+            extensions,
+            extension_bus,
             proxy_config,
+            restart_policy: RestartPolicy::default(),
         });
         let remaining = Arc::new(AtomicUsize::new(configs.len()));
 
@@ -123,19 +181,18 @@ impl AgentManager {
         }
 
         // Spawn new agent
-        let handle = AgentHandle::spawn(
-            name.clone(),
-            config,
-            self.permission_store.clone(),
-            self.session_bus.clone(),
-            self.permission_bus.clone(),
-            self.proxy_config.read().await.clone(),
-        )
-        .await?;
+        let (handle, exit_rx) = self
+            .spawn_handle(&name, config)
+            .await
+            .inspect_err(|e| self.report_init_failure(&name, e))?;
+        let handle = Arc::new(handle);
 
         // Add to agents map
         let mut agents = self.agents.write().await;
-        agents.insert(name.clone(), Arc::new(handle));
+        agents.insert(name.clone(), handle.clone());
+        drop(agents);
+
+        self.supervise(name.clone(), &handle, exit_rx, 0);
         log::info!("Successfully added agent '{}'", name);
         Ok(())
     }
@@ -171,19 +228,18 @@ impl AgentManager {
         }
 
         // Spawn new agent
-        let new_handle = AgentHandle::spawn(
-            name.to_string(),
-            config,
-            self.permission_store.clone(),
-            self.session_bus.clone(),
-            self.permission_bus.clone(),
-            self.proxy_config.read().await.clone(),
-        )
-        .await?;
+        let (new_handle, exit_rx) = self
+            .spawn_handle(name, config)
+            .await
+            .inspect_err(|e| self.report_init_failure(name, e))?;
+        let new_handle = Arc::new(new_handle);
 
         // Add new agent to map
         let mut agents = self.agents.write().await;
-        agents.insert(name.to_string(), Arc::new(new_handle));
+        agents.insert(name.to_string(), new_handle.clone());
+        drop(agents);
+
+        self.supervise(name.to_string(), &new_handle, exit_rx, 0);
         log::info!("Successfully restarted agent '{}'", name);
         Ok(())
     }
@@ -216,10 +272,194 @@ impl AgentManager {
     pub async fn get_proxy_config(&self) -> ProxyConfig {
         self.proxy_config.read().await.clone()
     }
+
+    /// Captured stderr of all agents
+    pub fn agent_logs(&self) -> &AgentLogStore {
+        &self.agent_logs
+    }
+
+    /// Last `count` stderr lines of an agent
+    pub fn stderr_tail(&self, name: &str, count: usize) -> Vec<String> {
+        self.agent_logs.tail(name, count)
+    }
+
+    /// Recorded ACP traffic of all agents
+    pub fn protocol_trace(&self) -> &ProtocolTraceStore {
+        &self.protocol_trace
+    }
+
+    /// Handlers for ACP extension methods and notifications, shared by all agents
+    pub fn extensions(&self) -> &ExtensionRegistry {
+        &self.extensions
+    }
+
+    /// Pending permission requests of all agents
+    pub fn permission_store(&self) -> &Arc<PermissionStore> {
+        &self.permission_store
+    }
+
+    /// Tell the UI that an agent could not be started, with its last stderr lines
+    fn report_init_failure(&self, name: &str, error: &anyhow::Error) {
+        self.workspace_bus
+            .publish(WorkspaceUpdateEvent::AgentInitFailed {
+                agent_name: name.to_string(),
+                error: error.to_string(),
+                stderr_tail: self.stderr_tail(name, STDERR_TAIL_LINES),
+            });
+    }
+
+    async fn spawn_handle(
+        &self,
+        name: &str,
+        config: AgentProcessConfig,
+    ) -> Result<(AgentHandle, oneshot::Receiver<AgentExit>)> {
+        AgentHandle::spawn(
+            name.to_string(),
+            config,
+            self.permission_store.clone(),
+            self.session_bus.clone(),
+            self.permission_bus.clone(),
+            self.file_change_bus.clone(),
+            self.open_buffers.clone(),
+            self.terminal_bus.clone(),
+            self.terminals.clone(),
+            self.agent_logs.clone(),
+            self.protocol_trace.clone(),
+            self.extensions.clone(),
+            self.extension_bus.clone(),
+            self.proxy_config.read().await.clone(),
+        )
+        .await
+    }
+
+    /// Whether `handle` is still the registered handle for `name`
+    async fn is_current(&self, name: &str, handle: &Weak<AgentHandle>) -> bool {
+        self.agents
+            .read()
+            .await
+            .get(name)
+            .is_some_and(|current| std::ptr::eq(Arc::as_ptr(current), handle.as_ptr()))
+    }
+
+    /// Watch an agent worker and restart it when it dies unexpectedly.
+    ///
+    /// `attempts` is the number of restarts already spent on this agent.
+    fn supervise(
+        &self,
+        name: String,
+        handle: &Arc<AgentHandle>,
+        exit_rx: oneshot::Receiver<AgentExit>,
+        attempts: u32,
+    ) {
+        let manager = self.clone();
+        let config = handle.config().clone();
+        let handle = Arc::downgrade(handle);
+        let started_at = Instant::now();
+
+        smol::spawn(async move {
+            let reason = match exit_rx.await {
+                Ok(AgentExit::Shutdown) => return,
+                Ok(AgentExit::Crashed(reason)) => reason,
+                Err(_) => "agent worker thread ended unexpectedly".to_string(),
+            };
+
+            // Removed or replaced while it was going down
+            if !manager.is_current(&name, &handle).await {
+                return;
+            }
+
+            let attempts = manager
+                .restart_policy
+                .attempts_after(attempts, started_at.elapsed());
+            manager
+                .restart_crashed_agent(name, config, handle, reason, attempts)
+                .await;
+        })
+        .detach();
+    }
+
+    async fn restart_crashed_agent(
+        &self,
+        name: String,
+        config: AgentProcessConfig,
+        crashed: Weak<AgentHandle>,
+        reason: String,
+        mut attempts: u32,
+    ) {
+        error!("Agent '{}' crashed: {}", name, reason);
+        self.workspace_bus
+            .publish(WorkspaceUpdateEvent::AgentCrashed {
+                agent_name: name.clone(),
+                reason,
+            });
+
+        loop {
+            if attempts >= self.restart_policy.max_restarts {
+                error!(
+                    "Giving up restarting agent '{}' after {} attempts",
+                    name, attempts
+                );
+                self.workspace_bus
+                    .publish(WorkspaceUpdateEvent::AgentRestartFailed {
+                        agent_name: name,
+                        attempts,
+                    });
+                return;
+            }
+
+            attempts += 1;
+            let delay = self.restart_policy.backoff(attempts);
+            log::info!(
+                "Restarting agent '{}' in {:?} (attempt {}/{})",
+                name,
+                delay,
+                attempts,
+                self.restart_policy.max_restarts
+            );
+            smol::Timer::after(delay).await;
+
+            // The user removed or restarted the agent in the meantime
+            if !self.is_current(&name, &crashed).await {
+                return;
+            }
+
+            let (handle, exit_rx) = match self.spawn_handle(&name, config.clone()).await {
+                Ok(spawned) => spawned,
+                Err(e) => {
+                    warn!("Failed to restart agent '{}': {}", name, e);
+                    continue;
+                }
+            };
+            let handle = Arc::new(handle);
+
+            let mut agents = self.agents.write().await;
+            let still_crashed = agents
+                .get(&name)
+                .is_some_and(|current| std::ptr::eq(Arc::as_ptr(current), crashed.as_ptr()));
+            if !still_crashed {
+                drop(agents);
+                let _ = handle.shutdown().await;
+                return;
+            }
+            agents.insert(name.clone(), handle.clone());
+            drop(agents);
+
+            self.supervise(name.clone(), &handle, exit_rx, attempts);
+            log::info!("Agent '{}' restarted after crash", name);
+            self.workspace_bus
+                .publish(WorkspaceUpdateEvent::AgentRestarted {
+                    agent_name: name,
+                    attempt: attempts,
+                });
+            return;
+        }
+    }
 }
 
 pub struct AgentHandle {
     name: String,
+    /// Process configuration, kept to restart the agent after a crash
+    config: AgentProcessConfig,
     sender: mpsc::Sender<AgentCommand>,
     /// Initialize response from the agent
     init_response: Arc<std::sync::RwLock<Option<acp::InitializeResponse>>>,
@@ -232,10 +472,20 @@ impl AgentHandle {
         permission_store: Arc<PermissionStore>,
         session_bus: SessionUpdateBusContainer,
         permission_bus: PermissionBusContainer,
+        file_change_bus: FileChangeBusContainer,
+        open_buffers: OpenBufferRegistry,
+        terminal_bus: TerminalBusContainer,
+        terminals: TerminalRegistry,
+        agent_logs: AgentLogStore,
+        protocol_trace: ProtocolTraceStore,
+        extensions: ExtensionRegistry,
+        extension_bus: ExtensionBusContainer,
         proxy_config: ProxyConfig,
-    ) -> Result<Self> {
+    ) -> Result<(Self, oneshot::Receiver<AgentExit>)> {
         let (sender, receiver) = mpsc::channel(32);
         let (ready_tx, ready_rx) = oneshot::channel();
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let worker_config = config.clone();
         let init_response = Arc::new(std::sync::RwLock::new(None));
         let init_response_clone = init_response.clone();
         let thread_name = format!("agent-worker-{name}");
@@ -244,19 +494,32 @@ impl AgentHandle {
             .name(thread_name)
             .spawn(move || {
                 let log_name = worker_name.clone();
-                if let Err(err) = run_agent_worker(
+                let exit = match run_agent_worker(
                     worker_name,
-                    config,
+                    worker_config,
                     permission_store,
                     session_bus,
                     permission_bus,
+                    file_change_bus,
+                    open_buffers,
+                    terminal_bus,
+                    terminals,
+                    agent_logs,
+                    protocol_trace,
+                    extensions,
+                    extension_bus,
                     receiver,
                     ready_tx,
                     init_response_clone,
                     proxy_config,
                 ) {
-                    error!("agent {log_name} exited with error: {:?}", err);
-                }
+                    Ok(()) => AgentExit::Shutdown,
+                    Err(err) => {
+                        error!("agent {log_name} exited with error: {:?}", err);
+                        AgentExit::Crashed(err.to_string())
+                    }
+                };
+                let _ = exit_tx.send(exit);
             })
             .context("failed to spawn worker thread")?;
         let start_name = name.clone();
@@ -264,11 +527,25 @@ impl AgentHandle {
             .await
             .map_err(|_| anyhow!("agent {start_name} failed to start"))??;
 
-        Ok(Self {
-            name,
-            sender,
-            init_response,
-        })
+        Ok((
+            Self {
+                name,
+                config,
+                sender,
+                init_response,
+            },
+            exit_rx,
+        ))
+    }
+
+    /// Get the process configuration this agent was started with
+    pub fn config(&self) -> &AgentProcessConfig {
+        &self.config
+    }
+
+    /// Whether the worker talking to the agent is still running
+    pub fn is_running(&self) -> bool {
+        !self.sender.is_closed()
     }
 
     pub async fn new_session(
@@ -307,6 +584,25 @@ impl AgentHandle {
         result
     }
 
+    /// Load a session, replaying its history as session updates before responding
+    pub async fn load_session(
+        &self,
+        request: acp::LoadSessionRequest,
+    ) -> Result<acp::LoadSessionResponse> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(AgentCommand::LoadSession {
+                request,
+                respond: tx,
+            })
+            .await
+            .map_err(|_| anyhow!("agent {} is not running", self.name))?;
+        let result = rx
+            .await
+            .map_err(|_| anyhow!("agent {} stopped", self.name))?;
+        result
+    }
+
     pub async fn prompt(&self, request: acp::PromptRequest) -> Result<acp::PromptResponse> {
         let (tx, rx) = oneshot::channel();
         self.sender
@@ -438,6 +734,14 @@ fn run_agent_worker(
     permission_store: Arc<PermissionStore>,
     session_bus: SessionUpdateBusContainer,
     permission_bus: PermissionBusContainer,
+    file_change_bus: FileChangeBusContainer,
+    open_buffers: OpenBufferRegistry,
+    terminal_bus: TerminalBusContainer,
+    terminals: TerminalRegistry,
+    agent_logs: AgentLogStore,
+    protocol_trace: ProtocolTraceStore,
+    extensions: ExtensionRegistry,
+    extension_bus: ExtensionBusContainer,
     command_rx: mpsc::Receiver<AgentCommand>,
     ready_tx: oneshot::Sender<Result<agent_client_protocol::InitializeResponse>>,
     init_response: Arc<std::sync::RwLock<Option<acp::InitializeResponse>>>,
@@ -457,6 +761,14 @@ fn run_agent_worker(
                 permission_store,
                 session_bus,
                 permission_bus,
+                file_change_bus,
+                open_buffers,
+                terminal_bus,
+                terminals,
+                agent_logs,
+                protocol_trace,
+                extensions,
+                extension_bus,
                 command_rx,
                 ready_tx,
                 init_response,
@@ -472,143 +784,109 @@ async fn agent_event_loop(
     permission_store: Arc<PermissionStore>,
     session_bus: SessionUpdateBusContainer,
     permission_bus: PermissionBusContainer,
+    file_change_bus: FileChangeBusContainer,
+    open_buffers: OpenBufferRegistry,
+    terminal_bus: TerminalBusContainer,
+    terminals: TerminalRegistry,
+    agent_logs: AgentLogStore,
+    protocol_trace: ProtocolTraceStore,
+    extensions: ExtensionRegistry,
+    extension_bus: ExtensionBusContainer,
     mut command_rx: mpsc::Receiver<AgentCommand>,
     ready_tx: oneshot::Sender<Result<agent_client_protocol::InitializeResponse>>,
     init_response: Arc<std::sync::RwLock<Option<acp::InitializeResponse>>>,
     proxy_config: ProxyConfig,
 ) -> Result<()> {
-    // Node.js environment validation
-    let requires_nodejs = config.command.ends_with(".js")
-        || config.command.ends_with(".ts")
-        || config.command.contains("node")
-        || config.command.contains("npx");
-
-    if requires_nodejs {
-        log::info!(
-            "Agent '{}' requires Node.js, validating environment...",
-            agent_name
-        );
-
-        use crate::core::nodejs::NodeJsChecker;
-        use std::path::PathBuf;
-
-        let custom_path = config.nodejs_path.as_ref().map(PathBuf::from);
-        let nodejs_checker = NodeJsChecker::new(custom_path);
-
-        match nodejs_checker.check_nodejs_available().await {
-            Ok(result) if result.available => {
-                log::info!(
-                    "Node.js found for '{}': {} ({})",
-                    agent_name,
-                    result.path.unwrap().display(),
-                    result.version.unwrap()
-                );
-            }
-            Ok(result) => {
-                let error_msg = format!(
-                    "Node.js required but not found for agent '{}'.\n\n{}",
-                    agent_name,
-                    result.install_hint.unwrap_or_default()
-                );
-                log::error!("{}", error_msg);
-                let _ = ready_tx.send(Err(anyhow!(error_msg.clone())));
-                return Err(anyhow!(error_msg));
-            }
-            Err(e) => {
-                let error_msg = format!("Failed to validate Node.js for '{}': {}", agent_name, e);
-                log::error!("{}", error_msg);
-                let _ = ready_tx.send(Err(anyhow!(error_msg.clone())));
-                return Err(anyhow!(error_msg));
-            }
+    // Reach the agent: spawn it, or connect to a daemon that is already running
+    let streams = match &config.transport {
+        AgentTransport::Stdio => {
+            spawn_agent_process(&agent_name, &config, &proxy_config, &agent_logs).await
+        }
+        transport => {
+            transport::connect(&agent_name, transport)
+                .await
+                .map(|(outgoing, incoming)| AgentStreams {
+                    outgoing,
+                    incoming,
+                    exited: Box::pin(futures::future::pending()),
+                    process: None,
+                })
         }
-    }
-
-    let mut command = if cfg!(target_os = "windows") {
-        let mut shell_cmd = tokio::process::Command::new("cmd");
-        let mut full_args = vec!["/C".to_string(), config.command.clone()];
-        full_args.extend(config.args.iter().cloned());
-        shell_cmd.args(&full_args);
-        shell_cmd
-    } else {
-        let mut cmd = tokio::process::Command::new(&config.command);
-        cmd.args(&config.args);
-        cmd
     };
-
-    // Set environment variables from config
-    command.envs(&config.env);
-
-    // Set proxy environment variables if enabled
-    if let Some(proxy_url) = proxy_config.to_env_value() {
-        log::info!("Setting proxy for agent '{}': {}", agent_name, proxy_url);
-
-        // Set standard proxy environment variables
-        match proxy_config.proxy_type.as_str() {
-            "http" | "https" => {
-                command.env("HTTP_PROXY", &proxy_url);
-                command.env("HTTPS_PROXY", &proxy_url);
-                command.env("http_proxy", &proxy_url);
-                command.env("https_proxy", &proxy_url);
-            }
-            "socks5" => {
-                command.env("ALL_PROXY", &proxy_url);
-                command.env("all_proxy", &proxy_url);
-            }
-            _ => {
-                log::warn!(
-                    "Unknown proxy type '{}' for agent '{}'",
-                    proxy_config.proxy_type,
-                    agent_name
-                );
-            }
+    let AgentStreams {
+        outgoing,
+        incoming,
+        exited,
+        mut process,
+    } = match streams {
+        Ok(streams) => streams,
+        Err(err) => {
+            let _ = ready_tx.send(Err(anyhow!("{:#}", err)));
+            return Err(err);
         }
-    }
+    };
 
-    // Set stdio for all platforms
-    command.stdin(std::process::Stdio::piped());
-    command.stdout(std::process::Stdio::piped());
-    command.stderr(std::process::Stdio::inherit());
+    // Both directions go through the protocol inspector tap, which only
+    // records while tracing is enabled
+    let outgoing = TracedWriter::new(outgoing, protocol_trace.clone(), agent_name.clone());
+    let incoming = TracedReader::new(incoming, protocol_trace, agent_name.clone());
 
-    let mut child = command
-        .spawn()
-        .with_context(|| format!("failed to spawn agent {agent_name}"))?;
-    let outgoing = child
-        .stdin
-        .take()
-        .ok_or_else(|| anyhow!("agent {agent_name} missing stdin"))?
-        .compat_write();
-    let incoming = child
-        .stdout
-        .take()
-        .ok_or_else(|| anyhow!("agent {agent_name} missing stdout"))?
-        .compat();
+    // Workspace root of every session opened on this connection, used to scope file writes
+    let session_roots: SessionRoots = Rc::new(RefCell::new(HashMap::new()));
+
+    // What the client implements, narrowed by the agent's config
+    let features = ClientFeatures::for_agent(&config.client_capabilities);
+    let client_meta = capabilities::client_meta(&extensions, &config.client_capabilities);
 
     let client = GuiClient::new(
         agent_name.clone(),
         permission_store,
         session_bus,
         permission_bus,
+        file_change_bus,
+        open_buffers,
+        LocalTerminals::new(agent_name.clone(), terminals, terminal_bus),
+        session_roots.clone(),
+        features,
+        extensions,
+        extension_bus,
     );
     let (conn, io_task) = acp::ClientSideConnection::new(client, outgoing, incoming, |fut| {
         tokio::task::spawn_local(fut);
     });
     let conn = Rc::new(conn);
 
+    let (io_done_tx, io_done_rx) = oneshot::channel::<String>();
     let io_handle = tokio::task::spawn_local(async move {
-        if let Err(err) = io_task.await {
-            error!("agent I/O task ended: {:?}", err);
-        }
+        let reason = match io_task.await {
+            Ok(()) => "connection closed".to_string(),
+            Err(err) => {
+                error!("agent I/O task ended: {:?}", err);
+                format!("connection failed: {:?}", err)
+            }
+        };
+        let _ = io_done_tx.send(reason);
     });
-    // Assuming `InitializeRequest` and `Implementation` have `new` methods or implement `Default`
-    let version = env!("CARGO_PKG_VERSION").to_string();
-    let mut client_info = acp::Implementation::new("agentx", version);
-    client_info.name = "cli-client".into();
-    client_info.title = Some("CLI Client".into());
-    client_info.version = env!("CARGO_PKG_VERSION").into();
+
+    let io_done = Box::pin(async move {
+        io_done_rx
+            .await
+            .unwrap_or_else(|_| "connection closed".to_string())
+    });
+
+    // Resolves as soon as either the process or the ACP connection goes away
+    let mut crashed = Box::pin(select(exited, io_done));
+
+    let client_capabilities = capabilities::client_capabilities(features, client_meta);
+    log::info!(
+        "Offering agent {} client capabilities {:?}",
+        agent_name,
+        client_capabilities
+    );
 
     let mut init_request = acp::InitializeRequest::new(acp::ProtocolVersion::V1);
-    init_request.client_capabilities = acp::ClientCapabilities::default();
-    init_request.client_info = Some(client_info);
+    init_request.client_capabilities = client_capabilities;
+    init_request.client_info = Some(capabilities::client_info());
     init_request.meta = None;
     let init_result = conn.initialize(init_request).await;
     log::info!(
@@ -623,13 +901,29 @@ async fn agent_event_loop(
             let _ = ready_tx.send(Ok(res));
         }
         Err(err) => {
+            // Give the stderr reader a moment to collect what the agent printed
+            // before it failed, so callers can report it
+            if let Some(process) = process.as_mut() {
+                let _ =
+                    tokio::time::timeout(STDERR_DRAIN_TIMEOUT, &mut process.stderr_handle).await;
+            }
             let message = format!("failed to initialize agent {agent_name}: {:?}", err);
             let _ = ready_tx.send(Err(anyhow!(message.clone())));
             return Err(anyhow!(message));
         }
     }
 
-    while let Some(command) = command_rx.recv().await {
+    let crash_reason = loop {
+        let command = match select(Box::pin(command_rx.recv()), &mut crashed).await {
+            Either::Left((Some(command), _)) => command,
+            // Every handle was dropped
+            Either::Left((None, _)) => break None,
+            Either::Right((ended, _)) => {
+                let (Either::Left((reason, _)) | Either::Right((reason, _))) = ended;
+                break Some(reason);
+            }
+        };
+
         match command {
             AgentCommand::Initialize { request, respond } => {
                 let result = conn.initialize(*request).await.map_err(|err| anyhow!(err));
@@ -642,37 +936,29 @@ async fn agent_event_loop(
                     request.cwd
                 );
 
-                // Check if child process is still alive
-                match child.try_wait() {
-                    Ok(Some(status)) => {
-                        let error_msg = format!(
-                            "Agent {} process exited with status: {:?}",
-                            agent_name, status
-                        );
-                        log::error!("{}", error_msg);
-                        let _ = respond.send(Err(anyhow!(error_msg)));
-                        continue;
-                    }
-                    Ok(None) => {
-                        // Process is still running, continue
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to check agent {} process status: {}", agent_name, e);
-                    }
-                }
-
+                let cwd = request.cwd.clone();
                 let result = conn.new_session(request).await.map_err(|err| {
                     log::error!("Agent {} new_session failed: {:?}", agent_name, err);
                     anyhow!(err)
                 });
 
-                if let Err(ref e) = result {
-                    log::error!("Agent {} new_session error details: {}", agent_name, e);
+                match result {
+                    Ok(ref response) => {
+                        session_roots
+                            .borrow_mut()
+                            .insert(response.session_id.to_string(), cwd);
+                    }
+                    Err(ref e) => {
+                        log::error!("Agent {} new_session error details: {}", agent_name, e);
+                    }
                 }
 
                 let _ = respond.send(result);
             }
             AgentCommand::ResumeSession { request, respond } => {
+                session_roots
+                    .borrow_mut()
+                    .insert(request.session_id.to_string(), request.cwd.clone());
                 let result = conn
                     .resume_session(*request)
                     .await
@@ -694,6 +980,9 @@ async fn agent_event_loop(
                 let _ = respond.send(result);
             }
             AgentCommand::LoadSession { request, respond } => {
+                session_roots
+                    .borrow_mut()
+                    .insert(request.session_id.to_string(), request.cwd.clone());
                 let result = conn.load_session(request).await.map_err(|err| anyhow!(err));
                 let _ = respond.send(result);
             }
@@ -716,83 +1005,455 @@ async fn agent_event_loop(
             AgentCommand::Shutdown { respond } => {
                 log::info!("Agent {} received shutdown command", agent_name);
                 let _ = respond.send(Ok(()));
-                break; // Exit the command loop to shutdown
+                break None; // Exit the command loop to shutdown
             }
         }
-    }
+    };
 
     log::info!("Agent {} command loop ended, cleaning up", agent_name);
 
     drop(conn);
+    if let Some(process) = process {
+        let _ = process.kill_tx.send(());
+        let _ = process.child_handle.await;
+    }
     let _ = io_handle.await;
 
-    // Check if child process is still running
-    match child.try_wait() {
-        Ok(Some(status)) => {
-            log::warn!(
-                "Agent {} process already exited with status: {:?}",
-                agent_name,
-                status
-            );
-        }
-        Ok(None) => {
-            // Process is still running, kill it
-            log::info!("Agent {} process still running, killing it", agent_name);
-            if let Err(e) = child.kill().await {
-                log::error!("Failed to kill agent {} process: {}", agent_name, e);
-            }
-        }
-        Err(e) => {
-            log::error!("Failed to check agent {} process status: {}", agent_name, e);
-        }
+    match crash_reason {
+        Some(reason) => Err(anyhow!("agent {} {}", agent_name, reason)),
+        None => Ok(()),
     }
-
-    Ok(())
 }
 
-/// GUI Client that publishes session updates to the event bus
-struct GuiClient {
-    agent_name: String,
-    permission_store: Arc<PermissionStore>,
-    session_bus: SessionUpdateBusContainer,
-    permission_bus: PermissionBusContainer,
+/// A spawned agent process and the tasks that own it
+struct AgentProcess {
+    stderr_handle: tokio::task::JoinHandle<()>,
+    kill_tx: oneshot::Sender<()>,
+    child_handle: tokio::task::JoinHandle<()>,
 }
 
-impl GuiClient {
-    pub fn new(
-        agent_name: String,
-        permission_store: Arc<PermissionStore>,
-        session_bus: SessionUpdateBusContainer,
-        permission_bus: PermissionBusContainer,
-    ) -> Self {
-        Self {
-            agent_name,
-            permission_store,
-            session_bus,
-            permission_bus,
-        }
-    }
+/// Byte streams to an agent plus whatever keeps them alive
+struct AgentStreams {
+    outgoing: BoxedWriter,
+    incoming: BoxedReader,
+    /// Resolves with a reason when a spawned process exits; never for network agents
+    exited: LocalBoxFuture<'static, String>,
+    process: Option<AgentProcess>,
 }
 
-#[async_trait::async_trait(?Send)]
-impl acp::Client for GuiClient {
-    async fn request_permission(
+/// Spawn the agent command and speak ACP over its stdio
+async fn spawn_agent_process(
+    agent_name: &str,
+    config: &AgentProcessConfig,
+    proxy_config: &ProxyConfig,
+    agent_logs: &AgentLogStore,
+) -> Result<AgentStreams> {
+    // Node.js environment validation
+    let requires_nodejs = config.command.ends_with(".js")
+        || config.command.ends_with(".ts")
+        || config.command.contains("node")
+        || config.command.contains("npx");
+
+    if requires_nodejs {
+        log::info!(
+            "Agent '{}' requires Node.js, validating environment...",
+            agent_name
+        );
+
+        use crate::core::nodejs::NodeJsChecker;
+        use std::path::PathBuf;
+
+        let custom_path = config.nodejs_path.as_ref().map(PathBuf::from);
+        let nodejs_checker = NodeJsChecker::new(custom_path);
+
+        match nodejs_checker.check_nodejs_available().await {
+            Ok(result) if result.available => {
+                log::info!(
+                    "Node.js found for '{}': {} ({})",
+                    agent_name,
+                    result.path.unwrap().display(),
+                    result.version.unwrap()
+                );
+            }
+            Ok(result) => {
+                let error_msg = format!(
+                    "Node.js required but not found for agent '{}'.\n\n{}",
+                    agent_name,
+                    result.install_hint.unwrap_or_default()
+                );
+                log::error!("{}", error_msg);
+                return Err(anyhow!(error_msg));
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to validate Node.js for '{}': {}", agent_name, e);
+                log::error!("{}", error_msg);
+                return Err(anyhow!(error_msg));
+            }
+        }
+    }
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut shell_cmd = tokio::process::Command::new("cmd");
+        let mut full_args = vec!["/C".to_string(), config.command.clone()];
+        full_args.extend(config.args.iter().cloned());
+        shell_cmd.args(&full_args);
+        shell_cmd
+    } else {
+        let mut cmd = tokio::process::Command::new(&config.command);
+        cmd.args(&config.args);
+        cmd
+    };
+
+    // Set environment variables from config
+    command.envs(&config.env);
+
+    // Set proxy environment variables if enabled
+    if let Some(proxy_url) = proxy_config.to_env_value() {
+        log::info!("Setting proxy for agent '{}': {}", agent_name, proxy_url);
+
+        // Set standard proxy environment variables
+        match proxy_config.proxy_type.as_str() {
+            "http" | "https" => {
+                command.env("HTTP_PROXY", &proxy_url);
+                command.env("HTTPS_PROXY", &proxy_url);
+                command.env("http_proxy", &proxy_url);
+                command.env("https_proxy", &proxy_url);
+            }
+            "socks5" => {
+                command.env("ALL_PROXY", &proxy_url);
+                command.env("all_proxy", &proxy_url);
+            }
+            _ => {
+                log::warn!(
+                    "Unknown proxy type '{}' for agent '{}'",
+                    proxy_config.proxy_type,
+                    agent_name
+                );
+            }
+        }
+    }
+
+    // Set stdio for all platforms
+    command.stdin(std::process::Stdio::piped());
+    command.stdout(std::process::Stdio::piped());
+    command.stderr(std::process::Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("failed to spawn agent {agent_name}"))?;
+    let outgoing = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("agent {agent_name} missing stdin"))?
+        .compat_write();
+    let incoming = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("agent {agent_name} missing stdout"))?
+        .compat();
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| anyhow!("agent {agent_name} missing stderr"))?;
+
+    // Capture stderr line by line into the agent's log buffer
+    let stderr_name = agent_name.to_string();
+    let stderr_logs = agent_logs.clone();
+    let stderr_handle = tokio::task::spawn_local(async move {
+        use tokio::io::AsyncBufReadExt;
+
+        let mut reader = tokio::io::BufReader::new(stderr);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) => break,
+                Ok(_) => {
+                    let line = String::from_utf8_lossy(&buf);
+                    let line = line.trim_end_matches(['\r', '\n']);
+                    log::debug!("[{}] {}", stderr_name, line);
+                    stderr_logs.push(&stderr_name, line);
+                }
+                Err(e) => {
+                    log::warn!("Failed to read stderr of agent {}: {}", stderr_name, e);
+                    break;
+                }
+            }
+        }
+    });
+
+    // Own the process in a watcher task: it reports an unexpected exit and
+    // kills the process once `kill_tx` is sent or dropped.
+    let (kill_tx, kill_rx) = oneshot::channel::<()>();
+    let (exited_tx, exited_rx) = oneshot::channel::<String>();
+    let watcher_name = agent_name.to_string();
+    let child_handle = tokio::task::spawn_local(async move {
+        let exited = match select(Box::pin(child.wait()), kill_rx).await {
+            Either::Left((status, _)) => Some(status),
+            Either::Right(_) => None,
+        };
+        match exited {
+            Some(Ok(status)) => {
+                let _ = exited_tx.send(format!("process exited ({})", status));
+            }
+            Some(Err(e)) => {
+                let _ = exited_tx.send(format!("failed to wait for process: {}", e));
+            }
+            None => {
+                log::info!("Agent {} process still running, killing it", watcher_name);
+                if let Err(e) = child.kill().await {
+                    log::error!("Failed to kill agent {} process: {}", watcher_name, e);
+                }
+            }
+        }
+    });
+
+    Ok(AgentStreams {
+        outgoing: Box::new(outgoing),
+        incoming: Box::new(incoming),
+        exited: Box::pin(async move {
+            exited_rx
+                .await
+                .unwrap_or_else(|_| "agent process ended".to_string())
+        }),
+        process: Some(AgentProcess {
+            stderr_handle,
+            kill_tx,
+            child_handle,
+        }),
+    })
+}
+
+/// Session ID -> workspace root for the sessions of one agent connection
+type SessionRoots = Rc<RefCell<HashMap<String, PathBuf>>>;
+
+/// GUI Client that publishes session updates to the event bus
+struct GuiClient {
+    agent_name: String,
+    permission_store: Arc<PermissionStore>,
+    session_bus: SessionUpdateBusContainer,
+    permission_bus: PermissionBusContainer,
+    file_change_bus: FileChangeBusContainer,
+    open_buffers: OpenBufferRegistry,
+    terminals: LocalTerminals,
+    session_roots: SessionRoots,
+    /// Capabilities advertised to this agent
+    features: ClientFeatures,
+    extensions: ExtensionRegistry,
+    extension_bus: ExtensionBusContainer,
+}
+
+impl GuiClient {
+    pub fn new(
+        agent_name: String,
+        permission_store: Arc<PermissionStore>,
+        session_bus: SessionUpdateBusContainer,
+        permission_bus: PermissionBusContainer,
+        file_change_bus: FileChangeBusContainer,
+        open_buffers: OpenBufferRegistry,
+        terminals: LocalTerminals,
+        session_roots: SessionRoots,
+        features: ClientFeatures,
+        extensions: ExtensionRegistry,
+        extension_bus: ExtensionBusContainer,
+    ) -> Self {
+        Self {
+            agent_name,
+            permission_store,
+            session_bus,
+            permission_bus,
+            file_change_bus,
+            open_buffers,
+            terminals,
+            session_roots,
+            features,
+            extensions,
+            extension_bus,
+        }
+    }
+
+    /// Reject calls to capabilities that were not advertised to the agent
+    fn require(&self, advertised: bool, capability: &str) -> acp::Result<()> {
+        if advertised {
+            Ok(())
+        } else {
+            Err(acp::Error::method_not_found().data(format!(
+                "{} is disabled for agent {}",
+                capability, self.agent_name
+            )))
+        }
+    }
+
+    fn extension_context(&self) -> ExtensionContext {
+        ExtensionContext {
+            agent_name: self.agent_name.clone(),
+        }
+    }
+
+    fn publish_unhandled_extension(&self, method: &str, kind: ExtensionKind, params: Value) {
+        self.extension_bus.publish(ExtensionEvent {
+            agent_name: self.agent_name.clone(),
+            method: method.to_string(),
+            kind,
+            params,
+        });
+    }
+
+    /// Remember an "always" answer as a rule for the session's workspace, or
+    /// for the session when its working directory is unknown
+    fn learn_permission_rule(
+        &self,
+        session_id: &str,
+        workspace: Option<PathBuf>,
+        args: &acp::RequestPermissionRequest,
+        response: &acp::RequestPermissionResponse,
+    ) {
+        let acp::RequestPermissionOutcome::Selected(selected) = &response.outcome else {
+            return;
+        };
+        let Some(decision) = args
+            .options
+            .iter()
+            .find(|option| option.option_id == selected.option_id)
+            .and_then(|option| permission_rules::always_decision(option.kind))
+        else {
+            return;
+        };
+
+        let scope = match workspace {
+            Some(path) => RuleScope::Workspace { path },
+            None => RuleScope::Session {
+                session_id: session_id.to_string(),
+            },
+        };
+        let Some(rule) = PermissionRule::learned(decision, scope, &args.tool_call) else {
+            log::info!("[GuiClient] Not learning a rule for a tool call without criteria");
+            return;
+        };
+        log::info!("[GuiClient] Learned permission rule {:?}", rule);
+        if let Err(e) = self.permission_store.rules().add(rule) {
+            log::warn!("Failed to save permission rule: {}", e);
+        }
+    }
+
+    fn session_root(&self, session_id: &acp::SessionId) -> acp::Result<PathBuf> {
+        self.session_roots
+            .borrow()
+            .get(&session_id.to_string())
+            .cloned()
+            .ok_or_else(|| {
+                acp::Error::invalid_params().data(format!("unknown session: {}", session_id))
+            })
+    }
+}
+
+/// Parameters of an extension message; malformed JSON is passed on as text
+fn extension_params(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn to_acp_exit_status(exit: TerminalExit) -> acp::TerminalExitStatus {
+    let mut status = acp::TerminalExitStatus::default();
+    status.exit_code = exit.exit_code;
+    status.signal = exit.signal;
+    status
+}
+
+#[async_trait::async_trait(?Send)]
+impl acp::Client for GuiClient {
+    async fn request_permission(
         &self,
         args: acp::RequestPermissionRequest,
     ) -> acp::Result<acp::RequestPermissionResponse> {
+        let session_id = args.session_id.to_string();
+        let workspace = self.session_roots.borrow().get(&session_id).cloned();
+        let request = RuleRequest {
+            session_id: &session_id,
+            workspace: workspace.as_deref(),
+            tool_call: &args.tool_call,
+        };
+        if let Some(resolution) = self
+            .permission_store
+            .rules()
+            .resolve(&request, &args.options)
+        {
+            log::info!(
+                "[GuiClient] Permission for '{}' in session '{}' answered by rule {} ({:?})",
+                args.tool_call.fields.title.as_deref().unwrap_or_default(),
+                session_id,
+                resolution.rule.id,
+                resolution.rule.decision
+            );
+            let outcome = acp::RequestPermissionOutcome::Selected(
+                acp::SelectedPermissionOutcome::new(resolution.option_id),
+            );
+            self.permission_store.audit(&PermissionAuditEntry::new(
+                workspace,
+                &self.agent_name,
+                &session_id,
+                &args.tool_call,
+                &args.options,
+                &outcome,
+                AuditResolver::Rule {
+                    rule_id: resolution.rule.id.clone(),
+                },
+            ));
+            return Ok(acp::RequestPermissionResponse::new(outcome));
+        }
+
+        // Autopilot approves what its guardrails allow and escalates the rest
+        let escalation = match self.permission_store.autopilot_verdict(
+            &session_id,
+            &args.tool_call,
+            workspace.as_deref(),
+            &args.options,
+        ) {
+            Some(AutopilotVerdict::Approve(option_id)) => {
+                log::info!(
+                    "[GuiClient] Autopilot approved '{}' in session '{}'",
+                    args.tool_call.fields.title.as_deref().unwrap_or_default(),
+                    session_id
+                );
+                let outcome = acp::RequestPermissionOutcome::Selected(
+                    acp::SelectedPermissionOutcome::new(option_id),
+                );
+                self.permission_store.audit(&PermissionAuditEntry::new(
+                    workspace,
+                    &self.agent_name,
+                    &session_id,
+                    &args.tool_call,
+                    &args.options,
+                    &outcome,
+                    AuditResolver::Autopilot,
+                ));
+                return Ok(acp::RequestPermissionResponse::new(outcome));
+            }
+            Some(AutopilotVerdict::Escalate(reason)) => {
+                log::info!(
+                    "[GuiClient] Autopilot escalated '{}' in session '{}': {}",
+                    args.tool_call.fields.title.as_deref().unwrap_or_default(),
+                    session_id,
+                    reason
+                );
+                Some(reason)
+            }
+            None => None,
+        };
+
         let (tx, rx) = oneshot::channel();
         let permission_id = self
             .permission_store
-            .add(self.agent_name.clone(), args.session_id.to_string(), tx)
+            .add(self.agent_name.clone(), session_id.clone(), tx)
             .await;
 
         // Publish permission request event to the permission bus
         let event = PermissionRequestEvent {
             permission_id: permission_id.clone(),
-            session_id: args.session_id.to_string(),
+            session_id: session_id.clone(),
             agent_name: self.agent_name.clone(),
-            tool_call: args.tool_call,
-            options: args.options,
+            tool_call: args.tool_call.clone(),
+            options: args.options.clone(),
+            escalation,
         };
 
         log::debug!(
@@ -800,59 +1461,169 @@ impl acp::Client for GuiClient {
             permission_id,
             event.session_id
         );
+        self.permission_store
+            .record_request(&event, workspace.clone())
+            .await;
         self.permission_bus.publish(event);
 
-        rx.await
-            .map_err(|_| acp::Error::internal_error().data("permission request channel closed"))
+        // On timeout the store either answers the request or flags it, so
+        // the response still arrives through the channel
+        let response = match self.permission_store.timeout() {
+            Some(timeout) => match select(rx, Box::pin(smol::Timer::after(timeout))).await {
+                Either::Left((response, _)) => response,
+                Either::Right((_, rx)) => {
+                    self.permission_store.expire(&permission_id).await;
+                    rx.await
+                }
+            },
+            None => rx.await,
+        }
+        .map_err(|_| acp::Error::internal_error().data("permission request channel closed"))?;
+        self.learn_permission_rule(&session_id, workspace, &args, &response);
+        Ok(response)
     }
 
     async fn write_text_file(
         &self,
-        _args: acp::WriteTextFileRequest,
+        args: acp::WriteTextFileRequest,
     ) -> acp::Result<acp::WriteTextFileResponse> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.write_text_file, "fs/write_text_file")?;
+        let root = self.session_root(&args.session_id)?;
+        let path = resolve_in_workspace(&root, &args.path)
+            .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                acp::Error::internal_error().data(format!(
+                    "failed to create {}: {}",
+                    parent.display(),
+                    e
+                ))
+            })?;
+        }
+        tokio::fs::write(&path, &args.content).await.map_err(|e| {
+            acp::Error::internal_error().data(format!("failed to write {}: {}", path.display(), e))
+        })?;
+
+        log::info!(
+            "[GuiClient] Agent '{}' wrote {} bytes to {:?}",
+            self.agent_name,
+            args.content.len(),
+            path
+        );
+
+        // Keep open editors consistent with what is now on disk
+        self.open_buffers.update_if_open(&path, &args.content);
+        self.file_change_bus.publish(FileChangeEvent {
+            path,
+            session_id: Some(args.session_id.to_string()),
+            agent_name: Some(self.agent_name.clone()),
+        });
+
+        Ok(acp::WriteTextFileResponse::default())
     }
 
     async fn read_text_file(
         &self,
-        _args: acp::ReadTextFileRequest,
+        args: acp::ReadTextFileRequest,
     ) -> acp::Result<acp::ReadTextFileResponse> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.read_text_file, "fs/read_text_file")?;
+        let root = self.session_root(&args.session_id)?;
+        let path = resolve_in_workspace(&root, &args.path)
+            .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?;
+
+        let content = read_in_workspace(&root, &path, &self.open_buffers)
+            .await
+            .map_err(|e| acp::Error::internal_error().data(e.to_string()))?;
+
+        Ok(acp::ReadTextFileResponse::new(slice_lines(
+            &content, args.line, args.limit,
+        )))
     }
 
     async fn create_terminal(
         &self,
-        _args: acp::CreateTerminalRequest,
+        args: acp::CreateTerminalRequest,
     ) -> Result<acp::CreateTerminalResponse, acp::Error> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.terminal, "terminal")?;
+        let root = self.session_root(&args.session_id)?;
+        let cwd = match args.cwd {
+            Some(cwd) => resolve_in_workspace(&root, &cwd)
+                .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?,
+            None => root,
+        };
+
+        let terminal_id = self
+            .terminals
+            .create(TerminalSpawnRequest {
+                session_id: args.session_id.to_string(),
+                command: args.command,
+                args: args.args,
+                env: args
+                    .env
+                    .into_iter()
+                    .map(|var| (var.name, var.value))
+                    .collect(),
+                cwd,
+                output_byte_limit: args.output_byte_limit,
+            })
+            .map_err(|e| acp::Error::internal_error().data(e.to_string()))?;
+
+        Ok(acp::CreateTerminalResponse::new(acp::TerminalId::new(
+            terminal_id,
+        )))
     }
 
     async fn terminal_output(
         &self,
-        _args: acp::TerminalOutputRequest,
+        args: acp::TerminalOutputRequest,
     ) -> acp::Result<acp::TerminalOutputResponse> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.terminal, "terminal")?;
+        let snapshot = self
+            .terminals
+            .output(&args.session_id.to_string(), &args.terminal_id.to_string())
+            .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?;
+
+        let mut response = acp::TerminalOutputResponse::new(snapshot.output, snapshot.truncated);
+        response.exit_status = snapshot.exit.map(to_acp_exit_status);
+        Ok(response)
     }
 
     async fn release_terminal(
         &self,
-        _args: acp::ReleaseTerminalRequest,
+        args: acp::ReleaseTerminalRequest,
     ) -> acp::Result<acp::ReleaseTerminalResponse> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.terminal, "terminal")?;
+        self.terminals
+            .release(&args.session_id.to_string(), &args.terminal_id.to_string())
+            .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?;
+        Ok(acp::ReleaseTerminalResponse::default())
     }
 
     async fn wait_for_terminal_exit(
         &self,
-        _args: acp::WaitForTerminalExitRequest,
+        args: acp::WaitForTerminalExitRequest,
     ) -> acp::Result<acp::WaitForTerminalExitResponse> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.terminal, "terminal")?;
+        let exit = self
+            .terminals
+            .wait_for_exit(&args.session_id.to_string(), &args.terminal_id.to_string())
+            .await
+            .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?;
+        Ok(acp::WaitForTerminalExitResponse::new(to_acp_exit_status(
+            exit,
+        )))
     }
 
     async fn kill_terminal_command(
         &self,
-        _args: acp::KillTerminalCommandRequest,
+        args: acp::KillTerminalCommandRequest,
     ) -> acp::Result<acp::KillTerminalCommandResponse> {
-        Err(acp::Error::method_not_found())
+        self.require(self.features.terminal, "terminal")?;
+        self.terminals
+            .kill(&args.session_id.to_string(), &args.terminal_id.to_string())
+            .map_err(|e| acp::Error::invalid_params().data(e.to_string()))?;
+        Ok(acp::KillTerminalCommandResponse::default())
     }
 
     async fn session_notification(
@@ -878,13 +1649,33 @@ impl acp::Client for GuiClient {
         Ok(())
     }
 
-    async fn ext_method(&self, _args: acp::ExtRequest) -> acp::Result<acp::ExtResponse> {
-        log::debug!("[GuiClient] ext_method called");
-        Err(acp::Error::method_not_found())
+    async fn ext_method(&self, args: acp::ExtRequest) -> acp::Result<acp::ExtResponse> {
+        log::debug!("[GuiClient] ext_method called: {}", args.method);
+        let params = extension_params(args.params.get());
+
+        let Some(handler) = self.extensions.method_handler(&args.method) else {
+            self.publish_unhandled_extension(&args.method, ExtensionKind::Method, params);
+            return Err(acp::Error::method_not_found());
+        };
+
+        let result = handler(&self.extension_context(), &params).map_err(|e| {
+            log::warn!("Extension method {} failed: {:#}", args.method, e);
+            acp::Error::internal_error().data(format!("{:#}", e))
+        })?;
+        serde_json::from_str::<acp::ExtResponse>(&result.to_string())
+            .map_err(|e| acp::Error::internal_error().data(e.to_string()))
     }
 
-    async fn ext_notification(&self, _args: acp::ExtNotification) -> acp::Result<()> {
-        log::debug!("[GuiClient] Received ExtNotification");
+    async fn ext_notification(&self, args: acp::ExtNotification) -> acp::Result<()> {
+        log::debug!("[GuiClient] Received ExtNotification: {}", args.method);
+        let params = extension_params(args.params.get());
+
+        match self.extensions.notification_handler(&args.method) {
+            Some(handler) => handler(&self.extension_context(), &params),
+            None => {
+                self.publish_unhandled_extension(&args.method, ExtensionKind::Notification, params)
+            }
+        }
         Ok(())
     }
 }
@@ -893,15 +1684,100 @@ pub struct PendingPermission {
     agent: String,
     session_id: String,
     responder: oneshot::Sender<acp::RequestPermissionResponse>,
+    /// Details shown in the permissions queue, once published
+    request: Option<PermissionRequestEvent>,
+    /// Working directory of the session, for the audit log
+    workspace: Option<PathBuf>,
+    requested_at: chrono::DateTime<chrono::Utc>,
+    overdue: bool,
+}
+
+/// A request waiting for an answer, as listed in the permissions queue
+#[derive(Clone, Debug)]
+pub struct QueuedPermission {
+    pub request: PermissionRequestEvent,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+    /// The timeout passed under the "keep waiting" policy
+    pub overdue: bool,
 }
 
 #[derive(Default)]
 pub struct PermissionStore {
     pending: RwLock<HashMap<String, PendingPermission>>,
     next_id: AtomicU64,
+    rules: PermissionRuleStore,
+    permission_bus: PermissionBusContainer,
+    config: std::sync::RwLock<PermissionConfig>,
+    audit: PermissionAuditLog,
+    autopilot: AutopilotSessions,
+    autopilot_policy: std::sync::RwLock<AutopilotPolicy>,
 }
 
 impl PermissionStore {
+    pub fn new(
+        rules: PermissionRuleStore,
+        permission_bus: PermissionBusContainer,
+        audit: PermissionAuditLog,
+        autopilot: AutopilotSessions,
+    ) -> Self {
+        Self {
+            rules,
+            permission_bus,
+            audit,
+            autopilot,
+            ..Default::default()
+        }
+    }
+
+    /// Apply the timeout policy and autopilot guardrails to requests from
+    /// now on
+    pub fn configure(&self, config: &PermissionConfig) {
+        *self.autopilot_policy.write().unwrap() = AutopilotPolicy::from_config(&config.autopilot);
+        *self.config.write().unwrap() = config.clone();
+    }
+
+    /// How autopilot answers a request, or `None` when the session's task
+    /// is not in autopilot mode
+    pub fn autopilot_verdict(
+        &self,
+        session_id: &str,
+        tool_call: &acp::ToolCallUpdate,
+        workspace: Option<&std::path::Path>,
+        options: &[acp::PermissionOption],
+    ) -> Option<AutopilotVerdict> {
+        if !self.autopilot.is_enabled(session_id) {
+            return None;
+        }
+        Some(
+            self.autopilot_policy
+                .read()
+                .unwrap()
+                .evaluate(tool_call, workspace, options),
+        )
+    }
+
+    /// How long a request waits before its timeout policy applies
+    pub fn timeout(&self) -> Option<Duration> {
+        self.config
+            .read()
+            .unwrap()
+            .timeout_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Rules consulted before a request is shown to the user
+    pub fn rules(&self) -> &PermissionRuleStore {
+        &self.rules
+    }
+
+    /// Append a decision to the audit log; failures are logged, never fatal
+    pub fn audit(&self, entry: &PermissionAuditEntry) {
+        if let Err(e) = self.audit.append(entry) {
+            log::warn!("Failed to write permission audit entry: {}", e);
+        }
+    }
+
     pub async fn add(
         &self,
         agent: String,
@@ -915,19 +1791,159 @@ impl PermissionStore {
                 agent,
                 session_id,
                 responder,
+                request: None,
+                workspace: None,
+                requested_at: chrono::Utc::now(),
+                overdue: false,
             },
         );
         id
     }
 
+    /// Keep the published details of a request for the permissions queue
+    /// and the audit log
+    pub async fn record_request(&self, event: &PermissionRequestEvent, workspace: Option<PathBuf>) {
+        if let Some(pending) = self.pending.write().await.get_mut(&event.permission_id) {
+            pending.request = Some(event.clone());
+            pending.workspace = workspace;
+        }
+    }
+
+    /// Published requests still waiting for an answer, oldest first
+    pub async fn pending(&self) -> Vec<QueuedPermission> {
+        let mut queued: Vec<QueuedPermission> = self
+            .pending
+            .read()
+            .await
+            .values()
+            .filter_map(|pending| {
+                Some(QueuedPermission {
+                    request: pending.request.clone()?,
+                    requested_at: pending.requested_at,
+                    overdue: pending.overdue,
+                })
+            })
+            .collect();
+        queued.sort_by_key(|queued| queued.requested_at);
+        queued
+    }
+
     /// Respond to a permission request with the given response
     pub async fn respond(
         &self,
         id: &str,
         response: acp::RequestPermissionResponse,
+    ) -> anyhow::Result<()> {
+        self.resolve(id, response, PermissionResolution::User).await
+    }
+
+    /// Apply the timeout policy to a request that got no answer in time
+    pub async fn expire(&self, id: &str) {
+        let action = self.config.read().unwrap().on_timeout;
+        match action {
+            PermissionTimeoutAction::Deny => {
+                let reject = self.pending.read().await.get(id).and_then(|pending| {
+                    let options = &pending.request.as_ref()?.options;
+                    [
+                        acp::PermissionOptionKind::RejectOnce,
+                        acp::PermissionOptionKind::RejectAlways,
+                    ]
+                    .into_iter()
+                    .find_map(|kind| options.iter().find(|option| option.kind == kind))
+                    .map(|option| option.option_id.clone())
+                });
+                // Without a reject option the request is cancelled instead
+                let outcome = match reject {
+                    Some(option_id) => acp::RequestPermissionOutcome::Selected(
+                        acp::SelectedPermissionOutcome::new(option_id),
+                    ),
+                    None => acp::RequestPermissionOutcome::Cancelled,
+                };
+                log::info!("Permission request {} timed out, denying", id);
+                if let Err(e) = self
+                    .resolve(
+                        id,
+                        acp::RequestPermissionResponse::new(outcome),
+                        PermissionResolution::TimedOut,
+                    )
+                    .await
+                {
+                    log::debug!("Timed out permission request already answered: {}", e);
+                }
+            }
+            PermissionTimeoutAction::Wait => {
+                let session_id = {
+                    let mut pending = self.pending.write().await;
+                    let Some(pending) = pending.get_mut(id) else {
+                        return;
+                    };
+                    pending.overdue = true;
+                    pending.session_id.clone()
+                };
+                log::info!("Permission request {} is overdue, still waiting", id);
+                self.permission_bus
+                    .publish_status(PermissionStatusEvent::Overdue {
+                        permission_id: id.to_string(),
+                        session_id,
+                    });
+            }
+        }
+    }
+
+    /// Answer every pending request of a session with `Cancelled`, as ACP
+    /// requires after `session/cancel`. Returns the number of requests.
+    pub async fn cancel_session(&self, session_id: &str) -> usize {
+        let ids: Vec<String> = self
+            .pending
+            .read()
+            .await
+            .iter()
+            .filter(|(_, pending)| pending.session_id == session_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut cancelled = 0;
+        for id in ids {
+            let response =
+                acp::RequestPermissionResponse::new(acp::RequestPermissionOutcome::Cancelled);
+            if self
+                .resolve(&id, response, PermissionResolution::Cancelled)
+                .await
+                .is_ok()
+            {
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    async fn resolve(
+        &self,
+        id: &str,
+        response: acp::RequestPermissionResponse,
+        resolution: PermissionResolution,
     ) -> anyhow::Result<()> {
         let pending = self.remove(id).await;
         if let Some(pending) = pending {
+            if let Some(request) = &pending.request {
+                self.audit(&PermissionAuditEntry::new(
+                    pending.workspace.clone(),
+                    &pending.agent,
+                    &pending.session_id,
+                    &request.tool_call,
+                    &request.options,
+                    &response.outcome,
+                    resolution.into(),
+                ));
+            }
+            self.permission_bus
+                .publish_status(PermissionStatusEvent::Resolved {
+                    permission_id: id.to_string(),
+                    session_id: pending.session_id,
+                    agent_name: pending.agent,
+                    outcome: response.outcome.clone(),
+                    resolution,
+                });
             pending
                 .responder
                 .send(response)
@@ -1054,4 +2070,102 @@ mod tests {
         let result = store.respond(&id, response2).await;
         assert!(result.is_err());
     }
+
+    fn permission_event(id: &str, session_id: &str) -> PermissionRequestEvent {
+        PermissionRequestEvent {
+            permission_id: id.to_string(),
+            session_id: session_id.to_string(),
+            agent_name: "agent".to_string(),
+            tool_call: acp::ToolCallUpdate::new(
+                acp::ToolCallId::from("tool-1".to_string()),
+                acp::ToolCallUpdateFields::default(),
+            ),
+            options: serde_json::from_value(serde_json::json!([
+                { "optionId": "allow", "name": "Allow", "kind": "allow_once" },
+                { "optionId": "reject", "name": "Reject", "kind": "reject_once" }
+            ]))
+            .unwrap(),
+            escalation: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permission_store_cancel_session() {
+        let bus = PermissionBusContainer::new();
+        let resolved = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let resolved_clone = resolved.clone();
+        bus.subscribe_status(move |event| {
+            if let PermissionStatusEvent::Resolved { resolution, .. } = event {
+                resolved_clone.lock().unwrap().push(resolution.clone());
+            }
+        });
+        let store = PermissionStore::new(
+            PermissionRuleStore::new(),
+            bus,
+            PermissionAuditLog::default(),
+            AutopilotSessions::new(),
+        );
+
+        let (tx1, rx1) = oneshot::channel();
+        let (tx2, _rx2) = oneshot::channel();
+        let id1 = store.add("agent".into(), "s1".into(), tx1).await;
+        let id2 = store.add("agent".into(), "s2".into(), tx2).await;
+        store
+            .record_request(&permission_event(&id1, "s1"), None)
+            .await;
+        store
+            .record_request(&permission_event(&id2, "s2"), None)
+            .await;
+        assert_eq!(store.pending().await.len(), 2);
+
+        assert_eq!(store.cancel_session("s1").await, 1);
+        assert!(matches!(
+            rx1.await.unwrap().outcome,
+            acp::RequestPermissionOutcome::Cancelled
+        ));
+        assert_eq!(
+            *resolved.lock().unwrap(),
+            vec![PermissionResolution::Cancelled]
+        );
+
+        let pending = store.pending().await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].request.permission_id, id2);
+    }
+
+    #[tokio::test]
+    async fn test_permission_store_expire_policies() {
+        let store = PermissionStore::default();
+        store.configure(&PermissionConfig {
+            timeout_secs: Some(30),
+            on_timeout: PermissionTimeoutAction::Wait,
+            ..Default::default()
+        });
+        assert_eq!(store.timeout(), Some(Duration::from_secs(30)));
+
+        let (tx, rx) = oneshot::channel();
+        let id = store.add("agent".into(), "s1".into(), tx).await;
+        store
+            .record_request(&permission_event(&id, "s1"), None)
+            .await;
+
+        // Waiting only flags the request
+        store.expire(&id).await;
+        assert!(store.pending().await[0].overdue);
+
+        // Denying picks the reject option
+        store.configure(&PermissionConfig {
+            timeout_secs: Some(30),
+            on_timeout: PermissionTimeoutAction::Deny,
+            ..Default::default()
+        });
+        store.expire(&id).await;
+        match rx.await.unwrap().outcome {
+            acp::RequestPermissionOutcome::Selected(selected) => {
+                assert_eq!(selected.option_id.to_string(), "reject");
+            }
+            _ => panic!("Expected Selected outcome"),
+        }
+        assert!(store.pending().await.is_empty());
+    }
 }
@@ -0,0 +1,293 @@
+//! Handlers for ACP extension methods and notifications.
+//!
+//! Agents may send requests and notifications outside the ACP schema; their
+//! method names start with an underscore (`_claude/...`). Modules register
+//! handlers in the [`ExtensionRegistry`] held by `AgentManager`, either for an
+//! exact method name or for a pattern using `*` as a wildcard. Requests
+//! without a handler are answered with `method_not_found`; notifications
+//! without one are published on the extension bus so panels can react.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::event_bus::{
+    ExtensionBusContainer, WorkspaceUpdateBusContainer, WorkspaceUpdateEvent,
+};
+
+use super::protocol_trace::ProtocolTraceStore;
+
+/// Notifications carrying agent-provided status text, e.g. `_claude/status`
+pub const STATUS_NOTIFICATION_PATTERN: &str = "*/status";
+
+/// Whether an extension message expects a response
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionKind {
+    Method,
+    Notification,
+}
+
+/// Where an extension message came from
+#[derive(Clone, Debug)]
+pub struct ExtensionContext {
+    pub agent_name: String,
+}
+
+/// Answers an extension request with a JSON result
+pub type ExtMethodHandler = Arc<dyn Fn(&ExtensionContext, &Value) -> Result<Value> + Send + Sync>;
+/// Reacts to an extension notification
+pub type ExtNotificationHandler = Arc<dyn Fn(&ExtensionContext, &Value) + Send + Sync>;
+
+/// Handlers registered by method name or pattern
+#[derive(Clone, Default)]
+pub struct ExtensionRegistry {
+    methods: Arc<RwLock<BTreeMap<String, ExtMethodHandler>>>,
+    notifications: Arc<RwLock<BTreeMap<String, ExtNotificationHandler>>>,
+}
+
+impl ExtensionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle extension requests matching `pattern`, replacing an earlier
+    /// handler for the same pattern
+    pub fn register_method<F>(&self, pattern: &str, handler: F)
+    where
+        F: Fn(&ExtensionContext, &Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.methods
+            .write()
+            .unwrap()
+            .insert(normalize_method(pattern).to_string(), Arc::new(handler));
+    }
+
+    /// Handle extension notifications matching `pattern`, replacing an
+    /// earlier handler for the same pattern
+    pub fn register_notification<F>(&self, pattern: &str, handler: F)
+    where
+        F: Fn(&ExtensionContext, &Value) + Send + Sync + 'static,
+    {
+        self.notifications
+            .write()
+            .unwrap()
+            .insert(normalize_method(pattern).to_string(), Arc::new(handler));
+    }
+
+    /// Remove the request handler registered for `pattern`
+    pub fn unregister_method(&self, pattern: &str) -> bool {
+        self.methods
+            .write()
+            .unwrap()
+            .remove(normalize_method(pattern))
+            .is_some()
+    }
+
+    /// Remove the notification handler registered for `pattern`
+    pub fn unregister_notification(&self, pattern: &str) -> bool {
+        self.notifications
+            .write()
+            .unwrap()
+            .remove(normalize_method(pattern))
+            .is_some()
+    }
+
+    /// Registered request method names and patterns, sorted
+    pub fn method_patterns(&self) -> Vec<String> {
+        self.methods.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Registered notification method names and patterns, sorted
+    pub fn notification_patterns(&self) -> Vec<String> {
+        self.notifications.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Handler for an extension request, if any
+    pub fn method_handler(&self, method: &str) -> Option<ExtMethodHandler> {
+        find_handler(&self.methods.read().unwrap(), method)
+    }
+
+    /// Handler for an extension notification, if any
+    pub fn notification_handler(&self, method: &str) -> Option<ExtNotificationHandler> {
+        find_handler(&self.notifications.read().unwrap(), method)
+    }
+}
+
+/// The exact name wins; otherwise the most specific matching pattern, i.e.
+/// the one with the most literal characters
+fn find_handler<H: Clone>(handlers: &BTreeMap<String, H>, method: &str) -> Option<H> {
+    let method = normalize_method(method);
+    if let Some(handler) = handlers.get(method) {
+        return Some(handler.clone());
+    }
+    handlers
+        .iter()
+        .filter(|(pattern, _)| pattern.contains('*') && pattern_matches(pattern, method))
+        .max_by_key(|(pattern, _)| pattern.replace('*', "").len())
+        .map(|(_, handler)| handler.clone())
+}
+
+/// Extension methods are written with a leading underscore on the wire; the
+/// ACP library may or may not strip it, so names are compared without it
+pub fn normalize_method(method: &str) -> &str {
+    method.strip_prefix('_').unwrap_or(method)
+}
+
+/// Match `method` against a pattern where `*` stands for any text
+fn pattern_matches(pattern: &str, method: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = method.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// An extension no handler claimed, as listed by the protocol inspector
+#[derive(Clone, Debug, Serialize)]
+pub struct UnknownExtension {
+    pub agent_name: String,
+    pub method: String,
+    pub kind: ExtensionKind,
+    pub count: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Status text from a `*/status` notification
+#[derive(Debug, PartialEq)]
+struct AgentStatusText {
+    session_id: Option<String>,
+    /// `None` clears the status
+    text: Option<String>,
+}
+
+/// Accepts `{ "sessionId"?, "text" | "message" | "status": string | null }`
+fn parse_status_text(params: &Value) -> Option<AgentStatusText> {
+    let session_id = params
+        .get("sessionId")
+        .or_else(|| params.get("session_id"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let text = ["text", "message", "status"]
+        .into_iter()
+        .find_map(|key| params.get(key))?;
+    let text = match text {
+        Value::Null => None,
+        Value::String(text) if text.trim().is_empty() => None,
+        Value::String(text) => Some(text.trim().to_string()),
+        _ => return None,
+    };
+    Some(AgentStatusText { session_id, text })
+}
+
+/// Install the handlers that ship with AgentX:
+/// - unknown extensions are listed in the protocol inspector
+/// - `*/status` notifications are shown as status text in conversations
+pub fn register_builtin_handlers(
+    registry: &ExtensionRegistry,
+    extension_bus: &ExtensionBusContainer,
+    workspace_bus: &WorkspaceUpdateBusContainer,
+    protocol_trace: &ProtocolTraceStore,
+) {
+    let protocol_trace = protocol_trace.clone();
+    extension_bus.subscribe(move |event| {
+        protocol_trace.note_unknown_extension(&event.agent_name, &event.method, event.kind);
+    });
+
+    let workspace_bus = workspace_bus.clone();
+    registry.register_notification(STATUS_NOTIFICATION_PATTERN, move |context, params| {
+        match parse_status_text(params) {
+            Some(status) => workspace_bus.publish(WorkspaceUpdateEvent::AgentStatusText {
+                agent_name: context.agent_name.clone(),
+                session_id: status.session_id,
+                text: status.text,
+            }),
+            None => log::debug!(
+                "Ignoring status notification from {} without text: {}",
+                context.agent_name,
+                params
+            ),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("claude/status", "claude/status"));
+        assert!(!pattern_matches("claude/status", "claude/statuses"));
+        assert!(pattern_matches("claude/*", "claude/usage"));
+        assert!(!pattern_matches("claude/*", "zed/usage"));
+        assert!(pattern_matches("*/status", "zed/status"));
+        assert!(!pattern_matches("*/status", "zed/status/extra"));
+        assert!(pattern_matches("claude/*/done", "claude/tool/done"));
+        assert!(pattern_matches("*", "anything"));
+    }
+
+    #[test]
+    fn test_registry_prefers_exact_then_most_specific_pattern() {
+        let registry = ExtensionRegistry::new();
+        registry.register_method("*", |_, _| Ok(Value::from("any")));
+        registry.register_method("_claude/*", |_, _| Ok(Value::from("claude")));
+        registry.register_method("_claude/usage", |_, _| Ok(Value::from("usage")));
+
+        let context = ExtensionContext {
+            agent_name: "claude".to_string(),
+        };
+        let call = |method: &str| {
+            registry.method_handler(method).unwrap()(&context, &Value::Null).unwrap()
+        };
+
+        // Leading underscores are optional on both sides
+        assert_eq!(call("claude/usage"), "usage");
+        assert_eq!(call("_claude/usage"), "usage");
+        assert_eq!(call("_claude/other"), "claude");
+        assert_eq!(call("_zed/other"), "any");
+
+        assert!(registry.unregister_method("*"));
+        assert!(registry.method_handler("_zed/other").is_none());
+    }
+
+    #[test]
+    fn test_parse_status_text() {
+        let status = parse_status_text(&serde_json::json!({
+            "sessionId": "s1",
+            "text": " Compacting conversation "
+        }))
+        .unwrap();
+        assert_eq!(status.session_id.as_deref(), Some("s1"));
+        assert_eq!(status.text.as_deref(), Some("Compacting conversation"));
+
+        let cleared = parse_status_text(&serde_json::json!({ "message": null })).unwrap();
+        assert_eq!(
+            cleared,
+            AgentStatusText {
+                session_id: None,
+                text: None
+            }
+        );
+
+        assert!(parse_status_text(&serde_json::json!({ "progress": 3 })).is_none());
+    }
+}
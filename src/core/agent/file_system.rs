@@ -0,0 +1,257 @@
+//! File system support for the ACP `fs/*` client methods.
+//!
+//! Agents read through [`OpenBufferRegistry`] first so they see unsaved editor
+//! contents. Both reads and writes are confined to the session's workspace root.
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Result, anyhow};
+
+/// Shared view of the text buffers currently open in code editors.
+///
+/// Editors update it from the GUI thread; agent workers read it from their own
+/// runtime threads.
+#[derive(Clone, Default)]
+pub struct OpenBufferRegistry {
+    buffers: Arc<RwLock<HashMap<PathBuf, String>>>,
+}
+
+impl OpenBufferRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the current contents of an open buffer
+    pub fn update(&self, path: impl Into<PathBuf>, content: impl Into<String>) {
+        self.buffers
+            .write()
+            .unwrap()
+            .insert(normalize_path(&path.into()), content.into());
+    }
+
+    /// Replace the contents only if the buffer is currently open
+    pub fn update_if_open(&self, path: &Path, content: &str) {
+        if let Some(buffer) = self.buffers.write().unwrap().get_mut(&normalize_path(path)) {
+            *buffer = content.to_string();
+        }
+    }
+
+    /// Forget a buffer (editor closed or switched files)
+    pub fn remove(&self, path: &Path) {
+        self.buffers.write().unwrap().remove(&normalize_path(path));
+    }
+
+    /// Get the contents of an open buffer
+    pub fn get(&self, path: &Path) -> Option<String> {
+        self.buffers
+            .read()
+            .unwrap()
+            .get(&normalize_path(path))
+            .cloned()
+    }
+}
+
+/// Lexically normalize a path, resolving `.` and `..` without touching the disk
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Resolve an agent-supplied path and ensure it stays inside `root`.
+///
+/// Symlinks are followed for the longest existing prefix so a link inside the
+/// workspace cannot be used to escape it.
+pub fn resolve_in_workspace(root: &Path, path: &Path) -> Result<PathBuf> {
+    if !path.is_absolute() {
+        return Err(anyhow!("path must be absolute: {}", path.display()));
+    }
+
+    let root = normalize_path(root);
+    let target = normalize_path(path);
+    if !target.starts_with(&root) {
+        return Err(anyhow!(
+            "path {} is outside of workspace {}",
+            target.display(),
+            root.display()
+        ));
+    }
+
+    if let (Ok(real_root), Some(real_target)) =
+        (root.canonicalize(), canonicalize_existing(&target))
+    {
+        if !real_target.starts_with(&real_root) {
+            return Err(anyhow!(
+                "path {} resolves outside of workspace {}",
+                target.display(),
+                root.display()
+            ));
+        }
+    }
+
+    Ok(target)
+}
+
+/// Read a text file inside `root` for an agent.
+///
+/// The path is confined like writes are, and an open editor buffer wins over
+/// the file on disk so agents see unsaved changes.
+pub async fn read_in_workspace(
+    root: &Path,
+    path: &Path,
+    open_buffers: &OpenBufferRegistry,
+) -> Result<String> {
+    let path = resolve_in_workspace(root, path)?;
+    if let Some(content) = open_buffers.get(&path) {
+        return Ok(content);
+    }
+    tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))
+}
+
+/// Canonicalize the longest existing ancestor of `path` and re-append the rest
+fn canonicalize_existing(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(real) = existing.canonicalize() {
+            let mut resolved = real;
+            for part in rest.iter().rev() {
+                resolved.push(part);
+            }
+            return Some(resolved);
+        }
+        rest.push(existing.file_name()?.to_os_string());
+        existing = existing.parent()?;
+    }
+}
+
+/// Select a line range from `content`.
+///
+/// `line` is 1-based; `limit` caps the number of returned lines.
+pub fn slice_lines(content: &str, line: Option<u32>, limit: Option<u32>) -> String {
+    if line.is_none() && limit.is_none() {
+        return content.to_string();
+    }
+
+    let start = line.unwrap_or(1).max(1) as usize - 1;
+    let lines = content.split_inclusive('\n').skip(start);
+    match limit {
+        Some(limit) => lines.take(limit as usize).collect(),
+        None => lines.collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path_resolves_parent_dirs() {
+        let path = normalize_path(Path::new("/work/project/./src/../Cargo.toml"));
+        assert_eq!(path, PathBuf::from("/work/project/Cargo.toml"));
+    }
+
+    #[test]
+    fn test_resolve_in_workspace_rejects_escape() {
+        let root = Path::new("/work/project");
+
+        assert!(resolve_in_workspace(root, Path::new("/work/project/src/main.rs")).is_ok());
+        assert!(resolve_in_workspace(root, Path::new("/work/project/../secret")).is_err());
+        assert!(resolve_in_workspace(root, Path::new("/etc/passwd")).is_err());
+        assert!(resolve_in_workspace(root, Path::new("relative.txt")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_in_workspace_rejects_symlink_escape() {
+        let outside = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let link = workspace.path().join("link");
+        std::os::unix::fs::symlink(outside.path(), &link).unwrap();
+
+        assert!(resolve_in_workspace(workspace.path(), &link.join("file.txt")).is_err());
+        assert!(resolve_in_workspace(workspace.path(), &workspace.path().join("new.txt")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_read_in_workspace_refuses_outside_paths() {
+        let outside = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let secret = outside.path().join("id_rsa");
+        std::fs::write(&secret, "secret").unwrap();
+        let file = workspace.path().join("notes.txt");
+        std::fs::write(&file, "on disk").unwrap();
+        let buffers = OpenBufferRegistry::new();
+
+        assert!(
+            read_in_workspace(workspace.path(), &secret, &buffers)
+                .await
+                .is_err()
+        );
+        // An open buffer outside the workspace is refused too
+        buffers.update(&secret, "unsaved secret");
+        assert!(
+            read_in_workspace(workspace.path(), &secret, &buffers)
+                .await
+                .is_err()
+        );
+
+        assert_eq!(
+            read_in_workspace(workspace.path(), &file, &buffers)
+                .await
+                .unwrap(),
+            "on disk"
+        );
+        buffers.update(&file, "unsaved");
+        assert_eq!(
+            read_in_workspace(workspace.path(), &file, &buffers)
+                .await
+                .unwrap(),
+            "unsaved"
+        );
+    }
+
+    #[test]
+    fn test_slice_lines() {
+        let content = "one\ntwo\nthree\nfour\n";
+
+        assert_eq!(slice_lines(content, None, None), content);
+        assert_eq!(slice_lines(content, Some(2), None), "two\nthree\nfour\n");
+        assert_eq!(slice_lines(content, Some(2), Some(2)), "two\nthree\n");
+        assert_eq!(slice_lines(content, None, Some(1)), "one\n");
+        assert_eq!(slice_lines(content, Some(10), None), "");
+    }
+
+    #[test]
+    fn test_open_buffer_registry() {
+        let registry = OpenBufferRegistry::new();
+        let path = Path::new("/work/project/src/lib.rs");
+
+        assert!(registry.get(path).is_none());
+        registry.update_if_open(path, "ignored");
+        assert!(registry.get(path).is_none());
+
+        registry.update(path, "unsaved");
+        assert_eq!(registry.get(path).as_deref(), Some("unsaved"));
+
+        registry.update_if_open(Path::new("/work/project/src/../src/lib.rs"), "written");
+        assert_eq!(registry.get(path).as_deref(), Some("written"));
+
+        registry.remove(path);
+        assert!(registry.get(path).is_none());
+    }
+}
@@ -1,5 +1,32 @@
 // Agent client modules
+mod agent_logs;
+mod autopilot;
+mod capabilities;
 mod client;
+mod extensions;
+mod file_system;
+mod permission_audit;
+mod permission_rules;
+mod protocol_trace;
+mod supervisor;
+mod terminal;
+mod transport;
 
 // Re-export agent types
-pub use client::{AgentHandle, AgentManager, PermissionStore};
+pub use agent_logs::{AgentLogLine, AgentLogStore, STDERR_TAIL_LINES, format_log_line};
+pub use autopilot::{AutopilotPolicy, AutopilotSessions, AutopilotVerdict};
+pub use client::{AgentHandle, AgentManager, PermissionStore, QueuedPermission};
+pub use extensions::{
+    ExtMethodHandler, ExtNotificationHandler, ExtensionContext, ExtensionKind, ExtensionRegistry,
+    UnknownExtension, register_builtin_handlers,
+};
+pub use file_system::{OpenBufferRegistry, normalize_path};
+pub use permission_audit::{
+    AuditChoice, AuditResolver, PermissionAuditEntry, PermissionAuditLog, export_csv, export_json,
+};
+pub use permission_rules::{
+    PermissionRule, PermissionRuleStore, RuleDecision, RuleResolution, RuleScope,
+};
+pub use protocol_trace::{FrameKind, ProtocolTraceStore, TraceDirection, TraceFrame};
+pub use supervisor::{AgentExit, RestartPolicy};
+pub use terminal::{TerminalExit, TerminalRegistry, TerminalSnapshot};
@@ -0,0 +1,396 @@
+//! Append-only record of permission decisions.
+//!
+//! Every answered permission request becomes one [`PermissionAuditEntry`],
+//! appended as a JSON line to a file per workspace under the user data
+//! directory. Entries are never rewritten; the audit viewer reads them back
+//! and exports them as JSON or CSV.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead as _, BufReader, Write as _},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use agent_client_protocol as acp;
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::permission_rules::tool_kind_name;
+use crate::core::event_bus::PermissionResolution;
+use crate::utils::csv;
+
+/// File for sessions whose working directory is unknown
+const NO_WORKSPACE_FILE: &str = "no-workspace.jsonl";
+
+/// Who or what decided a permission request
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditResolver {
+    User,
+    /// Answered by a permission rule without asking
+    Rule {
+        rule_id: String,
+    },
+    /// Approved by the autopilot mode of the session's task
+    Autopilot,
+    TimedOut,
+    Cancelled,
+}
+
+impl AuditResolver {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditResolver::User => "user",
+            AuditResolver::Rule { .. } => "rule",
+            AuditResolver::Autopilot => "autopilot",
+            AuditResolver::TimedOut => "timeout",
+            AuditResolver::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl From<PermissionResolution> for AuditResolver {
+    fn from(resolution: PermissionResolution) -> Self {
+        match resolution {
+            PermissionResolution::User => AuditResolver::User,
+            PermissionResolution::TimedOut => AuditResolver::TimedOut,
+            PermissionResolution::Cancelled => AuditResolver::Cancelled,
+        }
+    }
+}
+
+/// The option picked for a request, or `None` when it was cancelled
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditChoice {
+    pub option_id: String,
+    pub name: String,
+    pub kind: acp::PermissionOptionKind,
+}
+
+impl AuditChoice {
+    pub fn is_allow(&self) -> bool {
+        matches!(
+            self.kind,
+            acp::PermissionOptionKind::AllowOnce | acp::PermissionOptionKind::AllowAlways
+        )
+    }
+}
+
+/// One answered permission request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PermissionAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub workspace: Option<PathBuf>,
+    pub agent_name: String,
+    pub session_id: String,
+    /// Tool call the agent asked about, as received
+    pub tool_call: acp::ToolCallUpdate,
+    pub options: Vec<acp::PermissionOption>,
+    pub choice: Option<AuditChoice>,
+    pub resolver: AuditResolver,
+}
+
+impl PermissionAuditEntry {
+    pub fn new(
+        workspace: Option<PathBuf>,
+        agent_name: &str,
+        session_id: &str,
+        tool_call: &acp::ToolCallUpdate,
+        options: &[acp::PermissionOption],
+        outcome: &acp::RequestPermissionOutcome,
+        resolver: AuditResolver,
+    ) -> Self {
+        let choice = match outcome {
+            acp::RequestPermissionOutcome::Selected(selected) => options
+                .iter()
+                .find(|option| option.option_id == selected.option_id)
+                .map(|option| AuditChoice {
+                    option_id: option.option_id.to_string(),
+                    name: option.name.clone(),
+                    kind: option.kind,
+                }),
+            _ => None,
+        };
+        Self {
+            timestamp: Utc::now(),
+            workspace,
+            agent_name: agent_name.to_string(),
+            session_id: session_id.to_string(),
+            tool_call: tool_call.clone(),
+            options: options.to_vec(),
+            choice,
+            resolver,
+        }
+    }
+
+    pub fn tool_title(&self) -> &str {
+        self.tool_call.fields.title.as_deref().unwrap_or_default()
+    }
+
+    pub fn tool_kind(&self) -> Option<String> {
+        tool_kind_name(&self.tool_call)
+    }
+
+    /// Whether the tool call was allowed to run
+    pub fn allowed(&self) -> bool {
+        self.choice.as_ref().is_some_and(AuditChoice::is_allow)
+    }
+}
+
+/// Append-only audit files, one per workspace
+#[derive(Clone, Default)]
+pub struct PermissionAuditLog {
+    /// `None` keeps no record
+    dir: Option<PathBuf>,
+    /// Serializes appends from agent worker threads
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl PermissionAuditLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Append an entry to its workspace's file
+    pub fn append(&self, entry: &PermissionAuditEntry) -> Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let path = dir.join(file_name(entry.workspace.as_deref()));
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().unwrap();
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("failed to append to {}", path.display()))
+    }
+
+    /// Workspaces with recorded decisions; `None` stands for sessions
+    /// without a known working directory
+    pub fn workspaces(&self) -> Vec<Option<PathBuf>> {
+        let Some(dir) = &self.dir else {
+            return Vec::new();
+        };
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut workspaces: Vec<Option<PathBuf>> = read_dir
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
+            .filter_map(|entry| {
+                // Every entry of a file names the same workspace
+                let file = fs::File::open(entry.path()).ok()?;
+                let first = BufReader::new(file).lines().next()?.ok()?;
+                let entry: PermissionAuditEntry = serde_json::from_str(&first).ok()?;
+                Some(entry.workspace)
+            })
+            .collect();
+        workspaces.sort();
+        workspaces
+    }
+
+    /// Entries of one workspace, oldest first. Lines that fail to parse,
+    /// e.g. a line cut short by a crash, are skipped.
+    pub fn entries(&self, workspace: Option<&Path>) -> Result<Vec<PermissionAuditEntry>> {
+        let Some(dir) = &self.dir else {
+            return Ok(Vec::new());
+        };
+        let path = dir.join(file_name(workspace));
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("failed to open {}", path.display())),
+        };
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => log::warn!("Skipping invalid audit line in {}: {}", path.display(), e),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Stable file name for a workspace: its directory name plus a hash of the
+/// full path
+fn file_name(workspace: Option<&Path>) -> String {
+    let Some(workspace) = workspace else {
+        return NO_WORKSPACE_FILE.to_string();
+    };
+    let name: String = workspace
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(40)
+        .collect();
+    // FNV-1a, so names do not change between builds
+    let hash = workspace
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{}-{:016x}.jsonl", name, hash)
+}
+
+/// Entries as a pretty-printed JSON array
+pub fn export_json(entries: &[PermissionAuditEntry]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(entries)?)
+}
+
+/// Entries as CSV with one row per decision
+pub fn export_csv(entries: &[PermissionAuditEntry]) -> String {
+    let mut csv = String::from(
+        "timestamp,workspace,agent,session_id,tool_kind,tool_title,choice,choice_kind,allowed,resolver,rule_id\n",
+    );
+    for entry in entries {
+        let (choice, choice_kind) = match &entry.choice {
+            Some(choice) => (
+                choice.name.clone(),
+                serde_json::to_value(choice.kind)
+                    .ok()
+                    .and_then(|kind| kind.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+            ),
+            None => (String::new(), String::new()),
+        };
+        let rule_id = match &entry.resolver {
+            AuditResolver::Rule { rule_id } => rule_id.as_str(),
+            _ => "",
+        };
+        let workspace = entry
+            .workspace
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let fields = [
+            entry.timestamp.to_rfc3339(),
+            workspace,
+            entry.agent_name.clone(),
+            entry.session_id.clone(),
+            entry.tool_kind().unwrap_or_default(),
+            entry.tool_title().to_string(),
+            choice,
+            choice_kind,
+            entry.allowed().to_string(),
+            entry.resolver.label().to_string(),
+            rule_id.to_string(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv::field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> Vec<acp::PermissionOption> {
+        serde_json::from_value(serde_json::json!([
+            { "optionId": "allow", "name": "Allow", "kind": "allow_once" },
+            { "optionId": "reject", "name": "Reject", "kind": "reject_once" }
+        ]))
+        .unwrap()
+    }
+
+    fn entry(
+        workspace: Option<&str>,
+        option_id: &str,
+        resolver: AuditResolver,
+    ) -> PermissionAuditEntry {
+        let tool_call: acp::ToolCallUpdate = serde_json::from_value(serde_json::json!({
+            "toolCallId": "t1",
+            "kind": "execute",
+            "title": "Run \"cargo test\", quietly"
+        }))
+        .unwrap();
+        let outcome = acp::RequestPermissionOutcome::Selected(acp::SelectedPermissionOutcome::new(
+            option_id.to_string(),
+        ));
+        PermissionAuditEntry::new(
+            workspace.map(PathBuf::from),
+            "claude",
+            "s1",
+            &tool_call,
+            &options(),
+            &outcome,
+            resolver,
+        )
+    }
+
+    #[test]
+    fn test_append_and_read_per_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = PermissionAuditLog::new(dir.path().to_path_buf());
+
+        log.append(&entry(Some("/work/a"), "allow", AuditResolver::User))
+            .unwrap();
+        log.append(&entry(
+            Some("/work/a"),
+            "reject",
+            AuditResolver::Rule {
+                rule_id: "r1".to_string(),
+            },
+        ))
+        .unwrap();
+        log.append(&entry(None, "allow", AuditResolver::TimedOut))
+            .unwrap();
+
+        let entries = log.entries(Some(Path::new("/work/a"))).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].allowed());
+        assert!(!entries[1].allowed());
+        assert_eq!(entries[1].resolver.label(), "rule");
+
+        assert_eq!(log.workspaces(), vec![None, Some(PathBuf::from("/work/a"))]);
+        assert!(log.entries(Some(Path::new("/work/b"))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_export_csv_escapes_fields() {
+        let csv = export_csv(&[entry(Some("/work/a"), "allow", AuditResolver::User)]);
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("timestamp,workspace,agent")
+        );
+        let row = lines.next().unwrap();
+        assert!(
+            row.contains(
+                ",execute,\"Run \"\"cargo test\"\", quietly\",Allow,allow_once,true,user,"
+            )
+        );
+
+        let json = export_json(&[entry(None, "reject", AuditResolver::Cancelled)]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["resolver"]["type"], "cancelled");
+    }
+}
@@ -0,0 +1,750 @@
+//! Persistent rules that answer permission requests without asking.
+//!
+//! A [`PermissionRule`] matches a request by tool kind, tool title, the paths
+//! the tool touches and its command line, within one session, one workspace
+//! or everywhere. `GuiClient` consults the [`PermissionRuleStore`] before
+//! showing a request and learns a new rule whenever the user picks an
+//! "always" option. Rules are kept in a JSON file under the user data
+//! directory and can be edited in Settings.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use agent_client_protocol as acp;
+use anyhow::{Context as _, Result, anyhow};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What a matching rule answers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleDecision {
+    Allow,
+    Deny,
+}
+
+/// Where a rule applies
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleScope {
+    Global,
+    /// Sessions whose working directory is `path` or lies inside it
+    Workspace {
+        path: PathBuf,
+    },
+    Session {
+        session_id: String,
+    },
+}
+
+impl RuleScope {
+    /// Narrower scopes take precedence over wider ones
+    fn rank(&self) -> u8 {
+        match self {
+            RuleScope::Session { .. } => 2,
+            RuleScope::Workspace { .. } => 1,
+            RuleScope::Global => 0,
+        }
+    }
+
+    fn applies_to(&self, request: &RuleRequest) -> bool {
+        match self {
+            RuleScope::Global => true,
+            RuleScope::Workspace { path } => request
+                .workspace
+                .is_some_and(|workspace| workspace.starts_with(path)),
+            RuleScope::Session { session_id } => session_id == request.session_id,
+        }
+    }
+}
+
+/// A stored answer to permission requests.
+///
+/// Every criterion that is set must match; a rule without criteria matches
+/// every request in its scope.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRule {
+    pub id: String,
+    pub decision: RuleDecision,
+    pub scope: RuleScope,
+    /// ACP tool kind, e.g. `edit` or `execute`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_kind: Option<String>,
+    /// Tool title; `*` matches any text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Tool title that must match as written, without glob characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact_title: Option<String>,
+    /// Every location of the tool call must match one of these globs.
+    /// Relative globs are matched against paths inside the workspace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_globs: Vec<String>,
+    /// Regex the command line of the tool call must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_regex: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PermissionRule {
+    pub fn new(decision: RuleDecision, scope: RuleScope) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            decision,
+            scope,
+            tool_kind: None,
+            title: None,
+            exact_title: None,
+            path_globs: Vec::new(),
+            command_regex: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Rule remembering an "always" answer: commands are matched exactly,
+    /// other tools by kind and exact title
+    ///
+    /// Returns None when the tool call has neither, as a rule without
+    /// criteria would answer every request in its scope; the answer then
+    /// applies to this request only.
+    pub fn learned(
+        decision: RuleDecision,
+        scope: RuleScope,
+        tool_call: &acp::ToolCallUpdate,
+    ) -> Option<Self> {
+        let mut rule = Self::new(decision, scope);
+        rule.tool_kind = tool_kind_name(tool_call);
+        match tool_command(tool_call) {
+            Some(command) => {
+                rule.command_regex = Some(format!("^{}$", regex::escape(&command)));
+            }
+            None => rule.exact_title = tool_call.fields.title.clone(),
+        }
+        (rule.tool_kind.is_some() || rule.command_regex.is_some() || rule.exact_title.is_some())
+            .then_some(rule)
+    }
+
+    /// Compile the command regex, failing if it is invalid
+    fn compile_command_regex(&self) -> Result<Option<Regex>> {
+        self.command_regex
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).with_context(|| format!("invalid command regex '{}'", pattern))
+            })
+            .transpose()
+    }
+
+    /// Check the request against the criteria, with the rule's command regex
+    /// already compiled
+    fn matches(&self, request: &RuleRequest, command_regex: Option<&Regex>) -> bool {
+        if !self.scope.applies_to(request) {
+            return false;
+        }
+        let fields = &request.tool_call.fields;
+
+        let kind = tool_kind_name(request.tool_call);
+        if self.tool_kind.is_some() && self.tool_kind != kind {
+            return false;
+        }
+
+        if let Some(title) = &self.title {
+            match fields.title.as_deref() {
+                Some(actual) if glob_matches(title, actual, true) => {}
+                _ => return false,
+            }
+        }
+
+        if self.exact_title.is_some() && fields.title != self.exact_title {
+            return false;
+        }
+
+        if !self.path_globs.is_empty() {
+            let paths: Vec<&Path> = fields
+                .locations
+                .iter()
+                .flatten()
+                .map(|location| location.path.as_path())
+                .collect();
+            if paths.is_empty() {
+                return false;
+            }
+            let all_match = paths.iter().all(|path| {
+                self.path_globs
+                    .iter()
+                    .any(|glob| path_matches(glob, path, request.workspace))
+            });
+            if !all_match {
+                return false;
+            }
+        }
+
+        if let Some(regex) = command_regex {
+            let command = tool_command(request.tool_call).or_else(|| fields.title.clone());
+            if !command.is_some_and(|command| regex.is_match(&command)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// The request a rule is checked against
+pub struct RuleRequest<'a> {
+    pub session_id: &'a str,
+    /// Working directory of the session, if known
+    pub workspace: Option<&'a Path>,
+    pub tool_call: &'a acp::ToolCallUpdate,
+}
+
+/// A rule that answered a request, and the option it picked
+#[derive(Clone, Debug)]
+pub struct RuleResolution {
+    pub rule: PermissionRule,
+    pub option_id: acp::PermissionOptionId,
+}
+
+/// A rule with its command regex compiled when the rule is loaded or added
+struct StoredRule {
+    rule: PermissionRule,
+    command_regex: Option<Regex>,
+    /// The stored command regex does not compile, so the rule never matches
+    broken: bool,
+}
+
+impl StoredRule {
+    fn new(rule: PermissionRule) -> Result<Self> {
+        let command_regex = rule.compile_command_regex()?;
+        Ok(Self {
+            rule,
+            command_regex,
+            broken: false,
+        })
+    }
+
+    /// Keep a rule read from the file even if its regex does not compile, so
+    /// it can still be listed and fixed in Settings
+    fn loaded(rule: PermissionRule) -> Self {
+        match rule.compile_command_regex() {
+            Ok(command_regex) => Self {
+                rule,
+                command_regex,
+                broken: false,
+            },
+            Err(e) => {
+                log::warn!("Permission rule {} will not match: {:#}", rule.id, e);
+                Self {
+                    rule,
+                    command_regex: None,
+                    broken: true,
+                }
+            }
+        }
+    }
+
+    fn matches(&self, request: &RuleRequest) -> bool {
+        !self.broken && self.rule.matches(request, self.command_regex.as_ref())
+    }
+}
+
+/// Shared permission rules, optionally backed by a JSON file
+#[derive(Clone, Default)]
+pub struct PermissionRuleStore {
+    rules: Arc<RwLock<Vec<StoredRule>>>,
+    path: Option<PathBuf>,
+}
+
+impl PermissionRuleStore {
+    /// Rules kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules stored in `path`; a missing or unreadable file starts empty
+    pub fn load(path: PathBuf) -> Self {
+        let rules: Vec<PermissionRule> = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                log::warn!(
+                    "Ignoring invalid permission rules in {}: {}",
+                    path.display(),
+                    e
+                );
+                Vec::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                log::warn!(
+                    "Failed to read permission rules from {}: {}",
+                    path.display(),
+                    e
+                );
+                Vec::new()
+            }
+        };
+        log::info!("Loaded {} permission rules", rules.len());
+        Self {
+            rules: Arc::new(RwLock::new(
+                rules.into_iter().map(StoredRule::loaded).collect(),
+            )),
+            path: Some(path),
+        }
+    }
+
+    /// All rules, oldest first
+    pub fn rules(&self) -> Vec<PermissionRule> {
+        self.rules
+            .read()
+            .unwrap()
+            .iter()
+            .map(|stored| stored.rule.clone())
+            .collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<PermissionRule> {
+        self.rules
+            .read()
+            .unwrap()
+            .iter()
+            .find(|stored| stored.rule.id == id)
+            .map(|stored| stored.rule.clone())
+    }
+
+    /// Add a rule, replacing an existing rule with the same criteria
+    pub fn add(&self, rule: PermissionRule) -> Result<()> {
+        let rule = StoredRule::new(rule)?;
+        {
+            let mut rules = self.rules.write().unwrap();
+            rules.retain(|existing| !same_criteria(&existing.rule, &rule.rule));
+            rules.push(rule);
+        }
+        self.save()
+    }
+
+    /// Replace the rule with the same id
+    pub fn update(&self, rule: PermissionRule) -> Result<()> {
+        let rule = StoredRule::new(rule)?;
+        {
+            let mut rules = self.rules.write().unwrap();
+            let existing = rules
+                .iter_mut()
+                .find(|existing| existing.rule.id == rule.rule.id)
+                .ok_or_else(|| anyhow!("Permission rule not found: {}", rule.rule.id))?;
+            *existing = rule;
+        }
+        self.save()
+    }
+
+    /// Revoke a rule; returns whether it existed
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        let removed = {
+            let mut rules = self.rules.write().unwrap();
+            let before = rules.len();
+            rules.retain(|stored| stored.rule.id != id);
+            rules.len() != before
+        };
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Find the rule answering a request and the option it selects.
+    ///
+    /// The narrowest matching scope wins, and within a scope a deny rule
+    /// wins over an allow rule. Requests without a suitable option are left
+    /// to the user.
+    pub fn resolve(
+        &self,
+        request: &RuleRequest,
+        options: &[acp::PermissionOption],
+    ) -> Option<RuleResolution> {
+        let rules = self.rules.read().unwrap();
+        let rule = rules
+            .iter()
+            .filter(|stored| stored.matches(request))
+            .map(|stored| &stored.rule)
+            .max_by_key(|rule| (rule.scope.rank(), rule.decision == RuleDecision::Deny))?;
+        let option_id = select_option(rule.decision, options)?;
+        Some(RuleResolution {
+            rule: rule.clone(),
+            option_id,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&self.rules())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        // Write to a sibling file first so a crash never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))?;
+        Ok(())
+    }
+}
+
+fn same_criteria(a: &PermissionRule, b: &PermissionRule) -> bool {
+    a.scope == b.scope
+        && a.tool_kind == b.tool_kind
+        && a.title == b.title
+        && a.exact_title == b.exact_title
+        && a.path_globs == b.path_globs
+        && a.command_regex == b.command_regex
+}
+
+/// Prefer the "once" option so the agent does not widen the rule on its own
+fn select_option(
+    decision: RuleDecision,
+    options: &[acp::PermissionOption],
+) -> Option<acp::PermissionOptionId> {
+    let preferred = match decision {
+        RuleDecision::Allow => [
+            acp::PermissionOptionKind::AllowOnce,
+            acp::PermissionOptionKind::AllowAlways,
+        ],
+        RuleDecision::Deny => [
+            acp::PermissionOptionKind::RejectOnce,
+            acp::PermissionOptionKind::RejectAlways,
+        ],
+    };
+    preferred.into_iter().find_map(|kind| {
+        options
+            .iter()
+            .find(|option| option.kind == kind)
+            .map(|option| option.option_id.clone())
+    })
+}
+
+/// Decision implied by a selected option, if it asks to be remembered
+pub fn always_decision(kind: acp::PermissionOptionKind) -> Option<RuleDecision> {
+    match kind {
+        acp::PermissionOptionKind::AllowAlways => Some(RuleDecision::Allow),
+        acp::PermissionOptionKind::RejectAlways => Some(RuleDecision::Deny),
+        _ => None,
+    }
+}
+
+/// Wire name of the tool kind, e.g. `execute`
+pub fn tool_kind_name(tool_call: &acp::ToolCallUpdate) -> Option<String> {
+    let kind = tool_call.fields.kind.as_ref()?;
+    serde_json::to_value(kind)
+        .ok()?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Command line from the raw input of a tool call; agents send it either as
+/// a string or as an argv array
+pub fn tool_command(tool_call: &acp::ToolCallUpdate) -> Option<String> {
+    let command = tool_call.fields.raw_input.as_ref()?.get("command")?;
+    match command {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(args) => {
+            let args: Vec<&str> = args.iter().filter_map(Value::as_str).collect();
+            (!args.is_empty()).then(|| args.join(" "))
+        }
+        _ => None,
+    }
+}
+
+fn path_matches(glob: &str, path: &Path, workspace: Option<&Path>) -> bool {
+    let glob = glob.replace('\\', "/");
+    let candidate = if Path::new(&glob).is_absolute() {
+        path
+    } else {
+        match workspace.and_then(|workspace| path.strip_prefix(workspace).ok()) {
+            Some(relative) => relative,
+            None if path.is_relative() => path,
+            None => return false,
+        }
+    };
+    glob_matches(
+        &glob,
+        &candidate.to_string_lossy().replace('\\', "/"),
+        false,
+    )
+}
+
+/// Match `text` against a glob. `?` matches a single character and `*` any
+/// run of characters; in paths they stop at `/`, and `**` as a whole path
+/// segment matches any number of directories, none included.
+///
+/// Matching is iterative: on a mismatch the last `*` takes one more character,
+/// and once it cannot, the last `**` skips one more directory. A later star
+/// can take over everything an earlier one could, so only the last of each
+/// kind is retried and long texts never backtrack exponentially.
+fn glob_matches(pattern: &str, text: &str, plain_text: bool) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let may_skip = |c: &char| plain_text || *c != '/';
+
+    // Pattern position after the last `*` and the text position it reached
+    let mut star: Option<(usize, usize)> = None;
+    // The same for the last `**`, which resumes after the next `/` instead
+    let mut globstar: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while p < pattern.len() || t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                let stars = pattern[p..].iter().take_while(|c| **c == '*').count();
+                let end = p + stars;
+                let whole_segment = !plain_text
+                    && stars > 1
+                    && (p == 0 || pattern[p - 1] == '/')
+                    && matches!(pattern.get(end), None | Some('/'));
+                if !whole_segment {
+                    p = end;
+                    star = Some((p, t));
+                } else if end == pattern.len() {
+                    // A trailing `**` takes the rest of the path
+                    return true;
+                } else {
+                    p = end + 1;
+                    globstar = Some((p, t));
+                    star = None;
+                }
+                continue;
+            }
+            Some('?') if text.get(t).is_some_and(may_skip) => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some(c) if *c != '?' && text.get(t) == Some(c) => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some((star_p, star_t)) = star
+            && text.get(star_t).is_some_and(may_skip)
+        {
+            star = Some((star_p, star_t + 1));
+            (p, t) = (star_p, star_t + 1);
+            continue;
+        }
+        if let Some((globstar_p, globstar_t)) = globstar
+            && let Some(slash) = text[globstar_t..].iter().position(|c| *c == '/')
+        {
+            let next = globstar_t + slash + 1;
+            globstar = Some((globstar_p, next));
+            star = None;
+            (p, t) = (globstar_p, next);
+            continue;
+        }
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(value: Value) -> acp::ToolCallUpdate {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn options() -> Vec<acp::PermissionOption> {
+        serde_json::from_value(serde_json::json!([
+            { "optionId": "allow", "name": "Allow", "kind": "allow_once" },
+            { "optionId": "allow-always", "name": "Always allow", "kind": "allow_always" },
+            { "optionId": "reject", "name": "Reject", "kind": "reject_once" }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(
+            "src/**/*.rs",
+            "src/core/agent/client.rs",
+            false
+        ));
+        assert!(glob_matches("src/**/*.rs", "src/main.rs", false));
+        assert!(!glob_matches("src/*.rs", "src/core/main.rs", false));
+        assert!(glob_matches("Read ?.txt", "Read a.txt", true));
+        assert!(glob_matches("Run *", "Run cargo test --workspace", true));
+        assert!(!glob_matches("Run *", "Edit src/main.rs", true));
+        assert!(glob_matches("**/*.rs", "main.rs", false));
+        assert!(glob_matches("src/**", "src/core/main.rs", false));
+        assert!(!glob_matches("src/?", "src/", false));
+        assert!(glob_matches("a/**/b/*.rs", "a/x/b/y/b/z.rs", false));
+        assert!(!glob_matches("a/**/b/*.rs", "a/x/b/y/z.rs", false));
+    }
+
+    #[test]
+    fn test_glob_matches_many_stars_quickly() {
+        let text = "a".repeat(200);
+        assert!(!glob_matches(&format!("{}b", "*a".repeat(30)), &text, true));
+        assert!(!glob_matches(
+            &format!("{}b", "**/*a".repeat(20)),
+            &text,
+            false
+        ));
+        assert!(glob_matches(&"*a".repeat(30), &text, false));
+    }
+
+    #[test]
+    fn test_rule_matching_criteria() {
+        let workspace = Path::new("/work/project");
+        let edit = tool_call(serde_json::json!({
+            "toolCallId": "t1",
+            "kind": "edit",
+            "title": "Edit src/main.rs",
+            "locations": [{ "path": "/work/project/src/main.rs" }]
+        }));
+        let request = RuleRequest {
+            session_id: "s1",
+            workspace: Some(workspace),
+            tool_call: &edit,
+        };
+        let matches =
+            |rule: &PermissionRule| StoredRule::new(rule.clone()).unwrap().matches(&request);
+
+        let mut rule = PermissionRule::new(
+            RuleDecision::Allow,
+            RuleScope::Workspace {
+                path: workspace.to_path_buf(),
+            },
+        );
+        rule.tool_kind = Some("edit".to_string());
+        rule.path_globs = vec!["src/**".to_string()];
+        assert!(matches(&rule));
+
+        rule.path_globs = vec!["docs/**".to_string()];
+        assert!(!matches(&rule));
+
+        rule.path_globs.clear();
+        rule.tool_kind = Some("execute".to_string());
+        assert!(!matches(&rule));
+
+        rule.tool_kind = None;
+        rule.scope = RuleScope::Session {
+            session_id: "other".to_string(),
+        };
+        assert!(!matches(&rule));
+    }
+
+    #[test]
+    fn test_resolve_prefers_narrow_scope_and_deny() {
+        let store = PermissionRuleStore::new();
+        let execute = tool_call(serde_json::json!({
+            "toolCallId": "t1",
+            "kind": "execute",
+            "title": "cargo test",
+            "rawInput": { "command": ["cargo", "test"] }
+        }));
+        let request = RuleRequest {
+            session_id: "s1",
+            workspace: None,
+            tool_call: &execute,
+        };
+
+        let mut allow = PermissionRule::new(RuleDecision::Allow, RuleScope::Global);
+        allow.command_regex = Some("^cargo ".to_string());
+        store.add(allow).unwrap();
+        let resolution = store.resolve(&request, &options()).unwrap();
+        assert_eq!(resolution.option_id.to_string(), "allow");
+
+        let mut deny = PermissionRule::new(
+            RuleDecision::Deny,
+            RuleScope::Session {
+                session_id: "s1".to_string(),
+            },
+        );
+        deny.tool_kind = Some("execute".to_string());
+        let deny_id = deny.id.clone();
+        store.add(deny).unwrap();
+        let resolution = store.resolve(&request, &options()).unwrap();
+        assert_eq!(resolution.option_id.to_string(), "reject");
+
+        assert!(store.remove(&deny_id).unwrap());
+        assert_eq!(store.rules().len(), 1);
+    }
+
+    #[test]
+    fn test_learned_rule_matches_only_the_approved_tool_call() {
+        let edit = tool_call(serde_json::json!({
+            "toolCallId": "t1",
+            "kind": "edit",
+            "title": "Edit *.rs"
+        }));
+        let other = tool_call(serde_json::json!({
+            "toolCallId": "t2",
+            "kind": "edit",
+            "title": "Edit main.rs"
+        }));
+        let rule = PermissionRule::learned(RuleDecision::Allow, RuleScope::Global, &edit).unwrap();
+        let stored = StoredRule::new(rule).unwrap();
+        for (tool_call, expected) in [(&edit, true), (&other, false)] {
+            let request = RuleRequest {
+                session_id: "s1",
+                workspace: None,
+                tool_call,
+            };
+            assert_eq!(stored.matches(&request), expected);
+        }
+
+        // Nothing to tell the tool call apart by: no rule is learned
+        let anonymous = tool_call(serde_json::json!({ "toolCallId": "t3" }));
+        assert!(
+            PermissionRule::learned(RuleDecision::Allow, RuleScope::Global, &anonymous).is_none()
+        );
+    }
+
+    #[test]
+    fn test_learned_rule_and_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("permission-rules.json");
+        let execute = tool_call(serde_json::json!({
+            "toolCallId": "t1",
+            "kind": "execute",
+            "title": "Run tests",
+            "rawInput": { "command": "cargo test" }
+        }));
+
+        let store = PermissionRuleStore::load(path.clone());
+        let rule =
+            PermissionRule::learned(RuleDecision::Allow, RuleScope::Global, &execute).unwrap();
+        assert_eq!(rule.command_regex.as_deref(), Some("^cargo test$"));
+        assert_eq!(rule.exact_title, None);
+        store.add(rule.clone()).unwrap();
+        // Learning the same answer again replaces the earlier rule
+        store
+            .add(PermissionRule::learned(RuleDecision::Allow, RuleScope::Global, &execute).unwrap())
+            .unwrap();
+
+        let reloaded = PermissionRuleStore::load(path);
+        assert_eq!(reloaded.rules().len(), 1);
+        assert_eq!(reloaded.rules()[0].command_regex, rule.command_regex);
+
+        let mut invalid = PermissionRule::new(RuleDecision::Deny, RuleScope::Global);
+        invalid.command_regex = Some("(".to_string());
+        assert!(reloaded.add(invalid.clone()).is_err());
+
+        // A broken rule in the file is kept but never matches
+        fs::write(&path, serde_json::to_string(&vec![invalid]).unwrap()).unwrap();
+        let broken = PermissionRuleStore::load(path);
+        assert_eq!(broken.rules().len(), 1);
+        let request = RuleRequest {
+            session_id: "s1",
+            workspace: None,
+            tool_call: &execute,
+        };
+        assert!(broken.resolve(&request, &options()).is_none());
+    }
+}
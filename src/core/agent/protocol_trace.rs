@@ -0,0 +1,703 @@
+//! Recording of the raw ACP JSON-RPC traffic between the client and agents.
+//!
+//! [`TracedReader`] and [`TracedWriter`] wrap the stdio streams handed to
+//! `ClientSideConnection` and split them into newline-delimited frames. While
+//! tracing is enabled every frame is classified, paired with its request and
+//! kept in [`ProtocolTraceStore`], optionally appended to a size-capped JSONL
+//! file. Nothing is buffered while tracing is off.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{self, Write as _},
+    path::PathBuf,
+    pin::Pin,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use chrono::{DateTime, Utc};
+use futures::io::{AsyncRead, AsyncWrite};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::agent_logs::rotate;
+use super::extensions::{ExtensionKind, UnknownExtension};
+use crate::core::config::ProtocolTraceConfig;
+use crate::core::event_bus::{ProtocolTraceBusContainer, ProtocolTraceEvent};
+
+/// Frames kept in memory until a config is applied
+pub const DEFAULT_TRACE_BUFFER_FRAMES: usize = 5000;
+
+/// Requests kept waiting for a response; the oldest are dropped beyond this
+const MAX_PENDING_REQUESTS: usize = 1024;
+
+/// Longest frame buffered while looking for its end; longer ones are skipped
+const MAX_FRAME_BYTES: usize = 8 * 1024 * 1024;
+
+/// Which way a frame travelled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceDirection {
+    /// Client to agent (agent stdin)
+    Outgoing,
+    /// Agent to client (agent stdout)
+    Incoming,
+}
+
+impl TraceDirection {
+    fn opposite(self) -> Self {
+        match self {
+            Self::Outgoing => Self::Incoming,
+            Self::Incoming => Self::Outgoing,
+        }
+    }
+}
+
+/// JSON-RPC message type of a frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameKind {
+    Request,
+    Response,
+    Error,
+    Notification,
+    /// Not valid JSON-RPC
+    Invalid,
+}
+
+/// One JSON-RPC message as seen on the wire
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceFrame {
+    /// Sequence number, unique per store
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub agent_name: String,
+    pub direction: TraceDirection,
+    pub kind: FrameKind,
+    /// Method of the request; filled in from the request for responses
+    pub method: Option<String>,
+    /// JSON-RPC id rendered as text
+    pub id: Option<String>,
+    pub session_id: Option<String>,
+    /// Sequence number of the request this frame answers
+    pub request_seq: Option<u64>,
+    /// Time between the request and this response
+    pub latency_ms: Option<f64>,
+    pub payload: Value,
+}
+
+/// JSONL file frames are appended to, rotated once it reaches `max_bytes`
+struct TraceFile {
+    file: File,
+    path: PathBuf,
+    size: u64,
+    max_bytes: u64,
+}
+
+impl TraceFile {
+    fn open(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            file,
+            path,
+            size,
+            max_bytes,
+        })
+    }
+
+    /// Append one line, moving a full file to `<path>.1` first
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            rotate(&self.path, 1)?;
+            *self = Self::open(self.path.clone(), self.max_bytes)?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+struct PendingRequest {
+    seq: u64,
+    method: Option<String>,
+    session_id: Option<String>,
+    timestamp: DateTime<Utc>,
+}
+
+struct TraceBuffer {
+    capacity: usize,
+    frames: VecDeque<TraceFrame>,
+    /// Requests waiting for a response, keyed by agent, direction and id
+    pending: HashMap<(String, TraceDirection, String), PendingRequest>,
+}
+
+/// Shared buffer of recorded protocol frames for all agents
+#[derive(Clone)]
+pub struct ProtocolTraceStore {
+    enabled: Arc<AtomicBool>,
+    next_seq: Arc<AtomicU64>,
+    buffer: Arc<RwLock<TraceBuffer>>,
+    file: Arc<Mutex<Option<TraceFile>>>,
+    trace_bus: ProtocolTraceBusContainer,
+    /// Extensions without a handler, kept whether or not tracing is enabled
+    unknown_extensions: Arc<RwLock<BTreeMap<(String, String, ExtensionKind), UnknownExtension>>>,
+}
+
+impl ProtocolTraceStore {
+    pub fn new(trace_bus: ProtocolTraceBusContainer) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            next_seq: Arc::new(AtomicU64::new(1)),
+            buffer: Arc::new(RwLock::new(TraceBuffer {
+                capacity: DEFAULT_TRACE_BUFFER_FRAMES,
+                frames: VecDeque::new(),
+                pending: HashMap::new(),
+            })),
+            file: Arc::new(Mutex::new(None)),
+            trace_bus,
+            unknown_extensions: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Apply the trace settings; `path` is the JSONL file frames are appended to
+    pub fn configure(&self, config: &ProtocolTraceConfig, path: PathBuf) {
+        {
+            let mut buffer = self.buffer.write().unwrap();
+            buffer.capacity = config.buffer_frames.max(1);
+            while buffer.frames.len() > buffer.capacity {
+                buffer.frames.pop_front();
+            }
+        }
+
+        let file = if config.write_to_file {
+            match TraceFile::open(path.clone(), config.max_file_bytes.max(1)) {
+                Ok(file) => {
+                    log::info!("Writing ACP trace to {}", path.display());
+                    Some(file)
+                }
+                Err(e) => {
+                    log::warn!("Failed to open ACP trace file {}: {}", path.display(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        *self.file.lock().unwrap() = file;
+
+        self.set_enabled(config.enabled);
+    }
+
+    /// Whether frames are currently recorded
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop recording
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.buffer.write().unwrap().pending.clear();
+        }
+    }
+
+    /// Classify and store one raw frame
+    pub fn record(&self, agent_name: &str, direction: TraceDirection, raw: &str) {
+        let timestamp = Utc::now();
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let payload =
+            serde_json::from_str::<Value>(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+
+        let method = payload
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let id = payload
+            .get("id")
+            .filter(|id| !id.is_null())
+            .map(|id| match id {
+                Value::String(id) => id.clone(),
+                other => other.to_string(),
+            });
+        let kind = match (&method, &id) {
+            _ if !payload.is_object() => FrameKind::Invalid,
+            (Some(_), Some(_)) => FrameKind::Request,
+            (Some(_), None) => FrameKind::Notification,
+            (None, Some(_)) if payload.get("error").is_some() => FrameKind::Error,
+            (None, Some(_)) => FrameKind::Response,
+            (None, None) => FrameKind::Invalid,
+        };
+
+        let mut frame = TraceFrame {
+            seq,
+            timestamp,
+            agent_name: agent_name.to_string(),
+            direction,
+            kind,
+            method,
+            session_id: session_id_of(&payload),
+            id,
+            request_seq: None,
+            latency_ms: None,
+            payload,
+        };
+
+        {
+            let mut buffer = self.buffer.write().unwrap();
+            match frame.kind {
+                FrameKind::Request => {
+                    // Requests that never get an answer must not pile up
+                    if buffer.pending.len() >= MAX_PENDING_REQUESTS
+                        && let Some(oldest) = buffer
+                            .pending
+                            .iter()
+                            .min_by_key(|(_, request)| request.seq)
+                            .map(|(key, _)| key.clone())
+                    {
+                        buffer.pending.remove(&oldest);
+                    }
+                    let key = (
+                        frame.agent_name.clone(),
+                        direction,
+                        frame.id.clone().unwrap_or_default(),
+                    );
+                    buffer.pending.insert(
+                        key,
+                        PendingRequest {
+                            seq,
+                            method: frame.method.clone(),
+                            session_id: frame.session_id.clone(),
+                            timestamp,
+                        },
+                    );
+                }
+                FrameKind::Response | FrameKind::Error => {
+                    let key = (
+                        frame.agent_name.clone(),
+                        direction.opposite(),
+                        frame.id.clone().unwrap_or_default(),
+                    );
+                    if let Some(request) = buffer.pending.remove(&key) {
+                        frame.request_seq = Some(request.seq);
+                        frame.method = request.method;
+                        frame.session_id = frame.session_id.take().or(request.session_id);
+                        frame.latency_ms = (timestamp - request.timestamp)
+                            .num_microseconds()
+                            .map(|us| us as f64 / 1000.0);
+                    }
+                }
+                FrameKind::Notification | FrameKind::Invalid => {}
+            }
+
+            if buffer.frames.len() >= buffer.capacity {
+                buffer.frames.pop_front();
+            }
+            buffer.frames.push_back(frame.clone());
+        }
+
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let written = serde_json::to_string(&frame)
+                .map_err(io::Error::from)
+                .and_then(|line| file.write_line(&line));
+            if let Err(e) = written {
+                log::warn!("Failed to write ACP trace frame: {}", e);
+            }
+        }
+
+        self.trace_bus.publish(ProtocolTraceEvent { frame });
+    }
+
+    /// All recorded frames, oldest first
+    pub fn frames(&self) -> Vec<TraceFrame> {
+        self.buffer.read().unwrap().frames.iter().cloned().collect()
+    }
+
+    /// Drop all recorded frames
+    pub fn clear(&self) {
+        let mut buffer = self.buffer.write().unwrap();
+        buffer.frames.clear();
+        buffer.pending.clear();
+        self.unknown_extensions.write().unwrap().clear();
+    }
+
+    /// Count an extension message that no handler claimed
+    pub fn note_unknown_extension(&self, agent_name: &str, method: &str, kind: ExtensionKind) {
+        log::info!(
+            "Unhandled ACP extension {:?} {} from agent {}",
+            kind,
+            method,
+            agent_name
+        );
+        let key = (agent_name.to_string(), method.to_string(), kind);
+        let mut unknown = self.unknown_extensions.write().unwrap();
+        let entry = unknown.entry(key).or_insert_with(|| UnknownExtension {
+            agent_name: agent_name.to_string(),
+            method: method.to_string(),
+            kind,
+            count: 0,
+            last_seen: Utc::now(),
+        });
+        entry.count += 1;
+        entry.last_seen = Utc::now();
+    }
+
+    /// Extensions without a handler, by agent and method
+    pub fn unknown_extensions(&self) -> Vec<UnknownExtension> {
+        self.unknown_extensions
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for ProtocolTraceStore {
+    fn default() -> Self {
+        Self::new(ProtocolTraceBusContainer::new())
+    }
+}
+
+/// Session id carried in the params or result of a frame
+fn session_id_of(payload: &Value) -> Option<String> {
+    ["params", "result"]
+        .iter()
+        .filter_map(|field| payload.get(field))
+        .find_map(|value| value.get("sessionId").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+/// Splits a byte stream into newline-delimited frames
+struct FrameTap {
+    store: ProtocolTraceStore,
+    agent_name: String,
+    direction: TraceDirection,
+    partial: Vec<u8>,
+    /// Drop bytes up to the next newline: the start of that frame was not seen
+    /// or it grew past `MAX_FRAME_BYTES`
+    skipping: bool,
+}
+
+impl FrameTap {
+    fn new(store: ProtocolTraceStore, agent_name: String, direction: TraceDirection) -> Self {
+        Self {
+            store,
+            agent_name,
+            direction,
+            partial: Vec::new(),
+            skipping: false,
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        if !self.store.is_enabled() {
+            // Frames in flight when recording starts again are incomplete
+            self.partial.clear();
+            self.skipping = true;
+            return;
+        }
+
+        // Only the new bytes are searched; `partial` never holds a newline
+        let mut rest = bytes;
+        while let Some(end) = rest.iter().position(|b| *b == b'\n') {
+            let (line, tail) = rest.split_at(end + 1);
+            rest = tail;
+            if std::mem::take(&mut self.skipping) {
+                self.partial.clear();
+                continue;
+            }
+            if self.partial.is_empty() {
+                self.record(line);
+            } else {
+                let mut frame = std::mem::take(&mut self.partial);
+                frame.extend_from_slice(line);
+                self.record(&frame);
+            }
+        }
+
+        if self.skipping {
+            return;
+        }
+        if self.partial.len() + rest.len() > MAX_FRAME_BYTES {
+            log::warn!(
+                "Skipping ACP frame of agent {} larger than {} bytes in the trace",
+                self.agent_name,
+                MAX_FRAME_BYTES
+            );
+            self.partial = Vec::new();
+            self.skipping = true;
+            return;
+        }
+        self.partial.extend_from_slice(rest);
+    }
+
+    fn record(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if !line.is_empty() {
+            self.store.record(&self.agent_name, self.direction, line);
+        }
+    }
+}
+
+/// Agent stdout wrapper that records incoming frames
+pub struct TracedReader<R> {
+    inner: R,
+    tap: FrameTap,
+}
+
+impl<R> TracedReader<R> {
+    pub fn new(inner: R, store: ProtocolTraceStore, agent_name: String) -> Self {
+        Self {
+            inner,
+            tap: FrameTap::new(store, agent_name, TraceDirection::Incoming),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for TracedReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(read)) = &poll {
+            this.tap.feed(&buf[..*read]);
+        }
+        poll
+    }
+}
+
+/// Agent stdin wrapper that records outgoing frames
+pub struct TracedWriter<W> {
+    inner: W,
+    tap: FrameTap,
+}
+
+impl<W> TracedWriter<W> {
+    pub fn new(inner: W, store: ProtocolTraceStore, agent_name: String) -> Self {
+        Self {
+            inner,
+            tap: FrameTap::new(store, agent_name, TraceDirection::Outgoing),
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for TracedWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = &poll {
+            this.tap.feed(&buf[..*written]);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+
+    fn enabled_store() -> ProtocolTraceStore {
+        let store = ProtocolTraceStore::default();
+        store.set_enabled(true);
+        store
+    }
+
+    #[test]
+    fn test_unknown_extensions_are_counted_while_not_recording() {
+        let store = ProtocolTraceStore::default();
+        store.note_unknown_extension("agent", "_claude/usage", ExtensionKind::Notification);
+        store.note_unknown_extension("agent", "_claude/usage", ExtensionKind::Notification);
+        store.note_unknown_extension("agent", "_claude/ask", ExtensionKind::Method);
+
+        let unknown = store.unknown_extensions();
+        assert_eq!(unknown.len(), 2);
+        let usage = unknown
+            .iter()
+            .find(|u| u.method == "_claude/usage")
+            .unwrap();
+        assert_eq!(usage.count, 2);
+        assert!(store.frames().is_empty());
+
+        store.clear();
+        assert!(store.unknown_extensions().is_empty());
+    }
+
+    #[test]
+    fn test_response_is_paired_with_request() {
+        let store = enabled_store();
+        store.record(
+            "agent",
+            TraceDirection::Outgoing,
+            r#"{"jsonrpc":"2.0","id":3,"method":"session/prompt","params":{"sessionId":"s1"}}"#,
+        );
+        store.record(
+            "agent",
+            TraceDirection::Incoming,
+            r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s1"}}"#,
+        );
+        store.record(
+            "agent",
+            TraceDirection::Incoming,
+            r#"{"jsonrpc":"2.0","id":3,"result":{"stopReason":"end_turn"}}"#,
+        );
+
+        let frames = store.frames();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].kind, FrameKind::Request);
+        assert_eq!(frames[1].kind, FrameKind::Notification);
+
+        let response = &frames[2];
+        assert_eq!(response.kind, FrameKind::Response);
+        assert_eq!(response.request_seq, Some(frames[0].seq));
+        assert_eq!(response.method.as_deref(), Some("session/prompt"));
+        assert_eq!(response.session_id.as_deref(), Some("s1"));
+        assert!(response.latency_ms.is_some());
+    }
+
+    #[test]
+    fn test_requests_from_agent_pair_with_outgoing_responses() {
+        let store = enabled_store();
+        store.record(
+            "agent",
+            TraceDirection::Incoming,
+            r#"{"jsonrpc":"2.0","id":0,"method":"fs/read_text_file","params":{"sessionId":"s2"}}"#,
+        );
+        store.record(
+            "agent",
+            TraceDirection::Outgoing,
+            r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32603,"message":"boom"}}"#,
+        );
+
+        let frames = store.frames();
+        assert_eq!(frames[1].kind, FrameKind::Error);
+        assert_eq!(frames[1].method.as_deref(), Some("fs/read_text_file"));
+        assert_eq!(frames[1].request_seq, Some(frames[0].seq));
+    }
+
+    #[test]
+    fn test_traced_streams_split_frames() {
+        let store = enabled_store();
+
+        let input =
+            b"{\"jsonrpc\":\"2.0\",\"method\":\"a\"}\n{\"jsonrpc\":\"2.0\",\"method\":\"b\"}\n";
+        let mut reader = TracedReader::new(Cursor::new(input.to_vec()), store.clone(), "x".into());
+        let mut writer = TracedWriter::new(Vec::new(), store.clone(), "x".into());
+
+        futures::executor::block_on(async {
+            let mut byte = [0u8; 7];
+            loop {
+                let read = reader.read(&mut byte).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+            }
+            writer.write_all(b"{\"jsonrpc\":\"2.0\",").await.unwrap();
+            writer.write_all(b"\"method\":\"c\"}\n").await.unwrap();
+        });
+
+        let methods: Vec<_> = store
+            .frames()
+            .into_iter()
+            .map(|frame| (frame.direction, frame.method.unwrap()))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                (TraceDirection::Incoming, "a".to_string()),
+                (TraceDirection::Incoming, "b".to_string()),
+                (TraceDirection::Outgoing, "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_tap_skips_frames_cut_by_toggling_and_size() {
+        let store = ProtocolTraceStore::default();
+        let mut tap = FrameTap::new(store.clone(), "x".into(), TraceDirection::Incoming);
+
+        // Recording starts in the middle of a frame
+        tap.feed(b"{\"method\":");
+        store.set_enabled(true);
+        tap.feed(b"\"a\"}\n{\"method\":\"b\"}\n{\"method\"");
+        tap.feed(b":\"c\"}\n");
+
+        tap.feed(&vec![b' '; MAX_FRAME_BYTES + 1]);
+        tap.feed(b"{\"method\":\"too long\"}\n{\"method\":\"d\"}\n");
+
+        let methods: Vec<_> = store
+            .frames()
+            .into_iter()
+            .map(|frame| frame.method.unwrap())
+            .collect();
+        assert_eq!(methods, vec!["b", "c", "d"]);
+        assert!(tap.partial.is_empty());
+    }
+
+    #[test]
+    fn test_pending_requests_are_capped() {
+        let store = enabled_store();
+        for id in 0..MAX_PENDING_REQUESTS + 10 {
+            store.record(
+                "agent",
+                TraceDirection::Outgoing,
+                &format!(r#"{{"jsonrpc":"2.0","id":{},"method":"m"}}"#, id),
+            );
+        }
+        assert_eq!(
+            store.buffer.read().unwrap().pending.len(),
+            MAX_PENDING_REQUESTS
+        );
+    }
+
+    #[test]
+    fn test_trace_file_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("acp-trace.jsonl");
+        let mut file = TraceFile::open(path.clone(), 10).unwrap();
+
+        file.write_line("12345678").unwrap();
+        file.write_line("abcdefgh").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "abcdefgh\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("acp-trace.jsonl.1")).unwrap(),
+            "12345678\n"
+        );
+    }
+
+    #[test]
+    fn test_disabled_store_records_nothing() {
+        let store = ProtocolTraceStore::default();
+        let mut writer = TracedWriter::new(Vec::new(), store.clone(), "x".into());
+        futures::executor::block_on(async {
+            writer.write_all(b"{\"method\":\"a\"}\n").await.unwrap();
+        });
+        assert!(store.frames().is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+//! Restart policy for supervised agent processes.
+//!
+//! Each agent worker reports how it ended; when the process or its ACP I/O
+//! task dies unexpectedly the [`AgentManager`](super::AgentManager) restarts it
+//! following a [`RestartPolicy`].
+
+use std::time::Duration;
+
+/// How an agent worker ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AgentExit {
+    /// Shut down on request (remove, restart, app exit)
+    Shutdown,
+    /// Process or connection died unexpectedly
+    Crashed(String),
+}
+
+/// Exponential backoff with a cap on the number of restarts
+#[derive(Clone, Debug)]
+pub struct RestartPolicy {
+    /// Restarts attempted before giving up
+    pub max_restarts: u32,
+    /// Delay before the first restart
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between restarts
+    pub max_backoff: Duration,
+    /// An agent that ran at least this long is considered healthy again and
+    /// starts over with a fresh restart budget
+    pub stable_uptime: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            stable_uptime: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restart number `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        self.initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff)
+    }
+
+    /// Number of restarts already used when an agent that ran for `uptime` crashes
+    pub fn attempts_after(&self, previous_attempts: u32, uptime: Duration) -> u32 {
+        if uptime >= self.stable_uptime {
+            0
+        } else {
+            previous_attempts
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_exponentially_up_to_cap() {
+        let policy = RestartPolicy::default();
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(6), Duration::from_secs(30));
+        assert_eq!(policy.backoff(100), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_stable_uptime_resets_attempts() {
+        let policy = RestartPolicy::default();
+
+        assert_eq!(policy.attempts_after(3, Duration::from_secs(5)), 3);
+        assert_eq!(policy.attempts_after(3, Duration::from_secs(120)), 0);
+    }
+}
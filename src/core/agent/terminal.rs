@@ -0,0 +1,588 @@
+//! Terminal support for the ACP `terminal/*` client methods.
+//!
+//! [`TerminalRegistry`] keeps the output of every terminal an agent created so
+//! the UI can render it while the command runs. [`LocalTerminals`] owns the
+//! running processes on the agent worker thread of one connection.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::PathBuf,
+    process::{ExitStatus, Stdio},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::future::{Either, join_all, select};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::Command,
+    sync::{oneshot, watch},
+    task::JoinHandle,
+};
+
+use crate::core::event_bus::{TerminalBusContainer, TerminalEvent, TerminalEventKind};
+
+/// Output kept per terminal when the agent does not set `outputByteLimit`
+pub const DEFAULT_OUTPUT_BYTE_LIMIT: usize = 1024 * 1024;
+
+/// How long to wait for buffered output after the command exited
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Released terminals of a session whose output stays around for display
+const MAX_RELEASED_TERMINALS: usize = 32;
+
+/// Exit status of a terminal command
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TerminalExit {
+    pub exit_code: Option<u32>,
+    pub signal: Option<String>,
+}
+
+impl TerminalExit {
+    fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal().map(signal_name)
+        };
+        #[cfg(not(unix))]
+        let signal = None;
+
+        Self {
+            exit_code: status.code().map(|code| code as u32),
+            signal,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> String {
+    match signal {
+        1 => "SIGHUP".to_string(),
+        2 => "SIGINT".to_string(),
+        9 => "SIGKILL".to_string(),
+        15 => "SIGTERM".to_string(),
+        other => format!("SIG{}", other),
+    }
+}
+
+/// Point-in-time view of a terminal
+#[derive(Clone, Debug, Default)]
+pub struct TerminalSnapshot {
+    pub session_id: String,
+    /// Command line as requested by the agent
+    pub command: String,
+    pub output: String,
+    /// Whether output was dropped from the beginning to respect the byte limit
+    pub truncated: bool,
+    pub exit: Option<TerminalExit>,
+}
+
+struct TerminalEntry {
+    snapshot: TerminalSnapshot,
+    byte_limit: usize,
+    /// Order of the release; released terminals stay visible in the UI but
+    /// are no longer valid for agents
+    released: Option<u64>,
+}
+
+/// Output of all agent terminals, shared between agent workers and the UI
+#[derive(Clone, Default)]
+pub struct TerminalRegistry {
+    terminals: Arc<RwLock<HashMap<String, TerminalEntry>>>,
+    releases: Arc<AtomicU64>,
+}
+
+impl TerminalRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, terminal_id: &str, session_id: &str, command: String, byte_limit: usize) {
+        self.terminals.write().unwrap().insert(
+            terminal_id.to_string(),
+            TerminalEntry {
+                snapshot: TerminalSnapshot {
+                    session_id: session_id.to_string(),
+                    command,
+                    ..Default::default()
+                },
+                byte_limit,
+                released: None,
+            },
+        );
+    }
+
+    fn append(&self, terminal_id: &str, chunk: &str) {
+        if let Some(entry) = self.terminals.write().unwrap().get_mut(terminal_id) {
+            if append_limited(&mut entry.snapshot.output, chunk, entry.byte_limit) {
+                entry.snapshot.truncated = true;
+            }
+        }
+    }
+
+    fn set_exit(&self, terminal_id: &str, exit: TerminalExit) {
+        if let Some(entry) = self.terminals.write().unwrap().get_mut(terminal_id) {
+            entry.snapshot.exit = Some(exit);
+        }
+    }
+
+    /// Mark a terminal released, dropping the oldest released terminals of
+    /// its session beyond [`MAX_RELEASED_TERMINALS`]
+    fn release(&self, terminal_id: &str) {
+        let mut terminals = self.terminals.write().unwrap();
+        let Some(entry) = terminals.get_mut(terminal_id) else {
+            return;
+        };
+        if entry.released.is_some() {
+            return;
+        }
+        entry.released = Some(self.releases.fetch_add(1, Ordering::Relaxed));
+        let session_id = entry.snapshot.session_id.clone();
+
+        let mut released: Vec<(u64, String)> = terminals
+            .iter()
+            .filter(|(_, entry)| entry.snapshot.session_id == session_id)
+            .filter_map(|(id, entry)| Some((entry.released?, id.clone())))
+            .collect();
+        if released.len() > MAX_RELEASED_TERMINALS {
+            released.sort();
+            for (_, id) in &released[..released.len() - MAX_RELEASED_TERMINALS] {
+                terminals.remove(id);
+            }
+        }
+    }
+
+    /// Snapshot of a terminal that is still valid for the given session
+    fn active_snapshot(&self, session_id: &str, terminal_id: &str) -> Option<TerminalSnapshot> {
+        self.terminals
+            .read()
+            .unwrap()
+            .get(terminal_id)
+            .filter(|entry| entry.released.is_none() && entry.snapshot.session_id == session_id)
+            .map(|entry| entry.snapshot.clone())
+    }
+
+    /// Get the current state of a terminal (including released ones) for display
+    pub fn snapshot(&self, terminal_id: &str) -> Option<TerminalSnapshot> {
+        self.terminals
+            .read()
+            .unwrap()
+            .get(terminal_id)
+            .map(|entry| entry.snapshot.clone())
+    }
+
+    /// Drop all terminals of a session
+    pub fn remove_session(&self, session_id: &str) {
+        self.terminals
+            .write()
+            .unwrap()
+            .retain(|_, entry| entry.snapshot.session_id != session_id);
+    }
+}
+
+/// Append `chunk` and drop output from the beginning until it fits in `limit` bytes.
+///
+/// Returns `true` if anything was dropped.
+fn append_limited(output: &mut String, chunk: &str, limit: usize) -> bool {
+    output.push_str(chunk);
+    if output.len() <= limit {
+        return false;
+    }
+
+    let mut cut = output.len() - limit;
+    while !output.is_char_boundary(cut) {
+        cut += 1;
+    }
+    output.drain(..cut);
+    true
+}
+
+/// Decode a chunk of process output, keeping an incomplete UTF-8 sequence for the next read
+fn decode_chunk(pending: &mut Vec<u8>, bytes: &[u8]) -> String {
+    pending.extend_from_slice(bytes);
+    match std::str::from_utf8(pending) {
+        Ok(text) => {
+            let text = text.to_string();
+            pending.clear();
+            text
+        }
+        Err(e) if e.error_len().is_none() => {
+            let valid = e.valid_up_to();
+            let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+            text
+        }
+        Err(_) => {
+            let text = String::from_utf8_lossy(pending).into_owned();
+            pending.clear();
+            text
+        }
+    }
+}
+
+/// Build the process for a terminal request.
+///
+/// A bare command line without arguments is run through the platform shell.
+fn build_command(command: &str, args: &[String]) -> Command {
+    if !args.is_empty() {
+        let mut cmd = Command::new(command);
+        cmd.args(args);
+        return cmd;
+    }
+
+    if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Parameters of a `terminal/create` request
+pub struct TerminalSpawnRequest {
+    pub session_id: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub cwd: PathBuf,
+    pub output_byte_limit: Option<u64>,
+}
+
+struct RunningTerminal {
+    kill_tx: Option<oneshot::Sender<()>>,
+    exit_rx: watch::Receiver<Option<TerminalExit>>,
+}
+
+/// Terminals owned by one agent connection.
+///
+/// Must be used from within the worker's `LocalSet`. Dropping it kills every
+/// command that is still running.
+pub struct LocalTerminals {
+    agent_name: String,
+    registry: TerminalRegistry,
+    terminal_bus: TerminalBusContainer,
+    running: RefCell<HashMap<String, RunningTerminal>>,
+}
+
+impl LocalTerminals {
+    pub fn new(
+        agent_name: String,
+        registry: TerminalRegistry,
+        terminal_bus: TerminalBusContainer,
+    ) -> Self {
+        Self {
+            agent_name,
+            registry,
+            terminal_bus,
+            running: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Spawn the command and return the new terminal id
+    pub fn create(&self, request: TerminalSpawnRequest) -> Result<String> {
+        let mut command = build_command(&request.command, &request.args);
+        command
+            .current_dir(&request.cwd)
+            .envs(request.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("failed to spawn terminal command `{}`", request.command))?;
+
+        let terminal_id = uuid::Uuid::new_v4().to_string();
+        let command_line = std::iter::once(request.command.as_str())
+            .chain(request.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let byte_limit = request
+            .output_byte_limit
+            .map(|limit| limit as usize)
+            .unwrap_or(DEFAULT_OUTPUT_BYTE_LIMIT);
+        self.registry
+            .insert(&terminal_id, &request.session_id, command_line, byte_limit);
+
+        log::info!(
+            "[Terminal] Agent '{}' started terminal {} in {:?}: {}",
+            self.agent_name,
+            terminal_id,
+            request.cwd,
+            request.command
+        );
+
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(self.spawn_reader(stdout, &terminal_id, &request.session_id));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(self.spawn_reader(stderr, &terminal_id, &request.session_id));
+        }
+
+        let (kill_tx, kill_rx) = oneshot::channel::<()>();
+        let (exit_tx, exit_rx) = watch::channel(None);
+
+        let registry = self.registry.clone();
+        let terminal_bus = self.terminal_bus.clone();
+        let agent_name = self.agent_name.clone();
+        let session_id = request.session_id.clone();
+        let id = terminal_id.clone();
+        tokio::task::spawn_local(async move {
+            // A kill request (or dropping the sender on release) stops the command
+            let exited = match select(Box::pin(child.wait()), kill_rx).await {
+                Either::Left((status, _)) => Some(status),
+                Either::Right(_) => None,
+            };
+            let status = match exited {
+                Some(status) => status,
+                None => {
+                    if let Err(e) = child.kill().await {
+                        log::warn!("[Terminal] Failed to kill terminal {}: {}", id, e);
+                    }
+                    child.wait().await
+                }
+            };
+
+            // Let the readers pick up whatever the command wrote before exiting
+            let abort_handles: Vec<_> = readers.iter().map(|r| r.abort_handle()).collect();
+            let drained = select(
+                Box::pin(join_all(readers)),
+                Box::pin(smol::Timer::after(OUTPUT_DRAIN_TIMEOUT)),
+            )
+            .await;
+            if let Either::Right(_) = drained {
+                // Background processes may keep the pipes open; stop reading
+                abort_handles.iter().for_each(|handle| handle.abort());
+            }
+
+            let exit = match status {
+                Ok(status) => TerminalExit::from_status(status),
+                Err(e) => {
+                    log::error!("[Terminal] Failed to wait for terminal {}: {}", id, e);
+                    TerminalExit::default()
+                }
+            };
+            log::info!("[Terminal] Terminal {} exited: {:?}", id, exit);
+
+            registry.set_exit(&id, exit.clone());
+            let _ = exit_tx.send(Some(exit.clone()));
+            terminal_bus.publish(TerminalEvent {
+                terminal_id: id,
+                session_id,
+                agent_name,
+                kind: TerminalEventKind::Exited(exit),
+            });
+        });
+
+        self.running.borrow_mut().insert(
+            terminal_id.clone(),
+            RunningTerminal {
+                kill_tx: Some(kill_tx),
+                exit_rx,
+            },
+        );
+
+        Ok(terminal_id)
+    }
+
+    fn spawn_reader<R>(&self, mut reader: R, terminal_id: &str, session_id: &str) -> JoinHandle<()>
+    where
+        R: AsyncRead + Unpin + 'static,
+    {
+        let registry = self.registry.clone();
+        let terminal_bus = self.terminal_bus.clone();
+        let agent_name = self.agent_name.clone();
+        let terminal_id = terminal_id.to_string();
+        let session_id = session_id.to_string();
+
+        tokio::task::spawn_local(async move {
+            let mut buf = [0u8; 8192];
+            let mut pending = Vec::new();
+            loop {
+                let read = match reader.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) => {
+                        log::warn!("[Terminal] Read error on terminal {}: {}", terminal_id, e);
+                        break;
+                    }
+                };
+
+                let chunk = decode_chunk(&mut pending, &buf[..read]);
+                if chunk.is_empty() {
+                    continue;
+                }
+                registry.append(&terminal_id, &chunk);
+                terminal_bus.publish(TerminalEvent {
+                    terminal_id: terminal_id.clone(),
+                    session_id: session_id.clone(),
+                    agent_name: agent_name.clone(),
+                    kind: TerminalEventKind::Output(chunk),
+                });
+            }
+        })
+    }
+
+    /// Current output of a terminal
+    pub fn output(&self, session_id: &str, terminal_id: &str) -> Result<TerminalSnapshot> {
+        self.registry
+            .active_snapshot(session_id, terminal_id)
+            .ok_or_else(|| anyhow!("unknown terminal: {}", terminal_id))
+    }
+
+    /// Wait until the command exits
+    pub async fn wait_for_exit(&self, session_id: &str, terminal_id: &str) -> Result<TerminalExit> {
+        self.output(session_id, terminal_id)?;
+        let mut exit_rx = self
+            .running
+            .borrow()
+            .get(terminal_id)
+            .map(|terminal| terminal.exit_rx.clone())
+            .ok_or_else(|| anyhow!("unknown terminal: {}", terminal_id))?;
+
+        let exit = exit_rx
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| anyhow!("terminal {} was dropped before exiting", terminal_id))?;
+        Ok(exit.clone().unwrap_or_default())
+    }
+
+    /// Kill the command but keep the terminal (and its output) valid
+    pub fn kill(&self, session_id: &str, terminal_id: &str) -> Result<()> {
+        self.output(session_id, terminal_id)?;
+        if let Some(terminal) = self.running.borrow_mut().get_mut(terminal_id) {
+            if let Some(kill_tx) = terminal.kill_tx.take() {
+                let _ = kill_tx.send(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Kill the command if needed and invalidate the terminal id
+    pub fn release(&self, session_id: &str, terminal_id: &str) -> Result<()> {
+        self.output(session_id, terminal_id)?;
+        // Dropping the kill sender stops the command if it is still running
+        self.running.borrow_mut().remove(terminal_id);
+        self.registry.release(terminal_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_limited_truncates_from_start() {
+        let mut output = String::new();
+
+        assert!(!append_limited(&mut output, "hello ", 10));
+        assert!(append_limited(&mut output, "world!", 10));
+        assert_eq!(output, "llo world!");
+    }
+
+    #[test]
+    fn test_append_limited_respects_char_boundaries() {
+        let mut output = String::new();
+
+        assert!(append_limited(&mut output, "aé€", 4));
+        assert_eq!(output, "€");
+    }
+
+    #[test]
+    fn test_decode_chunk_keeps_incomplete_sequence() {
+        let mut pending = Vec::new();
+        let bytes = "é".as_bytes();
+
+        assert_eq!(decode_chunk(&mut pending, &bytes[..1]), "");
+        assert_eq!(decode_chunk(&mut pending, &bytes[1..]), "é");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_registry_release_hides_terminal_from_agent() {
+        let registry = TerminalRegistry::new();
+        registry.insert("term-1", "session-1", "ls".to_string(), 1024);
+        registry.append("term-1", "file.txt\n");
+
+        assert!(registry.active_snapshot("session-2", "term-1").is_none());
+        let snapshot = registry.active_snapshot("session-1", "term-1").unwrap();
+        assert_eq!(snapshot.output, "file.txt\n");
+        assert!(!snapshot.truncated);
+
+        registry.release("term-1");
+        assert!(registry.active_snapshot("session-1", "term-1").is_none());
+        assert!(registry.snapshot("term-1").is_some());
+    }
+
+    #[test]
+    fn test_registry_prunes_released_terminals() {
+        let registry = TerminalRegistry::new();
+        for ix in 0..=MAX_RELEASED_TERMINALS {
+            let id = format!("term-{}", ix);
+            registry.insert(&id, "session-1", "ls".to_string(), 1024);
+            registry.release(&id);
+        }
+        registry.insert("running", "session-1", "ls".to_string(), 1024);
+        registry.insert("other", "session-2", "ls".to_string(), 1024);
+        registry.release("other");
+
+        // Only the oldest released terminal of the session is dropped
+        assert!(registry.snapshot("term-0").is_none());
+        assert!(registry.snapshot("term-1").is_some());
+        assert!(registry.snapshot("running").is_some());
+        assert!(registry.snapshot("other").is_some());
+
+        registry.remove_session("session-1");
+        assert!(registry.snapshot("running").is_none());
+        assert!(registry.snapshot("other").is_some());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_create_and_wait_for_exit() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let registry = TerminalRegistry::new();
+                let terminals = LocalTerminals::new(
+                    "agent".to_string(),
+                    registry.clone(),
+                    TerminalBusContainer::new(),
+                );
+
+                let id = terminals
+                    .create(TerminalSpawnRequest {
+                        session_id: "session-1".to_string(),
+                        command: "printf 'hi'; exit 3".to_string(),
+                        args: Vec::new(),
+                        env: Vec::new(),
+                        cwd: std::env::temp_dir(),
+                        output_byte_limit: None,
+                    })
+                    .unwrap();
+
+                let exit = terminals.wait_for_exit("session-1", &id).await.unwrap();
+                assert_eq!(exit.exit_code, Some(3));
+
+                let snapshot = terminals.output("session-1", &id).unwrap();
+                assert_eq!(snapshot.output, "hi");
+                assert_eq!(snapshot.exit, Some(exit));
+
+                terminals.release("session-1", &id).unwrap();
+                assert!(terminals.output("session-1", &id).is_err());
+            })
+            .await;
+    }
+}
@@ -0,0 +1,244 @@
+//! Connections to agents that already run as daemons.
+//!
+//! Besides spawning a child process, an agent can be reached over a Unix
+//! domain socket, TCP or WebSocket (see [`AgentTransport`]). Every transport is
+//! turned into the newline delimited byte streams `ClientSideConnection`
+//! expects, so the rest of the worker does not care how the agent is reached.
+
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{Context, Result, anyhow};
+use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio_tungstenite::tungstenite::{
+    Message,
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+};
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use crate::core::config::AgentTransport;
+
+/// Outgoing ACP bytes
+pub(super) type BoxedWriter = Box<dyn futures::AsyncWrite + Unpin>;
+/// Incoming ACP bytes
+pub(super) type BoxedReader = Box<dyn futures::AsyncRead + Unpin>;
+
+/// Attempts made to reach an endpoint before giving up
+const CONNECT_ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled for each further one
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Limit for a single connection attempt
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Buffer between the WebSocket pumps and the ACP connection
+const WEBSOCKET_PIPE_BYTES: usize = 64 * 1024;
+
+/// Connect to a running agent, retrying a few times while it comes up.
+///
+/// Must be called inside a `LocalSet`: WebSocket connections are pumped by
+/// local tasks that end when the connection closes.
+pub(super) async fn connect(
+    agent_name: &str,
+    transport: &AgentTransport,
+) -> Result<(BoxedWriter, BoxedReader)> {
+    let endpoint = transport
+        .endpoint()
+        .ok_or_else(|| anyhow!("agent {agent_name} is not a network agent"))?;
+
+    let mut attempt = 1;
+    loop {
+        log::info!(
+            "Connecting to agent {} at {} (attempt {}/{})",
+            agent_name,
+            endpoint,
+            attempt,
+            CONNECT_ATTEMPTS
+        );
+        let result = tokio::time::timeout(CONNECT_TIMEOUT, connect_once(transport))
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("timed out after {:?}", CONNECT_TIMEOUT)));
+
+        match result {
+            Ok(streams) => return Ok(streams),
+            Err(e) if attempt < CONNECT_ATTEMPTS => {
+                let delay = CONNECT_RETRY_DELAY * 2u32.pow(attempt - 1);
+                log::warn!(
+                    "Failed to connect to agent {} at {}: {:#}, retrying in {:?}",
+                    agent_name,
+                    endpoint,
+                    e,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e.context(format!(
+                    "failed to connect to agent {agent_name} at {endpoint}"
+                )));
+            }
+        }
+    }
+}
+
+async fn connect_once(transport: &AgentTransport) -> Result<(BoxedWriter, BoxedReader)> {
+    match transport {
+        AgentTransport::Stdio => Err(anyhow!("stdio agents are spawned, not connected")),
+        #[cfg(unix)]
+        AgentTransport::Unix { path } => {
+            let stream = tokio::net::UnixStream::connect(path)
+                .await
+                .with_context(|| format!("cannot connect to {}", path.display()))?;
+            let (read, write) = stream.into_split();
+            Ok((Box::new(write.compat_write()), Box::new(read.compat())))
+        }
+        #[cfg(not(unix))]
+        AgentTransport::Unix { .. } => Err(anyhow!(
+            "Unix domain sockets are not supported on this platform"
+        )),
+        AgentTransport::Tcp { address } => {
+            let stream = tokio::net::TcpStream::connect(address.as_str())
+                .await
+                .with_context(|| format!("cannot connect to {}", address))?;
+            stream.set_nodelay(true)?;
+            let (read, write) = stream.into_split();
+            Ok((Box::new(write.compat_write()), Box::new(read.compat())))
+        }
+        AgentTransport::WebSocket { url, headers } => connect_websocket(url, headers).await,
+    }
+}
+
+/// Bridge a WebSocket to byte streams: every outgoing line is sent as one
+/// text frame and every incoming frame is delivered as one line
+async fn connect_websocket(
+    url: &str,
+    headers: &HashMap<String, String>,
+) -> Result<(BoxedWriter, BoxedReader)> {
+    let mut request = url
+        .into_client_request()
+        .with_context(|| format!("invalid WebSocket URL {}", url))?;
+    for (name, value) in headers {
+        request.headers_mut().insert(
+            HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("invalid header name {}", name))?,
+            HeaderValue::from_str(value)
+                .with_context(|| format!("invalid value for header {}", name))?,
+        );
+    }
+
+    let (socket, _response) = tokio_tungstenite::connect_async(request)
+        .await
+        .with_context(|| format!("cannot connect to {}", url))?;
+    let (mut sink, mut stream) = socket.split();
+
+    let (outgoing, outgoing_pipe) = tokio::io::duplex(WEBSOCKET_PIPE_BYTES);
+    let (incoming, mut incoming_pipe) = tokio::io::duplex(WEBSOCKET_PIPE_BYTES);
+
+    // Lines written by the ACP connection -> text frames
+    tokio::task::spawn_local(async move {
+        let mut lines = tokio::io::BufReader::new(outgoing_pipe).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = sink.send(Message::text(line)).await {
+                log::warn!("WebSocket send failed: {}", e);
+                break;
+            }
+        }
+        let _ = sink.close().await;
+    });
+
+    // Frames from the agent -> lines read by the ACP connection. Dropping the
+    // pipe on close or error ends the connection, which the supervisor
+    // reports as a crash and reconnects.
+    tokio::task::spawn_local(async move {
+        while let Some(message) = stream.next().await {
+            let payload = match message {
+                Ok(Message::Text(text)) => text.as_str().as_bytes().to_vec(),
+                Ok(Message::Binary(bytes)) => bytes.to_vec(),
+                Ok(Message::Close(frame)) => {
+                    log::info!("WebSocket closed by agent: {:?}", frame);
+                    break;
+                }
+                // Pings are answered by tungstenite itself
+                Ok(_) => continue,
+                Err(e) => {
+                    log::warn!("WebSocket receive failed: {}", e);
+                    break;
+                }
+            };
+            if incoming_pipe.write_all(&payload).await.is_err()
+                || incoming_pipe.write_all(b"\n").await.is_err()
+            {
+                break;
+            }
+        }
+        let _ = incoming_pipe.shutdown().await;
+    });
+
+    Ok((
+        Box::new(outgoing.compat_write()),
+        Box::new(incoming.compat()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{AsyncBufReadExt as _, AsyncWriteExt as _};
+
+    #[test]
+    fn test_tcp_transport_carries_lines_both_ways() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let local = tokio::task::LocalSet::new();
+
+        local.block_on(&runtime, async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+
+            // Echo server standing in for an agent daemon
+            tokio::task::spawn_local(async move {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (mut read, mut write) = socket.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+
+            let (mut writer, reader) = connect("echo", &AgentTransport::Tcp { address })
+                .await
+                .unwrap();
+            writer.write_all(b"{\"jsonrpc\":\"2.0\"}\n").await.unwrap();
+            writer.flush().await.unwrap();
+
+            let mut line = String::new();
+            futures::io::BufReader::new(reader)
+                .read_line(&mut line)
+                .await
+                .unwrap();
+            assert_eq!(line, "{\"jsonrpc\":\"2.0\"}\n");
+        });
+    }
+
+    #[test]
+    fn test_connect_gives_up_after_retries() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let local = tokio::task::LocalSet::new();
+
+        local.block_on(&runtime, async {
+            let transport = AgentTransport::Unix {
+                path: std::env::temp_dir().join("agentx-missing-agent.sock"),
+            };
+            let err = match connect("missing", &transport).await {
+                Ok(_) => panic!("connected to a missing socket"),
+                Err(e) => e,
+            };
+            assert!(format!("{:#}", err).contains("failed to connect to agent missing"));
+        });
+    }
+}
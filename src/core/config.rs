@@ -1,6 +1,6 @@
 use agent_client_protocol as acp;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -9,7 +9,11 @@ pub struct Config {
     pub upload_dir: PathBuf,
     #[serde(default)]
     pub models: HashMap<String, ModelConfig>,
-    #[serde(default, alias = "mcpServers")]
+    #[serde(
+        default,
+        alias = "mcpServers",
+        deserialize_with = "deserialize_mcp_servers"
+    )]
     pub mcp_servers: HashMap<String, McpServerConfig>,
     #[serde(default)]
     pub commands: HashMap<String, CommandConfig>,
@@ -23,6 +27,18 @@ pub struct Config {
     /// Network proxy configuration
     #[serde(default)]
     pub proxy: ProxyConfig,
+    /// Agent stderr capture settings
+    #[serde(default)]
+    pub agent_logs: AgentLogConfig,
+    /// ACP protocol traffic recording
+    #[serde(default)]
+    pub protocol_trace: ProtocolTraceConfig,
+    /// Handling of agent permission requests
+    #[serde(default)]
+    pub permissions: PermissionConfig,
+    /// When persisted sessions expire
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 fn default_upload_dir() -> PathBuf {
@@ -37,17 +53,156 @@ fn default_tool_call_preview_max_lines() -> usize {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AgentProcessConfig {
+    /// Command to spawn; unused when `transport` points at a running agent
+    #[serde(default)]
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// How AgentX talks to the agent
+    #[serde(default, skip_serializing_if = "AgentTransport::is_stdio")]
+    pub transport: AgentTransport,
+    /// Adjustments to the client capabilities offered to this agent
+    #[serde(default, skip_serializing_if = "ClientCapabilitiesConfig::is_empty")]
+    pub client_capabilities: ClientCapabilitiesConfig,
+    /// Limits after which a prompt turn counts as hung
+    #[serde(default, skip_serializing_if = "PromptTimeoutConfig::is_empty")]
+    pub timeouts: PromptTimeoutConfig,
 
     /// Custom Node.js path (populated at runtime from AppSettings)
     #[serde(skip)]
     pub nodejs_path: Option<String>,
 }
 
+/// Per-agent limits on how long a prompt turn may run.
+///
+/// Both are disabled when unset. When one is exceeded the turn is cancelled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PromptTimeoutConfig {
+    /// Maximum length of a whole turn in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_secs: Option<u64>,
+    /// Maximum time in seconds without a session update from the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactivity_secs: Option<u64>,
+}
+
+impl PromptTimeoutConfig {
+    pub fn is_empty(&self) -> bool {
+        self.turn().is_none() && self.inactivity().is_none()
+    }
+
+    /// Turn limit, `None` when unset or zero
+    pub fn turn(&self) -> Option<Duration> {
+        self.turn_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Inactivity limit, `None` when unset or zero
+    pub fn inactivity(&self) -> Option<Duration> {
+        self.inactivity_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+}
+
+/// Per-agent overrides of the capabilities sent in `initialize`.
+///
+/// Unset fields keep what AgentX implements; a capability can be turned off
+/// for an agent but not claimed when AgentX lacks it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ClientCapabilitiesConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_text_file: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_text_file: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<bool>,
+    /// Merged into the advertised `_meta`; a `null` value removes a key
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub meta: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ClientCapabilitiesConfig {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Connection used to speak ACP with an agent
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AgentTransport {
+    /// Spawn `command` and use its stdin/stdout
+    #[default]
+    Stdio,
+    /// Connect to an agent listening on a Unix domain socket
+    Unix { path: PathBuf },
+    /// Connect to an agent listening on `host:port`
+    Tcp { address: String },
+    /// Connect to a `ws://` or `wss://` endpoint, one JSON-RPC message per frame
+    #[serde(rename = "websocket")]
+    WebSocket {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+impl AgentTransport {
+    pub fn is_stdio(&self) -> bool {
+        matches!(self, AgentTransport::Stdio)
+    }
+
+    /// Human readable endpoint, `None` for spawned agents
+    pub fn endpoint(&self) -> Option<String> {
+        match self {
+            AgentTransport::Stdio => None,
+            AgentTransport::Unix { path } => Some(format!("unix:{}", path.display())),
+            AgentTransport::Tcp { address } => Some(format!("tcp://{}", address)),
+            AgentTransport::WebSocket { url, .. } => Some(url.clone()),
+        }
+    }
+
+    /// Parse an endpoint as written by [`AgentTransport::endpoint`]; an empty
+    /// string means a spawned agent
+    pub fn parse_endpoint(endpoint: &str) -> Result<Self, String> {
+        let endpoint = endpoint.trim();
+        if endpoint.is_empty() {
+            return Ok(AgentTransport::Stdio);
+        }
+        if let Some(path) = endpoint.strip_prefix("unix:") {
+            let path = path.trim_start_matches("//");
+            if path.is_empty() {
+                return Err("missing socket path".to_string());
+            }
+            return Ok(AgentTransport::Unix {
+                path: PathBuf::from(path),
+            });
+        }
+        if let Some(address) = endpoint.strip_prefix("tcp://") {
+            if !address.contains(':') {
+                return Err(format!("expected host:port, got '{}'", address));
+            }
+            return Ok(AgentTransport::Tcp {
+                address: address.to_string(),
+            });
+        }
+        if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+            return Ok(AgentTransport::WebSocket {
+                url: endpoint.to_string(),
+                headers: HashMap::new(),
+            });
+        }
+        Err(format!(
+            "unsupported endpoint '{}' (use unix:, tcp://, ws:// or wss://)",
+            endpoint
+        ))
+    }
+}
+
 /// Model configuration for LLM providers
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ModelConfig {
@@ -56,52 +211,137 @@ pub struct ModelConfig {
     pub base_url: String,
     pub api_key: String,
     pub model_name: String,
+    /// Prices used to cost the token usage of this model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<ModelPrice>,
+}
+
+/// Price of a model in US dollars per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    /// Price of cached input tokens; the input price when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input_per_million: Option<f64>,
 }
 
 /// MCP (Model Context Protocol) server configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct McpServerConfig {
-    #[serde(default = "default_true")]
-    pub enabled: bool,
-    pub command: String,
-    #[serde(default)]
-    pub args: Vec<String>,
-    #[serde(default)]
-    pub env: HashMap<String, String>,
+///
+/// Serialized with a `type` tag. Entries without a tag are stdio servers (the
+/// simplified `{ "command", "args", "env" }` form), and the full form that
+/// nests the server under `config` is accepted as well; it is written back in
+/// the tagged form, keeping its description. Headers and env may be
+/// written either as an object or as a list of `{ "name", "value" }` pairs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "lowercase",
+    try_from = "RawMcpServerConfig"
+)]
+pub enum McpServerConfig {
+    /// Server spawned by the agent as a child process
+    Stdio {
+        enabled: bool,
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        /// Note about the server, kept for the user
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// Server reached over streamable HTTP
+    Http {
+        enabled: bool,
+        url: String,
+        headers: HashMap<String, String>,
+        /// Note about the server, kept for the user
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// Server reached over HTTP with server-sent events
+    Sse {
+        enabled: bool,
+        url: String,
+        headers: HashMap<String, String>,
+        /// Note about the server, kept for the user
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
 }
 
 impl McpServerConfig {
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Self::Stdio { enabled, .. }
+            | Self::Http { enabled, .. }
+            | Self::Sse { enabled, .. } => *enabled,
+        }
+    }
+
+    /// Transport name as written in the `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Stdio { .. } => "stdio",
+            Self::Http { .. } => "http",
+            Self::Sse { .. } => "sse",
+        }
+    }
+
+    /// Command line or URL, for display
+    pub fn target(&self) -> String {
+        match self {
+            Self::Stdio { command, args, .. } => std::iter::once(command.as_str())
+                .chain(args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Self::Http { url, .. } | Self::Sse { url, .. } => url.clone(),
+        }
+    }
+
+    /// Whether an agent advertising `capabilities` can use this server.
+    /// Stdio support is mandatory in ACP, HTTP and SSE are opt-in.
+    pub fn is_supported_by(&self, capabilities: &acp::McpCapabilities) -> bool {
+        match self {
+            Self::Stdio { .. } => true,
+            Self::Http { .. } => capabilities.http,
+            Self::Sse { .. } => capabilities.sse,
+        }
+    }
+
     /// Convert to agent_client_protocol::McpServer
     pub fn to_acp_mcp_server(&self, name: String) -> acp::McpServer {
-        // Try to deserialize into McpServerStdio via JSON
-        let env_vars: Vec<serde_json::Value> = self
-            .env
-            .iter()
-            .map(|(k, v)| {
-                serde_json::json!({
-                    "name": k,
-                    "value": v
-                })
-            })
-            .collect();
-
-        let stdio_json = serde_json::json!({
-            "name": name,
-            "command": self.command,
-            "args": self.args,
-            "env": env_vars
-        });
-
-        match serde_json::from_value::<acp::McpServerStdio>(stdio_json) {
-            Ok(stdio) => acp::McpServer::Stdio(stdio),
+        let json = match self {
+            Self::Stdio {
+                command, args, env, ..
+            } => serde_json::json!({
+                "name": name,
+                "command": command,
+                "args": args,
+                "env": name_value_pairs(env)
+            }),
+            Self::Http { url, headers, .. } | Self::Sse { url, headers, .. } => serde_json::json!({
+                "type": self.kind(),
+                "name": name,
+                "url": url,
+                "headers": name_value_pairs(headers)
+            }),
+        };
+
+        match serde_json::from_value::<acp::McpServer>(json) {
+            Ok(server) => server,
             Err(e) => {
-                log::error!("Failed to create McpServerStdio for '{}': {}", name, e);
+                log::error!("Failed to create McpServer for '{}': {}", name, e);
                 // Fallback to a minimal valid config
+                let (command, args) = match self {
+                    Self::Stdio { command, args, .. } => (command.clone(), args.clone()),
+                    _ => (String::new(), Vec::new()),
+                };
                 acp::McpServer::Stdio(
                     serde_json::from_value(serde_json::json!({
                         "name": name,
-                        "command": self.command,
-                        "args": self.args,
+                        "command": command,
+                        "args": args,
                         "env": []
                     }))
                     .unwrap(),
@@ -111,6 +351,147 @@ impl McpServerConfig {
     }
 }
 
+/// MCP servers keyed by name, skipping `"_comment": "..."` entries
+fn deserialize_mcp_servers<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, McpServerConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let entries = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    entries
+        .into_iter()
+        .filter(|(name, value)| !(name.starts_with('_') && value.is_string()))
+        .map(|(name, value)| {
+            serde_json::from_value(value)
+                .map(|config| (name.clone(), config))
+                .map_err(|e| serde::de::Error::custom(format!("MCP server '{name}': {e}")))
+        })
+        .collect()
+}
+
+/// ACP writes env vars and headers as `[{ "name", "value" }]`, sorted here so
+/// the request is stable between sessions
+fn name_value_pairs(map: &HashMap<String, String>) -> Vec<serde_json::Value> {
+    let mut pairs: Vec<_> = map.iter().collect();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+        .collect()
+}
+
+/// Env vars or headers, as an object or as a list of name/value pairs
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyValues {
+    Map(HashMap<String, String>),
+    List(Vec<NameValue>),
+}
+
+#[derive(Deserialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+impl Default for KeyValues {
+    fn default() -> Self {
+        Self::Map(HashMap::new())
+    }
+}
+
+impl From<KeyValues> for HashMap<String, String> {
+    fn from(values: KeyValues) -> Self {
+        match values {
+            KeyValues::Map(map) => map,
+            KeyValues::List(list) => list.into_iter().map(|kv| (kv.name, kv.value)).collect(),
+        }
+    }
+}
+
+/// Every accepted spelling of an MCP server entry, checked by `TryFrom`
+#[derive(Deserialize)]
+struct RawMcpServerConfig {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: KeyValues,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    headers: KeyValues,
+    #[serde(default)]
+    description: Option<String>,
+    /// Full format: `{ "enabled", "description", "config": { ... } }`
+    #[serde(default)]
+    config: Option<Box<RawMcpServerConfig>>,
+}
+
+impl TryFrom<RawMcpServerConfig> for McpServerConfig {
+    type Error = String;
+
+    fn try_from(mut raw: RawMcpServerConfig) -> Result<Self, Self::Error> {
+        let enabled = raw.enabled;
+        let mut description = raw.description.take();
+        if let Some(inner) = raw.config.take() {
+            raw = *inner;
+            description = description.or(raw.description.take());
+        }
+
+        let url = |raw: &mut RawMcpServerConfig, kind: &str| {
+            let url = raw.url.take().unwrap_or_default().trim().to_string();
+            if url.is_empty() {
+                return Err(format!("{kind} MCP server requires a url"));
+            }
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(format!(
+                    "{kind} MCP server url must start with http:// or https://, got '{url}'"
+                ));
+            }
+            Ok(url)
+        };
+
+        let kind = raw.kind.take().unwrap_or_else(|| "stdio".to_string());
+        match kind.as_str() {
+            "stdio" => {
+                let command = raw.command.take().unwrap_or_default().trim().to_string();
+                if command.is_empty() {
+                    return Err("stdio MCP server requires a command".to_string());
+                }
+                Ok(Self::Stdio {
+                    enabled,
+                    command,
+                    args: raw.args,
+                    env: raw.env.into(),
+                    description,
+                })
+            }
+            "http" => Ok(Self::Http {
+                enabled,
+                url: url(&mut raw, "http")?,
+                headers: raw.headers.into(),
+                description,
+            }),
+            "sse" => Ok(Self::Sse {
+                enabled,
+                url: url(&mut raw, "sse")?,
+                headers: raw.headers.into(),
+                description,
+            }),
+            other => Err(format!(
+                "unknown MCP server type '{other}', expected stdio, http or sse"
+            )),
+        }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
@@ -145,6 +526,203 @@ pub struct ProxyConfig {
     pub password: String,
 }
 
+/// Agent stderr capture configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AgentLogConfig {
+    /// Lines kept in memory per agent
+    #[serde(default = "default_agent_log_buffer_lines")]
+    pub buffer_lines: usize,
+    /// Mirror stderr to `<user_data_dir>/logs/agents/<agent>.log`
+    #[serde(default)]
+    pub write_to_file: bool,
+    /// Size at which a log file is rotated
+    #[serde(default = "default_agent_log_max_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Rotated files kept per agent (`<agent>.log.1` ... `<agent>.log.N`)
+    #[serde(default = "default_agent_log_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for AgentLogConfig {
+    fn default() -> Self {
+        Self {
+            buffer_lines: default_agent_log_buffer_lines(),
+            write_to_file: false,
+            max_file_bytes: default_agent_log_max_file_bytes(),
+            max_files: default_agent_log_max_files(),
+        }
+    }
+}
+
+fn default_agent_log_buffer_lines() -> usize {
+    2000
+}
+
+fn default_agent_log_max_file_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_agent_log_max_files() -> usize {
+    3
+}
+
+/// ACP protocol inspector configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProtocolTraceConfig {
+    /// Record frames from startup (can also be toggled in the inspector panel)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Append recorded frames to `<user_data_dir>/logs/acp-trace.jsonl`
+    #[serde(default)]
+    pub write_to_file: bool,
+    /// Frames kept in memory
+    #[serde(default = "default_protocol_trace_buffer_frames")]
+    pub buffer_frames: usize,
+    /// Size at which the trace file is moved to `acp-trace.jsonl.1`
+    #[serde(default = "default_protocol_trace_max_file_bytes")]
+    pub max_file_bytes: u64,
+}
+
+impl Default for ProtocolTraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            write_to_file: false,
+            buffer_frames: default_protocol_trace_buffer_frames(),
+            max_file_bytes: default_protocol_trace_max_file_bytes(),
+        }
+    }
+}
+
+fn default_protocol_trace_buffer_frames() -> usize {
+    5000
+}
+
+fn default_protocol_trace_max_file_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+/// What happens to a permission request nobody answers in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionTimeoutAction {
+    /// Reject the tool call
+    Deny,
+    /// Keep the request open and flag it as overdue
+    #[default]
+    Wait,
+}
+
+/// Permission request configuration
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct PermissionConfig {
+    /// Seconds before a pending request times out; unset never times out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub on_timeout: PermissionTimeoutAction,
+    /// Guardrails for tasks running in autopilot mode
+    #[serde(default)]
+    pub autopilot: AutopilotConfig,
+}
+
+/// Requests a task in autopilot mode still escalates to the user; everything
+/// else is approved without asking
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AutopilotConfig {
+    /// Regexes matched against the command line of tool calls that may run
+    /// commands, including those without a kind
+    #[serde(default = "default_autopilot_deny_commands")]
+    pub deny_commands: Vec<String>,
+    /// Escalate tool calls touching paths outside the session's workspace
+    #[serde(default = "default_true")]
+    pub deny_outside_workspace: bool,
+    /// Escalate fetch tools and commands such as `curl` or `ssh`
+    #[serde(default = "default_true")]
+    pub deny_network: bool,
+}
+
+impl Default for AutopilotConfig {
+    fn default() -> Self {
+        Self {
+            deny_commands: default_autopilot_deny_commands(),
+            deny_outside_workspace: true,
+            deny_network: true,
+        }
+    }
+}
+
+fn default_autopilot_deny_commands() -> Vec<String> {
+    [
+        r"\bsudo\b",
+        r"\brm\s+-[a-zA-Z]*[rf]",
+        r"\bgit\s+(push|reset\s+--hard|clean)\b",
+        r"\b(chmod|chown|mkfs|dd)\b",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+/// What happens to a session that expires
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Compress it into `<sessions_dir>/archive`
+    #[default]
+    Archive,
+    /// Remove it for good
+    Delete,
+}
+
+/// Session history retention configuration
+///
+/// Nothing expires while both limits are unset.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RetentionConfig {
+    /// Sessions untouched for longer than this many days expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    /// Oldest sessions expire while all session files together are larger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_mb: Option<u64>,
+    #[serde(default)]
+    pub action: RetentionAction,
+    /// Sessions that never expire
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_sessions: Vec<String>,
+    /// Hours between retention passes after the one at startup
+    #[serde(default = "default_retention_interval_hours")]
+    pub interval_hours: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_total_mb: None,
+            action: RetentionAction::Archive,
+            pinned_sessions: Vec::new(),
+            interval_hours: default_retention_interval_hours(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Whether any limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_total_mb.is_some()
+    }
+
+    pub fn is_pinned(&self, session_id: &str) -> bool {
+        self.pinned_sessions.iter().any(|id| id == session_id)
+    }
+}
+
+fn default_retention_interval_hours() -> u64 {
+    6
+}
+
 fn default_proxy_type() -> String {
     "http".to_string()
 }
@@ -192,6 +770,15 @@ mod tests {
             DEFAULT_TOOL_CALL_PREVIEW_MAX_LINES
         );
         assert!(!config.proxy.enabled);
+        assert!(!config.agent_logs.write_to_file);
+        assert!(!config.protocol_trace.enabled);
+        assert_eq!(config.agent_logs.buffer_lines, 2000);
+        assert_eq!(config.permissions.timeout_secs, None);
+        assert_eq!(config.permissions.on_timeout, PermissionTimeoutAction::Wait);
+        assert!(config.permissions.autopilot.deny_outside_workspace);
+        assert!(!config.permissions.autopilot.deny_commands.is_empty());
+        assert!(!config.retention.is_enabled());
+        assert_eq!(config.retention.action, RetentionAction::Archive);
     }
 
     #[test]
@@ -205,6 +792,10 @@ mod tests {
             system_prompts: HashMap::new(),
             tool_call_preview_max_lines: 20,
             proxy: ProxyConfig::default(),
+            agent_logs: AgentLogConfig::default(),
+            protocol_trace: ProtocolTraceConfig::default(),
+            permissions: PermissionConfig::default(),
+            retention: RetentionConfig::default(),
         };
 
         config.agent_servers.insert(
@@ -213,6 +804,9 @@ mod tests {
                 command: "node".to_string(),
                 args: vec!["server.js".to_string()],
                 env: HashMap::new(),
+                transport: AgentTransport::Stdio,
+                client_capabilities: Default::default(),
+                timeouts: Default::default(),
                 nodejs_path: None,
             },
         );
@@ -248,6 +842,9 @@ mod tests {
                 env.insert("API_KEY".to_string(), "secret".to_string());
                 env
             },
+            transport: AgentTransport::Stdio,
+            client_capabilities: Default::default(),
+            timeouts: Default::default(),
             nodejs_path: Some("/usr/bin/node".to_string()), // should be skipped
         };
 
@@ -259,6 +856,86 @@ mod tests {
         assert!(restored.env.contains_key("API_KEY"));
         // nodejs_path is skipped during serialization
         assert!(restored.nodejs_path.is_none());
+        // The default transport is not written out
+        assert!(!json.contains("transport"));
+        assert_eq!(restored.transport, AgentTransport::Stdio);
+        assert!(!json.contains("timeouts"));
+    }
+
+    #[test]
+    fn test_agent_process_config_timeouts() {
+        let config: AgentProcessConfig = serde_json::from_str(
+            r#"{"command": "agent", "timeouts": {"turn_secs": 600, "inactivity_secs": 0}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.timeouts.turn(), Some(Duration::from_secs(600)));
+        // Zero disables a limit
+        assert_eq!(config.timeouts.inactivity(), None);
+        assert!(!config.timeouts.is_empty());
+        assert!(PromptTimeoutConfig::default().is_empty());
+    }
+
+    #[test]
+    fn test_agent_process_config_network_transports() {
+        let json = r#"{
+            "tcp": {"transport": {"type": "tcp", "address": "127.0.0.1:9000"}},
+            "unix": {"transport": {"type": "unix", "path": "/run/agent.sock"}},
+            "ws": {"transport": {
+                "type": "websocket",
+                "url": "wss://agents.example.com/acp",
+                "headers": {"Authorization": "Bearer token"}
+            }}
+        }"#;
+        let configs: HashMap<String, AgentProcessConfig> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            configs["tcp"].transport,
+            AgentTransport::Tcp {
+                address: "127.0.0.1:9000".to_string()
+            }
+        );
+        assert_eq!(
+            configs["unix"].transport.endpoint().as_deref(),
+            Some("unix:/run/agent.sock")
+        );
+        match &configs["ws"].transport {
+            AgentTransport::WebSocket { url, headers } => {
+                assert_eq!(url, "wss://agents.example.com/acp");
+                assert_eq!(headers["Authorization"], "Bearer token");
+            }
+            other => panic!("unexpected transport {:?}", other),
+        }
+        assert!(configs["ws"].command.is_empty());
+
+        let restored: AgentProcessConfig =
+            serde_json::from_str(&serde_json::to_string(&configs["ws"]).unwrap()).unwrap();
+        assert_eq!(restored.transport, configs["ws"].transport);
+    }
+
+    #[test]
+    fn test_agent_transport_parse_endpoint() {
+        assert_eq!(
+            AgentTransport::parse_endpoint("  "),
+            Ok(AgentTransport::Stdio)
+        );
+        for transport in [
+            AgentTransport::Unix {
+                path: PathBuf::from("/tmp/agent.sock"),
+            },
+            AgentTransport::Tcp {
+                address: "localhost:7000".to_string(),
+            },
+            AgentTransport::WebSocket {
+                url: "ws://localhost:7001/acp".to_string(),
+                headers: HashMap::new(),
+            },
+        ] {
+            let endpoint = transport.endpoint().unwrap();
+            assert_eq!(AgentTransport::parse_endpoint(&endpoint), Ok(transport));
+        }
+        assert!(AgentTransport::parse_endpoint("tcp://localhost").is_err());
+        assert!(AgentTransport::parse_endpoint("http://localhost:80").is_err());
     }
 
     // ============== ProxyConfig tests ==============
@@ -331,6 +1008,11 @@ mod tests {
             base_url: "https://api.openai.com/v1".to_string(),
             api_key: "sk-xxx".to_string(),
             model_name: "gpt-4".to_string(),
+            price: Some(ModelPrice {
+                input_per_million: 30.0,
+                output_per_million: 60.0,
+                cached_input_per_million: None,
+            }),
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -338,6 +1020,14 @@ mod tests {
 
         assert_eq!(restored.provider, "openai");
         assert_eq!(restored.model_name, "gpt-4");
+        assert_eq!(restored.price, config.price);
+
+        // Configs written before prices existed still load
+        let legacy: ModelConfig = serde_json::from_str(
+            r#"{"enabled":true,"provider":"openai","base_url":"","api_key":"","model_name":"gpt-4"}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.price, None);
     }
 
     // ============== McpServerConfig tests ==============
@@ -350,12 +1040,116 @@ mod tests {
         }"#;
         let config: McpServerConfig = serde_json::from_str(json).unwrap();
 
-        assert!(config.enabled); // default_true()
+        assert!(config.is_enabled()); // default_true()
+        assert_eq!(config.kind(), "stdio");
+    }
+
+    #[test]
+    fn test_mcp_server_config_full_format() {
+        let json = r#"{
+            "enabled": false,
+            "description": "Example MCP server using HTTP transport",
+            "config": {
+                "type": "http",
+                "name": "example-http-server",
+                "url": "https://example.com/mcp",
+                "headers": [{ "name": "Authorization", "value": "Bearer token" }]
+            }
+        }"#;
+        let config: McpServerConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            config,
+            McpServerConfig::Http {
+                enabled: false,
+                url: "https://example.com/mcp".to_string(),
+                headers: HashMap::from([("Authorization".to_string(), "Bearer token".to_string())]),
+                description: Some("Example MCP server using HTTP transport".to_string()),
+            }
+        );
+
+        // Saved in the tagged form without losing anything
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["type"], "http");
+        assert_eq!(
+            json["description"],
+            "Example MCP server using HTTP transport"
+        );
+        let restored: McpServerConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, config);
+    }
+
+    #[test]
+    fn test_config_skips_mcp_comment_entries() {
+        let json = r#"{
+            "agent_servers": {},
+            "mcpServers": {
+                "_comment_simplified": "Simplified format",
+                "filesystem": { "command": "npx" }
+            }
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.mcp_servers.len(), 1);
+        assert!(config.mcp_servers.contains_key("filesystem"));
+    }
+
+    #[test]
+    fn test_mcp_server_config_roundtrip_all_types() {
+        let configs = [
+            McpServerConfig::Stdio {
+                enabled: true,
+                command: "npx".to_string(),
+                args: vec!["-y".to_string(), "server".to_string()],
+                env: HashMap::from([("DEBUG".to_string(), "true".to_string())]),
+                description: Some("Local server".to_string()),
+            },
+            McpServerConfig::Http {
+                enabled: true,
+                url: "https://example.com/mcp".to_string(),
+                headers: HashMap::from([("X-Key".to_string(), "secret".to_string())]),
+                description: None,
+            },
+            McpServerConfig::Sse {
+                enabled: false,
+                url: "http://localhost:8080/sse".to_string(),
+                headers: HashMap::new(),
+                description: None,
+            },
+        ];
+
+        for config in configs {
+            let json = serde_json::to_value(&config).unwrap();
+            assert_eq!(json["type"], config.kind());
+            let restored: McpServerConfig = serde_json::from_value(json).unwrap();
+            assert_eq!(restored, config);
+        }
+    }
+
+    #[test]
+    fn test_mcp_server_config_rejects_invalid_entries() {
+        let cases = [
+            (r#"{ "args": [] }"#, "requires a command"),
+            (r#"{ "type": "http" }"#, "requires a url"),
+            (
+                r#"{ "type": "sse", "url": "ws://host" }"#,
+                "must start with http",
+            ),
+            (
+                r#"{ "type": "grpc", "url": "https://host" }"#,
+                "unknown MCP server type",
+            ),
+        ];
+
+        for (json, expected) in cases {
+            let err = serde_json::from_str::<McpServerConfig>(json).unwrap_err();
+            assert!(err.to_string().contains(expected), "{json}: {err}");
+        }
     }
 
     #[test]
     fn test_mcp_server_config_to_acp() {
-        let config = McpServerConfig {
+        let config = McpServerConfig::Stdio {
             enabled: true,
             command: "npx".to_string(),
             args: vec!["-y".to_string(), "some-mcp-server".to_string()],
@@ -364,6 +1158,7 @@ mod tests {
                 env.insert("API_KEY".to_string(), "test-key".to_string());
                 env
             },
+            description: None,
         };
 
         let acp_server = config.to_acp_mcp_server("test-server".to_string());
@@ -379,4 +1174,52 @@ mod tests {
             _ => panic!("Expected Stdio variant"),
         }
     }
+
+    #[test]
+    fn test_mcp_server_config_http_and_sse_to_acp() {
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer t".to_string())]);
+        let http = McpServerConfig::Http {
+            enabled: true,
+            url: "https://example.com/mcp".to_string(),
+            headers: headers.clone(),
+            description: None,
+        };
+        let sse = McpServerConfig::Sse {
+            enabled: true,
+            url: "https://example.com/sse".to_string(),
+            headers,
+            description: None,
+        };
+
+        match http.to_acp_mcp_server("remote".to_string()) {
+            acp::McpServer::Http(server) => {
+                assert_eq!(server.url, "https://example.com/mcp");
+                assert_eq!(server.headers.len(), 1);
+            }
+            _ => panic!("Expected Http variant"),
+        }
+        assert!(matches!(
+            sse.to_acp_mcp_server("events".to_string()),
+            acp::McpServer::Sse(_)
+        ));
+    }
+
+    #[test]
+    fn test_mcp_server_config_capability_filter() {
+        let stdio: McpServerConfig = serde_json::from_str(r#"{ "command": "x" }"#).unwrap();
+        let http: McpServerConfig =
+            serde_json::from_str(r#"{ "type": "http", "url": "https://h" }"#).unwrap();
+        let sse: McpServerConfig =
+            serde_json::from_str(r#"{ "type": "sse", "url": "https://s" }"#).unwrap();
+
+        let none = acp::McpCapabilities::default();
+        assert!(stdio.is_supported_by(&none));
+        assert!(!http.is_supported_by(&none));
+        assert!(!sse.is_supported_by(&none));
+
+        let mut http_only = acp::McpCapabilities::default();
+        http_only.http = true;
+        assert!(http.is_supported_by(&http_only));
+        assert!(!sse.is_supported_by(&http_only));
+    }
 }
@@ -183,3 +183,29 @@ pub fn get_docks_layout_path() -> PathBuf {
 pub fn get_sessions_dir() -> PathBuf {
     user_data_dir_or_temp().join("sessions")
 }
+
+/// Get agent stderr log directory path
+/// Always uses user data directory: <user_data_dir>/logs/agents
+pub fn get_agent_logs_dir() -> PathBuf {
+    user_data_dir_or_temp().join("logs").join("agents")
+}
+
+/// Get ACP protocol trace file path
+/// Always uses user data directory: <user_data_dir>/logs/acp-trace.jsonl
+pub fn get_protocol_trace_path() -> PathBuf {
+    user_data_dir_or_temp().join("logs").join("acp-trace.jsonl")
+}
+
+/// Get permission rules file path
+/// Always uses user data directory: <user_data_dir>/permission-rules.json
+pub fn get_permission_rules_path() -> PathBuf {
+    user_data_dir_or_temp().join("permission-rules.json")
+}
+
+/// Get permission audit log directory, one file per workspace
+/// Always uses user data directory: <user_data_dir>/logs/permission-audit
+pub fn get_permission_audit_dir() -> PathBuf {
+    user_data_dir_or_temp()
+        .join("logs")
+        .join("permission-audit")
+}
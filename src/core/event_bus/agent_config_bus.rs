@@ -280,6 +280,9 @@ mod tests {
             command: "test-command".to_string(),
             args: vec![],
             env: HashMap::new(),
+            transport: crate::core::config::AgentTransport::Stdio,
+            client_capabilities: Default::default(),
+            timeouts: Default::default(),
             nodejs_path: None,
         };
 
@@ -0,0 +1,81 @@
+//! Agent Log Event Bus
+//!
+//! Broadcasts stderr lines captured from agent processes so the log viewer
+//! can follow them live.
+
+use crate::core::agent::AgentLogLine;
+
+use super::core::{EventBusContainer, SubscriptionId};
+
+/// A line an agent process wrote to stderr
+#[derive(Clone, Debug)]
+pub struct AgentLogEvent {
+    pub agent_name: String,
+    pub line: AgentLogLine,
+}
+
+/// Specialized container for agent log events
+#[derive(Clone)]
+pub struct AgentLogBusContainer {
+    inner: EventBusContainer<AgentLogEvent>,
+}
+
+impl AgentLogBusContainer {
+    /// Create a new agent log bus
+    pub fn new() -> Self {
+        Self {
+            inner: EventBusContainer::new(),
+        }
+    }
+
+    /// Subscribe to log lines of all agents
+    pub fn subscribe<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&AgentLogEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe(move |event| {
+            callback(event);
+            true // Keep subscription active
+        })
+    }
+
+    /// Subscribe to log lines of a specific agent only
+    pub fn subscribe_agent<F>(&self, agent_name: String, callback: F) -> SubscriptionId
+    where
+        F: Fn(&AgentLogEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe_with_filter(
+            move |event| {
+                callback(event);
+                true
+            },
+            move |event| event.agent_name == agent_name,
+        )
+    }
+
+    /// Unsubscribe using a subscription ID
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.inner.unsubscribe(id)
+    }
+
+    /// Publish a log line to all subscribers
+    pub fn publish(&self, event: AgentLogEvent) {
+        self.inner.publish(event);
+    }
+
+    /// Get the number of active subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.subscriber_count()
+    }
+
+    /// Clear all subscriptions
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+}
+
+impl Default for AgentLogBusContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+//! Extension Event Bus
+//!
+//! Broadcasts ACP extension requests and notifications that no registered
+//! handler claimed, so panels can react to vendor extensions.
+
+use serde_json::Value;
+
+use crate::core::agent::ExtensionKind;
+
+use super::core::{EventBusContainer, SubscriptionId};
+
+/// An extension message from an agent without a handler
+#[derive(Clone, Debug)]
+pub struct ExtensionEvent {
+    pub agent_name: String,
+    /// Method name as sent by the agent, e.g. `_claude/usage`
+    pub method: String,
+    pub kind: ExtensionKind,
+    pub params: Value,
+}
+
+/// Specialized container for extension events
+#[derive(Clone)]
+pub struct ExtensionBusContainer {
+    inner: EventBusContainer<ExtensionEvent>,
+}
+
+impl ExtensionBusContainer {
+    /// Create a new extension bus
+    pub fn new() -> Self {
+        Self {
+            inner: EventBusContainer::new(),
+        }
+    }
+
+    /// Subscribe to unhandled extensions of all agents
+    pub fn subscribe<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&ExtensionEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe(move |event| {
+            callback(event);
+            true // Keep subscription active
+        })
+    }
+
+    /// Subscribe to unhandled extensions with a given method name only
+    pub fn subscribe_method<F>(&self, method: String, callback: F) -> SubscriptionId
+    where
+        F: Fn(&ExtensionEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe_with_filter(
+            move |event| {
+                callback(event);
+                true
+            },
+            move |event| event.method == method,
+        )
+    }
+
+    /// Unsubscribe using a subscription ID
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.inner.unsubscribe(id)
+    }
+
+    /// Publish an unhandled extension to all subscribers
+    pub fn publish(&self, event: ExtensionEvent) {
+        self.inner.publish(event);
+    }
+
+    /// Get the number of active subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.subscriber_count()
+    }
+
+    /// Clear all subscriptions
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+}
+
+impl Default for ExtensionBusContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
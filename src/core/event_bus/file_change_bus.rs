@@ -0,0 +1,135 @@
+//! File Change Event Bus
+//!
+//! Broadcasts files written on behalf of agents so open editors can reload them.
+
+use std::path::PathBuf;
+
+use super::core::{EventBusContainer, SubscriptionId};
+
+/// File change event that can be broadcast to subscribers
+#[derive(Clone, Debug)]
+pub struct FileChangeEvent {
+    /// Absolute path of the file that changed
+    pub path: PathBuf,
+    /// Session that caused the change (if any)
+    pub session_id: Option<String>,
+    /// Agent that caused the change (if any)
+    pub agent_name: Option<String>,
+}
+
+/// Specialized container for file change events
+#[derive(Clone)]
+pub struct FileChangeBusContainer {
+    inner: EventBusContainer<FileChangeEvent>,
+}
+
+impl FileChangeBusContainer {
+    /// Create a new file change bus
+    pub fn new() -> Self {
+        Self {
+            inner: EventBusContainer::new(),
+        }
+    }
+
+    /// Subscribe to all file changes
+    pub fn subscribe<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&FileChangeEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe(move |event| {
+            callback(event);
+            true // Keep subscription active
+        })
+    }
+
+    /// Subscribe to changes of a single file only
+    pub fn subscribe_path<F>(&self, path: PathBuf, callback: F) -> SubscriptionId
+    where
+        F: Fn(&FileChangeEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe_with_filter(
+            move |event| {
+                callback(event);
+                true
+            },
+            move |event| event.path == path,
+        )
+    }
+
+    /// Unsubscribe using a subscription ID
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.inner.unsubscribe(id)
+    }
+
+    /// Publish a file change to all subscribers
+    pub fn publish(&self, event: FileChangeEvent) {
+        log::trace!("[FileChangeBus] Publishing change for: {:?}", event.path);
+        self.inner.publish(event);
+    }
+
+    /// Get the number of active subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.subscriber_count()
+    }
+
+    /// Clear all subscriptions
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+}
+
+impl Default for FileChangeBusContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn change(path: &str) -> FileChangeEvent {
+        FileChangeEvent {
+            path: PathBuf::from(path),
+            session_id: Some("session-1".to_string()),
+            agent_name: Some("agent-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_and_publish() {
+        let bus = FileChangeBusContainer::new();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let count_clone = count.clone();
+        bus.subscribe(move |_| {
+            count_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        bus.publish(change("/tmp/a.rs"));
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_subscribe_path_filter() {
+        let bus = FileChangeBusContainer::new();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let count_clone = count.clone();
+        bus.subscribe_path(PathBuf::from("/tmp/a.rs"), move |_| {
+            count_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        // Should be filtered out
+        bus.publish(change("/tmp/b.rs"));
+
+        // Should pass filter
+        bus.publish(change("/tmp/a.rs"));
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
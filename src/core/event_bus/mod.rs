@@ -13,9 +13,14 @@ pub mod core;
 
 // Specialized event buses
 pub mod agent_config_bus;
+pub mod agent_log_bus;
 pub mod code_selection_bus;
+pub mod extension_bus;
+pub mod file_change_bus;
 pub mod permission_bus;
+pub mod protocol_trace_bus;
 pub mod session_bus;
+pub mod terminal_bus;
 pub mod workspace_bus;
 
 // Re-export core types
@@ -24,9 +29,16 @@ pub use core::{EventBus, EventBusContainer, EventBusStats, SubscriptionId};
 
 // Re-export specialized event bus types
 pub use agent_config_bus::{AgentConfigBusContainer, AgentConfigEvent};
+pub use agent_log_bus::{AgentLogBusContainer, AgentLogEvent};
 pub use code_selection_bus::{
     CodeSelectionBusContainer, CodeSelectionEvent, subscribe_entity_to_code_selections,
 };
-pub use permission_bus::{PermissionBusContainer, PermissionRequestEvent};
+pub use extension_bus::{ExtensionBusContainer, ExtensionEvent};
+pub use file_change_bus::{FileChangeBusContainer, FileChangeEvent};
+pub use permission_bus::{
+    PermissionBusContainer, PermissionRequestEvent, PermissionResolution, PermissionStatusEvent,
+};
+pub use protocol_trace_bus::{ProtocolTraceBusContainer, ProtocolTraceEvent};
 pub use session_bus::{SessionUpdateBusContainer, SessionUpdateEvent};
+pub use terminal_bus::{TerminalBusContainer, TerminalEvent, TerminalEventKind};
 pub use workspace_bus::{WorkspaceUpdateBusContainer, WorkspaceUpdateEvent};
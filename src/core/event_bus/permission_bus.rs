@@ -20,14 +20,64 @@ pub struct PermissionRequestEvent {
     pub tool_call: acp::ToolCallUpdate,
     /// Available permission options
     pub options: Vec<acp::PermissionOption>,
+    /// Why autopilot handed the request to the user, if the session's task
+    /// runs in autopilot mode
+    pub escalation: Option<String>,
+}
+
+/// Who or what answered a permission request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermissionResolution {
+    /// Picked in a conversation or the permissions queue
+    User,
+    /// Denied after the configured timeout
+    TimedOut,
+    /// The session's prompt turn was cancelled
+    Cancelled,
+}
+
+/// Change of a pending permission request after it was published
+#[derive(Clone, Debug)]
+pub enum PermissionStatusEvent {
+    /// The timeout passed and the request keeps waiting for an answer
+    Overdue {
+        permission_id: String,
+        session_id: String,
+    },
+    /// The request was answered and left the queue
+    Resolved {
+        permission_id: String,
+        session_id: String,
+        agent_name: String,
+        outcome: acp::RequestPermissionOutcome,
+        resolution: PermissionResolution,
+    },
+}
+
+impl PermissionStatusEvent {
+    pub fn permission_id(&self) -> &str {
+        match self {
+            PermissionStatusEvent::Overdue { permission_id, .. }
+            | PermissionStatusEvent::Resolved { permission_id, .. } => permission_id,
+        }
+    }
+
+    pub fn session_id(&self) -> &str {
+        match self {
+            PermissionStatusEvent::Overdue { session_id, .. }
+            | PermissionStatusEvent::Resolved { session_id, .. } => session_id,
+        }
+    }
 }
 
 /// Specialized container for permission request events
 ///
 /// Provides additional convenience methods for permission-specific filtering.
+/// Requests and their later status changes are published separately.
 #[derive(Clone)]
 pub struct PermissionBusContainer {
     inner: EventBusContainer<PermissionRequestEvent>,
+    status: EventBusContainer<PermissionStatusEvent>,
 }
 
 impl PermissionBusContainer {
@@ -35,6 +85,7 @@ impl PermissionBusContainer {
     pub fn new() -> Self {
         Self {
             inner: EventBusContainer::new(),
+            status: EventBusContainer::new(),
         }
     }
 
@@ -109,6 +160,31 @@ impl PermissionBusContainer {
         self.inner.publish(event);
     }
 
+    /// Subscribe to status changes of published requests
+    pub fn subscribe_status<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&PermissionStatusEvent) + Send + Sync + 'static,
+    {
+        self.status.subscribe(move |event| {
+            callback(event);
+            true
+        })
+    }
+
+    /// Unsubscribe from status changes
+    pub fn unsubscribe_status(&self, id: SubscriptionId) -> bool {
+        self.status.unsubscribe(id)
+    }
+
+    /// Publish a status change of a request
+    pub fn publish_status(&self, event: PermissionStatusEvent) {
+        log::trace!(
+            "[PermissionBus] Publishing status of permission request: {}",
+            event.permission_id()
+        );
+        self.status.publish(event);
+    }
+
     /// Get the number of active subscriptions
     pub fn subscriber_count(&self) -> usize {
         self.inner.subscriber_count()
@@ -122,6 +198,7 @@ impl PermissionBusContainer {
     /// Clear all subscriptions
     pub fn clear(&self) {
         self.inner.clear();
+        self.status.clear();
     }
 }
 
@@ -150,6 +227,7 @@ mod tests {
             agent_name: agent_name.to_string(),
             tool_call,
             options: vec![],
+            escalation: None,
         }
     }
 
@@ -0,0 +1,80 @@
+//! Protocol Trace Event Bus
+//!
+//! Broadcasts ACP frames recorded by the protocol inspector so its panel can
+//! follow the traffic live.
+
+use crate::core::agent::TraceFrame;
+
+use super::core::{EventBusContainer, SubscriptionId};
+
+/// A JSON-RPC frame exchanged with an agent
+#[derive(Clone, Debug)]
+pub struct ProtocolTraceEvent {
+    pub frame: TraceFrame,
+}
+
+/// Specialized container for protocol trace events
+#[derive(Clone)]
+pub struct ProtocolTraceBusContainer {
+    inner: EventBusContainer<ProtocolTraceEvent>,
+}
+
+impl ProtocolTraceBusContainer {
+    /// Create a new protocol trace bus
+    pub fn new() -> Self {
+        Self {
+            inner: EventBusContainer::new(),
+        }
+    }
+
+    /// Subscribe to frames of all agents
+    pub fn subscribe<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&ProtocolTraceEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe(move |event| {
+            callback(event);
+            true // Keep subscription active
+        })
+    }
+
+    /// Subscribe to frames of a specific agent only
+    pub fn subscribe_agent<F>(&self, agent_name: String, callback: F) -> SubscriptionId
+    where
+        F: Fn(&ProtocolTraceEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe_with_filter(
+            move |event| {
+                callback(event);
+                true
+            },
+            move |event| event.frame.agent_name == agent_name,
+        )
+    }
+
+    /// Unsubscribe using a subscription ID
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.inner.unsubscribe(id)
+    }
+
+    /// Publish a frame to all subscribers
+    pub fn publish(&self, event: ProtocolTraceEvent) {
+        self.inner.publish(event);
+    }
+
+    /// Get the number of active subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.subscriber_count()
+    }
+
+    /// Clear all subscriptions
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+}
+
+impl Default for ProtocolTraceBusContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
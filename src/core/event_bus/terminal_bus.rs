@@ -0,0 +1,96 @@
+//! Terminal Event Bus
+//!
+//! Broadcasts output and exit notifications for terminals created by agents so
+//! tool call views can follow commands live.
+
+use crate::core::agent::TerminalExit;
+
+use super::core::{EventBusContainer, SubscriptionId};
+
+/// What happened to a terminal
+#[derive(Clone, Debug)]
+pub enum TerminalEventKind {
+    /// New output was appended (raw chunk, before truncation)
+    Output(String),
+    /// The command exited or was killed
+    Exited(TerminalExit),
+}
+
+/// Terminal event that can be broadcast to subscribers
+#[derive(Clone, Debug)]
+pub struct TerminalEvent {
+    pub terminal_id: String,
+    pub session_id: String,
+    pub agent_name: String,
+    pub kind: TerminalEventKind,
+}
+
+/// Specialized container for terminal events
+#[derive(Clone)]
+pub struct TerminalBusContainer {
+    inner: EventBusContainer<TerminalEvent>,
+}
+
+impl TerminalBusContainer {
+    /// Create a new terminal bus
+    pub fn new() -> Self {
+        Self {
+            inner: EventBusContainer::new(),
+        }
+    }
+
+    /// Subscribe to events of all terminals
+    pub fn subscribe<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&TerminalEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe(move |event| {
+            callback(event);
+            true // Keep subscription active
+        })
+    }
+
+    /// Subscribe to terminals of a specific session only
+    pub fn subscribe_session<F>(&self, session_id: String, callback: F) -> SubscriptionId
+    where
+        F: Fn(&TerminalEvent) + Send + Sync + 'static,
+    {
+        self.inner.subscribe_with_filter(
+            move |event| {
+                callback(event);
+                true
+            },
+            move |event| event.session_id == session_id,
+        )
+    }
+
+    /// Unsubscribe using a subscription ID
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.inner.unsubscribe(id)
+    }
+
+    /// Publish a terminal event to all subscribers
+    pub fn publish(&self, event: TerminalEvent) {
+        log::trace!(
+            "[TerminalBus] Publishing event for terminal: {}",
+            event.terminal_id
+        );
+        self.inner.publish(event);
+    }
+
+    /// Get the number of active subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.subscriber_count()
+    }
+
+    /// Clear all subscriptions
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+}
+
+impl Default for TerminalBusContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -35,6 +35,44 @@ pub enum WorkspaceUpdateEvent {
         last_active: DateTime<Utc>,
         message_count: usize,
     },
+    /// An agent process or its connection died unexpectedly
+    AgentCrashed { agent_name: String, reason: String },
+    /// A crashed agent was started again
+    AgentRestarted { agent_name: String, attempt: u32 },
+    /// Restarting a crashed agent was abandoned after too many attempts
+    AgentRestartFailed { agent_name: String, attempts: u32 },
+    /// An agent could not be started or failed its ACP initialization
+    AgentInitFailed {
+        agent_name: String,
+        error: String,
+        /// Last lines the agent wrote to stderr
+        stderr_tail: Vec<String>,
+    },
+    /// A prompt turn exceeded the agent's turn or inactivity timeout and
+    /// was cancelled
+    PromptTimedOut {
+        session_id: String,
+        agent_name: String,
+        reason: String,
+    },
+    /// A session interrupted by an agent crash could not be reopened after
+    /// the restart and a new session took its place
+    SessionReplaced {
+        agent_name: String,
+        old_session_id: String,
+        new_session_id: String,
+    },
+    /// History replayed by the agent on session/load added messages that
+    /// were missing from the local session file
+    SessionHistoryRestored { session_id: String, added: usize },
+    /// An agent reported what it is doing through a `*/status` extension
+    /// notification; `None` clears the text
+    AgentStatusText {
+        agent_name: String,
+        /// Session the text belongs to, all sessions of the agent if absent
+        session_id: Option<String>,
+        text: Option<String>,
+    },
 }
 
 /// Specialized container for workspace update events
@@ -8,7 +8,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::core::agent::AgentManager;
-use crate::core::config::{AgentProcessConfig, Config};
+use crate::core::config::{AgentProcessConfig, Config, RetentionConfig};
 use crate::core::event_bus::{AgentConfigBusContainer, AgentConfigEvent};
 use crate::core::services::AgentService;
 use anyhow::{Context, Result, anyhow};
@@ -142,8 +142,10 @@ impl AgentConfigService {
 
     /// Add a new agent
     pub async fn add_agent(&self, name: String, config: AgentProcessConfig) -> Result<()> {
-        // Validate command
-        self.validate_command(&config.command)?;
+        // Validate command; network agents are already running elsewhere
+        if config.transport.is_stdio() {
+            self.validate_command(&config.command)?;
+        }
 
         // Check for duplicate
         {
@@ -181,8 +183,10 @@ impl AgentConfigService {
 
     /// Update an existing agent's configuration
     pub async fn update_agent(&self, name: &str, config: AgentProcessConfig) -> Result<()> {
-        // Validate command
-        self.validate_command(&config.command)?;
+        // Validate command; network agents are already running elsewhere
+        if config.transport.is_stdio() {
+            self.validate_command(&config.command)?;
+        }
 
         // Check if agent exists
         {
@@ -583,6 +587,50 @@ impl AgentConfigService {
         Ok(())
     }
 
+    /// Get the session retention configuration
+    pub async fn retention_config(&self) -> RetentionConfig {
+        let config = self.config.read().await;
+        config.retention.clone()
+    }
+
+    /// Update the session retention limits; pinned sessions are kept
+    pub async fn update_retention(&self, retention: RetentionConfig) -> Result<()> {
+        {
+            let mut current_config = self.config.write().await;
+            let pinned_sessions = std::mem::take(&mut current_config.retention.pinned_sessions);
+            current_config.retention = RetentionConfig {
+                pinned_sessions,
+                ..retention
+            };
+        }
+
+        self.save_to_file().await?;
+
+        log::info!("Successfully updated session retention");
+        Ok(())
+    }
+
+    /// Pin a session so the retention policy never expires it, or unpin it
+    pub async fn set_session_pinned(&self, session_id: &str, pinned: bool) -> Result<()> {
+        {
+            let mut current_config = self.config.write().await;
+            let pinned_sessions = &mut current_config.retention.pinned_sessions;
+            pinned_sessions.retain(|id| id != session_id);
+            if pinned {
+                pinned_sessions.push(session_id.to_string());
+            }
+        }
+
+        self.save_to_file().await?;
+
+        log::info!(
+            "Session {} {}",
+            session_id,
+            if pinned { "pinned" } else { "unpinned" }
+        );
+        Ok(())
+    }
+
     /// Update system prompts configuration
     pub async fn update_system_prompts(
         &self,
@@ -811,6 +859,10 @@ mod tests {
             system_prompts: HashMap::new(),
             tool_call_preview_max_lines: 10,
             proxy: crate::core::config::ProxyConfig::default(),
+            agent_logs: crate::core::config::AgentLogConfig::default(),
+            protocol_trace: crate::core::config::ProtocolTraceConfig::default(),
+            permissions: crate::core::config::PermissionConfig::default(),
+            retention: crate::core::config::RetentionConfig::default(),
         };
 
         assert!(config.agent_servers.is_empty());
@@ -828,6 +880,9 @@ mod tests {
                 env.insert("API_KEY".to_string(), "test".to_string());
                 env
             },
+            transport: crate::core::config::AgentTransport::Stdio,
+            client_capabilities: Default::default(),
+            timeouts: Default::default(),
             nodejs_path: None,
         };
 
@@ -844,6 +899,9 @@ mod tests {
             command: "test".to_string(),
             args: vec![],
             env: HashMap::new(),
+            transport: crate::core::config::AgentTransport::Stdio,
+            client_capabilities: Default::default(),
+            timeouts: Default::default(),
             nodejs_path: None,
         };
 
@@ -884,6 +942,10 @@ mod tests {
             system_prompts: HashMap::new(),
             tool_call_preview_max_lines: 15,
             proxy: crate::core::config::ProxyConfig::default(),
+            agent_logs: crate::core::config::AgentLogConfig::default(),
+            protocol_trace: crate::core::config::ProtocolTraceConfig::default(),
+            permissions: crate::core::config::PermissionConfig::default(),
+            retention: crate::core::config::RetentionConfig::default(),
         };
 
         let event = AgentConfigEvent::ConfigReloaded {
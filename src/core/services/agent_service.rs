@@ -5,7 +5,8 @@
 //! and Session is a child entity.
 
 use std::{
-    collections::{HashMap, hash_map::Entry},
+    collections::{HashMap, HashSet, hash_map::Entry},
+    path::PathBuf,
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -13,11 +14,20 @@ use std::{
 use agent_client_protocol::{self as acp, AvailableCommand, PromptResponse};
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
+use futures::future::{Either, select};
 use serde::{Deserialize, Serialize};
 
-use crate::core::agent::{AgentHandle, AgentManager};
+use super::SessionStatsCollector;
+use super::prompt_watchdog::{self, TurnActivity, WatchdogTrigger};
+use super::usage_ledger::{UsageLedger, UsageRecord, UsageSource, usage_from_prompt_response};
+use crate::core::agent::{AgentHandle, AgentManager, STDERR_TAIL_LINES, TerminalRegistry};
+use crate::core::config::McpServerConfig;
+use crate::core::event_bus::session_bus::SessionUpdateBusContainer;
 use crate::core::event_bus::workspace_bus::{WorkspaceUpdateBusContainer, WorkspaceUpdateEvent};
 
+/// How long to wait for a hung agent to take a cancel request
+const TIMEOUT_CANCEL_WAIT: Duration = Duration::from_secs(5);
+
 /// Agent service - manages agents and their sessions
 pub struct AgentService {
     agent_manager: Arc<AgentManager>,
@@ -25,6 +35,16 @@ pub struct AgentService {
     sessions: Arc<RwLock<HashMap<String, HashMap<String, AgentSessionInfo>>>>,
     /// Workspace event bus for publishing status updates
     workspace_bus: Option<WorkspaceUpdateBusContainer>,
+    /// Session update bus, watched for activity while a prompt runs
+    session_bus: Option<SessionUpdateBusContainer>,
+    /// Session statistics, used for the message counts of status updates
+    session_stats: Option<SessionStatsCollector>,
+    /// Ledger recording the token usage reported for each prompt
+    usage_ledger: Option<UsageLedger>,
+    /// Terminal output of the sessions, dropped when a session closes
+    terminals: Option<TerminalRegistry>,
+    /// Sessions each agent's last crash interrupted, reopened once it restarts
+    interrupted: Arc<RwLock<HashMap<String, HashSet<String>>>>,
 }
 
 /// Agent session information
@@ -39,6 +59,10 @@ pub struct AgentSessionInfo {
     pub new_session_response: Option<acp::NewSessionResponse>,
     /// Available commands for this session (slash commands, etc.)
     pub available_commands: Vec<AvailableCommand>,
+    /// Working directory the session was opened with
+    pub cwd: Option<PathBuf>,
+    /// MCP servers the session was opened with, reused when resuming
+    pub mcp_servers: Vec<acp::McpServer>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -51,6 +75,53 @@ pub enum SessionStatus {
     Completed,
     Closed,
     Failed,
+    /// The agent refused to continue the turn
+    Refused,
+    /// The turn stopped at the model's token limit
+    MaxTokens,
+    /// The turn stopped at the agent's limit of model requests
+    MaxTurnRequests,
+    /// The turn was cancelled by the user
+    Cancelled,
+    /// The turn exceeded the agent's turn or inactivity timeout and was cancelled
+    TimedOut,
+}
+
+impl SessionStatus {
+    /// Status of a session after a prompt turn ended for `reason`
+    pub fn from_stop_reason(reason: &acp::StopReason) -> Self {
+        match reason {
+            acp::StopReason::EndTurn => SessionStatus::Completed,
+            acp::StopReason::MaxTokens => SessionStatus::MaxTokens,
+            acp::StopReason::MaxTurnRequests => SessionStatus::MaxTurnRequests,
+            acp::StopReason::Refusal => SessionStatus::Refused,
+            acp::StopReason::Cancelled => SessionStatus::Cancelled,
+            #[allow(unreachable_patterns)]
+            _ => SessionStatus::Completed,
+        }
+    }
+
+    /// Whether the agent finished its reply, for whatever reason
+    pub fn is_turn_end(&self) -> bool {
+        matches!(
+            self,
+            SessionStatus::Completed
+                | SessionStatus::Idle
+                | SessionStatus::Refused
+                | SessionStatus::MaxTokens
+                | SessionStatus::MaxTurnRequests
+                | SessionStatus::Cancelled
+                | SessionStatus::TimedOut
+        )
+    }
+
+    /// Whether the turn stopped at a limit and can be continued
+    pub fn can_continue(&self) -> bool {
+        matches!(
+            self,
+            SessionStatus::MaxTokens | SessionStatus::MaxTurnRequests
+        )
+    }
 }
 
 impl AgentService {
@@ -59,6 +130,11 @@ impl AgentService {
             agent_manager,
             sessions: Arc::new(RwLock::new(HashMap::new())),
             workspace_bus: None,
+            session_bus: None,
+            session_stats: None,
+            usage_ledger: None,
+            terminals: None,
+            interrupted: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -68,6 +144,57 @@ impl AgentService {
         self.workspace_bus = Some(bus);
     }
 
+    /// Set the session update bus watched for activity while a prompt runs
+    pub fn set_session_bus(&mut self, bus: SessionUpdateBusContainer) {
+        self.session_bus = Some(bus);
+    }
+
+    /// Set the statistics collector for the message counts of status updates
+    pub fn set_session_stats(&mut self, stats: SessionStatsCollector) {
+        self.session_stats = Some(stats);
+    }
+
+    /// Set the ledger recording the token usage reported for each prompt
+    pub fn set_usage_ledger(&mut self, ledger: UsageLedger) {
+        self.usage_ledger = Some(ledger);
+    }
+
+    /// Set the registry holding the terminal output of the sessions
+    pub fn set_terminals(&mut self, terminals: TerminalRegistry) {
+        self.terminals = Some(terminals);
+    }
+
+    /// Conversation messages of a session so far
+    fn message_count(&self, session_id: &str) -> usize {
+        self.session_stats
+            .as_ref()
+            .map(|stats| stats.message_count(session_id))
+            .unwrap_or_default()
+    }
+
+    /// Fail the sessions of agents whose crash the AgentManager supervisor reports
+    pub fn watch_agent_crashes(self: &Arc<Self>) {
+        let Some(workspace_bus) = self.workspace_bus.clone() else {
+            return;
+        };
+        let service = Arc::downgrade(self);
+
+        workspace_bus.subscribe(move |event| {
+            // Failing sessions publishes status updates, which must not happen
+            // from inside a bus callback, so the work is deferred to a task
+            if let WorkspaceUpdateEvent::AgentCrashed { agent_name, .. } = event {
+                let service = service.clone();
+                let agent_name = agent_name.clone();
+                smol::spawn(async move {
+                    if let Some(service) = service.upgrade() {
+                        service.fail_agent_sessions(&agent_name);
+                    }
+                })
+                .detach();
+            }
+        });
+    }
+
     // ========== Agent Operations ==========
 
     /// List all available agents
@@ -88,6 +215,38 @@ impl AgentService {
         self.agent_manager.list_agents_with_info().await
     }
 
+    /// Convert the enabled MCP servers to ACP, dropping those whose transport
+    /// the agent did not advertise in its `mcp_capabilities`
+    pub async fn mcp_servers_for_agent(
+        &self,
+        agent_name: &str,
+        configs: impl IntoIterator<Item = (String, McpServerConfig)>,
+    ) -> Vec<acp::McpServer> {
+        let capabilities = self
+            .get_agent_init_response(agent_name)
+            .await
+            .map(|response| response.agent_capabilities.mcp_capabilities)
+            .unwrap_or_default();
+
+        configs
+            .into_iter()
+            .filter(|(_, config)| config.is_enabled())
+            .filter(|(name, config)| {
+                let supported = config.is_supported_by(&capabilities);
+                if !supported {
+                    log::warn!(
+                        "Skipping {} MCP server '{}': agent {} does not support it",
+                        config.kind(),
+                        name,
+                        agent_name
+                    );
+                }
+                supported
+            })
+            .map(|(name, config)| config.to_acp_mcp_server(name))
+            .collect()
+    }
+
     /// Get agent handle (internal use)
     async fn get_agent_handle(&self, name: &str) -> Result<Arc<AgentHandle>> {
         self.agent_manager
@@ -127,8 +286,8 @@ impl AgentService {
         let agent_handle = self.get_agent_handle(agent_name).await?;
 
         let mut request = acp::NewSessionRequest::new(cwd.clone());
-        request.cwd = cwd;
-        request.mcp_servers = mcp_servers;
+        request.cwd = cwd.clone();
+        request.mcp_servers = mcp_servers.clone();
         request.meta = None;
 
         let new_session_response: acp::NewSessionResponse = agent_handle
@@ -154,6 +313,8 @@ impl AgentService {
                 info.last_active = now;
                 info.status = SessionStatus::Active;
                 info.new_session_response = Some(new_session_response);
+                info.cwd = Some(cwd);
+                info.mcp_servers = mcp_servers;
                 log::info!(
                     "Session {} for agent {} already exists; refreshed metadata",
                     session_id,
@@ -169,6 +330,8 @@ impl AgentService {
                     status: SessionStatus::Active,
                     new_session_response: Some(new_session_response),
                     available_commands: Vec::new(), // Will be populated by AvailableCommandsUpdate
+                    cwd: Some(cwd),
+                    mcp_servers,
                 });
                 log::info!("Created session {} for agent {}", session_id, agent_name);
             }
@@ -212,8 +375,8 @@ impl AgentService {
             acp::SessionId::from(session_id.to_string()),
             cwd.clone(),
         );
-        request.cwd = cwd;
-        request.mcp_servers = mcp_servers;
+        request.cwd = cwd.clone();
+        request.mcp_servers = mcp_servers.clone();
         request.meta = None;
 
         let resume_session_response: acp::ResumeSessionResponse = agent_handle
@@ -228,6 +391,85 @@ impl AgentService {
             .modes(resume_session_response.modes)
             .meta(resume_session_response.meta);
 
+        self.register_reopened_session(
+            agent_name,
+            session_id,
+            new_session_response,
+            cwd,
+            mcp_servers,
+        );
+        log::info!("Resumed session {} for agent {}", session_id, agent_name);
+        Ok(session_id.to_string())
+    }
+
+    /// Whether the agent advertised session/resume during initialization
+    pub async fn supports_resume_session(&self, agent_name: &str) -> bool {
+        self.get_agent_init_response(agent_name)
+            .await
+            .is_some_and(|response| {
+                response
+                    .agent_capabilities
+                    .session_capabilities
+                    .resume
+                    .is_some()
+            })
+    }
+
+    /// Whether the agent advertised `load_session` during initialization
+    pub async fn supports_load_session(&self, agent_name: &str) -> bool {
+        self.get_agent_init_response(agent_name)
+            .await
+            .is_some_and(|response| response.agent_capabilities.load_session)
+    }
+
+    /// Load an existing session with MCP servers and custom working directory.
+    ///
+    /// The agent replays the conversation as session updates before this returns.
+    pub async fn load_session_with_mcp_and_cwd(
+        &self,
+        agent_name: &str,
+        session_id: &str,
+        mcp_servers: Vec<acp::McpServer>,
+        cwd: std::path::PathBuf,
+    ) -> Result<String> {
+        let agent_handle = self.get_agent_handle(agent_name).await?;
+
+        let mut request =
+            acp::LoadSessionRequest::new(acp::SessionId::from(session_id.to_string()), cwd.clone());
+        request.mcp_servers = mcp_servers.clone();
+        request.meta = None;
+
+        let load_session_response: acp::LoadSessionResponse = agent_handle
+            .load_session(request)
+            .await
+            .map_err(|e| anyhow!("Failed to load session: {}", e))?;
+
+        let new_session_response = acp::NewSessionResponse::new(session_id.to_string())
+            .config_options(load_session_response.config_options)
+            .models(load_session_response.models)
+            .modes(load_session_response.modes)
+            .meta(load_session_response.meta);
+
+        self.register_reopened_session(
+            agent_name,
+            session_id,
+            new_session_response,
+            cwd,
+            mcp_servers,
+        );
+        log::info!("Loaded session {} for agent {}", session_id, agent_name);
+        Ok(session_id.to_string())
+    }
+
+    /// Record a resumed or loaded session as active
+    fn register_reopened_session(
+        &self,
+        agent_name: &str,
+        session_id: &str,
+        new_session_response: acp::NewSessionResponse,
+        cwd: PathBuf,
+        mcp_servers: Vec<acp::McpServer>,
+    ) {
         let now = Utc::now();
 
         // Insert into nested HashMap structure
@@ -243,7 +485,8 @@ impl AgentService {
                 info.last_active = now;
                 info.status = SessionStatus::Active;
                 info.new_session_response = Some(new_session_response);
-                log::info!("Resumed session {} for agent {}", session_id, agent_name);
+                info.cwd = Some(cwd);
+                info.mcp_servers = mcp_servers;
             }
             Entry::Vacant(entry) => {
                 entry.insert(AgentSessionInfo {
@@ -254,15 +497,11 @@ impl AgentService {
                     status: SessionStatus::Active,
                     new_session_response: Some(new_session_response),
                     available_commands: Vec::new(),
+                    cwd: Some(cwd),
+                    mcp_servers,
                 });
-                log::info!(
-                    "Resumed session {} for agent {} (created new entry)",
-                    session_id,
-                    agent_name
-                );
             }
         }
-        Ok(session_id.to_string())
     }
 
     /// Get session information
@@ -277,13 +516,18 @@ impl AgentService {
 
     /// Close an agent's session
     pub async fn close_session(&self, agent_name: &str, session_id: &str) -> Result<()> {
-        let mut sessions = self.sessions.write().unwrap();
-        if let Some(agent_sessions) = sessions.get_mut(agent_name) {
-            if let Some(info) = agent_sessions.get_mut(session_id) {
-                info.status = SessionStatus::Closed;
-                log::info!("Closed session {} for agent {}", session_id, agent_name);
+        {
+            let mut sessions = self.sessions.write().unwrap();
+            if let Some(agent_sessions) = sessions.get_mut(agent_name) {
+                if let Some(info) = agent_sessions.get_mut(session_id) {
+                    info.status = SessionStatus::Closed;
+                    log::info!("Closed session {} for agent {}", session_id, agent_name);
+                }
             }
         }
+        if let Some(terminals) = &self.terminals {
+            terminals.remove_session(session_id);
+        }
         Ok(())
     }
 
@@ -300,7 +544,23 @@ impl AgentService {
         log::info!("AgentService: Got agent handle for {}", agent_name);
 
         // Send cancel request to the agent
-        agent_handle.cancel(session_id.to_string()).await?;
+        let cancel_result = agent_handle.cancel(session_id.to_string()).await;
+
+        // Outstanding permission requests of the turn resolve as cancelled
+        let cancelled = self
+            .agent_manager
+            .permission_store()
+            .cancel_session(session_id)
+            .await;
+        if cancelled > 0 {
+            log::info!(
+                "AgentService: Cancelled {} pending permission request(s) for session {}",
+                cancelled,
+                session_id
+            );
+        }
+
+        cancel_result?;
         log::info!("AgentService: Sent cancel request to agent");
 
         // Update session status to Idle
@@ -321,7 +581,7 @@ impl AgentService {
                         agent_name: agent_name.to_string(),
                         status: SessionStatus::Idle,
                         last_active: info.last_active,
-                        message_count: 0,
+                        message_count: self.message_count(session_id),
                     };
                     workspace_bus.publish(event);
                     log::info!("AgentService: Published session status update to workspace bus");
@@ -403,6 +663,8 @@ impl AgentService {
                     status: SessionStatus::Active,
                     new_session_response: None,
                     available_commands: commands,
+                    cwd: None,
+                    mcp_servers: Vec::new(),
                 });
             }
         }
@@ -439,7 +701,7 @@ impl AgentService {
                         agent_name: agent_name.to_string(),
                         status,
                         last_active: info.last_active,
-                        message_count: 0, // TODO: Track actual message count
+                        message_count: self.message_count(session_id),
                     };
                     workspace_bus.publish(event);
                     log::debug!("Published session status update to workspace bus");
@@ -448,6 +710,49 @@ impl AgentService {
         }
     }
 
+    /// Mark every open session of a crashed agent as failed
+    ///
+    /// The sessions are remembered as interrupted so that only they are
+    /// reopened once the agent restarts.
+    pub fn fail_agent_sessions(&self, agent_name: &str) {
+        let affected: Vec<String> = self
+            .list_sessions_for_agent(agent_name)
+            .into_iter()
+            .filter(|info| !matches!(info.status, SessionStatus::Closed | SessionStatus::Failed))
+            .map(|info| info.session_id)
+            .collect();
+
+        log::warn!(
+            "Agent {} crashed, marking {} sessions as failed",
+            agent_name,
+            affected.len()
+        );
+        self.interrupted
+            .write()
+            .unwrap()
+            .entry(agent_name.to_string())
+            .or_default()
+            .extend(affected.iter().cloned());
+        for session_id in affected {
+            self.update_session_status(agent_name, &session_id, SessionStatus::Failed);
+        }
+    }
+
+    /// Take the sessions the agent's last crash interrupted
+    ///
+    /// Sessions that were closed or reopened in the meantime are left out.
+    pub fn take_interrupted_sessions(&self, agent_name: &str) -> Vec<AgentSessionInfo> {
+        let Some(interrupted) = self.interrupted.write().unwrap().remove(agent_name) else {
+            return Vec::new();
+        };
+        self.list_sessions_for_agent(agent_name)
+            .into_iter()
+            .filter(|info| {
+                info.status == SessionStatus::Failed && interrupted.contains(&info.session_id)
+            })
+            .collect()
+    }
+
     // ========== Prompt Operations ==========
 
     /// Send a prompt to an agent's session
@@ -461,12 +766,17 @@ impl AgentService {
         self.update_session_status(agent_name, session_id, SessionStatus::InProgress);
         let request = acp::PromptRequest::new(acp::SessionId::from(session_id.to_string()), prompt);
 
-        let result = agent_handle
-            .prompt(request)
-            .await
-            .map_err(|e| anyhow!("Failed to send prompt: {}", e))?;
+        let result = self
+            .prompt_with_watchdog(&agent_handle, agent_name, session_id, request)
+            .await?;
 
-        self.update_session_status(agent_name, session_id, SessionStatus::Completed);
+        // Recorded before the status update so totals shown on completion include it
+        self.record_usage(agent_name, session_id, &result).await;
+        self.update_session_status(
+            agent_name,
+            session_id,
+            SessionStatus::from_stop_reason(&result.stop_reason),
+        );
         // Update activity time
         self.update_session_activity(agent_name, session_id);
 
@@ -474,11 +784,169 @@ impl AgentService {
         Ok(result)
     }
 
+    /// Send a prompt, cancelling the turn when it exceeds the agent's timeouts
+    async fn prompt_with_watchdog(
+        &self,
+        agent_handle: &AgentHandle,
+        agent_name: &str,
+        session_id: &str,
+        request: acp::PromptRequest,
+    ) -> Result<PromptResponse> {
+        let timeouts = agent_handle.config().timeouts.clone();
+        let mut prompt = Box::pin(agent_handle.prompt(request));
+        if timeouts.is_empty() {
+            return prompt
+                .await
+                .map_err(|e| anyhow!("Failed to send prompt: {}", e));
+        }
+
+        let activity = TurnActivity::start();
+        let subscription = self.session_bus.as_ref().map(|bus| {
+            let activity = activity.clone();
+            let id = bus.subscribe_session(session_id.to_string(), move |event| {
+                activity.record(&event.update);
+            });
+            (bus, id)
+        });
+
+        let outcome = loop {
+            let watchdog = Box::pin(prompt_watchdog::watch(timeouts.clone(), activity.clone()));
+            match select(prompt, watchdog).await {
+                Either::Left((result, _)) => break Ok(result),
+                Either::Right((trigger, pending)) => {
+                    // The agent is quiet while the user decides on a permission
+                    if matches!(trigger, WatchdogTrigger::Inactivity(_))
+                        && self.awaits_permission(session_id).await
+                    {
+                        activity.keep_alive();
+                        prompt = pending;
+                        continue;
+                    }
+                    break Err(trigger);
+                }
+            }
+        };
+        if let Some((bus, id)) = subscription {
+            bus.unsubscribe(id);
+        }
+
+        match outcome {
+            Ok(result) => result.map_err(|e| anyhow!("Failed to send prompt: {}", e)),
+            Err(trigger) => {
+                self.time_out_prompt(agent_handle, agent_name, session_id, trigger, &activity)
+                    .await;
+                Err(anyhow!("Prompt timed out: {}", trigger))
+            }
+        }
+    }
+
+    /// Whether a permission request of the session is waiting for the user
+    async fn awaits_permission(&self, session_id: &str) -> bool {
+        self.agent_manager
+            .permission_store()
+            .pending()
+            .await
+            .iter()
+            .any(|queued| queued.request.session_id == session_id)
+    }
+
+    /// Cancel a hung turn, log what is known about the agent and tell the user
+    async fn time_out_prompt(
+        &self,
+        agent_handle: &AgentHandle,
+        agent_name: &str,
+        session_id: &str,
+        trigger: WatchdogTrigger,
+        activity: &TurnActivity,
+    ) {
+        let last_update = match activity.last_update() {
+            Some((received, kind)) => {
+                format!("{} {}s ago", kind, received.elapsed().as_secs())
+            }
+            None => "none".to_string(),
+        };
+        log::warn!(
+            "Prompt of session {} (agent {}) timed out: {}; running for {}s, last update: {}, agent running: {}",
+            session_id,
+            agent_name,
+            trigger,
+            activity.started().elapsed().as_secs(),
+            last_update,
+            agent_handle.is_running()
+        );
+        let stderr_tail = self
+            .agent_manager
+            .stderr_tail(agent_name, STDERR_TAIL_LINES);
+        if !stderr_tail.is_empty() {
+            log::warn!(
+                "Last stderr output of agent {}:\n{}",
+                agent_name,
+                stderr_tail.join("\n")
+            );
+        }
+
+        let cancel = smol::future::or(
+            async { Some(agent_handle.cancel(session_id.to_string()).await) },
+            async {
+                smol::Timer::after(TIMEOUT_CANCEL_WAIT).await;
+                None
+            },
+        );
+        match cancel.await {
+            Some(Ok(())) => {}
+            Some(Err(e)) => log::warn!("Failed to cancel timed out session {}: {}", session_id, e),
+            None => log::warn!(
+                "Agent {} did not take the cancel request for session {}",
+                agent_name,
+                session_id
+            ),
+        }
+        self.agent_manager
+            .permission_store()
+            .cancel_session(session_id)
+            .await;
+
+        self.update_session_status(agent_name, session_id, SessionStatus::TimedOut);
+        if let Some(ref workspace_bus) = self.workspace_bus {
+            workspace_bus.publish(WorkspaceUpdateEvent::PromptTimedOut {
+                session_id: session_id.to_string(),
+                agent_name: agent_name.to_string(),
+                reason: trigger.to_string(),
+            });
+        }
+    }
+
+    /// Record the token usage an agent reported for a prompt turn
+    async fn record_usage(&self, agent_name: &str, session_id: &str, response: &PromptResponse) {
+        let Some(ledger) = self.usage_ledger.clone() else {
+            return;
+        };
+        let Some((usage, model)) = usage_from_prompt_response(response) else {
+            return;
+        };
+
+        // Fall back to the model the session was created with
+        let model = model.or_else(|| {
+            self.get_session_info(agent_name, session_id)
+                .and_then(|info| info.new_session_response)
+                .and_then(|response| response.models)
+                .map(|models| models.current_model_id.to_string())
+        });
+        let mut record = UsageRecord::new(UsageSource::Agent, usage);
+        record.session_id = Some(session_id.to_string());
+        record.agent = Some(agent_name.to_string());
+        record.model = model;
+        if let Err(e) = smol::unblock(move || ledger.record(record)).await {
+            log::warn!("Failed to record usage of session {}: {}", session_id, e);
+        }
+    }
+
     // ========== Cleanup Operations ==========
 
     /// Clean up idle sessions
     pub async fn cleanup_idle_sessions(&self, idle_duration: Duration) {
         let now = Utc::now();
+        let mut removed = Vec::new();
         let mut sessions = self.sessions.write().unwrap();
 
         for (agent_name, agent_sessions) in sessions.iter_mut() {
@@ -493,11 +961,19 @@ impl AgentService {
                         agent_name,
                         idle_time.num_seconds()
                     );
+                    removed.push(session_id.clone());
                 }
 
                 should_keep
             });
         }
+        drop(sessions);
+
+        if let Some(terminals) = &self.terminals {
+            for session_id in &removed {
+                terminals.remove_session(session_id);
+            }
+        }
     }
 
     // ========== Multi-Session Query Methods ==========
@@ -11,6 +11,7 @@ use std::sync::{Arc, OnceLock, RwLock};
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 
+use super::usage_ledger::{TokenUsage, UsageLedger, UsageRecord, UsageSource};
 use crate::core::config::ModelConfig;
 
 /// Global Tokio runtime for HTTP requests
@@ -24,6 +25,8 @@ pub struct AiService {
     runtime_handle: tokio::runtime::Handle,
     /// Service configuration
     pub config: Arc<RwLock<AiServiceConfig>>,
+    /// Ledger recording the token usage of each call
+    usage_ledger: Option<UsageLedger>,
 }
 
 /// Configuration for AI service
@@ -68,6 +71,8 @@ struct ChatMessage {
 #[derive(Debug, Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -127,9 +132,15 @@ impl AiService {
             http_client,
             runtime_handle,
             config: Arc::new(RwLock::new(config)),
+            usage_ledger: None,
         }
     }
 
+    /// Set the ledger recording the token usage of each call
+    pub fn set_usage_ledger(&mut self, ledger: UsageLedger) {
+        self.usage_ledger = Some(ledger);
+    }
+
     /// Update service configuration (for hot-reload support)
     pub fn update_config(
         &self,
@@ -170,7 +181,7 @@ impl AiService {
         max_tokens: Option<u32>,
     ) -> Result<String> {
         // Extract config data and release lock immediately
-        let (url, model_name, api_key) = {
+        let (url, config_name, model_name, api_key) = {
             let config = self.config.read().unwrap();
 
             let model_name = config
@@ -194,6 +205,7 @@ impl AiService {
 
             (
                 url,
+                model_name.clone(),
                 model_config.model_name.clone(),
                 model_config.api_key.clone(),
             )
@@ -260,6 +272,16 @@ impl AiService {
         let completion: ChatCompletionResponse =
             serde_json::from_str(&response_text).context("Failed to parse AI service response")?;
 
+        if let Some(ledger) = self.usage_ledger.clone()
+            && let Some(usage) = completion.usage.as_ref().and_then(TokenUsage::from_json)
+        {
+            let mut record = UsageRecord::new(UsageSource::AiService, usage);
+            record.model = Some(config_name);
+            if let Err(e) = smol::unblock(move || ledger.record(record)).await {
+                log::warn!("Failed to record AI service usage: {}", e);
+            }
+        }
+
         let content = completion
             .choices
             .first()
@@ -361,6 +383,7 @@ mod tests {
                 base_url: "https://api.openai.com/v1".to_string(),
                 api_key: "test-key".to_string(),
                 model_name: "gpt-3.5-turbo".to_string(),
+                price: None,
             },
         );
         models
@@ -390,6 +413,7 @@ mod tests {
                 base_url: "https://api.openai.com/v1".to_string(),
                 api_key: "new-key".to_string(),
                 model_name: "gpt-4".to_string(),
+                price: None,
             },
         );
 
@@ -410,6 +434,7 @@ mod tests {
                 base_url: "https://api.openai.com/v1".to_string(),
                 api_key: "test-key".to_string(),
                 model_name: "gpt-3.5-turbo".to_string(),
+                price: None,
             },
         );
 
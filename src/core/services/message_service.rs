@@ -3,20 +3,23 @@
 //! This service provides a high-level API for sending messages and subscribing
 //! to session updates. It orchestrates between AgentService and SessionBus.
 
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+};
 
 use agent_client_protocol::{
-    AvailableCommand, ContentBlock, ContentChunk, ImageContent, PromptResponse, SessionUpdate,
-    TextContent,
+    self as acp, AvailableCommand, ContentBlock, ContentChunk, ImageContent, PromptResponse,
+    SessionUpdate, TextContent,
 };
 use anyhow::{Result, anyhow};
 
 use crate::core::event_bus::session_bus::{SessionUpdateBusContainer, SessionUpdateEvent};
 use crate::core::event_bus::workspace_bus::{WorkspaceUpdateBusContainer, WorkspaceUpdateEvent};
-use crate::core::services::SessionStatus;
 
-use super::agent_service::AgentService;
-use super::persistence_service::{PersistedMessage, PersistenceService};
+use super::agent_service::{AgentService, SessionStatus};
+use super::persistence_service::{PersistedMessage, PersistenceService, fork_context_prompt};
 
 /// Message service - handles message sending and event bus interaction
 pub struct MessageService {
@@ -24,6 +27,13 @@ pub struct MessageService {
     agent_service: Arc<AgentService>,
     persistence_service: Arc<PersistenceService>,
     workspace_bus: WorkspaceUpdateBusContainer,
+    /// Sessions whose history is being replayed by session/load or seeded
+    /// after a fork; their updates are neither persisted nor forwarded to
+    /// subscribers
+    replaying_sessions: Arc<RwLock<HashSet<String>>>,
+    /// Forked sessions whose transcript is still being handed to the agent;
+    /// the lock is held until the agent has taken it
+    seeding_sessions: Arc<Mutex<HashMap<String, Arc<smol::lock::Mutex<()>>>>>,
 }
 
 impl MessageService {
@@ -38,6 +48,8 @@ impl MessageService {
             agent_service,
             persistence_service,
             workspace_bus,
+            replaying_sessions: Arc::new(RwLock::new(HashSet::new())),
+            seeding_sessions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -49,6 +61,7 @@ impl MessageService {
         let persistence_service = self.persistence_service.clone();
         let session_bus = self.session_bus.clone();
         let agent_service = self.agent_service.clone();
+        let replaying_sessions = self.replaying_sessions.clone();
 
         // Subscribe to session bus for all session updates
         session_bus.subscribe(move |event| {
@@ -58,6 +71,10 @@ impl MessageService {
             let service = persistence_service.clone();
             let agent_svc = agent_service.clone();
 
+            if let Some(agent_name) = &agent_name {
+                service.index().set_agent(&session_id, agent_name);
+            }
+
             // Handle AvailableCommandsUpdate to store in AgentService
             if let SessionUpdate::AvailableCommandsUpdate(ref commands_update) = update {
                 log::debug!(
@@ -84,6 +101,12 @@ impl MessageService {
                 }
             }
 
+            // Replayed history is merged into the session file once loading completes
+            if replaying_sessions.read().unwrap().contains(&session_id) {
+                return;
+            }
+            service.stats().record_update(&session_id, &update);
+
             // Spawn async task using smol to save message
             smol::spawn(async move {
                 if let Err(e) = service.save_update(&session_id, update).await {
@@ -100,14 +123,31 @@ impl MessageService {
         // Subscribe to workspace bus for session status changes
         let persistence_service_ws = self.persistence_service.clone();
         let workspace_bus = self.workspace_bus.clone();
+        let replaying_sessions = self.replaying_sessions.clone();
 
         workspace_bus.subscribe(move |event| {
             if let WorkspaceUpdateEvent::SessionStatusUpdated {
                 session_id, status, ..
             } = event
             {
-                // Flush accumulator when session completes or becomes idle
-                if matches!(status, SessionStatus::Completed | SessionStatus::Idle) {
+                // Save the stats whenever a prompt turn ends
+                if !replaying_sessions.read().unwrap().contains(session_id)
+                    && persistence_service_ws
+                        .stats()
+                        .record_status(session_id, status)
+                {
+                    let service = persistence_service_ws.clone();
+                    let session_id = session_id.clone();
+                    smol::spawn(async move {
+                        if let Err(e) = service.save_stats(&session_id).await {
+                            log::error!("Failed to save stats of session {}: {}", session_id, e);
+                        }
+                    })
+                    .detach();
+                }
+
+                // Flush accumulator when the agent's reply ends
+                if status.is_turn_end() {
                     let service = persistence_service_ws.clone();
                     let session_id = session_id.clone();
 
@@ -133,7 +173,7 @@ impl MessageService {
     /// Send a user message to an existing session
     ///
     /// This method performs the following steps:
-    /// 1. Verify the session exists
+    /// 1. Verify the session exists and wait for a fork to be seeded
     /// 2. Publish the user message to the event bus (immediate UI feedback)
     /// 3. Send the prompt to the agent
     ///
@@ -153,6 +193,17 @@ impl MessageService {
         {
             return Err(anyhow!("Session not found: {}", session_id));
         }
+        // Updates are hidden while the fork context is sent, so the message
+        // waits until the agent has taken it
+        let seeding = self
+            .seeding_sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .cloned();
+        if let Some(seeding) = seeding {
+            drop(seeding.lock().await);
+        }
 
         // 2. Publish user message blocks to event bus (immediate UI feedback)
         for block in &content_blocks {
@@ -228,6 +279,7 @@ impl MessageService {
         session_id: Option<String>,
     ) -> tokio::sync::mpsc::UnboundedReceiver<SessionUpdateEvent> {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let replaying_sessions = self.replaying_sessions.clone();
 
         self.session_bus.subscribe(move |event| {
             // Filter by session_id if specified
@@ -237,12 +289,342 @@ impl MessageService {
                 }
             }
 
+            // Replayed history reaches panels through the reloaded session file
+            if replaying_sessions
+                .read()
+                .unwrap()
+                .contains(&event.session_id)
+            {
+                return;
+            }
+
             let _ = tx.send(event.clone());
         });
 
         rx
     }
 
+    /// Reopen the sessions an agent crash interrupted once the agent restarts
+    pub fn watch_agent_restarts(self: &Arc<Self>) {
+        let service = Arc::downgrade(self);
+
+        self.workspace_bus.subscribe(move |event| {
+            // Reopening publishes status updates, which must not happen from
+            // inside a bus callback, so the work is deferred to a task
+            if let WorkspaceUpdateEvent::AgentRestarted { agent_name, .. } = event {
+                let service = service.clone();
+                let agent_name = agent_name.clone();
+                smol::spawn(async move {
+                    if let Some(service) = service.upgrade() {
+                        service.resume_interrupted_sessions(&agent_name).await;
+                    }
+                })
+                .detach();
+            }
+        });
+    }
+
+    /// Reopen the sessions interrupted by the agent's last crash
+    ///
+    /// Sessions are resumed or loaded, whichever the agent advertised. When it
+    /// supports neither, or reopening fails, a new session with the same
+    /// working directory and MCP servers replaces the interrupted one and a
+    /// `SessionReplaced` event is published.
+    pub async fn resume_interrupted_sessions(&self, agent_name: &str) {
+        let interrupted = self.agent_service.take_interrupted_sessions(agent_name);
+        if interrupted.is_empty() {
+            return;
+        }
+        let can_resume = self.agent_service.supports_resume_session(agent_name).await;
+        let can_load = self.agent_service.supports_load_session(agent_name).await;
+
+        for info in interrupted {
+            let session_id = info.session_id;
+            let cwd = info
+                .cwd
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            let mcp_servers = info.mcp_servers;
+
+            let reopened = if can_resume {
+                self.agent_service
+                    .resume_session_with_mcp_and_cwd(
+                        agent_name,
+                        &session_id,
+                        mcp_servers.clone(),
+                        cwd.clone(),
+                    )
+                    .await
+                    .map(|_| ())
+            } else if can_load {
+                self.load_session(agent_name, &session_id, mcp_servers.clone(), cwd.clone())
+                    .await
+                    .map(|_| ())
+            } else {
+                Err(anyhow!(
+                    "agent supports neither session/resume nor session/load"
+                ))
+            };
+
+            match reopened {
+                Ok(()) => {
+                    log::info!(
+                        "Reopened session {} after agent {} restart",
+                        session_id,
+                        agent_name
+                    );
+                    self.agent_service.update_session_status(
+                        agent_name,
+                        &session_id,
+                        SessionStatus::Active,
+                    );
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Could not reopen session {} after agent {} restart: {}",
+                        session_id,
+                        agent_name,
+                        e
+                    );
+                    if let Err(e) = self
+                        .replace_session(agent_name, &session_id, mcp_servers, cwd)
+                        .await
+                    {
+                        log::error!(
+                            "Failed to replace session {} of agent {}: {}",
+                            session_id,
+                            agent_name,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start a new session in place of one that could not be reopened
+    async fn replace_session(
+        &self,
+        agent_name: &str,
+        old_session_id: &str,
+        mcp_servers: Vec<acp::McpServer>,
+        cwd: PathBuf,
+    ) -> Result<()> {
+        let new_session_id = self
+            .agent_service
+            .create_session_with_mcp_and_cwd(agent_name, mcp_servers, cwd)
+            .await?;
+        self.agent_service
+            .update_session_status(agent_name, old_session_id, SessionStatus::Closed);
+        log::info!(
+            "Replaced session {} of agent {} with new session {}",
+            old_session_id,
+            agent_name,
+            new_session_id
+        );
+        self.workspace_bus
+            .publish(WorkspaceUpdateEvent::SessionReplaced {
+                agent_name: agent_name.to_string(),
+                old_session_id: old_session_id.to_string(),
+                new_session_id,
+            });
+        Ok(())
+    }
+
+    /// Reopen a persisted session, e.g. when its task is opened after a restart
+    ///
+    /// Agents that advertise `load_session` replay the full conversation,
+    /// which is merged into the local history. Other agents are resumed.
+    pub async fn restore_session(
+        &self,
+        agent_name: &str,
+        session_id: &str,
+        mcp_servers: Vec<acp::McpServer>,
+        cwd: PathBuf,
+    ) -> Result<()> {
+        if self.agent_service.supports_load_session(agent_name).await {
+            self.load_session(agent_name, session_id, mcp_servers, cwd)
+                .await?;
+        } else {
+            self.agent_service
+                .resume_session_with_mcp_and_cwd(agent_name, session_id, mcp_servers, cwd)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Load a session through session/load and reconcile the replayed history
+    ///
+    /// The replayed updates are collected instead of being persisted and shown
+    /// live; messages missing from the session file are merged into it and a
+    /// `SessionHistoryRestored` event lets open panels reload. Returns the
+    /// number of restored messages.
+    pub async fn load_session(
+        &self,
+        agent_name: &str,
+        session_id: &str,
+        mcp_servers: Vec<acp::McpServer>,
+        cwd: PathBuf,
+    ) -> Result<usize> {
+        let replayed = Arc::new(Mutex::new(Vec::new()));
+        self.replaying_sessions
+            .write()
+            .unwrap()
+            .insert(session_id.to_string());
+        let subscription_id = {
+            let replayed = replayed.clone();
+            self.session_bus
+                .subscribe_session(session_id.to_string(), move |event| {
+                    replayed.lock().unwrap().push((*event.update).clone());
+                })
+        };
+
+        let result = self
+            .agent_service
+            .load_session_with_mcp_and_cwd(agent_name, session_id, mcp_servers, cwd)
+            .await;
+
+        self.session_bus.unsubscribe(subscription_id);
+        self.replaying_sessions.write().unwrap().remove(session_id);
+        result?;
+
+        let replayed = std::mem::take(&mut *replayed.lock().unwrap());
+        log::info!(
+            "Agent {} replayed {} updates for session {}",
+            agent_name,
+            replayed.len(),
+            session_id
+        );
+
+        let added = self
+            .persistence_service
+            .merge_replayed_history(session_id, replayed)
+            .await?;
+        if added > 0 {
+            self.workspace_bus
+                .publish(WorkspaceUpdateEvent::SessionHistoryRestored {
+                    session_id: session_id.to_string(),
+                    added,
+                });
+        }
+        Ok(added)
+    }
+
+    /// Fork a session into a new one with the same agent and working directory
+    ///
+    /// The new session's history is the transcript of `parent_session_id` up
+    /// to and including the `message_count`-th conversation message after
+    /// position `start` of its session file. When that is the parent's last
+    /// message, the agent supports session/load and the parent is not open,
+    /// the parent is loaded first so the fork includes the messages the agent
+    /// holds beyond the local file.
+    ///
+    /// A new agent session has nothing for session/load to replay, so the
+    /// agent receives the transcript as a context prompt. That prompt is sent
+    /// by the returned task, letting the caller open the fork right away;
+    /// messages sent to the fork wait for it. The agent's reply is neither
+    /// persisted nor shown, and both are left out when session/load replays
+    /// the fork later. Returns the new session ID.
+    pub async fn fork_session(
+        &self,
+        agent_name: &str,
+        parent_session_id: &str,
+        start: usize,
+        message_count: usize,
+        mcp_servers: Vec<acp::McpServer>,
+        cwd: PathBuf,
+    ) -> Result<(String, smol::Task<Result<()>>)> {
+        // Forking from the last message takes what the agent holds beyond the
+        // local file along; earlier fork points are kept as they are shown, as
+        // messages restored before them would shift them
+        let (start, message_count) = if self.agent_service.supports_load_session(agent_name).await
+            && self
+                .agent_service
+                .get_session_info(agent_name, parent_session_id)
+                .is_none()
+            && self
+                .persistence_service
+                .forks_from_last_message(parent_session_id, start, message_count)
+                .await?
+        {
+            match self
+                .load_session(
+                    agent_name,
+                    parent_session_id,
+                    mcp_servers.clone(),
+                    cwd.clone(),
+                )
+                .await
+            {
+                Ok(_) => (
+                    0,
+                    self.persistence_service
+                        .conversation_message_count(parent_session_id)
+                        .await?,
+                ),
+                Err(e) => {
+                    log::warn!(
+                        "Could not load session {} before forking it, using the local history: {}",
+                        parent_session_id,
+                        e
+                    );
+                    (start, message_count)
+                }
+            }
+        } else {
+            (start, message_count)
+        };
+
+        let session_id = self
+            .agent_service
+            .create_session_with_mcp_and_cwd(agent_name, mcp_servers, cwd)
+            .await?;
+        self.persistence_service
+            .index()
+            .set_agent(&session_id, agent_name);
+
+        let copied = self
+            .persistence_service
+            .fork_history(parent_session_id, &session_id, start, message_count)
+            .await?;
+        let prompt = fork_context_prompt(parent_session_id, &copied)?;
+
+        let seeding = Arc::new(smol::lock::Mutex::new(()));
+        let guard = seeding.try_lock_arc().expect("a new lock is not held");
+        self.seeding_sessions
+            .lock()
+            .unwrap()
+            .insert(session_id.clone(), seeding);
+        self.replaying_sessions
+            .write()
+            .unwrap()
+            .insert(session_id.clone());
+
+        let agent_service = self.agent_service.clone();
+        let replaying_sessions = self.replaying_sessions.clone();
+        let seeding_sessions = self.seeding_sessions.clone();
+        let agent_name = agent_name.to_string();
+        let new_session_id = session_id.clone();
+        let seeded = smol::spawn(async move {
+            let result = agent_service
+                .send_prompt(&agent_name, &new_session_id, vec![prompt.into()])
+                .await;
+            replaying_sessions.write().unwrap().remove(&new_session_id);
+            seeding_sessions.lock().unwrap().remove(&new_session_id);
+            drop(guard);
+            result.map_err(|e| anyhow!("Failed to send the forked conversation: {}", e))?;
+            log::info!("Seeded forked session {}", new_session_id);
+            Ok(())
+        });
+
+        log::info!(
+            "Forked session {} at message {} into {}",
+            parent_session_id,
+            start + message_count,
+            session_id
+        );
+        Ok((session_id, seeded))
+    }
+
     /// Load historical messages for a session
     ///
     /// Returns all persisted messages in chronological order
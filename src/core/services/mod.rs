@@ -8,11 +8,32 @@ mod agent_service;
 mod ai_service;
 mod message_service;
 mod persistence_service;
+mod prompt_watchdog;
+mod session_export;
+mod session_import;
+mod session_index;
+mod session_retention;
+mod session_stats;
+mod usage_ledger;
 mod workspace_service;
 
 pub use agent_config_service::AgentConfigService;
 pub use agent_service::{AgentService, AgentSessionInfo, SessionStatus};
 pub use ai_service::{AiService, CommentStyle};
 pub use message_service::MessageService;
-pub use persistence_service::PersistenceService;
+pub use persistence_service::{
+    HISTORY_PAGE_SIZE, HistoryPage, LoadedSession, OpenSession, PROVISIONAL_FLUSH_INTERVAL,
+    PersistenceService, RecoveredSession,
+};
+pub use session_export::{
+    ExportedSession, SessionExportFormat, SessionExportOptions, redact_secrets,
+};
+pub use session_import::{ImportedSession, is_imported_session};
+pub use session_index::{IndexedTextKind, SearchHit, SessionIndex, SessionMetadata};
+pub use session_retention::{RetentionReport, StorageUsage, WorkspaceStorage, run_retention};
+pub use session_stats::{SessionStats, SessionStatsCollector, ToolCallCounts, format_duration};
+pub use usage_ledger::{
+    SessionOwner, TokenUsage, UsageGrouping, UsageLedger, UsageRecord, UsageSource, UsageSummary,
+    UsageTotals, format_cost, format_tokens, summarize as summarize_usage, to_csv as usage_csv,
+};
 pub use workspace_service::WorkspaceService;
@@ -4,8 +4,9 @@
 //! and loads historical messages when needed.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use agent_client_protocol::{
     ContentBlock, ContentChunk, SessionUpdate, TextContent, ToolCallStatus, ToolCallUpdate,
@@ -14,6 +15,20 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use super::session_export::{ExportedSession, SessionExportFormat, SessionExportOptions};
+use super::session_import::{
+    IMPORTED_DIR_NAME, ImportedSession, is_imported_session, parse_session_file,
+};
+use super::session_index::SessionIndex;
+use super::session_retention::{
+    ARCHIVE_DIR_NAME, RetentionReport, SessionFileInfo, StorageUsage, WorkspaceStorage,
+    archive_file, select_expired, unarchive_file,
+};
+use super::session_stats::{STATS_DIR_NAME, SessionStatsCollector};
+use super::usage_ledger::{USAGE_DIR_NAME, USAGE_FILE_NAME, UsageLedger};
+use crate::core::agent::TerminalRegistry;
+use crate::core::config::{RetentionAction, RetentionConfig};
+
 /// Persisted message entry with timestamp
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedMessage {
@@ -60,6 +75,8 @@ struct ChunkAccumulator {
     /// Tool call updates: toolCallId -> (first_timestamp, latest_update)
     /// Only keeps the latest update for each tool call
     tool_call_updates: HashMap<String, (String, ToolCallUpdate)>,
+    /// Whether anything was accumulated since the last provisional write
+    dirty: bool,
 }
 
 impl ChunkAccumulator {
@@ -72,6 +89,7 @@ impl ChunkAccumulator {
             agent_thought_text: String::new(),
             user_message_chunks: Vec::new(),
             tool_call_updates: HashMap::new(),
+            dirty: false,
         }
     }
 
@@ -218,11 +236,62 @@ impl ChunkAccumulator {
         }
     }
 
+    /// Feed an update into the accumulator
+    /// Returns Some(FlushData) when something must be written now
+    fn accumulate(&mut self, session_id: &str, update: SessionUpdate) -> Option<FlushData> {
+        self.dirty = true;
+        match update {
+            SessionUpdate::AgentMessageChunk(chunk) => {
+                log::debug!("Accumulating AgentMessageChunk for session: {}", session_id);
+                self.try_append_agent_message_chunk(chunk)
+            }
+            SessionUpdate::AgentThoughtChunk(chunk) => {
+                log::debug!("Accumulating AgentThoughtChunk for session: {}", session_id);
+                self.try_append_agent_thought_chunk(chunk)
+            }
+            SessionUpdate::UserMessageChunk(chunk) => {
+                log::debug!("Accumulating UserMessageChunk for session: {}", session_id);
+                self.try_append_user_message_chunk(chunk)
+            }
+            SessionUpdate::ToolCallUpdate(update) => {
+                log::debug!(
+                    "Accumulating ToolCallUpdate for session: {}, toolCallId: {}",
+                    session_id,
+                    update.tool_call_id
+                );
+                self.accumulate_tool_call_update(update)
+            }
+            _ => {
+                // Non-chunk update: flush accumulator, then write both
+                log::debug!(
+                    "Non-chunk update received, flushing accumulator for session: {}",
+                    session_id
+                );
+                let flushed = self.flush();
+                Some(FlushData::Both(Box::new((flushed, update))))
+            }
+        }
+    }
+
     /// Flush accumulated chunks into a SessionUpdate
     /// Returns None if nothing accumulated, Some((timestamp, update)) otherwise
     fn flush(&mut self) -> Option<(String, SessionUpdate)> {
+        let entry = self.current_entry()?;
+
+        // Reset chunk state (but keep tool_call_updates)
+        self.chunk_type = AccumulatedChunkType::Empty;
+        self.first_timestamp = String::new();
+        self.agent_message_chunks.clear();
+        self.agent_thought_text.clear();
+        self.user_message_chunks.clear();
+
+        Some(entry)
+    }
+
+    /// The accumulated chunks as one SessionUpdate, without resetting them
+    fn current_entry(&self) -> Option<(String, SessionUpdate)> {
         if matches!(self.chunk_type, AccumulatedChunkType::Empty) {
-            return None; // Nothing to flush
+            return None; // Nothing accumulated
         }
 
         let timestamp = self.first_timestamp.clone();
@@ -243,16 +312,34 @@ impl ChunkAccumulator {
             AccumulatedChunkType::Empty => unreachable!(),
         };
 
-        // Reset chunk state (but keep tool_call_updates)
-        self.chunk_type = AccumulatedChunkType::Empty;
-        self.first_timestamp = String::new();
-        self.agent_message_chunks.clear();
-        self.agent_thought_text.clear();
-        self.user_message_chunks.clear();
-
         Some((timestamp, update))
     }
 
+    /// Whether anything is waiting to be written to the session file
+    fn has_pending(&self) -> bool {
+        !matches!(self.chunk_type, AccumulatedChunkType::Empty)
+            || !self.tool_call_updates.is_empty()
+    }
+
+    /// Everything not yet written to the session file, as provisional records
+    fn provisional_messages(&self) -> Vec<PersistedMessage> {
+        let mut messages: Vec<PersistedMessage> = self
+            .current_entry()
+            .map(|(timestamp, update)| PersistedMessage::with_timestamp(timestamp, update))
+            .into_iter()
+            .collect();
+        let mut tool_call_updates: Vec<&(String, ToolCallUpdate)> =
+            self.tool_call_updates.values().collect();
+        tool_call_updates.sort_by(|a, b| a.0.cmp(&b.0));
+        messages.extend(tool_call_updates.into_iter().map(|(timestamp, update)| {
+            PersistedMessage::with_timestamp(
+                timestamp.clone(),
+                SessionUpdate::ToolCallUpdate(update.clone()),
+            )
+        }));
+        messages
+    }
+
     /// Flush all tool call updates
     /// Returns a vector of (timestamp, update) pairs
     fn flush_tool_call_updates(&mut self) -> Vec<(String, SessionUpdate)> {
@@ -283,6 +370,27 @@ enum FlushData {
     ToolCallCompleted(Box<(String, SessionUpdate)>),
 }
 
+impl FlushData {
+    /// Updates in the order they are written to disk
+    fn into_updates(self) -> Vec<SessionUpdate> {
+        match self {
+            FlushData::Accumulated(boxed_data) => (*boxed_data)
+                .map(|(_, update)| update)
+                .into_iter()
+                .collect(),
+            FlushData::Both(boxed_data) => {
+                let (accumulated, non_chunk) = *boxed_data;
+                accumulated
+                    .map(|(_, update)| update)
+                    .into_iter()
+                    .chain(std::iter::once(non_chunk))
+                    .collect()
+            }
+            FlushData::ToolCallCompleted(boxed_data) => vec![boxed_data.1],
+        }
+    }
+}
+
 /// Merge multiple ContentChunks into a single ContentChunk
 /// For text chunks, concatenates all text into one chunk
 fn merge_text_chunks(chunks: &[ContentChunk]) -> ContentChunk {
@@ -338,12 +446,573 @@ fn extract_text_from_content_chunk(chunk: &ContentChunk) -> String {
     }
 }
 
+/// Collapse streamed updates into the entries the session file would hold
+fn coalesce_updates(updates: Vec<SessionUpdate>) -> Vec<SessionUpdate> {
+    let mut accumulator = ChunkAccumulator::new();
+    let mut entries = Vec::new();
+
+    for update in updates {
+        if let Some(data) = accumulator.accumulate("replay", update) {
+            entries.extend(data.into_updates());
+        }
+    }
+    entries.extend(accumulator.flush().map(|(_, update)| update));
+    entries.extend(
+        accumulator
+            .flush_tool_call_updates()
+            .into_iter()
+            .map(|(_, update)| update),
+    );
+    entries
+}
+
+/// Content of a conversation entry used to match replayed history against
+/// the session file. Returns None for updates that are not part of the
+/// conversation itself (commands, mode changes, ...).
+///
+/// Entries repeated word for word share a key; [`reconcile_history`] tells
+/// them apart by their position among the entries with the same key.
+fn history_key(update: &SessionUpdate) -> Option<String> {
+    let content_key = |chunk: &ContentChunk| match &chunk.content {
+        ContentBlock::Text(text) => text.text.trim().to_string(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    };
+
+    match update {
+        SessionUpdate::UserMessageChunk(chunk) => Some(format!("user:{}", content_key(chunk))),
+        SessionUpdate::AgentMessageChunk(chunk) => Some(format!("agent:{}", content_key(chunk))),
+        SessionUpdate::AgentThoughtChunk(chunk) => Some(format!("thought:{}", content_key(chunk))),
+        SessionUpdate::ToolCall(tool_call) => Some(format!("tool_call:{}", tool_call.tool_call_id)),
+        SessionUpdate::ToolCallUpdate(update) => {
+            Some(format!("tool_call_update:{}", update.tool_call_id))
+        }
+        SessionUpdate::Plan(plan) => serde_json::to_string(plan)
+            .ok()
+            .map(|plan| format!("plan:{}", plan)),
+        _ => None,
+    }
+}
+
+/// Identity of a persisted entry that does not depend on its content
+///
+/// An entry keeps the timestamp of its first chunk from the moment it is
+/// accumulated, so its provisional record and the message later written to
+/// the session file share it even when more chunks arrived in between.
+fn entry_id(message: &PersistedMessage) -> Option<String> {
+    let kind = match &message.update {
+        SessionUpdate::UserMessageChunk(_) => "user".to_string(),
+        SessionUpdate::AgentMessageChunk(_) => "agent".to_string(),
+        SessionUpdate::AgentThoughtChunk(_) => "thought".to_string(),
+        SessionUpdate::ToolCall(tool_call) => format!("tool_call:{}", tool_call.tool_call_id),
+        SessionUpdate::ToolCallUpdate(update) => {
+            format!("tool_call_update:{}", update.tool_call_id)
+        }
+        SessionUpdate::Plan(_) => "plan".to_string(),
+        _ => return None,
+    };
+    Some(format!("{}:{}", message.timestamp, kind))
+}
+
+/// Merge history replayed by the agent into the locally persisted messages.
+///
+/// Replayed entries are matched to local ones by content and position: the
+/// n-th occurrence of an entry in the replay is the n-th occurrence of the
+/// same entry locally, so a message repeated word for word is matched once
+/// per occurrence. Matched entries keep their local timestamp; entries
+/// missing locally are inserted after the last matched message. Returns the
+/// merged history and the number of inserted entries.
+pub fn reconcile_history(
+    local: Vec<PersistedMessage>,
+    replayed: Vec<SessionUpdate>,
+) -> (Vec<PersistedMessage>, usize) {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut local_positions: HashMap<(String, usize), usize> = HashMap::new();
+    for (ix, message) in local.iter().enumerate() {
+        if let Some(key) = history_key(&message.update) {
+            let occurrence = occurrences.entry(key.clone()).or_default();
+            local_positions.insert((key, *occurrence), ix);
+            *occurrence += 1;
+        }
+    }
+
+    // Local position each missing entry goes before, in replay order
+    let mut missing: Vec<(usize, SessionUpdate)> = Vec::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut cursor = 0;
+    for update in coalesce_updates(replayed) {
+        let Some(key) = history_key(&update) else {
+            continue;
+        };
+        let occurrence = occurrences.entry(key.clone()).or_default();
+        let position = local_positions.get(&(key, *occurrence)).copied();
+        *occurrence += 1;
+
+        match position {
+            Some(ix) => cursor = cursor.max(ix + 1),
+            None => missing.push((cursor, update)),
+        }
+    }
+
+    let added = missing.len();
+    let mut merged: Vec<PersistedMessage> = Vec::with_capacity(local.len() + added);
+    let mut missing = missing.into_iter().peekable();
+    for (ix, message) in local.into_iter().enumerate() {
+        while let Some((_, update)) = missing.next_if(|(position, _)| *position == ix) {
+            // Borrow the timestamp of a neighbour so ordering by time stays stable
+            let timestamp = merged
+                .last()
+                .map_or_else(|| message.timestamp.clone(), |prev| prev.timestamp.clone());
+            merged.push(PersistedMessage::with_timestamp(timestamp, update));
+        }
+        merged.push(message);
+    }
+    for (_, update) in missing {
+        let timestamp = merged
+            .last()
+            .map_or_else(|| Utc::now().to_rfc3339(), |prev| prev.timestamp.clone());
+        merged.push(PersistedMessage::with_timestamp(timestamp, update));
+    }
+
+    (merged, added)
+}
+
+/// Length of the history that ends with the `message_count`-th conversation
+/// message counted from position `start`
+///
+/// User and agent messages count, one per message as the conversation panel
+/// shows them, so consecutive user message chunks count once. Returns None
+/// when the history has fewer messages.
+pub fn fork_point(
+    messages: &[PersistedMessage],
+    start: usize,
+    message_count: usize,
+) -> Option<usize> {
+    conversation_message_ends(messages, start).nth(message_count.checked_sub(1)?)
+}
+
+/// History lengths that end with each conversation message after position
+/// `start`, counted as [`fork_point`] counts them
+fn conversation_message_ends(
+    messages: &[PersistedMessage],
+    start: usize,
+) -> impl Iterator<Item = usize> + '_ {
+    let is_user =
+        |message: &PersistedMessage| matches!(message.update, SessionUpdate::UserMessageChunk(_));
+
+    let mut previous_user = false;
+    messages
+        .iter()
+        .enumerate()
+        .skip(start)
+        .filter_map(move |(ix, message)| {
+            let user = is_user(message);
+            let counts = (user && !previous_user)
+                || matches!(message.update, SessionUpdate::AgentMessageChunk(_));
+            previous_user = user;
+            if !counts {
+                return None;
+            }
+            // The rest of a user message split over several chunks goes along
+            let rest = if user {
+                messages[ix + 1..].iter().take_while(|m| is_user(m)).count()
+            } else {
+                0
+            };
+            Some(ix + 1 + rest)
+        })
+}
+
+/// Start of the prompt that hands a forked conversation to the agent
+const FORK_CONTEXT_PREAMBLE: &str =
+    "This session continues an earlier conversation from the point below.";
+
+/// Longest transcript handed to a forked session, in bytes
+///
+/// The earliest entries of longer transcripts are left out so the context
+/// prompt fits the agent's context window.
+const FORK_CONTEXT_MAX_LEN: usize = 100_000;
+
+/// Prompt that hands the transcript of a forked conversation to the agent
+pub fn fork_context_prompt(
+    parent_session_id: &str,
+    messages: &[PersistedMessage],
+) -> Result<String> {
+    let options = SessionExportOptions {
+        format: SessionExportFormat::Markdown,
+        include_thoughts: false,
+        redact_secrets: false,
+    };
+    let mut session = ExportedSession::build(parent_session_id, None, messages, &options, None);
+    let entries = std::mem::take(&mut session.entries);
+    let mut render = |skip: usize| {
+        session.entries = entries[skip..].to_vec();
+        session.render(SessionExportFormat::Markdown)
+    };
+
+    // Fewest leading entries to leave out, keeping at least the last one
+    let mut transcript = render(0)?;
+    let mut skipped = 0;
+    if transcript.len() > FORK_CONTEXT_MAX_LEN && entries.len() > 1 {
+        let (mut low, mut high) = (1, entries.len() - 1);
+        while low < high {
+            let mid = (low + high) / 2;
+            if render(mid)?.len() > FORK_CONTEXT_MAX_LEN {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        skipped = low;
+        transcript = render(skipped)?;
+    }
+    // A single entry over the limit keeps its end
+    if transcript.len() > FORK_CONTEXT_MAX_LEN {
+        let mut cut = transcript.len() - FORK_CONTEXT_MAX_LEN;
+        while !transcript.is_char_boundary(cut) {
+            cut += 1;
+        }
+        transcript.replace_range(..cut, "");
+    }
+
+    let omitted = if skipped > 0 {
+        format!(
+            " The {} earliest entries of the conversation were left out for length.",
+            skipped
+        )
+    } else {
+        String::new()
+    };
+    Ok(format!(
+        "{} Treat the transcript as what has happened so far, including any \
+         file changes it describes, and wait for the next message. Reply only \
+         with \"Ready\".{}\n\n{}",
+        FORK_CONTEXT_PREAMBLE, omitted, transcript
+    ))
+}
+
+/// Drop the context prompt of a forked session and everything the agent
+/// replied to it from replayed history; neither is part of the conversation
+fn strip_fork_context(replayed: Vec<SessionUpdate>) -> Vec<SessionUpdate> {
+    let mut in_context = false;
+    let mut previous_user = false;
+    replayed
+        .into_iter()
+        .filter(|update| {
+            if let SessionUpdate::UserMessageChunk(chunk) = update {
+                let starts_context = matches!(
+                    &chunk.content,
+                    ContentBlock::Text(text) if text.text.starts_with(FORK_CONTEXT_PREAMBLE)
+                );
+                // Later chunks of the context prompt continue it
+                in_context = starts_context || (in_context && previous_user);
+                previous_user = true;
+            } else {
+                previous_user = false;
+            }
+            !in_context
+        })
+        .collect()
+}
+
+/// Suffix of the file holding a session's provisional records
+///
+/// The extension keeps these files out of [`PersistenceService::list_sessions`].
+const PROVISIONAL_SUFFIX: &str = "provisional";
+
+/// How often chunks that are still streaming are written as provisional records
+pub const PROVISIONAL_FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Messages of a session file together with the lines that could not be read
+#[derive(Debug, Clone, Default)]
+pub struct LoadedSession {
+    pub messages: Vec<PersistedMessage>,
+    /// 1-based line numbers that are not valid messages, e.g. a line cut short by a crash
+    pub corrupt_lines: Vec<usize>,
+}
+
+/// A session that had unflushed state when the app last stopped
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredSession {
+    pub session_id: String,
+    /// Provisional records appended to the session file
+    pub restored_messages: usize,
+    /// Whether the file ended in a partially written line
+    pub repaired_partial_line: bool,
+}
+
+/// Read all messages of a session file, skipping lines that fail to parse
+fn read_session_file(file_path: &Path) -> Result<Vec<PersistedMessage>> {
+    Ok(read_session_file_report(file_path)?.messages)
+}
+
+/// Read a session file, reporting corrupt lines instead of failing on them
+fn read_session_file_report(file_path: &Path) -> Result<LoadedSession> {
+    if !file_path.exists() {
+        return Ok(LoadedSession::default());
+    }
+
+    let bytes = std::fs::read(file_path).context("Failed to open session file")?;
+    let mut loaded = LoadedSession::default();
+
+    for (ix, line) in bytes.split(|byte| *byte == b'\n').enumerate() {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        match serde_json::from_slice::<PersistedMessage>(line) {
+            Ok(message) => loaded.messages.push(message),
+            Err(e) => {
+                log::warn!(
+                    "Skipping corrupt line {} in {}: {}",
+                    ix + 1,
+                    file_path.display(),
+                    e
+                );
+                loaded.corrupt_lines.push(ix + 1);
+            }
+        }
+    }
+
+    Ok(loaded)
+}
+
+/// Rewrite a session file from its current messages and re-index it
+///
+/// Writes to the session wait from the read until the index is updated, so
+/// nothing appended in between is lost. `rewrite` returns None to leave the
+/// file as it is.
+fn rewrite_session_file(
+    index: &SessionIndex,
+    file_path: &Path,
+    session_id: &str,
+    rewrite: impl FnOnce(Vec<PersistedMessage>) -> Option<Vec<PersistedMessage>>,
+) -> Result<()> {
+    index.with_session_writes(session_id, || {
+        let Some(messages) = rewrite(read_session_file(file_path)?) else {
+            return Ok(());
+        };
+        if let Some(dir) = file_path.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create base directory")?;
+        }
+
+        let mut contents = String::new();
+        for message in &messages {
+            let json = serde_json::to_string(message).context("Failed to serialize message")?;
+            contents.push_str(&json);
+            contents.push('\n');
+        }
+        // Write next to the original and rename so a crash never leaves a partial file
+        write_file_durably(file_path, contents.as_bytes())?;
+        index.index_session_within_writes(session_id, &messages, contents.len() as u64);
+        Ok(())
+    })
+}
+
+/// Replace a file with new contents so a crash leaves either the old or the new file
+fn write_file_durably(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = std::fs::File::create(&tmp_path)
+        .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+    file.write_all(contents)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    file.sync_all()
+        .with_context(|| format!("Failed to sync {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+/// Whether a non-empty file does not end with a newline
+fn has_partial_last_line(file: &mut std::fs::File) -> Result<bool> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    let mut last = [0u8];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+/// File holding the provisional records of a session file
+fn provisional_path(session_file: &Path) -> PathBuf {
+    let mut name = session_file.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(PROVISIONAL_SUFFIX);
+    session_file.with_file_name(name)
+}
+
+/// Mirror an accumulation into its provisional file; nothing pending removes the file
+fn write_provisional(session_file: &Path, messages: &[PersistedMessage]) -> Result<()> {
+    let path = provisional_path(session_file);
+    if messages.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).context("Failed to remove provisional records")?;
+        }
+        return Ok(());
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).context("Failed to create base directory")?;
+    }
+    let mut contents = String::new();
+    for message in messages {
+        contents.push_str(&serde_json::to_string(message).context("Failed to serialize message")?);
+        contents.push('\n');
+    }
+    write_file_durably(&path, contents.as_bytes())
+}
+
+/// Number of history messages a conversation loads at a time
+pub const HISTORY_PAGE_SIZE: usize = 200;
+
+/// A window of a session's history, oldest message first
+#[derive(Debug, Clone, Default)]
+pub struct HistoryPage {
+    pub messages: Vec<PersistedMessage>,
+    /// Position of the first message in the whole history
+    pub start: usize,
+    /// Number of messages in the whole history
+    pub total: usize,
+    /// Lines of the session file that are not valid messages
+    pub corrupt_lines: usize,
+}
+
+impl HistoryPage {
+    /// Whether older messages exist before this page
+    pub fn has_older(&self) -> bool {
+        self.start > 0
+    }
+}
+
+/// Byte ranges of the messages in a session file
+///
+/// Built by scanning the file once and extended as it grows, so a page can
+/// be read with a single seek instead of deserializing the whole history.
+#[derive(Debug, Default)]
+struct MessageOffsets {
+    /// Length of the file prefix already scanned, always at a line boundary
+    scanned: u64,
+    /// Start and end of each line holding a message
+    lines: Vec<(u64, u64)>,
+    corrupt_lines: usize,
+}
+
+impl MessageOffsets {
+    /// Scan what was appended since the last call
+    fn update(&mut self, file_path: &Path) -> Result<()> {
+        use std::io::{BufRead, BufReader, Seek, SeekFrom};
+
+        if !file_path.exists() {
+            *self = Self::default();
+            return Ok(());
+        }
+        let file = std::fs::File::open(file_path).context("Failed to open session file")?;
+        let len = file.metadata()?.len();
+        if len < self.scanned {
+            // Rewritten in place of a longer file
+            *self = Self::default();
+        }
+        if len == self.scanned {
+            return Ok(());
+        }
+
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(self.scanned))?;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            // A partial last line is picked up once it is complete
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            let start = self.scanned;
+            self.scanned += read as u64;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if serde_json::from_slice::<serde::de::IgnoredAny>(&line).is_ok() {
+                self.lines.push((start, self.scanned));
+            } else {
+                self.corrupt_lines += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the messages at the given byte ranges of a session file, which must be contiguous
+fn read_message_range(file_path: &Path, lines: &[(u64, u64)]) -> Result<Vec<PersistedMessage>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+        return Ok(Vec::new());
+    };
+    let mut file = std::fs::File::open(file_path).context("Failed to open session file")?;
+    file.seek(SeekFrom::Start(first.0))?;
+    let mut bytes = vec![0; (last.1 - first.0) as usize];
+    file.read_exact(&mut bytes)
+        .context("Failed to read session file")?;
+
+    let messages = lines
+        .iter()
+        .filter_map(|(start, end)| {
+            let line = &bytes[(start - first.0) as usize..(end - first.0) as usize];
+            serde_json::from_slice::<PersistedMessage>(line)
+                .inspect_err(|e| {
+                    log::warn!(
+                        "Skipping unreadable message in {}: {}",
+                        file_path.display(),
+                        e
+                    )
+                })
+                .ok()
+        })
+        .collect();
+    Ok(messages)
+}
+
 /// Message persistence service
 pub struct PersistenceService {
     /// Base directory for session files
     base_dir: PathBuf,
     /// Thread-safe storage for chunk accumulators per session
     accumulators: Arc<Mutex<HashMap<String, ChunkAccumulator>>>,
+    /// Metadata and full-text index of the session files
+    index: SessionIndex,
+    /// Message offsets of the session files read page by page
+    offsets: Arc<Mutex<HashMap<String, MessageOffsets>>>,
+    /// Message, tool call and timing statistics of the sessions
+    stats: SessionStatsCollector,
+    /// Token usage and cost of prompts and AI service calls
+    usage: UsageLedger,
+    /// Sessions shown in a conversation panel, with the number of panels
+    open_sessions: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+/// Keeps a session marked as open in a panel until dropped
+pub struct OpenSession {
+    session_id: String,
+    open_sessions: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Drop for OpenSession {
+    fn drop(&mut self) {
+        let mut open_sessions = self.open_sessions.lock().unwrap();
+        if let Some(count) = open_sessions.get_mut(&self.session_id) {
+            *count -= 1;
+            if *count == 0 {
+                open_sessions.remove(&self.session_id);
+            }
+        }
+    }
 }
 
 impl PersistenceService {
@@ -352,17 +1021,70 @@ impl PersistenceService {
     /// # Arguments
     /// * `base_dir` - Base directory for storing session files (e.g., "target/sessions")
     pub fn new(base_dir: PathBuf) -> Self {
+        let index = SessionIndex::new(&base_dir);
+        let stats = SessionStatsCollector::new(base_dir.join(STATS_DIR_NAME));
+        let usage = UsageLedger::new(base_dir.join(USAGE_DIR_NAME).join(USAGE_FILE_NAME));
         Self {
             base_dir,
             accumulators: Arc::new(Mutex::new(HashMap::new())),
+            index,
+            offsets: Arc::new(Mutex::new(HashMap::new())),
+            stats,
+            usage,
+            open_sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Mark a session as shown in a panel until the returned guard is
+    /// dropped; the retention policy leaves open sessions alone
+    pub fn open_session(&self, session_id: &str) -> OpenSession {
+        *self
+            .open_sessions
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_default() += 1;
+        OpenSession {
+            session_id: session_id.to_string(),
+            open_sessions: self.open_sessions.clone(),
         }
     }
 
+    /// Metadata and full-text index of the persisted sessions
+    pub fn index(&self) -> &SessionIndex {
+        &self.index
+    }
+
+    /// Statistics of the persisted sessions
+    pub fn stats(&self) -> &SessionStatsCollector {
+        &self.stats
+    }
+
+    /// Token usage ledger of the sessions and the AI service
+    pub fn usage(&self) -> &UsageLedger {
+        &self.usage
+    }
+
+    /// Write a session's statistics next to its history
+    pub async fn save_stats(&self, session_id: &str) -> Result<()> {
+        let stats = self.stats.clone();
+        let session_id = session_id.to_string();
+        smol::unblock(move || stats.save(&session_id)).await
+    }
+
     /// Get the file path for a session
     fn session_file_path(&self, session_id: &str) -> PathBuf {
+        if is_imported_session(session_id) {
+            return self.imported_dir().join(format!("{}.jsonl", session_id));
+        }
         self.base_dir.join(format!("{}.jsonl", session_id))
     }
 
+    /// Directory of the sessions imported for review
+    fn imported_dir(&self) -> PathBuf {
+        self.base_dir.join(IMPORTED_DIR_NAME)
+    }
+
     /// Ensure the base directory exists
     fn ensure_base_dir_sync(&self) -> Result<()> {
         if !self.base_dir.exists() {
@@ -382,37 +1104,7 @@ impl PersistenceService {
                 .entry(session_id.to_string())
                 .or_insert_with(ChunkAccumulator::new);
 
-            match update {
-                SessionUpdate::AgentMessageChunk(chunk) => {
-                    log::debug!("Accumulating AgentMessageChunk for session: {}", session_id);
-                    accumulator.try_append_agent_message_chunk(chunk)
-                }
-                SessionUpdate::AgentThoughtChunk(chunk) => {
-                    log::debug!("Accumulating AgentThoughtChunk for session: {}", session_id);
-                    accumulator.try_append_agent_thought_chunk(chunk)
-                }
-                SessionUpdate::UserMessageChunk(chunk) => {
-                    log::debug!("Accumulating UserMessageChunk for session: {}", session_id);
-                    accumulator.try_append_user_message_chunk(chunk)
-                }
-                SessionUpdate::ToolCallUpdate(update) => {
-                    log::debug!(
-                        "Accumulating ToolCallUpdate for session: {}, toolCallId: {}",
-                        session_id,
-                        update.tool_call_id
-                    );
-                    accumulator.accumulate_tool_call_update(update)
-                }
-                _ => {
-                    // Non-chunk update: flush accumulator, then write both
-                    log::debug!(
-                        "Non-chunk update received, flushing accumulator for session: {}",
-                        session_id
-                    );
-                    let flushed = accumulator.flush();
-                    Some(FlushData::Both(Box::new((flushed, update))))
-                }
-            }
+            accumulator.accumulate(session_id, update)
         }; // Lock released here
 
         // Write outside lock to avoid blocking
@@ -466,8 +1158,11 @@ impl PersistenceService {
         timestamp: String,
     ) -> Result<()> {
         let file_path = self.session_file_path(session_id);
+        let archive_path = self.archive_dir().join(format!("{}.jsonl.gz", session_id));
         let base_dir = self.base_dir.clone();
         let message = PersistedMessage::with_timestamp(timestamp, update);
+        let index = self.index.clone();
+        let session_id = session_id.to_string();
 
         smol::unblock(move || {
             // Ensure directory exists
@@ -478,18 +1173,44 @@ impl PersistenceService {
             // Serialize to JSON and append newline
             let json = serde_json::to_string(&message).context("Failed to serialize message")?;
 
-            // Open file in append mode
-            use std::fs::OpenOptions;
-            use std::io::Write;
+            let append = || -> Result<u64> {
+                // Open file in append mode
+                use std::fs::OpenOptions;
+                use std::io::Write;
+
+                // The retention policy archived the session before this
+                // message arrived; bring the history back so it continues
+                if !file_path.exists() && archive_path.exists() {
+                    unarchive_file(&archive_path, &file_path)?;
+                    std::fs::remove_file(&archive_path)
+                        .with_context(|| format!("Failed to delete {}", archive_path.display()))?;
+                    log::info!("Restored archived session {} to append to it", session_id);
+                }
 
-            let mut file = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&file_path)
-                .context("Failed to open session file")?;
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .read(true)
+                    .append(true)
+                    .open(&file_path)
+                    .context("Failed to open session file")?;
+
+                // A crash can leave a partial last line; start a fresh one so
+                // this message does not get glued onto it
+                if has_partial_last_line(&mut file)? {
+                    file.write_all(b"\n").context("Failed to write message")?;
+                }
 
-            // Write JSON line
-            write!(file, "{}\n", json).context("Failed to write message")?;
+                // Write JSON line and make sure it reached the disk
+                write!(file, "{}\n", json).context("Failed to write message")?;
+                file.sync_data().context("Failed to sync session file")?;
+                Ok(file
+                    .metadata()
+                    .context("Failed to stat session file")?
+                    .len())
+            };
+            index.record_append(&session_id, &message, append, || {
+                read_session_file(&file_path)
+            })?;
 
             log::debug!(
                 "Wrote merged message to session file: {}",
@@ -553,88 +1274,796 @@ impl PersistenceService {
             log::debug!("No accumulated data to flush for session: {}", session_id);
         }
 
-        Ok(())
+        // The provisional records are superseded now
+        self.sync_provisional(session_id).await
     }
 
-    /// Load all messages for a session
-    ///
-    /// Returns messages in chronological order
-    pub async fn load_messages(&self, session_id: &str) -> Result<Vec<PersistedMessage>> {
+    /// Rewrite the provisional records of one session from its accumulator
+    async fn sync_provisional(&self, session_id: &str) -> Result<()> {
+        let accumulators = self.accumulators.clone();
         let file_path = self.session_file_path(session_id);
-        let session_id = session_id.to_string(); // Clone for the closure
+        let session_id = session_id.to_string();
 
-        // Use smol::unblock to run blocking I/O
         smol::unblock(move || {
-            // Check if file exists
-            if !file_path.exists() {
-                log::debug!("No history file found for session: {}", session_id);
-                return Ok(Vec::new());
-            }
-
-            use std::fs::File;
-            use std::io::{BufRead, BufReader};
-
-            let file = File::open(&file_path).context("Failed to open session file")?;
-
-            let reader = BufReader::new(file);
-            let mut messages = Vec::new();
-
-            for line in reader.lines() {
-                let line = line?;
-                if line.trim().is_empty() {
-                    continue;
-                }
-
-                match serde_json::from_str::<PersistedMessage>(&line) {
-                    Ok(message) => messages.push(message),
-                    Err(e) => {
-                        log::warn!("Failed to parse line in session file: {}", e);
-                        // Continue reading other lines
-                    }
+            // Held while writing so the file never lags behind a newer state
+            let mut accumulators = accumulators.lock().unwrap();
+            let messages = match accumulators.get_mut(&session_id) {
+                Some(accumulator) => {
+                    accumulator.dirty = false;
+                    accumulator.provisional_messages()
                 }
-            }
-
-            log::info!(
-                "Loaded {} messages from session file: {}",
-                messages.len(),
-                file_path.display()
-            );
-            Ok(messages)
+                None => Vec::new(),
+            };
+            write_provisional(&file_path, &messages)
         })
         .await
     }
 
-    /// Delete a session's history file
+    /// Write chunks that are still streaming as provisional records
     ///
-    /// Flushes any pending chunks before deleting
-    pub async fn delete_session(&self, session_id: &str) -> Result<()> {
-        // Flush pending chunks first
-        self.flush_session(session_id).await?;
-
-        // Remove accumulator
-        {
-            let mut accumulators = self.accumulators.lock().unwrap();
-            accumulators.remove(session_id);
+    /// Called every [`PROVISIONAL_FLUSH_INTERVAL`] so a crash mid-response
+    /// loses at most that much text. The records are superseded when the
+    /// accumulation is flushed to the session file, and restored by
+    /// [`Self::recover_sessions`] if that never happens. Returns the number
+    /// of sessions written.
+    pub async fn flush_provisional(&self) -> Result<usize> {
+        let accumulators = self.accumulators.clone();
+        let paths: Vec<(String, PathBuf)> = {
+            let accumulators = self.accumulators.lock().unwrap();
+            accumulators
+                .iter()
+                .filter(|(_, accumulator)| accumulator.dirty)
+                .map(|(session_id, _)| (session_id.clone(), self.session_file_path(session_id)))
+                .collect()
+        };
+        if paths.is_empty() {
+            return Ok(0);
         }
 
-        // Delete file
-        let file_path = self.session_file_path(session_id);
-
         smol::unblock(move || {
-            if file_path.exists() {
-                std::fs::remove_file(&file_path).context("Failed to delete session file")?;
-                log::info!("Deleted session file: {}", file_path.display());
+            let mut accumulators = accumulators.lock().unwrap();
+            let mut written = 0;
+            for (session_id, file_path) in paths {
+                let Some(accumulator) = accumulators.get_mut(&session_id) else {
+                    continue;
+                };
+                accumulator.dirty = false;
+                match write_provisional(&file_path, &accumulator.provisional_messages()) {
+                    Ok(()) => written += 1,
+                    Err(e) => log::warn!(
+                        "Failed to write provisional records of session {}: {}",
+                        session_id,
+                        e
+                    ),
+                }
             }
-            Ok(())
+            Ok(written)
         })
         .await
     }
 
-    /// List all available sessions
-    pub async fn list_sessions(&self) -> Result<Vec<String>> {
+    /// Find sessions with unflushed state left behind by a crash and repair them
+    ///
+    /// Provisional records that did not make it into the session file are
+    /// appended to it, and files ending in a partially written line get a
+    /// newline so later messages stay readable. Call on startup before any
+    /// session streams again.
+    pub async fn recover_sessions(&self) -> Result<Vec<RecoveredSession>> {
         let base_dir = self.base_dir.clone();
-
-        smol::unblock(move || {
+        let mut recovered: Vec<RecoveredSession> = smol::unblock(move || {
+            if !base_dir.exists() {
+                return Ok::<_, anyhow::Error>(Vec::new());
+            }
+
+            let mut recovered = Vec::new();
+            for entry in
+                std::fs::read_dir(&base_dir).context("Failed to read sessions directory")?
+            {
+                let path = entry?.path();
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let session_id = if let Some(session_id) = name
+                    .strip_suffix(PROVISIONAL_SUFFIX)
+                    .and_then(|name| name.strip_suffix(".jsonl."))
+                {
+                    session_id
+                } else if let Some(session_id) = name.strip_suffix(".jsonl") {
+                    session_id
+                } else {
+                    continue;
+                };
+
+                let repaired_partial_line = if path.extension().is_some_and(|ext| ext == "jsonl") {
+                    use std::io::Write;
+
+                    let mut file = std::fs::OpenOptions::new()
+                        .read(true)
+                        .append(true)
+                        .open(&path)
+                        .context("Failed to open session file")?;
+                    let partial = has_partial_last_line(&mut file)?;
+                    if partial {
+                        file.write_all(b"\n")
+                            .context("Failed to repair session file")?;
+                        file.sync_data().context("Failed to sync session file")?;
+                    }
+                    partial
+                } else {
+                    false
+                };
+
+                match recovered
+                    .iter_mut()
+                    .find(|session: &&mut RecoveredSession| session.session_id == session_id)
+                {
+                    Some(session) => session.repaired_partial_line |= repaired_partial_line,
+                    None => recovered.push(RecoveredSession {
+                        session_id: session_id.to_string(),
+                        restored_messages: 0,
+                        repaired_partial_line,
+                    }),
+                }
+            }
+            Ok(recovered)
+        })
+        .await?;
+
+        for session in &mut recovered {
+            let file_path = self.session_file_path(&session.session_id);
+            let provisional = provisional_path(&file_path);
+            if !provisional.exists() {
+                continue;
+            }
+
+            let records = smol::unblock({
+                let provisional = provisional.clone();
+                move || read_session_file(&provisional)
+            })
+            .await?;
+            // Records that reached the session file before the crash are not
+            // repeated; they are recognized by identity, not by their text
+            let persisted: std::collections::HashSet<String> = self
+                .load_messages(&session.session_id)
+                .await?
+                .iter()
+                .filter_map(entry_id)
+                .collect();
+            for record in records {
+                if entry_id(&record).is_some_and(|id| persisted.contains(&id)) {
+                    continue;
+                }
+                self.write_with_timestamp(&session.session_id, record.update, record.timestamp)
+                    .await?;
+                session.restored_messages += 1;
+            }
+
+            smol::unblock(move || std::fs::remove_file(&provisional))
+                .await
+                .context("Failed to remove provisional records")?;
+        }
+
+        recovered.retain(|session| session.restored_messages > 0 || session.repaired_partial_line);
+        for session in &recovered {
+            log::warn!(
+                "Recovered session {} after an unclean shutdown: {} provisional messages restored{}",
+                session.session_id,
+                session.restored_messages,
+                if session.repaired_partial_line {
+                    ", partial last line repaired"
+                } else {
+                    ""
+                }
+            );
+        }
+        Ok(recovered)
+    }
+
+    /// Load all messages for a session
+    ///
+    /// Returns messages in chronological order
+    pub async fn load_messages(&self, session_id: &str) -> Result<Vec<PersistedMessage>> {
+        Ok(self.load_messages_with_report(session_id).await?.messages)
+    }
+
+    /// Load all messages for a session along with the lines that were skipped
+    pub async fn load_messages_with_report(&self, session_id: &str) -> Result<LoadedSession> {
+        let file_path = self.session_file_path(session_id);
+        let session_id = session_id.to_string(); // Clone for the closure
+
+        // Use smol::unblock to run blocking I/O
+        smol::unblock(move || {
+            // Check if file exists
+            if !file_path.exists() {
+                log::debug!("No history file found for session: {}", session_id);
+                return Ok(LoadedSession::default());
+            }
+
+            let loaded = read_session_file_report(&file_path)?;
+
+            log::info!(
+                "Loaded {} messages from session file: {}",
+                loaded.messages.len(),
+                file_path.display()
+            );
+            if !loaded.corrupt_lines.is_empty() {
+                log::warn!(
+                    "Skipped {} corrupt lines in session file {}: {:?}",
+                    loaded.corrupt_lines.len(),
+                    file_path.display(),
+                    loaded.corrupt_lines
+                );
+            }
+            Ok(loaded)
+        })
+        .await
+    }
+
+    /// Load the messages before `before` (the end of the history if None),
+    /// at most `limit` of them
+    ///
+    /// Message positions match [`Self::load_messages`] as long as the file
+    /// has no corrupt lines, which are left out of the count.
+    pub async fn load_history_page(
+        &self,
+        session_id: &str,
+        before: Option<usize>,
+        limit: usize,
+    ) -> Result<HistoryPage> {
+        let file_path = self.session_file_path(session_id);
+        let offsets = self.offsets.clone();
+        let session_id = session_id.to_string();
+
+        smol::unblock(move || {
+            let (lines, start, total, corrupt_lines) = {
+                let mut offsets = offsets.lock().unwrap();
+                let offsets = offsets.entry(session_id.clone()).or_default();
+                offsets.update(&file_path)?;
+
+                let total = offsets.lines.len();
+                let end = before.unwrap_or(total).min(total);
+                let start = end.saturating_sub(limit);
+                (
+                    offsets.lines[start..end].to_vec(),
+                    start,
+                    total,
+                    offsets.corrupt_lines,
+                )
+            };
+
+            let messages = read_message_range(&file_path, &lines)?;
+            log::debug!(
+                "Loaded messages {}..{} of {} for session {}",
+                start,
+                start + lines.len(),
+                total,
+                session_id
+            );
+            Ok(HistoryPage {
+                messages,
+                start,
+                total,
+                corrupt_lines,
+            })
+        })
+        .await
+    }
+
+    /// Forget the message offsets of a session file that was rewritten
+    fn invalidate_offsets(&self, session_id: &str) {
+        self.offsets.lock().unwrap().remove(session_id);
+    }
+
+    /// Merge history replayed by the agent into the session file
+    ///
+    /// Returns the number of messages that were missing locally. The file is
+    /// only rewritten when something was added.
+    pub async fn merge_replayed_history(
+        &self,
+        session_id: &str,
+        replayed: Vec<SessionUpdate>,
+    ) -> Result<usize> {
+        self.flush_session(session_id).await?;
+
+        let file_path = self.session_file_path(session_id);
+        let index = self.index.clone();
+        let session_id_owned = session_id.to_string();
+        let replayed = strip_fork_context(replayed);
+
+        let added = smol::unblock(move || {
+            let mut added = 0;
+            rewrite_session_file(&index, &file_path, &session_id_owned, |local| {
+                let (merged, inserted) = reconcile_history(local, replayed);
+                added = inserted;
+                (inserted > 0).then_some(merged)
+            })?;
+            Ok::<_, anyhow::Error>(added)
+        })
+        .await?;
+        if added == 0 {
+            log::debug!(
+                "Replayed history of session {} is already persisted",
+                session_id
+            );
+            return Ok(0);
+        }
+        self.invalidate_offsets(session_id);
+
+        log::info!(
+            "Merged {} replayed messages into session file for: {}",
+            added,
+            session_id
+        );
+        Ok(added)
+    }
+
+    /// Delete a session's history file, and its archived copy if any
+    ///
+    /// Flushes any pending chunks before deleting
+    pub async fn delete_session(&self, session_id: &str) -> Result<()> {
+        // Flush pending chunks first
+        self.flush_session(session_id).await?;
+
+        // Remove accumulator
+        {
+            let mut accumulators = self.accumulators.lock().unwrap();
+            accumulators.remove(session_id);
+        }
+
+        // Delete file
+        let file_path = self.session_file_path(session_id);
+        let archive_path = self.archive_dir().join(format!("{}.jsonl.gz", session_id));
+
+        smol::unblock(move || {
+            if file_path.exists() {
+                std::fs::remove_file(&file_path).context("Failed to delete session file")?;
+                log::info!("Deleted session file: {}", file_path.display());
+            }
+            if archive_path.exists() {
+                std::fs::remove_file(&archive_path).context("Failed to delete archived session")?;
+                log::info!("Deleted archived session: {}", archive_path.display());
+            }
+            write_provisional(&file_path, &[])
+        })
+        .await?;
+
+        self.index.remove_session(session_id);
+        self.invalidate_offsets(session_id);
+        if let Err(e) = self.stats.remove(session_id) {
+            log::warn!("Failed to remove stats of session {}: {}", session_id, e);
+        }
+        Ok(())
+    }
+
+    /// Render a session's history for export
+    ///
+    /// Pending chunks are flushed first so the export includes the message
+    /// that is still streaming.
+    pub async fn export_session(
+        &self,
+        session_id: &str,
+        options: &SessionExportOptions,
+        terminals: Option<&TerminalRegistry>,
+    ) -> Result<String> {
+        self.flush_session(session_id).await?;
+        let messages = self.load_messages(session_id).await?;
+        if messages.is_empty() {
+            anyhow::bail!("Session {} has no persisted history", session_id);
+        }
+
+        let metadata = self.index.session(session_id);
+        ExportedSession::build(session_id, metadata.as_ref(), &messages, options, terminals)
+            .render(options.format)
+    }
+
+    /// Copy the history of `source` into the new session `target`, up to and
+    /// including the `message_count`-th conversation message from `start`
+    ///
+    /// `start` is a position in the session file like [`HistoryPage::start`].
+    /// Anything `target` already persisted, such as its command list, stays
+    /// after the copy. Returns the copied messages.
+    pub async fn fork_history(
+        &self,
+        source: &str,
+        target: &str,
+        start: usize,
+        message_count: usize,
+    ) -> Result<Vec<PersistedMessage>> {
+        self.flush_session(source).await?;
+        self.flush_session(target).await?;
+
+        let mut copied = self.load_messages(source).await?;
+        let end = fork_point(&copied, start, message_count).with_context(|| {
+            format!(
+                "Session {} has no message {} after position {} to fork from",
+                source, message_count, start
+            )
+        })?;
+        copied.truncate(end);
+
+        // What the new session streams meanwhile is kept after the copy
+        let file_path = self.session_file_path(target);
+        let index = self.index.clone();
+        let target_owned = target.to_string();
+        let history = copied.clone();
+        smol::unblock(move || {
+            rewrite_session_file(&index, &file_path, &target_owned, |existing| {
+                let mut merged = history;
+                merged.extend(existing);
+                Some(merged)
+            })
+        })
+        .await?;
+        self.invalidate_offsets(target);
+
+        log::info!(
+            "Forked {} messages of session {} into {}",
+            copied.len(),
+            source,
+            target
+        );
+        Ok(copied)
+    }
+
+    /// Whether the `message_count`-th conversation message after position
+    /// `start` is the last one of the session
+    pub async fn forks_from_last_message(
+        &self,
+        session_id: &str,
+        start: usize,
+        message_count: usize,
+    ) -> Result<bool> {
+        self.flush_session(session_id).await?;
+        let messages = self.load_messages(session_id).await?;
+        Ok(fork_point(&messages, start, message_count)
+            .is_some_and(|end| conversation_message_ends(&messages, end).next().is_none()))
+    }
+
+    /// Number of conversation messages of a session, counted as
+    /// [`fork_point`] counts them
+    pub async fn conversation_message_count(&self, session_id: &str) -> Result<usize> {
+        self.flush_session(session_id).await?;
+        let messages = self.load_messages(session_id).await?;
+        Ok(conversation_message_ends(&messages, 0).count())
+    }
+
+    /// Bring the index up to date with the session files
+    ///
+    /// Only files that changed since they were indexed are read. Returns the
+    /// number of indexed sessions.
+    pub async fn rebuild_index(&self) -> Result<usize> {
+        let sessions = self.list_sessions().await?;
+        let base_dir = self.base_dir.clone();
+        let index = self.index.clone();
+
+        smol::unblock(move || {
+            let mut read = 0;
+            for session_id in &sessions {
+                let file_path = base_dir.join(format!("{}.jsonl", session_id));
+                let file_len = || Ok(std::fs::metadata(&file_path)?.len());
+                match index.load_session(session_id, file_len, || read_session_file(&file_path)) {
+                    Ok(true) => read += 1,
+                    Ok(false) => {}
+                    Err(e) => log::warn!("Failed to index session {}: {}", session_id, e),
+                }
+            }
+            if let Err(e) = index.forget_missing(&sessions) {
+                log::warn!("Failed to drop deleted sessions from the index: {}", e);
+            }
+            log::info!(
+                "Indexed {} persisted sessions, {} of them read again",
+                sessions.len(),
+                read
+            );
+            Ok(sessions.len())
+        })
+        .await
+    }
+
+    /// Import an exported JSON bundle or a raw session file for read-only review
+    ///
+    /// The messages are copied under a new imported session ID, so removing
+    /// the import never touches the session it came from.
+    pub async fn import_session(&self, source: &Path) -> Result<ImportedSession> {
+        let imported_dir = self.imported_dir();
+        let source = source.to_path_buf();
+
+        smol::unblock(move || {
+            let contents = std::fs::read_to_string(&source)
+                .with_context(|| format!("Failed to read {}", source.display()))?;
+            let (imported, messages) = parse_session_file(&source, &contents)?;
+
+            std::fs::create_dir_all(&imported_dir)
+                .context("Failed to create imported sessions directory")?;
+            let mut lines = String::new();
+            for message in &messages {
+                lines.push_str(
+                    &serde_json::to_string(message).context("Failed to serialize message")?,
+                );
+                lines.push('\n');
+            }
+            std::fs::write(
+                imported_dir.join(format!("{}.jsonl", imported.session_id)),
+                lines,
+            )
+            .context("Failed to write imported session")?;
+            std::fs::write(
+                imported_dir.join(format!("{}.json", imported.session_id)),
+                serde_json::to_string_pretty(&imported)?,
+            )
+            .context("Failed to write imported session metadata")?;
+
+            log::info!(
+                "Imported {} messages from {} as {}",
+                imported.message_count,
+                source.display(),
+                imported.session_id
+            );
+            Ok(imported)
+        })
+        .await
+    }
+
+    /// Sessions imported for review, most recently imported first
+    pub async fn imported_sessions(&self) -> Result<Vec<ImportedSession>> {
+        let imported_dir = self.imported_dir();
+
+        smol::unblock(move || {
+            if !imported_dir.exists() {
+                return Ok(Vec::new());
+            }
+
+            let mut sessions = Vec::new();
+            for entry in std::fs::read_dir(&imported_dir)
+                .context("Failed to read imported sessions directory")?
+            {
+                let path = entry?.path();
+                if path.extension().is_none_or(|extension| extension != "json") {
+                    continue;
+                }
+                let parsed = std::fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| Ok(serde_json::from_str::<ImportedSession>(&json)?));
+                match parsed {
+                    Ok(session) => sessions.push(session),
+                    Err(e) => log::warn!("Skipping {}: {}", path.display(), e),
+                }
+            }
+            sessions.sort_by(|a, b| b.imported_at.cmp(&a.imported_at));
+            Ok(sessions)
+        })
+        .await
+    }
+
+    /// Delete an imported session; real sessions are left alone
+    pub async fn remove_imported_session(&self, session_id: &str) -> Result<()> {
+        if !is_imported_session(session_id) {
+            anyhow::bail!("Session {} was not imported", session_id);
+        }
+        self.invalidate_offsets(session_id);
+        let imported_dir = self.imported_dir();
+        let session_id = session_id.to_string();
+
+        smol::unblock(move || {
+            for extension in ["jsonl", "json"] {
+                let path = imported_dir.join(format!("{}.{}", session_id, extension));
+                if path.exists() {
+                    std::fs::remove_file(&path)
+                        .with_context(|| format!("Failed to delete {}", path.display()))?;
+                }
+            }
+            log::info!("Removed imported session {}", session_id);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Directory of the archived sessions
+    fn archive_dir(&self) -> PathBuf {
+        self.base_dir.join(ARCHIVE_DIR_NAME)
+    }
+
+    /// Size and modification time of each session file
+    fn session_files_sync(base_dir: &Path) -> Result<Vec<SessionFileInfo>> {
+        if !base_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(base_dir).context("Failed to read sessions directory")? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            let Some(session_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let metadata = std::fs::metadata(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            files.push(SessionFileInfo {
+                session_id: session_id.to_string(),
+                bytes: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Archive or delete the sessions that expired under `config`
+    ///
+    /// Sessions in `live` (those with a running agent session), sessions open
+    /// in a panel and sessions with chunks still accumulating are in use and
+    /// left alone. Archived sessions stay in the index without their text.
+    pub async fn apply_retention(
+        &self,
+        config: &RetentionConfig,
+        live: &[String],
+    ) -> Result<RetentionReport> {
+        if !config.is_enabled() {
+            return Ok(RetentionReport::default());
+        }
+
+        let base_dir = self.base_dir.clone();
+        let archive_dir = self.archive_dir();
+        let index = self.index.clone();
+        let accumulators = self.accumulators.clone();
+        let mut in_use = live.to_vec();
+        in_use.extend(self.open_sessions.lock().unwrap().keys().cloned());
+        let config = config.clone();
+
+        let report = smol::unblock(move || {
+            let files: Vec<SessionFileInfo> = Self::session_files_sync(&base_dir)?
+                .into_iter()
+                .filter(|file| !in_use.contains(&file.session_id))
+                .collect();
+            let expired = select_expired(&files, &config, std::time::SystemTime::now());
+
+            let mut report = RetentionReport::default();
+            for session_id in expired {
+                let file_path = base_dir.join(format!("{}.jsonl", session_id));
+
+                // Writes to the session wait and no chunk can start
+                // accumulating until the file is gone, so nothing recreates
+                // it next to the archive
+                let moved = index.with_session_writes(&session_id, || -> Result<Option<u64>> {
+                    let accumulators = accumulators.lock().unwrap();
+                    if accumulators
+                        .get(&session_id)
+                        .is_some_and(ChunkAccumulator::has_pending)
+                    {
+                        return Ok(None);
+                    }
+
+                    let bytes = std::fs::metadata(&file_path)
+                        .map(|metadata| metadata.len())
+                        .unwrap_or_default();
+                    if config.action == RetentionAction::Archive {
+                        archive_file(&file_path, &archive_dir).context("Failed to archive")?;
+                    }
+                    std::fs::remove_file(&file_path)
+                        .with_context(|| format!("Failed to delete {}", file_path.display()))?;
+                    write_provisional(&file_path, &[])?;
+                    if config.action == RetentionAction::Archive {
+                        index.archive_session(&session_id);
+                    }
+                    Ok(Some(bytes))
+                });
+                let bytes = match moved {
+                    Ok(Some(bytes)) => bytes,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        log::error!("Failed to expire session {}: {:#}", session_id, e);
+                        continue;
+                    }
+                };
+                if config.action == RetentionAction::Delete {
+                    index.remove_session(&session_id);
+                }
+
+                report.freed_bytes += bytes;
+                match config.action {
+                    RetentionAction::Archive => report.archived.push(session_id),
+                    RetentionAction::Delete => report.deleted.push(session_id),
+                }
+            }
+            Ok::<_, anyhow::Error>(report)
+        })
+        .await?;
+
+        for session_id in report.archived.iter().chain(&report.deleted) {
+            self.invalidate_offsets(session_id);
+        }
+        for session_id in &report.deleted {
+            if let Err(e) = self.stats.remove(session_id) {
+                log::warn!("Failed to remove stats of session {}: {}", session_id, e);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Whether the session's history was moved into the archive
+    pub fn is_archived(&self, session_id: &str) -> bool {
+        !self.session_file_path(session_id).exists()
+            && self
+                .archive_dir()
+                .join(format!("{}.jsonl.gz", session_id))
+                .exists()
+    }
+
+    /// Move an archived session back into the sessions directory
+    ///
+    /// Returns false when the session is not archived.
+    pub async fn restore_archived_session(&self, session_id: &str) -> Result<bool> {
+        if !self.is_archived(session_id) {
+            return Ok(false);
+        }
+
+        let file_path = self.session_file_path(session_id);
+        let archive_path = self.archive_dir().join(format!("{}.jsonl.gz", session_id));
+        let (messages, file_len) = smol::unblock(move || {
+            unarchive_file(&archive_path, &file_path)?;
+            std::fs::remove_file(&archive_path)
+                .with_context(|| format!("Failed to delete {}", archive_path.display()))?;
+            let file_len = std::fs::metadata(&file_path)?.len();
+            Ok::<_, anyhow::Error>((read_session_file(&file_path)?, file_len))
+        })
+        .await?;
+
+        self.index.index_session(session_id, &messages, file_len);
+        log::info!("Restored archived session {}", session_id);
+        Ok(true)
+    }
+
+    /// Disk usage of the session files per workspace, and of the archive
+    pub async fn storage_usage(&self) -> Result<StorageUsage> {
+        let base_dir = self.base_dir.clone();
+        let archive_dir = self.archive_dir();
+        let index = self.index.clone();
+
+        smol::unblock(move || {
+            let mut usage = StorageUsage::default();
+            for file in Self::session_files_sync(&base_dir)? {
+                let workspace = index
+                    .session(&file.session_id)
+                    .and_then(|metadata| metadata.workspace);
+                match usage
+                    .workspaces
+                    .iter_mut()
+                    .find(|storage| storage.workspace == workspace)
+                {
+                    Some(storage) => {
+                        storage.sessions += 1;
+                        storage.bytes += file.bytes;
+                    }
+                    None => usage.workspaces.push(WorkspaceStorage {
+                        workspace,
+                        sessions: 1,
+                        bytes: file.bytes,
+                    }),
+                }
+            }
+            usage.workspaces.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+            if archive_dir.exists() {
+                for entry in
+                    std::fs::read_dir(&archive_dir).context("Failed to read archive directory")?
+                {
+                    let metadata = entry?.metadata()?;
+                    usage.archived_sessions += 1;
+                    usage.archived_bytes += metadata.len();
+                }
+            }
+            Ok(usage)
+        })
+        .await
+    }
+
+    /// List all available sessions
+    pub async fn list_sessions(&self) -> Result<Vec<String>> {
+        let base_dir = self.base_dir.clone();
+
+        smol::unblock(move || {
             if !base_dir.exists() {
                 return Ok(Vec::new());
             }
@@ -980,6 +2409,30 @@ mod tests {
         assert!(!file_path.exists());
     }
 
+    #[tokio::test]
+    async fn test_delete_archived_session() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+
+        service
+            .save_update("archived", text_update("user", "old"))
+            .await
+            .unwrap();
+        service.flush_session("archived").await.unwrap();
+        let file_path = temp_dir.path().join("archived.jsonl");
+        let archive_path =
+            archive_file(&file_path, &temp_dir.path().join(ARCHIVE_DIR_NAME)).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        service.index().archive_session("archived");
+        assert!(service.is_archived("archived"));
+
+        service.delete_session("archived").await.unwrap();
+
+        assert!(!archive_path.exists());
+        assert!(!service.is_archived("archived"));
+        assert!(service.index().session("archived").is_none());
+    }
+
     #[tokio::test]
     async fn test_list_sessions() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -1003,6 +2456,83 @@ mod tests {
         assert!(sessions.contains(&"session-c".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_index_follows_session_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+
+        for text in ["first question", "second question"] {
+            let chunk = ContentChunk::new(ContentBlock::Text(TextContent::new(text.to_string())));
+            service
+                .save_update("indexed", SessionUpdate::UserMessageChunk(chunk))
+                .await
+                .unwrap();
+            service
+                .save_update(
+                    "indexed",
+                    SessionUpdate::AgentMessageChunk(ContentChunk::new(ContentBlock::Text(
+                        TextContent::new("answer".to_string()),
+                    ))),
+                )
+                .await
+                .unwrap();
+        }
+        service.flush_session("indexed").await.unwrap();
+
+        let hits = service.index().search("second", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message_index, 2);
+
+        // A fresh service picks the session up from its file
+        let reopened = PersistenceService::new(temp_dir.path().to_path_buf());
+        assert_eq!(reopened.rebuild_index().await.unwrap(), 1);
+        assert_eq!(reopened.index().search("answer", 10).len(), 2);
+
+        service.delete_session("indexed").await.unwrap();
+        assert!(service.index().search("question", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_imported_sessions_stay_apart() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().join("sessions"));
+
+        let chunk = ContentChunk::new(ContentBlock::Text(TextContent::new("hi".to_string())));
+        service
+            .save_update("original", SessionUpdate::UserMessageChunk(chunk))
+            .await
+            .unwrap();
+        service.flush_session("original").await.unwrap();
+
+        let source = temp_dir.path().join("sessions").join("original.jsonl");
+        let imported = service.import_session(&source).await.unwrap();
+        assert_eq!(imported.title, "original");
+        assert_eq!(
+            service
+                .load_messages(&imported.session_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            service.imported_sessions().await.unwrap(),
+            vec![imported.clone()]
+        );
+        assert_eq!(
+            service.list_sessions().await.unwrap(),
+            vec!["original".to_string()]
+        );
+
+        service
+            .remove_imported_session(&imported.session_id)
+            .await
+            .unwrap();
+        assert!(service.imported_sessions().await.unwrap().is_empty());
+        assert!(service.remove_imported_session("original").await.is_err());
+        assert_eq!(service.load_messages("original").await.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_list_sessions_empty_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -1091,4 +2621,498 @@ mod tests {
             }
         }
     }
+
+    // ============== Replayed history reconciliation tests ==============
+
+    fn text_update(kind: &str, text: &str) -> SessionUpdate {
+        let chunk = ContentChunk::new(ContentBlock::Text(TextContent::new(text.to_string())));
+        match kind {
+            "user" => SessionUpdate::UserMessageChunk(chunk),
+            _ => SessionUpdate::AgentMessageChunk(chunk),
+        }
+    }
+
+    fn message_text(message: &PersistedMessage) -> String {
+        match &message.update {
+            SessionUpdate::UserMessageChunk(chunk) | SessionUpdate::AgentMessageChunk(chunk) => {
+                extract_text_from_content_chunk(chunk)
+            }
+            _ => String::new(),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_history_skips_already_persisted_messages() {
+        let local = vec![
+            PersistedMessage::with_timestamp("t1".to_string(), text_update("user", "Hi")),
+            PersistedMessage::with_timestamp("t2".to_string(), text_update("agent", "Hello there")),
+        ];
+        // The agent streams its reply in several chunks
+        let replayed = vec![
+            text_update("user", "Hi"),
+            text_update("agent", "Hello "),
+            text_update("agent", "there"),
+        ];
+
+        let (merged, added) = reconcile_history(local, replayed);
+
+        assert_eq!(added, 0);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1].timestamp, "t2");
+    }
+
+    #[test]
+    fn test_reconcile_history_inserts_missing_messages_in_order() {
+        let local = vec![
+            PersistedMessage::with_timestamp("t1".to_string(), text_update("user", "First")),
+            PersistedMessage::with_timestamp("t3".to_string(), text_update("user", "Second")),
+        ];
+        let replayed = vec![
+            text_update("user", "First"),
+            text_update("agent", "Answer one"),
+            text_update("user", "Second"),
+            text_update("agent", "Answer two"),
+        ];
+
+        let (merged, added) = reconcile_history(local, replayed);
+
+        assert_eq!(added, 2);
+        let texts: Vec<String> = merged.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["First", "Answer one", "Second", "Answer two"]);
+        assert_eq!(merged[1].timestamp, "t1");
+        assert_eq!(merged[3].timestamp, "t3");
+    }
+
+    #[tokio::test]
+    async fn test_merge_replayed_history_rewrites_file_once() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        let session_id = "replayed-session";
+
+        service
+            .save_update(session_id, text_update("user", "Question"))
+            .await
+            .unwrap();
+        service.flush_session(session_id).await.unwrap();
+
+        let replayed = vec![
+            text_update("user", "Question"),
+            text_update("agent", "Reply"),
+        ];
+        let added = service
+            .merge_replayed_history(session_id, replayed.clone())
+            .await
+            .unwrap();
+        assert_eq!(added, 1);
+
+        // Replaying the same history again must not duplicate anything
+        let added = service
+            .merge_replayed_history(session_id, replayed)
+            .await
+            .unwrap();
+        assert_eq!(added, 0);
+
+        let messages = service.load_messages(session_id).await.unwrap();
+        let texts: Vec<String> = messages.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["Question", "Reply"]);
+        assert!(!temp_dir.path().join("replayed-session.jsonl.tmp").exists());
+    }
+
+    #[test]
+    fn test_reconcile_history_matches_repeated_messages_by_position() {
+        let local = vec![
+            PersistedMessage::with_timestamp("t1".to_string(), text_update("user", "Continue")),
+            PersistedMessage::with_timestamp("t2".to_string(), text_update("agent", "Done")),
+        ];
+        let replayed = vec![
+            text_update("user", "Continue"),
+            text_update("agent", "Done"),
+            text_update("user", "Continue"),
+            text_update("agent", "Done"),
+        ];
+
+        let (merged, added) = reconcile_history(local, replayed.clone());
+
+        assert_eq!(added, 2);
+        let texts: Vec<String> = merged.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["Continue", "Done", "Continue", "Done"]);
+
+        // The merged history matches the same replay completely
+        let (merged, added) = reconcile_history(merged, replayed);
+        assert_eq!(added, 0);
+        assert_eq!(merged.len(), 4);
+    }
+
+    #[test]
+    fn test_replayed_fork_context_is_stripped() {
+        let context = fork_context_prompt("parent", &[]).unwrap();
+        let replayed = vec![
+            text_update("user", &context),
+            text_update("user", "rest of the transcript"),
+            text_update("agent", "Ready"),
+            text_update("user", "Next step"),
+            text_update("agent", "Done"),
+        ];
+
+        let (merged, added) = reconcile_history(Vec::new(), strip_fork_context(replayed));
+
+        assert_eq!(added, 2);
+        let texts: Vec<String> = merged.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["Next step", "Done"]);
+    }
+
+    #[test]
+    fn test_fork_context_leaves_out_the_start_of_long_transcripts() {
+        let long = "x".repeat(FORK_CONTEXT_MAX_LEN / 4);
+        let messages: Vec<PersistedMessage> = (0..8)
+            .map(|i| {
+                let kind = if i % 2 == 0 { "user" } else { "agent" };
+                PersistedMessage::new(text_update(kind, &format!("Message {} {}", i, long)))
+            })
+            .collect();
+
+        let prompt = fork_context_prompt("parent", &messages).unwrap();
+
+        assert!(prompt.len() < FORK_CONTEXT_MAX_LEN + 1_000);
+        assert!(prompt.contains("earliest entries of the conversation were left out"));
+        assert!(prompt.contains("Message 7"));
+        assert!(!prompt.contains("Message 0"));
+    }
+
+    #[tokio::test]
+    async fn test_fork_history_copies_up_to_the_message() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+
+        let mut lines = String::new();
+        for update in [
+            text_update("user", "First"),
+            text_update("user", "with an attachment"),
+            text_update("agent", "Answer one"),
+            text_update("user", "Second"),
+            text_update("agent", "Answer two"),
+        ] {
+            lines.push_str(&serde_json::to_string(&PersistedMessage::new(update)).unwrap());
+            lines.push('\n');
+        }
+        std::fs::write(temp_dir.path().join("parent.jsonl"), lines).unwrap();
+
+        let messages = service.load_messages("parent").await.unwrap();
+        assert_eq!(fork_point(&messages, 0, 1), Some(2));
+        assert_eq!(fork_point(&messages, 0, 3), Some(4));
+        // Counting from a later page start
+        assert_eq!(fork_point(&messages, 3, 2), Some(5));
+        assert_eq!(fork_point(&messages, 0, 5), None);
+        assert_eq!(conversation_message_ends(&messages, 0).count(), 4);
+        assert!(
+            !service
+                .forks_from_last_message("parent", 0, 3)
+                .await
+                .unwrap()
+        );
+        assert!(
+            service
+                .forks_from_last_message("parent", 0, 4)
+                .await
+                .unwrap()
+        );
+
+        service
+            .save_update("fork", text_update("agent", "Ready"))
+            .await
+            .unwrap();
+        let copied = service.fork_history("parent", "fork", 0, 2).await.unwrap();
+        assert_eq!(copied.len(), 3);
+
+        let texts: Vec<String> = service
+            .load_messages("fork")
+            .await
+            .unwrap()
+            .iter()
+            .map(message_text)
+            .collect();
+        assert_eq!(
+            texts,
+            vec!["First", "with an attachment", "Answer one", "Ready"]
+        );
+        assert!(service.fork_history("parent", "other", 0, 9).await.is_err());
+        assert_eq!(service.load_messages("parent").await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_load_report_lists_corrupt_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let line =
+            serde_json::to_string(&PersistedMessage::new(text_update("user", "valid"))).unwrap();
+        std::fs::write(
+            temp_dir.path().join("report.jsonl"),
+            format!("{}\n\n{{broken\n{}\n{{\"timestamp\":", line, line),
+        )
+        .unwrap();
+
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        let loaded = service.load_messages_with_report("report").await.unwrap();
+
+        assert_eq!(loaded.messages.len(), 2);
+        assert_eq!(loaded.corrupt_lines, vec![3, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_append_after_partial_line_starts_new_line() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("partial.jsonl");
+        std::fs::write(&file_path, "{\"timestamp\":\"t1\",\"upd").unwrap();
+
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        service
+            .save_update("partial", text_update("user", "after crash"))
+            .await
+            .unwrap();
+        service.flush_session("partial").await.unwrap();
+
+        let loaded = service.load_messages_with_report("partial").await.unwrap();
+        assert_eq!(loaded.corrupt_lines, vec![1]);
+        assert_eq!(loaded.messages.len(), 1);
+        assert_eq!(message_text(&loaded.messages[0]), "after crash");
+    }
+
+    #[tokio::test]
+    async fn test_provisional_records_recovered_after_crash() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let session_id = "crashed";
+        let file_path = temp_dir.path().join(format!("{}.jsonl", session_id));
+
+        {
+            let service = PersistenceService::new(temp_dir.path().to_path_buf());
+            service
+                .save_update(session_id, text_update("user", "Question"))
+                .await
+                .unwrap();
+            for text in ["Half ", "an answer"] {
+                service
+                    .save_update(session_id, text_update("agent", text))
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(service.flush_provisional().await.unwrap(), 1);
+            // Nothing changed since the last write
+            assert_eq!(service.flush_provisional().await.unwrap(), 0);
+            // Dropped without flush_session, as in a crash
+        }
+        assert!(provisional_path(&file_path).exists());
+
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        let recovered = service.recover_sessions().await.unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].session_id, session_id);
+        assert_eq!(recovered[0].restored_messages, 1);
+        assert!(!provisional_path(&file_path).exists());
+
+        let messages = service.load_messages(session_id).await.unwrap();
+        let texts: Vec<String> = messages.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["Question", "Half an answer"]);
+
+        // A second start has nothing left to recover
+        assert!(service.recover_sessions().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recovery_keeps_provisional_records_repeating_earlier_text() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let session_id = "repeated";
+
+        {
+            let service = PersistenceService::new(temp_dir.path().to_path_buf());
+            for (kind, text) in [("user", "Go on"), ("agent", "Okay"), ("user", "Go on")] {
+                service
+                    .save_update(session_id, text_update(kind, text))
+                    .await
+                    .unwrap();
+            }
+            service.flush_session(session_id).await.unwrap();
+            service
+                .save_update(session_id, text_update("agent", "Okay"))
+                .await
+                .unwrap();
+            assert_eq!(service.flush_provisional().await.unwrap(), 1);
+        }
+
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        let recovered = service.recover_sessions().await.unwrap();
+        assert_eq!(recovered[0].restored_messages, 1);
+
+        let texts: Vec<String> = service
+            .load_messages(session_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(message_text)
+            .collect();
+        assert_eq!(texts, vec!["Go on", "Okay", "Go on", "Okay"]);
+    }
+
+    #[tokio::test]
+    async fn test_flush_session_supersedes_provisional_records() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        let file_path = temp_dir.path().join("streaming.jsonl");
+
+        service
+            .save_update("streaming", text_update("agent", "partial"))
+            .await
+            .unwrap();
+        service.flush_provisional().await.unwrap();
+        assert!(provisional_path(&file_path).exists());
+
+        service.flush_session("streaming").await.unwrap();
+        assert!(!provisional_path(&file_path).exists());
+        assert_eq!(service.load_messages("streaming").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_history_pages_walk_backwards() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        let session_id = "long";
+
+        for ix in 0..5 {
+            service
+                .save_update(session_id, text_update("user", &format!("q{}", ix)))
+                .await
+                .unwrap();
+            service
+                .save_update(session_id, text_update("agent", &format!("a{}", ix)))
+                .await
+                .unwrap();
+        }
+        service.flush_session(session_id).await.unwrap();
+
+        let last = service
+            .load_history_page(session_id, None, 4)
+            .await
+            .unwrap();
+        assert_eq!((last.start, last.total), (6, 10));
+        let texts: Vec<String> = last.messages.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["q3", "a3", "q4", "a4"]);
+
+        let first = service
+            .load_history_page(session_id, Some(2), 4)
+            .await
+            .unwrap();
+        assert!(!first.has_older());
+        let texts: Vec<String> = first.messages.iter().map(message_text).collect();
+        assert_eq!(texts, vec!["q0", "a0"]);
+
+        // Appended messages extend the offsets, a corrupt line is left out
+        let file_path = temp_dir.path().join(format!("{}.jsonl", session_id));
+        let mut contents = std::fs::read_to_string(&file_path).unwrap();
+        contents.push_str("{broken\n");
+        std::fs::write(&file_path, contents).unwrap();
+        service
+            .save_update(session_id, text_update("user", "q5"))
+            .await
+            .unwrap();
+        service.flush_session(session_id).await.unwrap();
+
+        let last = service
+            .load_history_page(session_id, None, 1)
+            .await
+            .unwrap();
+        assert_eq!((last.start, last.total, last.corrupt_lines), (10, 11, 1));
+        assert_eq!(message_text(&last.messages[0]), "q5");
+    }
+
+    #[tokio::test]
+    async fn test_retention_archives_and_restores_sessions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+
+        for session_id in ["kept", "expired"] {
+            service
+                .save_update(session_id, text_update("user", session_id))
+                .await
+                .unwrap();
+            service.flush_session(session_id).await.unwrap();
+        }
+        // Make "expired" the older one
+        let old = std::time::SystemTime::now() - Duration::from_secs(3 * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(temp_dir.path().join("expired.jsonl"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let config = RetentionConfig {
+            max_age_days: Some(2),
+            ..Default::default()
+        };
+        // Sessions open in a panel or with a live agent session are kept
+        let open = service.open_session("expired");
+        let report = service.apply_retention(&config, &[]).await.unwrap();
+        assert!(report.is_empty());
+        drop(open);
+        let report = service
+            .apply_retention(&config, &["expired".to_string()])
+            .await
+            .unwrap();
+        assert!(report.is_empty());
+
+        service
+            .index()
+            .set_details("expired", Some("Old task".to_string()), None);
+        let report = service.apply_retention(&config, &[]).await.unwrap();
+        assert_eq!(report.archived, vec!["expired"]);
+        assert!(service.is_archived("expired"));
+        assert!(!service.is_archived("kept"));
+        // Archived sessions are still listed, without their text
+        let metadata = service.index().session("expired").unwrap();
+        assert!(metadata.archived);
+        assert_eq!(metadata.title.as_deref(), Some("Old task"));
+        assert_eq!(service.index().sessions().len(), 2);
+        assert!(service.index().search("expired", 10).is_empty());
+
+        let usage = service.storage_usage().await.unwrap();
+        assert_eq!(usage.archived_sessions, 1);
+        assert_eq!(
+            usage.workspaces.iter().map(|w| w.sessions).sum::<usize>(),
+            1
+        );
+
+        assert!(service.restore_archived_session("expired").await.unwrap());
+        let messages = service.load_messages("expired").await.unwrap();
+        assert_eq!(message_text(&messages[0]), "expired");
+        assert!(!service.restore_archived_session("expired").await.unwrap());
+        assert!(!service.index().session("expired").unwrap().archived);
+    }
+
+    #[tokio::test]
+    async fn test_write_to_archived_session_restores_it() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = PersistenceService::new(temp_dir.path().to_path_buf());
+        service
+            .save_update("late", text_update("user", "before"))
+            .await
+            .unwrap();
+        service.flush_session("late").await.unwrap();
+
+        let config = RetentionConfig {
+            max_total_mb: Some(0),
+            ..Default::default()
+        };
+        let report = service.apply_retention(&config, &[]).await.unwrap();
+        assert_eq!(report.archived, vec!["late"]);
+
+        // A message that was already on its way continues the history
+        service
+            .save_update("late", text_update("user", "after"))
+            .await
+            .unwrap();
+        service.flush_session("late").await.unwrap();
+        assert!(!service.is_archived("late"));
+        let messages = service.load_messages("late").await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(message_text(&messages[0]), "before");
+        assert_eq!(service.index().search("after", 10).len(), 1);
+    }
 }
@@ -0,0 +1,222 @@
+//! Prompt Watchdog - Ends prompt turns an agent never finishes
+//!
+//! An agent that never answers `session/prompt` would keep its session in
+//! progress forever. The watchdog fires once a turn runs longer than the
+//! agent's turn timeout, or goes longer than its inactivity timeout without a
+//! session update, so the turn can be cancelled.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use agent_client_protocol::SessionUpdate;
+
+use crate::core::config::PromptTimeoutConfig;
+
+/// Limit a turn exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogTrigger {
+    /// The whole turn took longer than this
+    Turn(Duration),
+    /// No session update arrived for this long
+    Inactivity(Duration),
+}
+
+impl std::fmt::Display for WatchdogTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchdogTrigger::Turn(limit) => {
+                write!(f, "turn did not finish within {}s", limit.as_secs())
+            }
+            WatchdogTrigger::Inactivity(limit) => {
+                write!(f, "no session update for {}s", limit.as_secs())
+            }
+        }
+    }
+}
+
+/// Session updates seen while a turn runs, shared with the session bus callback
+#[derive(Debug, Clone)]
+pub struct TurnActivity {
+    started: Instant,
+    state: Arc<Mutex<ActivityState>>,
+}
+
+#[derive(Debug)]
+struct ActivityState {
+    /// When the agent last counted as active, the turn start at first
+    last_activity: Instant,
+    last_update: Option<(Instant, &'static str)>,
+}
+
+impl TurnActivity {
+    /// Begin tracking a turn that starts now
+    pub fn start() -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            state: Arc::new(Mutex::new(ActivityState {
+                last_activity: started,
+                last_update: None,
+            })),
+        }
+    }
+
+    /// Note a session update received now
+    pub fn record(&self, update: &SessionUpdate) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.last_activity = now;
+        state.last_update = Some((now, update_kind(update)));
+    }
+
+    /// Count the turn as active now without an update, e.g. while the
+    /// agent waits for the user to answer a permission request
+    pub fn keep_alive(&self) {
+        self.state.lock().unwrap().last_activity = Instant::now();
+    }
+
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
+    /// Time and ACP kind of the latest update, `None` before the first
+    pub fn last_update(&self) -> Option<(Instant, &'static str)> {
+        self.state.lock().unwrap().last_update
+    }
+
+    fn last_activity(&self) -> Instant {
+        self.state.lock().unwrap().last_activity
+    }
+}
+
+/// Limit exceeded at `now`, if any
+pub fn check(
+    timeouts: &PromptTimeoutConfig,
+    started: Instant,
+    last_activity: Instant,
+    now: Instant,
+) -> Option<WatchdogTrigger> {
+    if let Some(limit) = timeouts.turn()
+        && now.saturating_duration_since(started) >= limit
+    {
+        return Some(WatchdogTrigger::Turn(limit));
+    }
+    if let Some(limit) = timeouts.inactivity()
+        && now.saturating_duration_since(last_activity) >= limit
+    {
+        return Some(WatchdogTrigger::Inactivity(limit));
+    }
+    None
+}
+
+/// Earliest time a limit can be exceeded, `None` without limits
+fn next_deadline(
+    timeouts: &PromptTimeoutConfig,
+    started: Instant,
+    last_activity: Instant,
+) -> Option<Instant> {
+    let turn = timeouts.turn().map(|limit| started + limit);
+    let inactivity = timeouts.inactivity().map(|limit| last_activity + limit);
+    match (turn, inactivity) {
+        (Some(turn), Some(inactivity)) => Some(turn.min(inactivity)),
+        (turn, inactivity) => turn.or(inactivity),
+    }
+}
+
+/// Resolve once the turn exceeds one of `timeouts`; never when none is set
+pub async fn watch(timeouts: PromptTimeoutConfig, activity: TurnActivity) -> WatchdogTrigger {
+    loop {
+        let last_activity = activity.last_activity();
+        if let Some(trigger) = check(&timeouts, activity.started(), last_activity, Instant::now()) {
+            return trigger;
+        }
+        match next_deadline(&timeouts, activity.started(), last_activity) {
+            Some(deadline) => {
+                smol::Timer::at(deadline).await;
+            }
+            None => std::future::pending::<()>().await,
+        }
+    }
+}
+
+/// ACP name of a session update, for diagnostics
+fn update_kind(update: &SessionUpdate) -> &'static str {
+    match update {
+        SessionUpdate::UserMessageChunk(_) => "user_message_chunk",
+        SessionUpdate::AgentMessageChunk(_) => "agent_message_chunk",
+        SessionUpdate::AgentThoughtChunk(_) => "agent_thought_chunk",
+        SessionUpdate::ToolCall(_) => "tool_call",
+        SessionUpdate::ToolCallUpdate(_) => "tool_call_update",
+        SessionUpdate::Plan(_) => "plan",
+        SessionUpdate::AvailableCommandsUpdate(_) => "available_commands_update",
+        SessionUpdate::CurrentModeUpdate(_) => "current_mode_update",
+        _ => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_check_turn_and_inactivity_limits() {
+        let timeouts = PromptTimeoutConfig {
+            turn_secs: Some(600),
+            inactivity_secs: Some(60),
+        };
+        let started = Instant::now();
+
+        assert_eq!(
+            check(&timeouts, started, started, started + 59 * SECOND),
+            None
+        );
+        assert_eq!(
+            check(&timeouts, started, started, started + 60 * SECOND),
+            Some(WatchdogTrigger::Inactivity(60 * SECOND))
+        );
+        // Updates keep the turn alive until the turn limit
+        let last = started + 590 * SECOND;
+        assert_eq!(
+            check(&timeouts, started, last, started + 599 * SECOND),
+            None
+        );
+        assert_eq!(
+            check(&timeouts, started, last, started + 600 * SECOND),
+            Some(WatchdogTrigger::Turn(600 * SECOND))
+        );
+
+        assert_eq!(
+            check(
+                &PromptTimeoutConfig::default(),
+                started,
+                started,
+                started + 3600 * SECOND
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_next_deadline_takes_earliest_limit() {
+        let timeouts = PromptTimeoutConfig {
+            turn_secs: Some(600),
+            inactivity_secs: Some(60),
+        };
+        let started = Instant::now();
+
+        assert_eq!(
+            next_deadline(&timeouts, started, started),
+            Some(started + 60 * SECOND)
+        );
+        assert_eq!(
+            next_deadline(&timeouts, started, started + 580 * SECOND),
+            Some(started + 600 * SECOND)
+        );
+        assert_eq!(
+            next_deadline(&PromptTimeoutConfig::default(), started, started),
+            None
+        );
+    }
+}
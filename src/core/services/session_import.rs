@@ -0,0 +1,334 @@
+//! Session Import - Reads exported bundles and raw session files for review
+//!
+//! Imported sessions live in their own directory next to the real session
+//! files and use IDs with the [`IMPORTED_SESSION_PREFIX`], so they are never
+//! indexed, resumed or mixed up with sessions of a running agent.
+
+use std::path::{Path, PathBuf};
+
+use agent_client_protocol::{self as acp, ContentBlock, ContentChunk, SessionUpdate, TextContent};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use super::persistence_service::PersistedMessage;
+use super::session_export::{ExportedEntry, ExportedSession, ExportedToolContent};
+
+/// Directory under the sessions directory holding imported sessions
+pub const IMPORTED_DIR_NAME: &str = "imported";
+
+/// Prefix of the IDs given to imported sessions
+pub const IMPORTED_SESSION_PREFIX: &str = "imported-";
+
+/// Whether the session was imported for review rather than created by an agent
+pub fn is_imported_session(session_id: &str) -> bool {
+    session_id.starts_with(IMPORTED_SESSION_PREFIX)
+}
+
+/// Metadata of an imported session, stored next to its messages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedSession {
+    pub session_id: String,
+    pub title: String,
+    pub agent_name: Option<String>,
+    /// File the session was imported from
+    pub source: PathBuf,
+    /// ISO 8601 time of the import
+    pub imported_at: String,
+    pub message_count: usize,
+}
+
+/// Parse an exported JSON bundle or a raw `.jsonl` session file
+///
+/// Returns the metadata for the new imported session and its messages.
+pub fn parse_session_file(
+    source: &Path,
+    contents: &str,
+) -> Result<(ImportedSession, Vec<PersistedMessage>)> {
+    let session_id = format!("{}{}", IMPORTED_SESSION_PREFIX, uuid::Uuid::new_v4());
+    let file_stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Imported session".to_string());
+
+    let (title, agent_name, messages) = match serde_json::from_str::<ExportedSession>(contents) {
+        Ok(bundle) => {
+            let title = bundle.title.clone().unwrap_or(file_stem);
+            let agent_name = bundle.agent.clone();
+            (title, agent_name, messages_from_bundle(bundle))
+        }
+        Err(_) => (file_stem, None, parse_jsonl(contents)?),
+    };
+    if messages.is_empty() {
+        anyhow::bail!("{} contains no conversation messages", source.display());
+    }
+
+    let imported = ImportedSession {
+        session_id,
+        title,
+        agent_name,
+        source: source.to_path_buf(),
+        imported_at: chrono::Utc::now().to_rfc3339(),
+        message_count: messages.len(),
+    };
+    Ok((imported, messages))
+}
+
+/// Messages of a raw session file; fails when no line is a persisted message
+fn parse_jsonl(contents: &str) -> Result<Vec<PersistedMessage>> {
+    let mut messages = Vec::new();
+    let mut first_error = None;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<PersistedMessage>(line) {
+            Ok(message) => messages.push(message),
+            Err(e) => {
+                log::warn!("Skipping unreadable line in imported session: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match (messages.is_empty(), first_error) {
+        (true, Some(e)) => Err(e).context("Not an exported session or a session file"),
+        _ => Ok(messages),
+    }
+}
+
+/// Turn a normalized export back into session updates
+fn messages_from_bundle(bundle: ExportedSession) -> Vec<PersistedMessage> {
+    bundle
+        .entries
+        .into_iter()
+        .filter_map(|entry| {
+            let text_chunk =
+                |text: String| ContentChunk::new(ContentBlock::Text(TextContent::new(text)));
+            let (timestamp, update) = match entry {
+                ExportedEntry::UserMessage { timestamp, text } => {
+                    (timestamp, SessionUpdate::UserMessageChunk(text_chunk(text)))
+                }
+                ExportedEntry::AgentMessage { timestamp, text } => (
+                    timestamp,
+                    SessionUpdate::AgentMessageChunk(text_chunk(text)),
+                ),
+                ExportedEntry::Thought { timestamp, text } => (
+                    timestamp,
+                    SessionUpdate::AgentThoughtChunk(text_chunk(text)),
+                ),
+                ExportedEntry::Plan { timestamp, entries } => {
+                    let entries = entries
+                        .into_iter()
+                        .map(|entry| {
+                            acp::PlanEntry::new(
+                                entry.content,
+                                from_label(&entry.priority)
+                                    .unwrap_or(acp::PlanEntryPriority::Medium),
+                                from_label(&entry.status).unwrap_or(acp::PlanEntryStatus::Pending),
+                            )
+                        })
+                        .collect();
+                    (timestamp, SessionUpdate::Plan(acp::Plan::new(entries)))
+                }
+                ExportedEntry::ToolCall(tool_call) => {
+                    let content: Vec<serde_json::Value> = tool_call
+                        .content
+                        .into_iter()
+                        .map(tool_content_json)
+                        .collect();
+                    let json = serde_json::json!({
+                        "toolCallId": &tool_call.tool_call_id,
+                        "title": &tool_call.title,
+                        "kind": &tool_call.kind,
+                        "status": &tool_call.status,
+                        "content": content,
+                        "locations": tool_call
+                            .locations
+                            .iter()
+                            .map(|path| serde_json::json!({ "path": path }))
+                            .collect::<Vec<_>>(),
+                    });
+                    match serde_json::from_value::<acp::ToolCall>(json) {
+                        Ok(parsed) => (tool_call.timestamp, SessionUpdate::ToolCall(parsed)),
+                        Err(e) => {
+                            log::warn!(
+                                "Skipping imported tool call {}: {}",
+                                tool_call.tool_call_id,
+                                e
+                            );
+                            return None;
+                        }
+                    }
+                }
+            };
+            Some(PersistedMessage::with_timestamp(timestamp, update))
+        })
+        .collect()
+}
+
+/// Wire format of a tool call content; terminals become their recorded output
+fn tool_content_json(content: ExportedToolContent) -> serde_json::Value {
+    let text_content = |text: String| {
+        serde_json::json!({
+            "type": "content",
+            "content": { "type": "text", "text": text },
+        })
+    };
+    match content {
+        ExportedToolContent::Text { text } => text_content(text),
+        ExportedToolContent::Diff { path, unified_diff } => {
+            let (old_text, new_text) = texts_from_unified_diff(&unified_diff);
+            serde_json::json!({
+                "type": "diff",
+                "path": path,
+                "oldText": old_text,
+                "newText": new_text,
+            })
+        }
+        ExportedToolContent::Terminal {
+            terminal_id,
+            command,
+            output,
+            exit_code,
+        } => {
+            let mut text = match command {
+                Some(command) => format!("$ {}", command),
+                None => format!("Terminal {}", terminal_id),
+            };
+            if let Some(output) = output {
+                text.push('\n');
+                text.push_str(&output);
+            }
+            if let Some(code) = exit_code {
+                text.push_str(&format!("\nExited with code {}", code));
+            }
+            text_content(text)
+        }
+    }
+}
+
+/// Old and new text covered by the hunks of a unified diff
+///
+/// Lines outside the hunks are not part of the diff, so both texts only hold
+/// the changed regions and their context. A `/dev/null` old side means the
+/// file was created.
+fn texts_from_unified_diff(diff: &str) -> (Option<String>, String) {
+    let mut old_text = String::new();
+    let mut new_text = String::new();
+    let mut created = false;
+    let mut in_hunk = false;
+
+    for line in diff.lines() {
+        if !in_hunk {
+            if line.starts_with("--- /dev/null") {
+                created = true;
+            }
+            if !line.starts_with("@@") {
+                continue;
+            }
+        }
+        in_hunk = true;
+        if line.starts_with("@@") {
+            continue;
+        }
+        match line.split_at_checked(1) {
+            Some(("+", rest)) => {
+                new_text.push_str(rest);
+                new_text.push('\n');
+            }
+            Some(("-", rest)) => {
+                old_text.push_str(rest);
+                old_text.push('\n');
+            }
+            Some(("\\", _)) => {}
+            Some((_, rest)) => {
+                old_text.push_str(rest);
+                old_text.push('\n');
+                new_text.push_str(rest);
+                new_text.push('\n');
+            }
+            None => {
+                old_text.push('\n');
+                new_text.push('\n');
+            }
+        }
+    }
+
+    ((!created).then_some(old_text), new_text)
+}
+
+fn from_label<T: DeserializeOwned>(label: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(label.to_string())).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::services::{SessionExportFormat, SessionExportOptions};
+
+    #[test]
+    fn test_bundle_round_trips_through_export() {
+        let mut tool_call = acp::ToolCall::new(
+            acp::ToolCallId::from("call-1".to_string()),
+            "Edit main.rs".to_string(),
+        );
+        tool_call.kind = acp::ToolKind::Edit;
+        tool_call.status = acp::ToolCallStatus::Completed;
+        tool_call.content = vec![acp::ToolCallContent::Diff(
+            acp::Diff::new(PathBuf::from("src/main.rs"), "fn main() {}\n".to_string())
+                .old_text(Some("fn old() {}\n".to_string())),
+        )];
+        let messages = vec![
+            PersistedMessage::with_timestamp(
+                "2025-01-01T00:00:00+00:00".to_string(),
+                SessionUpdate::UserMessageChunk(ContentChunk::new(ContentBlock::Text(
+                    TextContent::new("Rename main".to_string()),
+                ))),
+            ),
+            PersistedMessage::with_timestamp(
+                "2025-01-01T00:00:01+00:00".to_string(),
+                SessionUpdate::ToolCall(tool_call),
+            ),
+        ];
+        let json = ExportedSession::build(
+            "s1",
+            None,
+            &messages,
+            &SessionExportOptions::default(),
+            None,
+        )
+        .render(SessionExportFormat::Json)
+        .unwrap();
+
+        let (imported, parsed) = parse_session_file(Path::new("/tmp/review.json"), &json).unwrap();
+
+        assert!(is_imported_session(&imported.session_id));
+        assert_eq!(imported.title, "review");
+        assert_eq!(parsed.len(), 2);
+        let SessionUpdate::ToolCall(tool_call) = &parsed[1].update else {
+            panic!("expected a tool call");
+        };
+        assert_eq!(tool_call.status, acp::ToolCallStatus::Completed);
+        let acp::ToolCallContent::Diff(diff) = &tool_call.content[0] else {
+            panic!("expected a diff");
+        };
+        assert_eq!(diff.old_text.as_deref(), Some("fn old() {}\n"));
+        assert_eq!(diff.new_text, "fn main() {}\n");
+    }
+
+    #[test]
+    fn test_raw_session_file_and_garbage() {
+        let line = serde_json::to_string(&PersistedMessage::with_timestamp(
+            "2025-01-01T00:00:00+00:00".to_string(),
+            SessionUpdate::AgentMessageChunk(ContentChunk::new(ContentBlock::Text(
+                TextContent::new("Hello".to_string()),
+            ))),
+        ))
+        .unwrap();
+        let contents = format!("{}\n\n{}\n", line, line);
+
+        let (imported, parsed) =
+            parse_session_file(Path::new("/tmp/abc.jsonl"), &contents).unwrap();
+        assert_eq!(imported.message_count, 2);
+        assert_eq!(parsed.len(), 2);
+
+        assert!(parse_session_file(Path::new("/tmp/notes.txt"), "hello world").is_err());
+        assert!(parse_session_file(Path::new("/tmp/empty.jsonl"), "").is_err());
+    }
+}
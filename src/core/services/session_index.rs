@@ -0,0 +1,954 @@
+//! Session Index - Metadata and full-text search over persisted sessions
+//!
+//! The JSONL session files stay the source of truth for the conversation.
+//! This index lives in `index.db` next to them: a SQLite database holding
+//! what the files do not record (title, agent, workspace) and an FTS5 index
+//! over the text of user messages, agent messages, thoughts and tool call
+//! titles. Each session remembers the length of the file it was indexed
+//! from, so startup only re-reads files that changed while the app was
+//! closed, and `PersistenceService` indexes its appends as it writes them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use agent_client_protocol::{ContentBlock, ContentChunk, SessionUpdate};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction, params};
+use serde::{Deserialize, Serialize};
+
+use super::persistence_service::PersistedMessage;
+
+/// Database next to the session files holding the index
+pub const INDEX_FILE_NAME: &str = "index.db";
+
+/// Metadata file of earlier versions, imported into the database once
+const LEGACY_INDEX_FILE_NAME: &str = "index.json";
+
+/// Characters of context kept on each side of a match in a snippet
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Characters of the first user message used as a fallback title
+const PREVIEW_LENGTH: usize = 80;
+
+/// `file_len` is the length of the session file the texts were read from,
+/// NULL while the session has no indexed file. Archived sessions keep their
+/// metadata without any text.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        session_id TEXT PRIMARY KEY,
+        title TEXT,
+        agent_name TEXT,
+        workspace TEXT,
+        created_at TEXT,
+        updated_at TEXT,
+        message_count INTEGER NOT NULL DEFAULT 0,
+        preview TEXT,
+        file_len INTEGER,
+        archived INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS texts (
+        id INTEGER PRIMARY KEY,
+        session_id TEXT NOT NULL,
+        message_index INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        text TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS texts_session ON texts (session_id);
+    CREATE VIRTUAL TABLE IF NOT EXISTS texts_fts
+        USING fts5(tokens, content = '', contentless_delete = 1);
+";
+
+/// Columns read by [`metadata_from_row`]
+const METADATA_COLUMNS: &str = "s.session_id, s.title, s.agent_name, s.workspace, \
+     s.created_at, s.updated_at, s.message_count, s.preview, s.archived";
+
+/// Kind of conversation entry a piece of indexed text comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexedTextKind {
+    UserMessage,
+    AgentMessage,
+    AgentThought,
+    ToolCall,
+}
+
+impl IndexedTextKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IndexedTextKind::UserMessage => "user",
+            IndexedTextKind::AgentMessage => "agent",
+            IndexedTextKind::AgentThought => "thought",
+            IndexedTextKind::ToolCall => "tool",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "user" => Some(IndexedTextKind::UserMessage),
+            "agent" => Some(IndexedTextKind::AgentMessage),
+            "thought" => Some(IndexedTextKind::AgentThought),
+            "tool" => Some(IndexedTextKind::ToolCall),
+            _ => None,
+        }
+    }
+}
+
+/// Metadata of one persisted session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionMetadata {
+    pub session_id: String,
+    /// Name of the task the session belongs to
+    pub title: Option<String>,
+    pub agent_name: Option<String>,
+    pub workspace: Option<PathBuf>,
+    /// Timestamp of the first message, ISO 8601
+    pub created_at: Option<String>,
+    /// Timestamp of the last message, ISO 8601
+    pub updated_at: Option<String>,
+    /// Messages in the session file
+    pub message_count: usize,
+    /// Start of the first user message
+    pub preview: Option<String>,
+    /// History moved into the archive by the retention policy
+    pub archived: bool,
+}
+
+impl SessionMetadata {
+    /// Task name, else the start of the first user message, else the ID
+    pub fn display_title(&self) -> String {
+        self.title
+            .clone()
+            .or_else(|| self.preview.clone())
+            .unwrap_or_else(|| self.session_id.clone())
+    }
+}
+
+/// A message matching a search
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub session: SessionMetadata,
+    /// Position of the message in the session file
+    pub message_index: usize,
+    pub kind: IndexedTextKind,
+    pub timestamp: String,
+    /// Text around the first match
+    pub snippet: String,
+}
+
+/// Entry of the legacy `index.json`
+#[derive(Debug, Clone, Default, Deserialize)]
+struct StoredMetadata {
+    session_id: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    agent_name: Option<String>,
+    #[serde(default)]
+    workspace: Option<PathBuf>,
+}
+
+/// Searchable index of persisted sessions, shared by clones
+#[derive(Clone)]
+pub struct SessionIndex {
+    conn: Arc<Mutex<Connection>>,
+    /// Per-session guards keeping a session's file writes and their indexing
+    /// in order without blocking the other sessions
+    writers: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+}
+
+impl Default for SessionIndex {
+    /// Index kept in memory only
+    fn default() -> Self {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory database");
+        Self::with_connection(conn).expect("Failed to create session index schema")
+    }
+}
+
+impl SessionIndex {
+    /// Open the index of the session files in `base_dir`, keeping it in
+    /// memory if the database cannot be opened
+    pub fn new(base_dir: &Path) -> Self {
+        let path = base_dir.join(INDEX_FILE_NAME);
+        match Self::open(&path) {
+            Ok(index) => {
+                index.import_legacy(&base_dir.join(LEGACY_INDEX_FILE_NAME));
+                index
+            }
+            Err(e) => {
+                log::error!("Failed to open session index {}: {:#}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create sessions directory")?;
+        }
+        let conn = Connection::open(path).context("Failed to open database")?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to enable WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .context("Failed to set synchronous mode")?;
+        conn.busy_timeout(Duration::from_secs(5))
+            .context("Failed to set busy timeout")?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .context("Failed to create session index schema")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            writers: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Copy the metadata of a legacy `index.json` into the database and
+    /// delete the file
+    fn import_legacy(&self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+        let imported = read_stored_metadata(path).and_then(|stored| {
+            let mut conn = self.db();
+            let tx = conn.transaction()?;
+            for entry in &stored {
+                tx.execute(
+                    "INSERT INTO sessions (session_id, title, agent_name, workspace)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (session_id) DO NOTHING",
+                    params![
+                        entry.session_id,
+                        entry.title,
+                        entry.agent_name,
+                        entry.workspace.as_deref().map(|p| p.to_string_lossy())
+                    ],
+                )?;
+            }
+            tx.commit()?;
+            std::fs::remove_file(path).context("Failed to delete legacy session index")?;
+            Ok(stored.len())
+        });
+        match imported {
+            Ok(count) => log::info!(
+                "Imported metadata of {} sessions from {}",
+                count,
+                path.display()
+            ),
+            Err(e) => log::warn!("Failed to import session index {}: {:#}", path.display(), e),
+        }
+    }
+
+    fn db(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap()
+    }
+
+    /// Guard serializing the writes of one session
+    fn writer(&self, session_id: &str) -> Arc<Mutex<()>> {
+        self.writers
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Length of the session file the index was built from
+    fn indexed_len(&self, session_id: &str) -> Result<Option<u64>> {
+        let len: Option<Option<i64>> = self
+            .db()
+            .query_row(
+                "SELECT file_len FROM sessions WHERE session_id = ?1",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(len.flatten().map(|len| len as u64))
+    }
+
+    /// Index all messages of a session, replacing what was indexed before
+    ///
+    /// `file_len` is the length of the session file holding `messages`.
+    pub fn index_session(&self, session_id: &str, messages: &[PersistedMessage], file_len: u64) {
+        let writer = self.writer(session_id);
+        let _writer = writer.lock().unwrap();
+        self.index_session_within_writes(session_id, messages, file_len);
+    }
+
+    /// [`Self::index_session`] for callers already inside
+    /// [`Self::with_session_writes`] for the session
+    pub fn index_session_within_writes(
+        &self,
+        session_id: &str,
+        messages: &[PersistedMessage],
+        file_len: u64,
+    ) {
+        if let Err(e) = self.replace_session(session_id, messages, file_len) {
+            log::warn!("Failed to index session {}: {:#}", session_id, e);
+        }
+    }
+
+    fn replace_session(
+        &self,
+        session_id: &str,
+        messages: &[PersistedMessage],
+        file_len: u64,
+    ) -> Result<()> {
+        let mut conn = self.db();
+        let tx = conn.transaction()?;
+        clear_session(&tx, session_id)?;
+        for (ix, message) in messages.iter().enumerate() {
+            add_message(&tx, session_id, ix, message)?;
+        }
+        set_file_len(&tx, session_id, Some(file_len))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Run `append` (which writes `message` to the session file and returns
+    /// the new file length) and index the message. Other writes to the same
+    /// session wait for both; other sessions are not blocked.
+    ///
+    /// The first write to a session that has not been indexed yet reads the
+    /// whole file through `read_all` instead. Indexing failures are logged,
+    /// only a failed `append` is returned.
+    pub fn record_append(
+        &self,
+        session_id: &str,
+        message: &PersistedMessage,
+        append: impl FnOnce() -> Result<u64>,
+        read_all: impl FnOnce() -> Result<Vec<PersistedMessage>>,
+    ) -> Result<()> {
+        let writer = self.writer(session_id);
+        let _writer = writer.lock().unwrap();
+        let file_len = append()?;
+
+        let indexed = match self.indexed_len(session_id) {
+            Ok(Some(_)) => self.append_message(session_id, message, file_len),
+            Ok(None) => read_all()
+                .and_then(|messages| self.replace_session(session_id, &messages, file_len)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = indexed {
+            log::warn!("Failed to index session {}: {:#}", session_id, e);
+        }
+        Ok(())
+    }
+
+    fn append_message(
+        &self,
+        session_id: &str,
+        message: &PersistedMessage,
+        file_len: u64,
+    ) -> Result<()> {
+        let mut conn = self.db();
+        let tx = conn.transaction()?;
+        let message_index: i64 = tx.query_row(
+            "SELECT message_count FROM sessions WHERE session_id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        add_message(&tx, session_id, message_index as usize, message)?;
+        set_file_len(&tx, session_id, Some(file_len))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Index a session file unless the index was built from a file of the
+    /// same length, returning whether the file was read
+    pub fn load_session(
+        &self,
+        session_id: &str,
+        file_len: impl FnOnce() -> Result<u64>,
+        read_all: impl FnOnce() -> Result<Vec<PersistedMessage>>,
+    ) -> Result<bool> {
+        let writer = self.writer(session_id);
+        let _writer = writer.lock().unwrap();
+        let file_len = file_len()?;
+        if self.indexed_len(session_id)? == Some(file_len) {
+            return Ok(false);
+        }
+        let messages = read_all()?;
+        self.replace_session(session_id, &messages, file_len)?;
+        Ok(true)
+    }
+
+    /// Drop the indexed text of sessions whose file is not in `session_ids`,
+    /// keeping their title, agent and workspace
+    pub fn forget_missing(&self, session_ids: &[String]) -> Result<usize> {
+        let mut conn = self.db();
+        let tx = conn.transaction()?;
+        let indexed: Vec<String> = tx
+            .prepare("SELECT session_id FROM sessions WHERE file_len IS NOT NULL")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut forgotten = 0;
+        for session_id in indexed {
+            if !session_ids.contains(&session_id) {
+                clear_session(&tx, &session_id)?;
+                set_file_len(&tx, &session_id, None)?;
+                forgotten += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(forgotten)
+    }
+
+    /// Run `f` while no write to the session's file can start, e.g. to move
+    /// the file away. `f` must not call back into methods of this index that
+    /// write to the same session file.
+    pub fn with_session_writes<T>(&self, session_id: &str, f: impl FnOnce() -> T) -> T {
+        let writer = self.writer(session_id);
+        let _writer = writer.lock().unwrap();
+        f()
+    }
+
+    /// Drop the indexed text of a session whose file was archived, keeping
+    /// its metadata so it is still listed
+    pub fn archive_session(&self, session_id: &str) {
+        let archived = (|| -> Result<()> {
+            let mut conn = self.db();
+            let tx = conn.transaction()?;
+            delete_texts(&tx, session_id)?;
+            tx.execute(
+                "UPDATE sessions SET file_len = NULL, archived = 1 WHERE session_id = ?1",
+                [session_id],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })();
+        if let Err(e) = archived {
+            log::warn!(
+                "Failed to archive session {} in the index: {:#}",
+                session_id,
+                e
+            );
+        }
+    }
+
+    /// Forget a deleted session
+    pub fn remove_session(&self, session_id: &str) {
+        let writer = self.writer(session_id);
+        {
+            let _writer = writer.lock().unwrap();
+            let removed = (|| -> Result<()> {
+                let mut conn = self.db();
+                let tx = conn.transaction()?;
+                clear_session(&tx, session_id)?;
+                tx.execute("DELETE FROM sessions WHERE session_id = ?1", [session_id])?;
+                tx.commit()?;
+                Ok(())
+            })();
+            if let Err(e) = removed {
+                log::warn!(
+                    "Failed to remove session {} from the index: {:#}",
+                    session_id,
+                    e
+                );
+            }
+        }
+        self.writers.lock().unwrap().remove(session_id);
+    }
+
+    /// Record the agent of a session
+    pub fn set_agent(&self, session_id: &str, agent_name: &str) {
+        let updated = (|| -> Result<()> {
+            let conn = self.db();
+            // Called for every streamed update, so only write when it changed
+            let known: Option<Option<String>> = conn
+                .query_row(
+                    "SELECT agent_name FROM sessions WHERE session_id = ?1",
+                    [session_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if known.flatten().as_deref() == Some(agent_name) {
+                return Ok(());
+            }
+            conn.execute(
+                "INSERT INTO sessions (session_id, agent_name) VALUES (?1, ?2)
+                 ON CONFLICT (session_id) DO UPDATE SET agent_name = excluded.agent_name",
+                params![session_id, agent_name],
+            )?;
+            Ok(())
+        })();
+        if let Err(e) = updated {
+            log::warn!("Failed to record agent of session {}: {:#}", session_id, e);
+        }
+    }
+
+    /// Record the task name and workspace of a session
+    pub fn set_details(&self, session_id: &str, title: Option<String>, workspace: Option<PathBuf>) {
+        let updated = self.db().execute(
+            "INSERT INTO sessions (session_id, title, workspace) VALUES (?1, ?2, ?3)
+             ON CONFLICT (session_id) DO UPDATE
+             SET title = excluded.title, workspace = excluded.workspace",
+            params![
+                session_id,
+                title,
+                workspace.as_deref().map(|p| p.to_string_lossy())
+            ],
+        );
+        if let Err(e) = updated {
+            log::warn!("Failed to record details of session {}: {}", session_id, e);
+        }
+    }
+
+    /// Metadata of a session
+    pub fn session(&self, session_id: &str) -> Option<SessionMetadata> {
+        let sql = format!("SELECT {METADATA_COLUMNS} FROM sessions s WHERE s.session_id = ?1");
+        self.db()
+            .query_row(&sql, [session_id], |row| metadata_from_row(row, 0))
+            .optional()
+            .unwrap_or_else(|e| {
+                log::warn!(
+                    "Failed to read session {} from the index: {}",
+                    session_id,
+                    e
+                );
+                None
+            })
+    }
+
+    /// Metadata of all sessions with a session file or an archived one,
+    /// most recently updated first
+    pub fn sessions(&self) -> Vec<SessionMetadata> {
+        let sql = format!(
+            "SELECT {METADATA_COLUMNS} FROM sessions s
+             WHERE s.file_len IS NOT NULL OR s.archived
+             ORDER BY s.updated_at DESC"
+        );
+        let sessions = (|| -> Result<Vec<SessionMetadata>> {
+            let conn = self.db();
+            let mut statement = conn.prepare(&sql)?;
+            let sessions = statement
+                .query_map([], |row| metadata_from_row(row, 0))?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(sessions)
+        })();
+        sessions.unwrap_or_else(|e| {
+            log::warn!("Failed to list indexed sessions: {:#}", e);
+            Vec::new()
+        })
+    }
+
+    /// Messages containing every word of `query` (words match as prefixes),
+    /// newest first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let tokens = tokenize(query);
+        let Some(first) = tokens.first() else {
+            return Vec::new();
+        };
+        let pattern = tokens
+            .iter()
+            .map(|token| format!("\"{}\"*", token.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let sql = format!(
+            "SELECT {METADATA_COLUMNS}, t.message_index, t.kind, t.timestamp, t.text
+             FROM texts_fts f
+             JOIN texts t ON t.id = f.rowid
+             JOIN sessions s ON s.session_id = t.session_id
+             WHERE texts_fts MATCH ?1
+             ORDER BY t.timestamp DESC, t.message_index DESC
+             LIMIT ?2"
+        );
+        let hits = (|| -> Result<Vec<SearchHit>> {
+            let conn = self.db();
+            let mut statement = conn.prepare(&sql)?;
+            let rows = statement.query_map(params![pattern, limit as i64], |row| {
+                let kind: String = row.get(10)?;
+                let text: String = row.get(12)?;
+                Ok((
+                    metadata_from_row(row, 0)?,
+                    row.get::<_, i64>(9)? as usize,
+                    kind,
+                    row.get::<_, String>(11)?,
+                    text,
+                ))
+            })?;
+
+            let mut hits = Vec::new();
+            for row in rows {
+                let (session, message_index, kind, timestamp, text) = row?;
+                let Some(kind) = IndexedTextKind::from_label(&kind) else {
+                    continue;
+                };
+                hits.push(SearchHit {
+                    session,
+                    message_index,
+                    kind,
+                    timestamp,
+                    snippet: snippet(&text, first),
+                });
+            }
+            Ok(hits)
+        })();
+        hits.unwrap_or_else(|e| {
+            log::warn!("Failed to search sessions for {:?}: {:#}", query, e);
+            Vec::new()
+        })
+    }
+}
+
+/// Drop the indexed text and derived metadata of a session, creating its row
+/// if missing
+fn clear_session(tx: &Transaction<'_>, session_id: &str) -> Result<()> {
+    delete_texts(tx, session_id)?;
+    tx.execute(
+        "INSERT INTO sessions (session_id) VALUES (?1)
+         ON CONFLICT (session_id) DO UPDATE
+         SET created_at = NULL, updated_at = NULL, message_count = 0, preview = NULL",
+        [session_id],
+    )?;
+    Ok(())
+}
+
+fn delete_texts(tx: &Transaction<'_>, session_id: &str) -> Result<()> {
+    tx.execute(
+        "DELETE FROM texts_fts WHERE rowid IN (SELECT id FROM texts WHERE session_id = ?1)",
+        [session_id],
+    )?;
+    tx.execute("DELETE FROM texts WHERE session_id = ?1", [session_id])?;
+    Ok(())
+}
+
+/// Index one message appended at `message_index`
+fn add_message(
+    tx: &Transaction<'_>,
+    session_id: &str,
+    message_index: usize,
+    message: &PersistedMessage,
+) -> Result<()> {
+    tx.execute(
+        "UPDATE sessions
+         SET created_at = COALESCE(created_at, ?2), updated_at = ?2,
+             message_count = MAX(message_count, ?3)
+         WHERE session_id = ?1",
+        params![session_id, message.timestamp, (message_index + 1) as i64],
+    )?;
+
+    let Some((kind, text)) = indexed_text(&message.update) else {
+        return Ok(());
+    };
+    if kind == IndexedTextKind::UserMessage {
+        tx.execute(
+            "UPDATE sessions SET preview = COALESCE(preview, ?2) WHERE session_id = ?1",
+            params![session_id, truncate(&text, PREVIEW_LENGTH)],
+        )?;
+    }
+
+    tx.execute(
+        "INSERT INTO texts (session_id, message_index, kind, timestamp, text)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            session_id,
+            message_index as i64,
+            kind.label(),
+            message.timestamp,
+            text
+        ],
+    )?;
+    tx.execute(
+        "INSERT INTO texts_fts (rowid, tokens) VALUES (?1, ?2)",
+        params![tx.last_insert_rowid(), tokenize(&text).join(" ")],
+    )?;
+    Ok(())
+}
+
+/// Record the indexed file length; a session with a file is not archived
+fn set_file_len(tx: &Transaction<'_>, session_id: &str, file_len: Option<u64>) -> Result<()> {
+    tx.execute(
+        "UPDATE sessions
+         SET file_len = ?2, archived = CASE WHEN ?2 IS NULL THEN archived ELSE 0 END
+         WHERE session_id = ?1",
+        params![session_id, file_len.map(|len| len as i64)],
+    )?;
+    Ok(())
+}
+
+/// Metadata from the [`METADATA_COLUMNS`] starting at column `offset`
+fn metadata_from_row(row: &Row<'_>, offset: usize) -> rusqlite::Result<SessionMetadata> {
+    Ok(SessionMetadata {
+        session_id: row.get(offset)?,
+        title: row.get(offset + 1)?,
+        agent_name: row.get(offset + 2)?,
+        workspace: row.get::<_, Option<String>>(offset + 3)?.map(PathBuf::from),
+        created_at: row.get(offset + 4)?,
+        updated_at: row.get(offset + 5)?,
+        message_count: row.get::<_, i64>(offset + 6)? as usize,
+        preview: row.get(offset + 7)?,
+        archived: row.get(offset + 8)?,
+    })
+}
+
+fn read_stored_metadata(path: &Path) -> Result<Vec<StoredMetadata>> {
+    let raw = std::fs::read_to_string(path).context("Failed to read session index")?;
+    serde_json::from_str(&raw).context("Invalid session index")
+}
+
+/// Searchable text of an update, if it is part of the conversation
+fn indexed_text(update: &SessionUpdate) -> Option<(IndexedTextKind, String)> {
+    let (kind, text) = match update {
+        SessionUpdate::UserMessageChunk(chunk) => {
+            (IndexedTextKind::UserMessage, chunk_text(chunk)?)
+        }
+        SessionUpdate::AgentMessageChunk(chunk) => {
+            (IndexedTextKind::AgentMessage, chunk_text(chunk)?)
+        }
+        SessionUpdate::AgentThoughtChunk(chunk) => {
+            (IndexedTextKind::AgentThought, chunk_text(chunk)?)
+        }
+        SessionUpdate::ToolCall(tool_call) => (IndexedTextKind::ToolCall, tool_call.title.clone()),
+        SessionUpdate::ToolCallUpdate(update) => {
+            (IndexedTextKind::ToolCall, update.fields.title.clone()?)
+        }
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some((kind, text))
+}
+
+fn chunk_text(chunk: &ContentChunk) -> Option<String> {
+    match &chunk.content {
+        ContentBlock::Text(text) => Some(text.text.clone()),
+        _ => None,
+    }
+}
+
+/// Lowercase words; CJK characters are indexed one by one since the text
+/// has no spaces between words
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            tokens.push(ch.to_string());
+        } else if ch.is_alphanumeric() || ch == '_' {
+            current.extend(ch.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF
+    )
+}
+
+/// Single-line excerpt of `text` around the first occurrence of `token`
+fn snippet(text: &str, token: &str) -> String {
+    let chars: Vec<char> = text
+        .chars()
+        .map(|ch| if ch.is_whitespace() { ' ' } else { ch })
+        .collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect();
+    let needle: Vec<char> = token.chars().collect();
+    let position = if needle.is_empty() {
+        0
+    } else {
+        lower
+            .windows(needle.len())
+            .position(|window| window == needle.as_slice())
+            .unwrap_or(0)
+    };
+
+    let start = position.saturating_sub(SNIPPET_CONTEXT);
+    let end = (position + needle.len() + SNIPPET_CONTEXT).min(chars.len());
+    let mut snippet: String = chars[start..end].iter().collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= max_chars {
+        line
+    } else {
+        let mut truncated: String = line.chars().take(max_chars).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_client_protocol::TextContent;
+
+    fn message(timestamp: &str, update: SessionUpdate) -> PersistedMessage {
+        PersistedMessage::with_timestamp(timestamp.to_string(), update)
+    }
+
+    fn text_chunk(text: &str) -> ContentChunk {
+        ContentChunk::new(ContentBlock::Text(TextContent::new(text.to_string())))
+    }
+
+    #[test]
+    fn test_search_matches_all_words_newest_first() {
+        let index = SessionIndex::default();
+        index.index_session(
+            "s1",
+            &[
+                message(
+                    "2026-01-01T10:00:00+00:00",
+                    SessionUpdate::UserMessageChunk(text_chunk("Fix the parser bug")),
+                ),
+                message(
+                    "2026-01-01T10:00:05+00:00",
+                    SessionUpdate::AgentThoughtChunk(text_chunk("The parser fails on tabs")),
+                ),
+            ],
+            200,
+        );
+        index.index_session(
+            "s2",
+            &[message(
+                "2026-01-02T09:00:00+00:00",
+                SessionUpdate::AgentMessageChunk(text_chunk("Parsing works now")),
+            )],
+            100,
+        );
+
+        let hits = index.search("pars", 10);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].session.session_id, "s2");
+        assert_eq!(hits[1].kind, IndexedTextKind::AgentThought);
+        assert_eq!(hits[1].message_index, 1);
+
+        let hits = index.search("parser BUG", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message_index, 0);
+        assert_eq!(
+            hits[0].session.display_title(),
+            "Fix the parser bug".to_string()
+        );
+
+        assert!(index.search("missing", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_record_append_continues_message_numbering() {
+        let index = SessionIndex::default();
+        let first = message(
+            "2026-01-01T10:00:00+00:00",
+            SessionUpdate::UserMessageChunk(text_chunk("hello")),
+        );
+        let second = message(
+            "2026-01-01T10:00:01+00:00",
+            SessionUpdate::AgentMessageChunk(text_chunk("world")),
+        );
+
+        let on_disk = vec![first.clone()];
+        index
+            .record_append("s1", &first, || Ok(100), || Ok(on_disk))
+            .unwrap();
+        index
+            .record_append("s1", &second, || Ok(200), || unreachable!())
+            .unwrap();
+
+        let hits = index.search("world", 10);
+        assert_eq!(hits[0].message_index, 1);
+        let metadata = index.session("s1").unwrap();
+        assert_eq!(metadata.message_count, 2);
+        assert_eq!(
+            metadata.updated_at.as_deref(),
+            Some("2026-01-01T10:00:01+00:00")
+        );
+
+        index.remove_session("s1");
+        assert!(index.search("hello", 10).is_empty());
+        assert!(index.sessions().is_empty());
+    }
+
+    #[test]
+    fn test_metadata_is_stored_next_to_sessions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let index = SessionIndex::new(temp_dir.path());
+        index.set_agent("s1", "claude");
+        index.set_details(
+            "s1",
+            Some("Refactor".to_string()),
+            Some(PathBuf::from("/work/project")),
+        );
+
+        let reopened = SessionIndex::new(temp_dir.path());
+        let metadata = reopened.session("s1").unwrap();
+        assert_eq!(metadata.agent_name.as_deref(), Some("claude"));
+        assert_eq!(metadata.title.as_deref(), Some("Refactor"));
+        assert_eq!(metadata.workspace, Some(PathBuf::from("/work/project")));
+    }
+
+    #[test]
+    fn test_unchanged_session_files_are_not_read_again() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let messages = vec![message(
+            "2026-01-01T10:00:00+00:00",
+            SessionUpdate::UserMessageChunk(text_chunk("persisted question")),
+        )];
+        let index = SessionIndex::new(temp_dir.path());
+        assert!(
+            index
+                .load_session("s1", || Ok(100), || Ok(messages.clone()))
+                .unwrap()
+        );
+        index.set_agent("s1", "claude");
+
+        let reopened = SessionIndex::new(temp_dir.path());
+        assert!(
+            !reopened
+                .load_session("s1", || Ok(100), || unreachable!())
+                .unwrap()
+        );
+        assert_eq!(reopened.search("persisted", 10).len(), 1);
+        // A file that changed while the app was closed is read again
+        assert!(
+            reopened
+                .load_session("s1", || Ok(150), || Ok(messages.clone()))
+                .unwrap()
+        );
+        assert_eq!(reopened.search("question", 10).len(), 1);
+
+        // Sessions without a file lose their text but keep their metadata
+        assert_eq!(reopened.forget_missing(&[]).unwrap(), 1);
+        assert!(reopened.search("question", 10).is_empty());
+        assert!(reopened.sessions().is_empty());
+        assert_eq!(
+            reopened.session("s1").unwrap().agent_name.as_deref(),
+            Some("claude")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_splits_cjk_characters() {
+        assert_eq!(
+            tokenize("Hello, 世界 foo_bar"),
+            vec!["hello", "世", "界", "foo_bar"]
+        );
+    }
+}
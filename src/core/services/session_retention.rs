@@ -0,0 +1,262 @@
+//! Session Retention - Expires old session files by age and total size
+//!
+//! Expired sessions are compressed into the archive directory next to the
+//! session files, or deleted when the configuration asks for it. Pinned
+//! sessions and sessions that are in use (streaming, open in a panel or with
+//! a live agent session) never expire.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use super::{
+    AgentConfigService, AgentService, PersistenceService, SessionStatus, WorkspaceService,
+};
+use crate::core::config::{RetentionAction, RetentionConfig};
+
+/// Directory under the sessions directory holding archived sessions
+pub const ARCHIVE_DIR_NAME: &str = "archive";
+
+/// Size and age of a session file considered for expiry
+#[derive(Debug, Clone)]
+pub struct SessionFileInfo {
+    pub session_id: String,
+    pub bytes: u64,
+    pub modified: SystemTime,
+}
+
+/// Outcome of one retention pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionReport {
+    pub archived: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Bytes no longer taken by session files
+    pub freed_bytes: u64,
+}
+
+impl RetentionReport {
+    pub fn is_empty(&self) -> bool {
+        self.archived.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Disk usage of the sessions of one workspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceStorage {
+    /// None for sessions without a known workspace
+    pub workspace: Option<PathBuf>,
+    pub sessions: usize,
+    pub bytes: u64,
+}
+
+/// Disk usage of the sessions directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageUsage {
+    /// Largest first
+    pub workspaces: Vec<WorkspaceStorage>,
+    pub archived_sessions: usize,
+    pub archived_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Bytes taken by session files that are not archived
+    pub fn session_bytes(&self) -> u64 {
+        self.workspaces.iter().map(|usage| usage.bytes).sum()
+    }
+}
+
+/// Sessions that expire under `config`, oldest first
+///
+/// Sessions older than the maximum age expire first; then the oldest
+/// remaining ones expire until all files fit the size limit. Pinned sessions
+/// count towards the total but are never picked.
+pub fn select_expired(
+    files: &[SessionFileInfo],
+    config: &RetentionConfig,
+    now: SystemTime,
+) -> Vec<String> {
+    let mut candidates: Vec<&SessionFileInfo> = files
+        .iter()
+        .filter(|file| !config.is_pinned(&file.session_id))
+        .collect();
+    candidates.sort_by_key(|file| file.modified);
+
+    let max_age = config
+        .max_age_days
+        .map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60)));
+    let mut total: u64 = files.iter().map(|file| file.bytes).sum();
+    let max_total = config.max_total_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+
+    let mut expired = Vec::new();
+    for file in candidates {
+        let too_old = max_age.is_some_and(|max_age| {
+            now.duration_since(file.modified)
+                .is_ok_and(|age| age > max_age)
+        });
+        let too_large = max_total.is_some_and(|max_total| total > max_total);
+        if !too_old && !too_large {
+            // Candidates are sorted by age, so newer ones are kept too
+            break;
+        }
+        total = total.saturating_sub(file.bytes);
+        expired.push(file.session_id.clone());
+    }
+    expired
+}
+
+/// Compress a session file into `archive_dir` as `<name>.gz`
+pub fn archive_file(file_path: &Path, archive_dir: &Path) -> Result<PathBuf> {
+    use flate2::{Compression, write::GzEncoder};
+
+    std::fs::create_dir_all(archive_dir).context("Failed to create archive directory")?;
+    let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".gz");
+    let archive_path = archive_dir.join(name);
+
+    let mut source = std::fs::File::open(file_path)
+        .with_context(|| format!("Failed to open {}", file_path.display()))?;
+    let target = std::fs::File::create(&archive_path)
+        .with_context(|| format!("Failed to create {}", archive_path.display()))?;
+    let mut encoder = GzEncoder::new(target, Compression::default());
+    std::io::copy(&mut source, &mut encoder).context("Failed to compress session file")?;
+    let target = encoder
+        .finish()
+        .context("Failed to compress session file")?;
+    // The original is removed next, so the archive must be on disk first
+    target.sync_all().context("Failed to sync archive")?;
+    Ok(archive_path)
+}
+
+/// Decompress an archived session file back to `file_path`
+pub fn unarchive_file(archive_path: &Path, file_path: &Path) -> Result<()> {
+    use flate2::read::GzDecoder;
+
+    let mut decoder = GzDecoder::new(
+        std::fs::File::open(archive_path)
+            .with_context(|| format!("Failed to open {}", archive_path.display()))?,
+    );
+    let mut contents = Vec::new();
+    decoder
+        .read_to_end(&mut contents)
+        .context("Failed to decompress archived session")?;
+
+    let mut file = std::fs::File::create(file_path)
+        .with_context(|| format!("Failed to create {}", file_path.display()))?;
+    file.write_all(&contents)
+        .with_context(|| format!("Failed to write {}", file_path.display()))?;
+    file.sync_all()
+        .with_context(|| format!("Failed to sync {}", file_path.display()))?;
+    Ok(())
+}
+
+/// Run a retention pass now and then every `interval_hours`
+///
+/// The configuration is read again before each pass so changes made in the
+/// settings apply without a restart. Tasks of expired sessions are updated.
+pub async fn run_retention(
+    persistence_service: Arc<PersistenceService>,
+    workspace_service: Arc<WorkspaceService>,
+    agent_config_service: Arc<AgentConfigService>,
+    agent_service: Arc<AgentService>,
+) {
+    loop {
+        let config = agent_config_service.retention_config().await;
+        if config.is_enabled() {
+            let live: Vec<String> = agent_service
+                .list_sessions()
+                .into_iter()
+                .filter(|info| info.status != SessionStatus::Closed)
+                .map(|info| info.session_id)
+                .collect();
+            match persistence_service.apply_retention(&config, &live).await {
+                Ok(report) if !report.is_empty() => {
+                    log::info!(
+                        "Session retention {} {} sessions, freed {} bytes",
+                        match config.action {
+                            RetentionAction::Archive => "archived",
+                            RetentionAction::Delete => "deleted",
+                        },
+                        report.archived.len() + report.deleted.len(),
+                        report.freed_bytes
+                    );
+                    if let Err(e) = workspace_service.retire_sessions(&report).await {
+                        log::error!("Failed to update tasks of expired sessions: {}", e);
+                    }
+                }
+                Ok(_) => log::debug!("Session retention found nothing to expire"),
+                Err(e) => log::error!("Session retention failed: {}", e),
+            }
+        }
+
+        let interval = Duration::from_secs(config.interval_hours.max(1).saturating_mul(60 * 60));
+        smol::Timer::after(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+    const MB: u64 = 1024 * 1024;
+
+    fn file(session_id: &str, bytes: u64, days_old: u32, now: SystemTime) -> SessionFileInfo {
+        SessionFileInfo {
+            session_id: session_id.to_string(),
+            bytes,
+            modified: now - DAY * days_old,
+        }
+    }
+
+    #[test]
+    fn test_select_expired_by_age_and_size() {
+        let now = SystemTime::now();
+        let files = vec![
+            file("new", 2 * MB, 1, now),
+            file("old", MB, 40, now),
+            file("older-pinned", 5 * MB, 90, now),
+            file("middle", 3 * MB, 10, now),
+        ];
+
+        let mut config = RetentionConfig {
+            max_age_days: Some(30),
+            pinned_sessions: vec!["older-pinned".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(select_expired(&files, &config, now), vec!["old"]);
+
+        // 11 MB in total, the pinned 5 MB stay, so "old" and "middle" go
+        config.max_age_days = None;
+        config.max_total_mb = Some(8);
+        assert_eq!(select_expired(&files, &config, now), vec!["old", "middle"]);
+
+        assert!(select_expired(&files, &RetentionConfig::default(), now).is_empty());
+
+        // Limits too large to represent mean nothing expires
+        config.max_age_days = Some(u64::MAX);
+        config.max_total_mb = Some(u64::MAX);
+        assert!(select_expired(&files, &config, now).is_empty());
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("s1.jsonl");
+        std::fs::write(&file_path, "{\"a\":1}\n".repeat(100)).unwrap();
+
+        let archive_path =
+            archive_file(&file_path, &temp_dir.path().join(ARCHIVE_DIR_NAME)).unwrap();
+        assert!(archive_path.ends_with("archive/s1.jsonl.gz"));
+        assert!(std::fs::metadata(&archive_path).unwrap().len() < 800);
+
+        let restored = temp_dir.path().join("restored.jsonl");
+        unarchive_file(&archive_path, &restored).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(restored).unwrap(),
+            "{\"a\":1}\n".repeat(100)
+        );
+    }
+}
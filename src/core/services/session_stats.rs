@@ -0,0 +1,463 @@
+//! Session Statistics - Per-session counters collected from the event buses
+//!
+//! Counts conversation messages, tool calls by kind and status, prompt turn
+//! durations, time spent waiting for permission answers and the lines changed
+//! by tool call diffs. The stats of a session are kept in memory while it
+//! runs and saved as `stats/<session>.json` next to the session files, so
+//! they survive restarts without rereading the history.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use agent_client_protocol::{SessionUpdate, ToolCallContent, ToolCallStatus};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use super::SessionStatus;
+use super::session_export::serde_label;
+
+/// Directory under the sessions directory holding the saved stats
+pub const STATS_DIR_NAME: &str = "stats";
+
+/// Tool calls of one kind by their latest status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolCallCounts {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+impl ToolCallCounts {
+    pub fn total(&self) -> usize {
+        self.pending + self.in_progress + self.completed + self.failed
+    }
+
+    fn slot(&mut self, status: &ToolCallStatus) -> &mut usize {
+        match status {
+            ToolCallStatus::InProgress => &mut self.in_progress,
+            ToolCallStatus::Completed => &mut self.completed,
+            ToolCallStatus::Failed => &mut self.failed,
+            _ => &mut self.pending,
+        }
+    }
+}
+
+/// Statistics of one session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionStats {
+    pub user_messages: usize,
+    pub agent_messages: usize,
+    /// Tool calls by kind, e.g. `edit` or `execute`
+    pub tool_calls: BTreeMap<String, ToolCallCounts>,
+    /// Prompt turns that ran to an end
+    pub prompt_turns: usize,
+    pub total_turn_ms: u64,
+    pub longest_turn_ms: u64,
+    /// Permission requests that were answered
+    pub permission_requests: usize,
+    /// Time between asking for a permission and getting the answer
+    pub permission_wait_ms: u64,
+    /// Lines added and removed by the diffs of tool calls
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl SessionStats {
+    pub fn message_count(&self) -> usize {
+        self.user_messages + self.agent_messages
+    }
+
+    pub fn tool_call_count(&self) -> usize {
+        self.tool_calls.values().map(ToolCallCounts::total).sum()
+    }
+
+    pub fn failed_tool_calls(&self) -> usize {
+        self.tool_calls.values().map(|counts| counts.failed).sum()
+    }
+
+    pub fn average_turn(&self) -> Option<Duration> {
+        (self.prompt_turns > 0)
+            .then(|| Duration::from_millis(self.total_turn_ms / self.prompt_turns as u64))
+    }
+
+    pub fn permission_wait(&self) -> Duration {
+        Duration::from_millis(self.permission_wait_ms)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Short human readable duration, e.g. `850ms`, `12.4s`, `3m 5s` or `1h 20m`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 * 60 {
+        format!("{}h {}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else if duration.as_millis() >= 1000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChunkKind {
+    User,
+    Agent,
+    Other,
+}
+
+/// What the collector remembers about a tool call to move it between counts
+#[derive(Debug)]
+struct TrackedToolCall {
+    kind: String,
+    status: ToolCallStatus,
+    lines: (usize, usize),
+}
+
+/// Stats of a session together with the state of what is still running
+#[derive(Debug, Default)]
+struct SessionState {
+    stats: SessionStats,
+    last_chunk: Option<ChunkKind>,
+    tool_calls: HashMap<String, TrackedToolCall>,
+    turn_started: Option<Instant>,
+    pending_permissions: HashMap<String, Instant>,
+}
+
+impl SessionState {
+    fn record_update(&mut self, update: &SessionUpdate) {
+        let chunk = match update {
+            SessionUpdate::UserMessageChunk(_) => ChunkKind::User,
+            SessionUpdate::AgentMessageChunk(_) => ChunkKind::Agent,
+            _ => ChunkKind::Other,
+        };
+        // Chunks of one message arrive back to back
+        if self.last_chunk != Some(chunk) {
+            match chunk {
+                ChunkKind::User => self.stats.user_messages += 1,
+                ChunkKind::Agent => self.stats.agent_messages += 1,
+                ChunkKind::Other => {}
+            }
+        }
+        self.last_chunk = Some(chunk);
+
+        match update {
+            SessionUpdate::ToolCall(tool_call) => {
+                self.track_tool_call(
+                    tool_call.tool_call_id.to_string(),
+                    Some(serde_label(&tool_call.kind)),
+                    Some(tool_call.status.clone()),
+                    Some(&tool_call.content),
+                );
+            }
+            SessionUpdate::ToolCallUpdate(update) => {
+                self.track_tool_call(
+                    update.tool_call_id.to_string(),
+                    update.fields.kind.as_ref().map(serde_label),
+                    update.fields.status.clone(),
+                    update.fields.content.as_deref(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Move a tool call to its new status and count the lines of its diffs
+    fn track_tool_call(
+        &mut self,
+        id: String,
+        kind: Option<String>,
+        status: Option<ToolCallStatus>,
+        content: Option<&[ToolCallContent]>,
+    ) {
+        let stats = &mut self.stats;
+        let tracked = self.tool_calls.entry(id).or_insert_with(|| {
+            let tracked = TrackedToolCall {
+                kind: kind.clone().unwrap_or_else(|| "other".to_string()),
+                status: status.clone().unwrap_or(ToolCallStatus::Pending),
+                lines: (0, 0),
+            };
+            *stats
+                .tool_calls
+                .entry(tracked.kind.clone())
+                .or_default()
+                .slot(&tracked.status) += 1;
+            tracked
+        });
+
+        let new_kind = kind.unwrap_or_else(|| tracked.kind.clone());
+        let new_status = status.unwrap_or_else(|| tracked.status.clone());
+        if new_kind != tracked.kind || new_status != tracked.status {
+            let old = stats
+                .tool_calls
+                .entry(tracked.kind.clone())
+                .or_default()
+                .slot(&tracked.status);
+            *old = old.saturating_sub(1);
+            *stats
+                .tool_calls
+                .entry(new_kind.clone())
+                .or_default()
+                .slot(&new_status) += 1;
+            tracked.kind = new_kind;
+            tracked.status = new_status;
+        }
+
+        // Updates carry the whole content, so the diff lines are replaced
+        if let Some(content) = content {
+            let lines = diff_lines(content);
+            stats.lines_added = (stats.lines_added + lines.0).saturating_sub(tracked.lines.0);
+            stats.lines_removed = (stats.lines_removed + lines.1).saturating_sub(tracked.lines.1);
+            tracked.lines = lines;
+        }
+    }
+
+    /// Returns true when a prompt turn ended
+    fn record_status(&mut self, status: &SessionStatus) -> bool {
+        match status {
+            SessionStatus::InProgress => {
+                self.turn_started.get_or_insert_with(Instant::now);
+                false
+            }
+            status
+                if status.is_turn_end()
+                    || matches!(status, SessionStatus::Failed | SessionStatus::Closed) =>
+            {
+                let Some(started) = self.turn_started.take() else {
+                    return false;
+                };
+                let elapsed = started.elapsed().as_millis() as u64;
+                self.stats.prompt_turns += 1;
+                self.stats.total_turn_ms += elapsed;
+                self.stats.longest_turn_ms = self.stats.longest_turn_ms.max(elapsed);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Lines added and removed by the diffs among a tool call's content
+fn diff_lines(content: &[ToolCallContent]) -> (usize, usize) {
+    content
+        .iter()
+        .filter_map(|content| match content {
+            ToolCallContent::Diff(diff) => Some(diff),
+            _ => None,
+        })
+        .fold((0, 0), |(added, removed), diff| {
+            let old_text = diff.old_text.as_deref().unwrap_or("");
+            let (mut diff_added, mut diff_removed) = (0, 0);
+            for change in TextDiff::from_lines(old_text, &diff.new_text).iter_all_changes() {
+                match change.tag() {
+                    ChangeTag::Insert => diff_added += 1,
+                    ChangeTag::Delete => diff_removed += 1,
+                    ChangeTag::Equal => {}
+                }
+            }
+            (added + diff_added, removed + diff_removed)
+        })
+}
+
+/// Collects the stats of all sessions; cheap to clone
+#[derive(Clone)]
+pub struct SessionStatsCollector {
+    dir: PathBuf,
+    sessions: Arc<Mutex<HashMap<String, SessionState>>>,
+}
+
+impl SessionStatsCollector {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn stats_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", session_id))
+    }
+
+    /// Run `f` on the state of a session, loading saved stats the first time
+    fn with_session<R>(&self, session_id: &str, f: impl FnOnce(&mut SessionState) -> R) -> R {
+        let mut sessions = self.sessions.lock().unwrap();
+        let state = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionState {
+                stats: read_stats(&self.stats_path(session_id)).unwrap_or_default(),
+                ..Default::default()
+            });
+        f(state)
+    }
+
+    /// Count a session update
+    pub fn record_update(&self, session_id: &str, update: &SessionUpdate) {
+        self.with_session(session_id, |state| state.record_update(update));
+    }
+
+    /// Time prompt turns from the session status; returns true when a turn
+    /// ended and the stats should be saved
+    pub fn record_status(&self, session_id: &str, status: &SessionStatus) -> bool {
+        self.with_session(session_id, |state| state.record_status(status))
+    }
+
+    /// A permission request started waiting for an answer
+    pub fn record_permission_requested(&self, session_id: &str, permission_id: &str) {
+        self.with_session(session_id, |state| {
+            state
+                .pending_permissions
+                .insert(permission_id.to_string(), Instant::now());
+        });
+    }
+
+    /// A permission request was answered
+    pub fn record_permission_resolved(&self, session_id: &str, permission_id: &str) {
+        self.with_session(session_id, |state| {
+            if let Some(asked) = state.pending_permissions.remove(permission_id) {
+                state.stats.permission_requests += 1;
+                state.stats.permission_wait_ms += asked.elapsed().as_millis() as u64;
+            }
+        });
+    }
+
+    /// Current stats of a session, the saved ones if it has not run since
+    pub fn stats(&self, session_id: &str) -> SessionStats {
+        if let Some(state) = self.sessions.lock().unwrap().get(session_id) {
+            return state.stats.clone();
+        }
+        read_stats(&self.stats_path(session_id)).unwrap_or_default()
+    }
+
+    /// Conversation messages of a session so far
+    pub fn message_count(&self, session_id: &str) -> usize {
+        self.stats(session_id).message_count()
+    }
+
+    /// Write the stats of a session to disk
+    pub fn save(&self, session_id: &str) -> Result<()> {
+        let stats = match self.sessions.lock().unwrap().get(session_id) {
+            Some(state) => state.stats.clone(),
+            None => return Ok(()),
+        };
+        std::fs::create_dir_all(&self.dir).context("Failed to create stats directory")?;
+        let json = serde_json::to_string_pretty(&stats).context("Failed to serialize stats")?;
+        std::fs::write(self.stats_path(session_id), json)
+            .with_context(|| format!("Failed to write stats of session {}", session_id))
+    }
+
+    /// Forget the stats of a deleted session
+    pub fn remove(&self, session_id: &str) -> Result<()> {
+        self.sessions.lock().unwrap().remove(session_id);
+        let path = self.stats_path(session_id);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+fn read_stats(path: &Path) -> Option<SessionStats> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(stats) => Some(stats),
+        Err(e) => {
+            log::warn!("Ignoring unreadable stats file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_client_protocol::{
+        self as acp, ContentBlock, ContentChunk, TextContent, ToolCallUpdateFields,
+    };
+
+    fn chunk(text: &str) -> ContentChunk {
+        ContentChunk::new(ContentBlock::Text(TextContent::new(text.to_string())))
+    }
+
+    #[test]
+    fn test_counts_messages_tool_calls_and_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let collector = SessionStatsCollector::new(temp_dir.path().join(STATS_DIR_NAME));
+
+        let mut tool_call = acp::ToolCall::new(
+            acp::ToolCallId::from("call-1".to_string()),
+            "Edit main.rs".to_string(),
+        );
+        tool_call.kind = acp::ToolKind::Edit;
+        let mut fields = ToolCallUpdateFields::default();
+        fields.status = Some(acp::ToolCallStatus::Completed);
+        fields.content = Some(vec![acp::ToolCallContent::Diff(
+            acp::Diff::new("src/main.rs".into(), "a\nb\nc\n".to_string())
+                .old_text(Some("a\nx\n".to_string())),
+        )]);
+
+        for update in [
+            SessionUpdate::UserMessageChunk(chunk("Fix it")),
+            SessionUpdate::UserMessageChunk(chunk("please")),
+            SessionUpdate::AgentMessageChunk(chunk("On ")),
+            SessionUpdate::AgentMessageChunk(chunk("it")),
+            SessionUpdate::ToolCall(tool_call),
+            SessionUpdate::ToolCallUpdate(acp::ToolCallUpdate::new(
+                acp::ToolCallId::from("call-1".to_string()),
+                fields,
+            )),
+            SessionUpdate::AgentMessageChunk(chunk("Done")),
+        ] {
+            collector.record_update("s1", &update);
+        }
+
+        let stats = collector.stats("s1");
+        assert_eq!(stats.user_messages, 1);
+        assert_eq!(stats.agent_messages, 2);
+        assert_eq!(stats.tool_call_count(), 1);
+        assert_eq!(stats.tool_calls["edit"].completed, 1);
+        assert_eq!(stats.tool_calls["edit"].pending, 0);
+        assert_eq!((stats.lines_added, stats.lines_removed), (2, 1));
+    }
+
+    #[test]
+    fn test_turns_and_permission_waits_survive_a_restart() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join(STATS_DIR_NAME);
+        let collector = SessionStatsCollector::new(dir.clone());
+
+        assert!(!collector.record_status("s1", &SessionStatus::Completed));
+        assert!(!collector.record_status("s1", &SessionStatus::InProgress));
+        collector.record_permission_requested("s1", "perm-1");
+        collector.record_permission_resolved("s1", "perm-1");
+        collector.record_permission_resolved("s1", "unknown");
+        assert!(collector.record_status("s1", &SessionStatus::MaxTokens));
+        collector.save("s1").unwrap();
+
+        let stats = SessionStatsCollector::new(dir).stats("s1");
+        assert_eq!(stats.prompt_turns, 1);
+        assert_eq!(stats.permission_requests, 1);
+        assert!(stats.average_turn().is_some());
+
+        collector.remove("s1").unwrap();
+        assert!(collector.stats("s1").is_empty());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(12_400)), "12.4s");
+        assert_eq!(format_duration(Duration::from_secs(185)), "3m 5s");
+        assert_eq!(format_duration(Duration::from_secs(4800)), "1h 20m");
+    }
+}
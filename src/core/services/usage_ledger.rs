@@ -0,0 +1,615 @@
+//! Usage Ledger - Token usage and cost of agent prompts and AI service calls
+//!
+//! Every response that reports token usage becomes one record in
+//! `usage/records.jsonl` next to the session files. Records are priced when they are
+//! written, with the prices of the configured models, so later price changes
+//! do not rewrite past spend. Totals by session are kept as records are added;
+//! totals by task, workspace, agent, model and day are computed from the
+//! records on demand.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use agent_client_protocol::PromptResponse;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::config::{ModelConfig, ModelPrice};
+use crate::utils::csv;
+
+/// Directory under the sessions directory holding the usage records
+pub const USAGE_DIR_NAME: &str = "usage";
+
+/// File in the usage directory holding one record per line
+pub const USAGE_FILE_NAME: &str = "records.jsonl";
+
+/// Tokens reported by one response
+///
+/// Cached input tokens are part of the input tokens, they are only priced
+/// differently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub thought_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.thought_tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.thought_tokens += other.thought_tokens;
+    }
+
+    /// Read a usage object in the ACP (`inputTokens`) or OpenAI
+    /// (`prompt_tokens`) shape
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let count = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| value.get(*key).and_then(Value::as_u64))
+                .unwrap_or_default()
+        };
+        let usage = Self {
+            input_tokens: count(&["input_tokens", "inputTokens", "prompt_tokens"]),
+            cached_input_tokens: count(&[
+                "cached_read_tokens",
+                "cachedReadTokens",
+                "cached_input_tokens",
+            ])
+            .max(
+                value
+                    .pointer("/prompt_tokens_details/cached_tokens")
+                    .and_then(Value::as_u64)
+                    .unwrap_or_default(),
+            ),
+            output_tokens: count(&["output_tokens", "outputTokens", "completion_tokens"]),
+            thought_tokens: count(&["thought_tokens", "thoughtTokens"]),
+        };
+        (!usage.is_empty()).then_some(usage)
+    }
+
+    /// Cost in US dollars under `price`
+    pub fn cost(&self, price: &ModelPrice) -> f64 {
+        let cached = self.cached_input_tokens.min(self.input_tokens);
+        let cached_price = price
+            .cached_input_per_million
+            .unwrap_or(price.input_per_million);
+        ((self.input_tokens - cached) as f64 * price.input_per_million
+            + cached as f64 * cached_price
+            + (self.output_tokens + self.thought_tokens) as f64 * price.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Token usage and model reported by an agent for a prompt turn
+///
+/// Agents report usage either in the response's `usage` field or under
+/// `usage` in its `_meta`; the model may be named next to it.
+pub fn usage_from_prompt_response(
+    response: &PromptResponse,
+) -> Option<(TokenUsage, Option<String>)> {
+    let value = serde_json::to_value(response).ok()?;
+    let usage_value = value
+        .get("usage")
+        .or_else(|| value.pointer("/_meta/usage"))
+        .or_else(|| value.pointer("/_meta/tokenUsage"))?;
+    let usage = TokenUsage::from_json(usage_value)?;
+    let model = usage_value
+        .get("model")
+        .or_else(|| value.pointer("/_meta/model"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Some((usage, model))
+}
+
+/// What spent the tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSource {
+    /// A prompt turn of an agent session
+    Agent,
+    /// A code annotation request of the AI service
+    AiService,
+}
+
+/// One priced response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// ISO 8601 time of the response
+    pub timestamp: String,
+    pub source: UsageSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    /// None when the model has no price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageRecord {
+    pub fn new(source: UsageSource, usage: TokenUsage) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            source,
+            session_id: None,
+            agent: None,
+            model: None,
+            usage,
+            cost_usd: None,
+        }
+    }
+
+    /// Local date of the record, e.g. `2025-01-31`
+    pub fn day(&self) -> String {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .map(|time| time.with_timezone(&Local).format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| self.timestamp.chars().take(10).collect())
+    }
+}
+
+/// Summed usage of a set of records
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageTotals {
+    pub responses: usize,
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    /// Responses of models without a price, not part of the cost
+    pub unpriced: usize,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, record: &UsageRecord) {
+        self.responses += 1;
+        self.usage.add(&record.usage);
+        match record.cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced += 1,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses == 0
+    }
+}
+
+/// Task and workspace a session belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionOwner {
+    pub task: String,
+    pub workspace: String,
+}
+
+/// How the usage totals are grouped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UsageGrouping {
+    Session,
+    Task,
+    Workspace,
+    #[default]
+    Agent,
+    Model,
+    Day,
+}
+
+impl UsageGrouping {
+    pub const ALL: [UsageGrouping; 6] = [
+        UsageGrouping::Session,
+        UsageGrouping::Task,
+        UsageGrouping::Workspace,
+        UsageGrouping::Agent,
+        UsageGrouping::Model,
+        UsageGrouping::Day,
+    ];
+
+    /// Stable identifier, used by the settings dropdown
+    pub fn id(&self) -> &'static str {
+        match self {
+            UsageGrouping::Session => "session",
+            UsageGrouping::Task => "task",
+            UsageGrouping::Workspace => "workspace",
+            UsageGrouping::Agent => "agent",
+            UsageGrouping::Model => "model",
+            UsageGrouping::Day => "day",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|grouping| grouping.id() == id)
+    }
+
+    /// Group of a record; None when the record has no such attribute
+    fn key(&self, record: &UsageRecord, owners: &HashMap<String, SessionOwner>) -> Option<String> {
+        let owner = || {
+            record
+                .session_id
+                .as_ref()
+                .and_then(|session_id| owners.get(session_id))
+        };
+        match self {
+            UsageGrouping::Session => record.session_id.clone(),
+            UsageGrouping::Task => owner().map(|owner| owner.task.clone()),
+            UsageGrouping::Workspace => owner().map(|owner| owner.workspace.clone()),
+            UsageGrouping::Agent => match record.source {
+                UsageSource::Agent => record.agent.clone(),
+                UsageSource::AiService => Some("AI service".to_string()),
+            },
+            UsageGrouping::Model => record.model.clone(),
+            UsageGrouping::Day => Some(record.day()),
+        }
+    }
+}
+
+/// Totals of one group
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageSummary {
+    /// None for records outside any group, e.g. AI service calls by session
+    pub key: Option<String>,
+    pub totals: UsageTotals,
+}
+
+/// Totals of `records` by `grouping`, most expensive first (newest first by day)
+pub fn summarize(
+    records: &[UsageRecord],
+    grouping: UsageGrouping,
+    owners: &HashMap<String, SessionOwner>,
+) -> Vec<UsageSummary> {
+    let mut groups: HashMap<Option<String>, UsageTotals> = HashMap::new();
+    for record in records {
+        groups
+            .entry(grouping.key(record, owners))
+            .or_default()
+            .add(record);
+    }
+
+    let mut summaries: Vec<UsageSummary> = groups
+        .into_iter()
+        .map(|(key, totals)| UsageSummary { key, totals })
+        .collect();
+    if grouping == UsageGrouping::Day {
+        summaries.sort_by(|a, b| b.key.cmp(&a.key));
+    } else {
+        summaries.sort_by(|a, b| {
+            b.totals
+                .cost_usd
+                .total_cmp(&a.totals.cost_usd)
+                .then(b.totals.usage.total().cmp(&a.totals.usage.total()))
+                .then(a.key.cmp(&b.key))
+        });
+    }
+    summaries
+}
+
+/// One CSV row per record, with the task and workspace of its session
+pub fn to_csv(records: &[UsageRecord], owners: &HashMap<String, SessionOwner>) -> String {
+    let mut csv = String::from(
+        "timestamp,source,session_id,task,workspace,agent,model,input_tokens,cached_input_tokens,output_tokens,thought_tokens,cost_usd\n",
+    );
+    for record in records {
+        let owner = record
+            .session_id
+            .as_ref()
+            .and_then(|session_id| owners.get(session_id));
+        let row = [
+            record.timestamp.clone(),
+            match record.source {
+                UsageSource::Agent => "agent".to_string(),
+                UsageSource::AiService => "ai_service".to_string(),
+            },
+            record.session_id.clone().unwrap_or_default(),
+            owner.map(|owner| owner.task.clone()).unwrap_or_default(),
+            owner
+                .map(|owner| owner.workspace.clone())
+                .unwrap_or_default(),
+            record.agent.clone().unwrap_or_default(),
+            record.model.clone().unwrap_or_default(),
+            record.usage.input_tokens.to_string(),
+            record.usage.cached_input_tokens.to_string(),
+            record.usage.output_tokens.to_string(),
+            record.usage.thought_tokens.to_string(),
+            record
+                .cost_usd
+                .map(|cost| format!("{:.6}", cost))
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|value| csv::field(value)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Token count for display, e.g. `950`, `12.3k` or `1.2M`
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+/// Cost for display; small amounts keep more digits
+pub fn format_cost(usd: f64) -> String {
+    if usd > 0.0 && usd < 0.01 {
+        format!("${:.4}", usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+/// Append-only store of usage records; cheap to clone
+#[derive(Clone)]
+pub struct UsageLedger {
+    path: PathBuf,
+    /// Records read from disk the first time they are needed
+    records: Arc<Mutex<Option<Vec<UsageRecord>>>>,
+    /// Totals by session, kept up to date as records are added so they can
+    /// be read while rendering; empty until the records are loaded
+    session_totals: Arc<RwLock<HashMap<String, UsageTotals>>>,
+    /// Configured models by name, for their prices
+    models: Arc<RwLock<HashMap<String, ModelConfig>>>,
+}
+
+impl UsageLedger {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            records: Arc::new(Mutex::new(None)),
+            session_totals: Arc::new(RwLock::new(HashMap::new())),
+            models: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the models used for pricing
+    pub fn set_models(&self, models: HashMap<String, ModelConfig>) {
+        *self.models.write().unwrap() = models;
+    }
+
+    /// Add or change a model used for pricing
+    pub fn set_model(&self, name: &str, config: ModelConfig) {
+        self.models
+            .write()
+            .unwrap()
+            .insert(name.to_string(), config);
+    }
+
+    pub fn remove_model(&self, name: &str) {
+        self.models.write().unwrap().remove(name);
+    }
+
+    /// Price of a model, matched by configured name or model name
+    pub fn price_of(&self, model: &str) -> Option<ModelPrice> {
+        let models = self.models.read().unwrap();
+        models
+            .get(model)
+            .or_else(|| models.values().find(|config| config.model_name == model))
+            .and_then(|config| config.price)
+    }
+
+    /// Price a record and append it to the ledger
+    pub fn record(&self, mut record: UsageRecord) -> Result<UsageRecord> {
+        if record.cost_usd.is_none() {
+            record.cost_usd = record
+                .model
+                .as_deref()
+                .and_then(|model| self.price_of(model))
+                .map(|price| record.usage.cost(&price));
+        }
+
+        let mut records = self.records.lock().unwrap();
+        let records = self.loaded(&mut records);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create usage directory")?;
+        }
+        let mut line = serde_json::to_string(&record).context("Failed to serialize usage")?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+
+        if let Some(session_id) = &record.session_id {
+            self.session_totals
+                .write()
+                .unwrap()
+                .entry(session_id.clone())
+                .or_default()
+                .add(&record);
+        }
+        records.push(record.clone());
+        Ok(record)
+    }
+
+    /// Read the records from disk unless they were read already
+    ///
+    /// Blocks on file I/O; call it off the UI thread.
+    pub fn load(&self) {
+        let mut records = self.records.lock().unwrap();
+        self.loaded(&mut records);
+    }
+
+    /// All records, oldest first
+    pub fn records(&self) -> Vec<UsageRecord> {
+        let mut records = self.records.lock().unwrap();
+        self.loaded(&mut records).clone()
+    }
+
+    /// Totals of one session, without touching the disk
+    ///
+    /// Empty until the records are loaded, see [`UsageLedger::load`].
+    pub fn session_totals(&self, session_id: &str) -> UsageTotals {
+        self.session_totals
+            .read()
+            .unwrap()
+            .get(session_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Records in `slot`, read from disk with their session totals on first use
+    fn loaded<'a>(&self, slot: &'a mut Option<Vec<UsageRecord>>) -> &'a mut Vec<UsageRecord> {
+        slot.get_or_insert_with(|| {
+            let records = self.read_records();
+            let mut totals: HashMap<String, UsageTotals> = HashMap::new();
+            for record in &records {
+                if let Some(session_id) = &record.session_id {
+                    totals.entry(session_id.clone()).or_default().add(record);
+                }
+            }
+            *self.session_totals.write().unwrap() = totals;
+            records
+        })
+    }
+
+    fn read_records(&self) -> Vec<UsageRecord> {
+        let Ok(contents) = std::fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    log::warn!("Skipping unreadable usage record: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(model_name: &str, input: f64, output: f64) -> ModelConfig {
+        ModelConfig {
+            enabled: true,
+            provider: "openai".to_string(),
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key: String::new(),
+            model_name: model_name.to_string(),
+            price: Some(ModelPrice {
+                input_per_million: input,
+                output_per_million: output,
+                cached_input_per_million: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_usage_from_acp_and_openai_shapes() {
+        let acp = serde_json::json!({ "inputTokens": 1200, "outputTokens": 300, "cachedReadTokens": 200 });
+        assert_eq!(
+            TokenUsage::from_json(&acp),
+            Some(TokenUsage {
+                input_tokens: 1200,
+                cached_input_tokens: 200,
+                output_tokens: 300,
+                thought_tokens: 0,
+            })
+        );
+
+        let openai = serde_json::json!({
+            "prompt_tokens": 100,
+            "completion_tokens": 20,
+            "total_tokens": 120,
+            "prompt_tokens_details": { "cached_tokens": 40 },
+        });
+        let usage = TokenUsage::from_json(&openai).unwrap();
+        assert_eq!((usage.input_tokens, usage.cached_input_tokens), (100, 40));
+        assert_eq!(usage.total(), 120);
+
+        assert_eq!(TokenUsage::from_json(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn test_records_are_priced_and_summarized() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ledger = UsageLedger::new(temp_dir.path().join(USAGE_DIR_NAME).join(USAGE_FILE_NAME));
+        ledger.set_model("gpt", model("gpt-4o", 2.5, 10.0));
+
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            ..Default::default()
+        };
+        let mut priced = UsageRecord::new(UsageSource::Agent, usage);
+        priced.session_id = Some("s1".to_string());
+        priced.agent = Some("claude".to_string());
+        priced.model = Some("gpt-4o".to_string());
+        let priced = ledger.record(priced).unwrap();
+        assert_eq!(priced.cost_usd, Some(3.5));
+
+        let mut unpriced = UsageRecord::new(UsageSource::AiService, usage);
+        unpriced.model = Some("local".to_string());
+        ledger.record(unpriced).unwrap();
+
+        // A new ledger reads the records back
+        let reopened = UsageLedger::new(temp_dir.path().join(USAGE_DIR_NAME).join(USAGE_FILE_NAME));
+        assert!(reopened.session_totals("s1").is_empty());
+        reopened.load();
+        assert_eq!(reopened.session_totals("s1").cost_usd, 3.5);
+        let records = reopened.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(ledger.session_totals("s1").cost_usd, 3.5);
+
+        let owners = HashMap::from([(
+            "s1".to_string(),
+            SessionOwner {
+                task: "Fix login".to_string(),
+                workspace: "web".to_string(),
+            },
+        )]);
+        let by_agent = summarize(&records, UsageGrouping::Agent, &owners);
+        assert_eq!(by_agent[0].key.as_deref(), Some("claude"));
+        assert_eq!(by_agent[1].key.as_deref(), Some("AI service"));
+        assert_eq!(by_agent[1].totals.unpriced, 1);
+
+        let by_task = summarize(&records, UsageGrouping::Task, &owners);
+        assert_eq!(by_task[0].key.as_deref(), Some("Fix login"));
+        assert_eq!(by_task[1].key, None);
+
+        let csv = to_csv(&records, &owners);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .contains(",s1,Fix login,web,claude,gpt-4o,")
+        );
+    }
+
+    #[test]
+    fn test_format_tokens_and_cost() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(1_200_000), "1.2M");
+        assert_eq!(format_cost(0.0), "$0.00");
+        assert_eq!(format_cost(0.0042), "$0.0042");
+        assert_eq!(format_cost(3.5), "$3.50");
+    }
+}
@@ -3,8 +3,9 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::core::agent::AutopilotSessions;
 use crate::core::event_bus::{WorkspaceUpdateBusContainer, WorkspaceUpdateEvent};
-use crate::core::services::SessionStatus;
+use crate::core::services::{RetentionReport, SessionIndex, SessionStatus};
 use crate::schemas::workspace_schema::{Workspace, WorkspaceConfig, WorkspaceTask};
 
 /// Service for managing workspaces and tasks
@@ -20,6 +21,10 @@ pub struct WorkspaceService {
     config: Arc<RwLock<WorkspaceConfig>>,
     config_path: PathBuf,
     workspace_bus: Option<WorkspaceUpdateBusContainer>,
+    /// Sessions of tasks in autopilot mode, consulted by the permission store
+    autopilot_sessions: AutopilotSessions,
+    /// Index of persisted sessions, told the task name and workspace of each
+    session_index: Option<SessionIndex>,
 }
 
 impl WorkspaceService {
@@ -27,18 +32,60 @@ impl WorkspaceService {
     pub fn new(config_path: PathBuf) -> Self {
         let config = Self::load_config(&config_path).unwrap_or_default();
 
+        let autopilot_sessions = AutopilotSessions::new();
+        for task in config.tasks.iter().filter(|t| t.autopilot) {
+            if let Some(session_id) = &task.session_id {
+                autopilot_sessions.set(session_id, true);
+            }
+        }
+
         Self {
             config: Arc::new(RwLock::new(config)),
             config_path,
             workspace_bus: None,
+            autopilot_sessions,
+            session_index: None,
         }
     }
 
+    /// Sessions whose task runs in autopilot mode
+    pub fn autopilot_sessions(&self) -> &AutopilotSessions {
+        &self.autopilot_sessions
+    }
+
     /// Set the workspace event bus (called after AppState initialization)
     pub fn set_workspace_bus(&mut self, bus: WorkspaceUpdateBusContainer) {
         self.workspace_bus = Some(bus);
     }
 
+    /// Set the session index (called after AppState initialization) and
+    /// record the details of existing task sessions
+    pub fn set_session_index(&mut self, index: SessionIndex) {
+        if let Ok(config) = self.config.try_read() {
+            for task in &config.tasks {
+                Self::record_session_details(&index, &config, task);
+            }
+        }
+        self.session_index = Some(index);
+    }
+
+    /// Tell the session index the task name and workspace of a task's session
+    fn record_session_details(
+        index: &SessionIndex,
+        config: &WorkspaceConfig,
+        task: &WorkspaceTask,
+    ) {
+        let Some(session_id) = &task.session_id else {
+            return;
+        };
+        let workspace = config
+            .workspaces
+            .iter()
+            .find(|w| w.id == task.workspace_id)
+            .map(|w| w.path.clone());
+        index.set_details(session_id, Some(task.name.clone()), workspace);
+    }
+
     /// Publish a workspace update event if bus is available
     fn publish_event(&self, event: WorkspaceUpdateEvent) {
         if let Some(bus) = &self.workspace_bus {
@@ -121,6 +168,11 @@ impl WorkspaceService {
     pub async fn remove_workspace(&self, workspace_id: &str) -> Result<()> {
         {
             let mut config = self.config.write().await;
+            for task in config.tasks_for_workspace(workspace_id) {
+                if let Some(session_id) = &task.session_id {
+                    self.autopilot_sessions.set(session_id, false);
+                }
+            }
             config.remove_workspace(workspace_id);
 
             // Clear active workspace if it was removed
@@ -222,6 +274,49 @@ impl WorkspaceService {
         Ok(task_clone)
     }
 
+    /// Create the task of a session forked from the session of `parent_task_id`
+    ///
+    /// The fork is a child of the parent task, in the same workspace and with
+    /// the same agent and mode.
+    pub async fn create_fork_task(
+        &self,
+        parent_task_id: &str,
+        name: String,
+        session_id: String,
+    ) -> Result<WorkspaceTask> {
+        let task = {
+            let mut config = self.config.write().await;
+
+            let parent = config
+                .tasks
+                .iter()
+                .find(|t| t.id == parent_task_id)
+                .context("Parent task not found")?;
+            let mut task = WorkspaceTask::new_fork(parent, name);
+            task.set_session(session_id);
+
+            if let Some(index) = &self.session_index {
+                Self::record_session_details(index, &config, &task);
+            }
+            config.add_task(task.clone());
+            task
+        };
+
+        self.save_config().await?;
+
+        self.publish_event(WorkspaceUpdateEvent::TaskCreated {
+            workspace_id: task.workspace_id.clone(),
+            task_id: task.id.clone(),
+        });
+
+        log::info!(
+            "Created fork task '{}' of task {}",
+            task.name,
+            parent_task_id
+        );
+        Ok(task)
+    }
+
     /// Associate a session with a task
     pub async fn set_task_session(&self, task_id: &str, session_id: String) -> Result<()> {
         {
@@ -233,7 +328,18 @@ impl WorkspaceService {
                 .find(|t| t.id == task_id)
                 .context("Task not found")?;
 
+            if task.autopilot {
+                if let Some(previous) = &task.session_id {
+                    self.autopilot_sessions.set(previous, false);
+                }
+                self.autopilot_sessions.set(&session_id, true);
+            }
             task.set_session(session_id);
+
+            if let Some(index) = &self.session_index {
+                let task = task.clone();
+                Self::record_session_details(index, &config, &task);
+            }
         }
 
         self.save_config().await?;
@@ -270,6 +376,98 @@ impl WorkspaceService {
         Ok(())
     }
 
+    /// Turn autopilot mode of a task on or off
+    pub async fn set_task_autopilot(&self, task_id: &str, enabled: bool) -> Result<()> {
+        {
+            let mut config = self.config.write().await;
+
+            let task = config
+                .tasks
+                .iter_mut()
+                .find(|t| t.id == task_id)
+                .context("Task not found")?;
+
+            task.autopilot = enabled;
+            if let Some(session_id) = &task.session_id {
+                self.autopilot_sessions.set(session_id, enabled);
+            }
+        }
+
+        self.save_config().await?;
+
+        self.publish_event(WorkspaceUpdateEvent::TaskUpdated {
+            task_id: task_id.to_string(),
+        });
+
+        log::info!(
+            "Autopilot {} for task: {}",
+            if enabled { "enabled" } else { "disabled" },
+            task_id
+        );
+        Ok(())
+    }
+
+    /// Update the tasks of sessions expired by the retention policy
+    ///
+    /// Tasks of archived sessions are flagged so the history can be restored;
+    /// tasks of deleted sessions lose their session. Returns the number of
+    /// updated tasks.
+    pub async fn retire_sessions(&self, report: &RetentionReport) -> Result<usize> {
+        let updated: Vec<String> = {
+            let mut config = self.config.write().await;
+            let mut updated = Vec::new();
+            for task in config.tasks.iter_mut() {
+                let Some(session_id) = task.session_id.clone() else {
+                    continue;
+                };
+                if report.archived.contains(&session_id) {
+                    task.history_archived = true;
+                } else if report.deleted.contains(&session_id) {
+                    if task.autopilot {
+                        self.autopilot_sessions.set(&session_id, false);
+                    }
+                    task.session_id = None;
+                    task.history_archived = false;
+                } else {
+                    continue;
+                }
+                updated.push(task.id.clone());
+            }
+            updated
+        };
+        if updated.is_empty() {
+            return Ok(0);
+        }
+
+        self.save_config().await?;
+        for task_id in &updated {
+            self.publish_event(WorkspaceUpdateEvent::TaskUpdated {
+                task_id: task_id.clone(),
+            });
+        }
+        Ok(updated.len())
+    }
+
+    /// Clear the archived flag of the task of a session restored from the archive
+    pub async fn mark_session_restored(&self, session_id: &str) -> Result<()> {
+        let task_id = {
+            let mut config = self.config.write().await;
+            let Some(task) = config
+                .tasks
+                .iter_mut()
+                .find(|t| t.session_id.as_deref() == Some(session_id) && t.history_archived)
+            else {
+                return Ok(());
+            };
+            task.history_archived = false;
+            task.id.clone()
+        };
+
+        self.save_config().await?;
+        self.publish_event(WorkspaceUpdateEvent::TaskUpdated { task_id });
+        Ok(())
+    }
+
     /// Update task's last message
     pub async fn update_task_message(&self, session_id: &str, message: String) -> Result<()> {
         {
@@ -315,6 +513,9 @@ impl WorkspaceService {
 
             let task = config.remove_task(task_id).context("Task not found")?;
 
+            if let Some(session_id) = &task.session_id {
+                self.autopilot_sessions.set(session_id, false);
+            }
             task.workspace_id.clone()
         };
 
@@ -617,6 +818,97 @@ mod tests {
         assert!(matches!(updated_task.status, SessionStatus::InProgress));
     }
 
+    #[tokio::test]
+    async fn test_create_fork_task() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = create_test_service(temp_dir.path());
+
+        let project_dir = temp_dir.path().join("fork-test");
+        std::fs::create_dir(&project_dir).unwrap();
+        let ws = service.add_workspace(project_dir).await.unwrap();
+
+        let parent = service
+            .create_task(
+                &ws.id,
+                "Task".to_string(),
+                "claude".to_string(),
+                "Code".to_string(),
+            )
+            .await
+            .unwrap();
+        service
+            .set_task_session(&parent.id, "parent-session".to_string())
+            .await
+            .unwrap();
+
+        let fork = service
+            .create_fork_task(
+                &parent.id,
+                "Task (fork)".to_string(),
+                "fork-session".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let stored = service.get_task_by_session("fork-session").await.unwrap();
+        assert_eq!(stored.id, fork.id);
+        assert_eq!(stored.parent_task_id.as_deref(), Some(parent.id.as_str()));
+        assert_eq!(stored.workspace_id, ws.id);
+        assert_eq!(stored.mode, "Code");
+        assert!(matches!(stored.status, SessionStatus::InProgress));
+
+        assert!(
+            service
+                .create_fork_task("missing", "Fork".to_string(), "s".to_string())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retire_sessions_updates_tasks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = create_test_service(temp_dir.path());
+
+        let project_dir = temp_dir.path().join("retention-test");
+        std::fs::create_dir(&project_dir).unwrap();
+        let ws = service.add_workspace(project_dir).await.unwrap();
+
+        let mut tasks = Vec::new();
+        for session_id in ["archived", "deleted", "kept"] {
+            let task = service
+                .create_task(
+                    &ws.id,
+                    session_id.to_string(),
+                    "claude".to_string(),
+                    "Auto".to_string(),
+                )
+                .await
+                .unwrap();
+            service
+                .set_task_session(&task.id, session_id.to_string())
+                .await
+                .unwrap();
+            tasks.push(task.id);
+        }
+
+        let report = RetentionReport {
+            archived: vec!["archived".to_string()],
+            deleted: vec!["deleted".to_string()],
+            freed_bytes: 0,
+        };
+        assert_eq!(service.retire_sessions(&report).await.unwrap(), 2);
+
+        let archived = service.get_task(&tasks[0]).await.unwrap();
+        assert!(archived.history_archived);
+        assert_eq!(archived.session_id.as_deref(), Some("archived"));
+        assert_eq!(service.get_task(&tasks[1]).await.unwrap().session_id, None);
+        assert!(!service.get_task(&tasks[2]).await.unwrap().history_archived);
+
+        service.mark_session_restored("archived").await.unwrap();
+        assert!(!service.get_task(&tasks[0]).await.unwrap().history_archived);
+    }
+
     #[tokio::test]
     async fn test_get_workspace_tasks() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -764,6 +1056,42 @@ mod tests {
         assert!(matches!(updated.status, SessionStatus::Completed));
     }
 
+    #[tokio::test]
+    async fn test_set_task_autopilot() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = create_test_service(temp_dir.path());
+
+        let project_dir = temp_dir.path().join("autopilot-test");
+        std::fs::create_dir(&project_dir).unwrap();
+        let ws = service.add_workspace(project_dir).await.unwrap();
+
+        let task = service
+            .create_task(
+                &ws.id,
+                "Task".to_string(),
+                "claude".to_string(),
+                "Auto".to_string(),
+            )
+            .await
+            .unwrap();
+
+        // Enabled before the session exists
+        service.set_task_autopilot(&task.id, true).await.unwrap();
+        service
+            .set_task_session(&task.id, "session-1".to_string())
+            .await
+            .unwrap();
+        assert!(service.get_task(&task.id).await.unwrap().autopilot);
+        assert!(service.autopilot_sessions().is_enabled("session-1"));
+
+        // Survives a restart
+        let reloaded = create_test_service(temp_dir.path());
+        assert!(reloaded.autopilot_sessions().is_enabled("session-1"));
+
+        service.set_task_autopilot(&task.id, false).await.unwrap();
+        assert!(!service.autopilot_sessions().is_enabled("session-1"));
+    }
+
     #[tokio::test]
     async fn test_config_persistence_roundtrip() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -21,8 +21,9 @@ mod test_mock_data;
 // Re-export from panels module
 use crate::panels::{DockPanelContainer, DockPanelState};
 pub use panels::{
-    AppSettings, CodeEditorPanel, ConversationPanel, SessionManagerPanel, SettingsPanel, TaskPanel,
-    TerminalPanel, ToolCallDetailPanel, WelcomePanel,
+    AgentLogsPanel, AppSettings, CodeEditorPanel, ConversationPanel, PermissionAuditPanel,
+    PermissionQueuePanel, ProtocolInspectorPanel, SessionManagerPanel, SessionSearchPanel,
+    SettingsPanel, TaskPanel, TerminalPanel, ToolCallDetailPanel, WelcomePanel,
 };
 
 // Re-export from core module
@@ -39,9 +40,9 @@ pub use core::{
 pub use app::app_state::{AppState, WelcomeSession};
 pub use app::{
     actions::{
-        About, AddAgent, AddSessionToList, CancelSession, CloseWindow, CreateTaskFromWelcome, Info,
-        NewSessionConversationPanel, Open, PanelAction, Quit, ReloadAgentConfig, RemoveAgent,
-        RestartAgent, SelectFont, SelectLocale, SelectRadius, SelectScrollbarShow,
+        About, AddAgent, AddSessionToList, CancelSession, CloseWindow, CreateTaskFromWelcome,
+        ForkSession, Info, NewSessionConversationPanel, Open, PanelAction, Quit, ReloadAgentConfig,
+        RemoveAgent, RestartAgent, SelectFont, SelectLocale, SelectRadius, SelectScrollbarShow,
         SelectedAgentTask, SendMessageToSession, SetUploadDir, ShowPanelInfo, Tab, TabPrev,
         TestAction, ToggleDockToggleButton, TogglePanelVisible, ToggleSearch, UpdateAgent,
     },
@@ -60,8 +61,8 @@ pub use title_bar::AppTitleBar;
 pub use components::{
     AgentMessage, AgentMessageData, AgentMessageMeta, AgentMessageView, AgentTodoList,
     AgentTodoListView, ChatInputBox, DiffSummary, DiffSummaryData, FileChangeStats,
-    PermissionRequest, PermissionRequestView, PlanMeta, StatusIndicator, ToolCallItem,
-    ToolCallItemView, UserMessage, UserMessageData, UserMessageView,
+    PermissionRequest, PermissionRequestView, PlanMeta, SessionExportButton, StatusIndicator,
+    ToolCallItem, ToolCallItemView, UserMessage, UserMessageData, UserMessageView,
 };
 
 // Re-export ACP types for convenience
@@ -29,9 +29,25 @@ fn main() {
             }
         }
 
-        // Get session_bus and permission_bus from global AppState
+        // Get the event buses and shared registries from global AppState
         let session_bus = agentx::AppState::global(cx).session_bus.clone();
         let permission_bus = agentx::AppState::global(cx).permission_bus.clone();
+        let workspace_bus = agentx::AppState::global(cx).workspace_bus.clone();
+        let file_change_bus = agentx::AppState::global(cx).file_change_bus.clone();
+        let open_buffers = agentx::AppState::global(cx).open_buffers.clone();
+        let terminal_bus = agentx::AppState::global(cx).terminal_bus.clone();
+        let terminals = agentx::AppState::global(cx).terminals.clone();
+        let agent_logs = agentx::AppState::global(cx).agent_logs.clone();
+        let protocol_trace = agentx::AppState::global(cx).protocol_trace.clone();
+        let extensions = agentx::AppState::global(cx).extensions.clone();
+        let extension_bus = agentx::AppState::global(cx).extension_bus.clone();
+        let permission_rules = agentx::AppState::global(cx).permission_rules.clone();
+        let permission_audit = agentx::AppState::global(cx).permission_audit.clone();
+        let agent_config_bus = agentx::AppState::global(cx).agent_config_bus.clone();
+        let autopilot_sessions = agentx::AppState::global(cx)
+            .workspace_service()
+            .map(|service| service.autopilot_sessions().clone())
+            .unwrap_or_default();
 
         // Open GUI window immediately (non-blocking)
         open_new(cx, |_, _, _| {
@@ -88,14 +104,40 @@ fn main() {
             }
             let agent_server_count = agent_servers.len();
 
+            agent_logs.configure(&config.agent_logs, config_manager::get_agent_logs_dir());
+            protocol_trace.configure(
+                &config.protocol_trace,
+                config_manager::get_protocol_trace_path(),
+            );
+
             // Initialize agent manager (this happens in background after GUI is shown)
-            let permission_store = Arc::new(PermissionStore::default());
+            let permission_store = Arc::new(PermissionStore::new(
+                permission_rules,
+                permission_bus.clone(),
+                permission_audit,
+                autopilot_sessions,
+            ));
+            permission_store.configure(&config.permissions);
+            // Permission settings edited in the config file apply without a restart
+            agent_config_bus.subscribe_config_reloads({
+                let permission_store = permission_store.clone();
+                move |config| permission_store.configure(&config.permissions)
+            });
 
             match AgentManager::initialize(
                 agent_servers,
                 permission_store.clone(),
                 session_bus.clone(),
                 permission_bus.clone(),
+                workspace_bus.clone(),
+                file_change_bus.clone(),
+                open_buffers.clone(),
+                terminal_bus.clone(),
+                terminals.clone(),
+                agent_logs.clone(),
+                protocol_trace.clone(),
+                extensions.clone(),
+                extension_bus.clone(),
                 config.proxy.clone(),
             )
             .await
@@ -118,6 +160,32 @@ fn main() {
                         agentx::AppState::global(cx).message_service().cloned()
                     });
 
+                    // Expire old sessions now and periodically
+                    let retention_services = cx.update(|cx| {
+                        let app_state = agentx::AppState::global(cx);
+                        Some((
+                            app_state.persistence_service()?.clone(),
+                            app_state.workspace_service()?.clone(),
+                            app_state.agent_config_service()?.clone(),
+                            app_state.agent_service()?.clone(),
+                        ))
+                    });
+                    if let Some((
+                        persistence_service,
+                        workspace_service,
+                        agent_config_service,
+                        agent_service,
+                    )) = retention_services
+                    {
+                        smol::spawn(agentx::core::services::run_retention(
+                            persistence_service,
+                            workspace_service,
+                            agent_config_service,
+                            agent_service,
+                        ))
+                        .detach();
+                    }
+
                     // Initialize persistence subscription in async context
                     if let Some(message_service) = init_result {
                         message_service.init_persistence();
@@ -0,0 +1,354 @@
+use gpui::{
+    AnyElement, App, AppContext, ClipboardItem, Context, Entity, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, Render, ScrollHandle, SharedString,
+    StatefulInteractiveElement, Styled, Subscription, Window, div, prelude::FluentBuilder, px,
+};
+use gpui_component::{
+    ActiveTheme, Icon, IconName, Selectable, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex,
+};
+use rust_i18n::t;
+
+use crate::{
+    AppState,
+    core::agent::{AgentLogLine, format_log_line},
+    panels::dock_panel::DockPanel,
+};
+
+/// Panel that shows the stderr output captured from agent processes
+pub struct AgentLogsPanel {
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    search_input: Entity<InputState>,
+    /// Agent to show; `None` shows all agents interleaved
+    selected_agent: Option<String>,
+    /// Keep the view scrolled to the newest line
+    follow: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DockPanel for AgentLogsPanel {
+    fn title() -> &'static str {
+        "Agent Logs"
+    }
+
+    fn title_key() -> Option<&'static str> {
+        Some("agent_logs_panel.title")
+    }
+
+    fn description() -> &'static str {
+        "Inspect stderr output of agent processes"
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render> {
+        Self::view(window, cx)
+    }
+
+    fn paddings() -> gpui::Pixels {
+        px(0.)
+    }
+}
+
+impl AgentLogsPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let entity = cx.new(|cx| Self::new(window, cx));
+        Self::subscribe_to_agent_logs(&entity, cx);
+        entity
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("agent_logs_panel.search.placeholder").to_string())
+        });
+
+        // Re-render when the search text changes
+        let search_subscription =
+            cx.subscribe(&search_input, |_this, _input, _event: &InputEvent, cx| {
+                cx.notify();
+            });
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            search_input,
+            selected_agent: None,
+            follow: true,
+            _subscriptions: vec![search_subscription],
+        }
+    }
+
+    /// Re-render as agents write new lines
+    fn subscribe_to_agent_logs(entity: &Entity<Self>, cx: &mut App) {
+        let agent_log_bus = AppState::global(cx).agent_log_bus.clone();
+        let weak_entity = entity.downgrade();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let subscription_id = agent_log_bus.subscribe(move |event| {
+            let _ = tx.send(event.agent_name.clone());
+        });
+
+        cx.spawn(async move |cx| {
+            while let Some(agent_name) = rx.recv().await {
+                // Coalesce bursts of output into a single refresh
+                let mut agents = vec![agent_name];
+                while let Ok(agent_name) = rx.try_recv() {
+                    agents.push(agent_name);
+                }
+
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                _ = entity.update(cx, |this, cx| {
+                    let visible = match &this.selected_agent {
+                        Some(selected) => agents.iter().any(|agent| agent == selected),
+                        None => true,
+                    };
+                    if visible {
+                        if this.follow {
+                            this.scroll_handle.scroll_to_bottom();
+                        }
+                        cx.notify();
+                    }
+                });
+            }
+            agent_log_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    fn select_agent(&mut self, agent_name: Option<String>, cx: &mut Context<Self>) {
+        self.selected_agent = agent_name;
+        self.scroll_handle.scroll_to_bottom();
+        cx.notify();
+    }
+
+    /// Lines matching the agent filter and search, oldest first, with their agent
+    fn visible_lines(&self, cx: &App) -> Vec<(String, AgentLogLine)> {
+        let store = &AppState::global(cx).agent_logs;
+        let query = self.search_input.read(cx).text().to_string().to_lowercase();
+
+        let mut lines: Vec<(String, AgentLogLine)> = match &self.selected_agent {
+            Some(agent_name) => store
+                .lines(agent_name)
+                .into_iter()
+                .map(|line| (agent_name.clone(), line))
+                .collect(),
+            None => store
+                .agents()
+                .into_iter()
+                .flat_map(|agent_name| {
+                    store
+                        .lines(&agent_name)
+                        .into_iter()
+                        .map(move |line| (agent_name.clone(), line))
+                })
+                .collect(),
+        };
+        if self.selected_agent.is_none() {
+            lines.sort_by_key(|(_, line)| line.timestamp);
+        }
+        if !query.is_empty() {
+            lines.retain(|(_, line)| line.text.to_lowercase().contains(&query));
+        }
+        lines
+    }
+
+    fn copy_visible_lines(&mut self, cx: &mut Context<Self>) {
+        let show_agent = self.selected_agent.is_none();
+        let text = self
+            .visible_lines(cx)
+            .iter()
+            .map(|(agent_name, line)| {
+                if show_agent {
+                    format!("[{}] {}", agent_name, format_log_line(line))
+                } else {
+                    format_log_line(line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    fn clear_selected(&mut self, cx: &mut Context<Self>) {
+        let store = AppState::global(cx).agent_logs.clone();
+        match &self.selected_agent {
+            Some(agent_name) => store.clear(agent_name),
+            None => {
+                for agent_name in store.agents() {
+                    store.clear(&agent_name);
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> AnyElement {
+        let theme = cx.theme();
+        let agents = AppState::global(cx).agent_logs.agents();
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(theme.border)
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div().flex_1().child(
+                            Input::new(&self.search_input)
+                                .small()
+                                .cleanable(true)
+                                .prefix(
+                                    Icon::new(IconName::Search)
+                                        .size_4()
+                                        .text_color(theme.muted_foreground),
+                                ),
+                        ),
+                    )
+                    .child(
+                        Button::new("agent-logs-follow")
+                            .icon(IconName::ArrowDown)
+                            .ghost()
+                            .xsmall()
+                            .selected(self.follow)
+                            .tooltip(t!("agent_logs_panel.action.follow").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.follow = !this.follow;
+                                if this.follow {
+                                    this.scroll_handle.scroll_to_bottom();
+                                }
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("agent-logs-copy")
+                            .icon(IconName::Copy)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("agent_logs_panel.action.copy").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.copy_visible_lines(cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("agent-logs-clear")
+                            .icon(IconName::Delete)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("agent_logs_panel.action.clear").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.clear_selected(cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_1()
+                    .flex_wrap()
+                    .child(
+                        Button::new("agent-logs-all")
+                            .label(t!("agent_logs_panel.filter.all").to_string())
+                            .xsmall()
+                            .ghost()
+                            .selected(self.selected_agent.is_none())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.select_agent(None, cx);
+                            })),
+                    )
+                    .children(agents.into_iter().enumerate().map(|(ix, agent_name)| {
+                        let selected = self.selected_agent.as_deref() == Some(agent_name.as_str());
+                        Button::new(("agent-logs-agent", ix))
+                            .label(agent_name.clone())
+                            .xsmall()
+                            .ghost()
+                            .selected(selected)
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.select_agent(Some(agent_name.clone()), cx);
+                            }))
+                    })),
+            )
+            .into_any_element()
+    }
+}
+
+impl Focusable for AgentLogsPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for AgentLogsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let toolbar = self.render_toolbar(cx);
+        let lines = self.visible_lines(cx);
+        let show_agent = self.selected_agent.is_none();
+        let theme = cx.theme();
+
+        v_flex().size_full().child(toolbar).child(
+            div()
+                .id("agent-logs-scroll")
+                .flex_1()
+                .w_full()
+                .overflow_y_scroll()
+                .track_scroll(&self.scroll_handle)
+                .p_3()
+                .when(lines.is_empty(), |this| {
+                    this.child(
+                        div()
+                            .text_size(px(13.))
+                            .text_color(theme.muted_foreground)
+                            .child(t!("agent_logs_panel.empty").to_string()),
+                    )
+                })
+                .children(lines.into_iter().map(|(agent_name, line)| {
+                    let timestamp: SharedString = line
+                        .timestamp
+                        .with_timezone(&chrono::Local)
+                        .format("%H:%M:%S%.3f")
+                        .to_string()
+                        .into();
+
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .items_start()
+                        .text_size(px(12.))
+                        .font_family("Monaco, 'Courier New', monospace")
+                        .line_height(px(18.))
+                        .child(
+                            div()
+                                .flex_shrink_0()
+                                .text_color(theme.muted_foreground)
+                                .child(timestamp),
+                        )
+                        .when(show_agent, |this| {
+                            this.child(
+                                div()
+                                    .flex_shrink_0()
+                                    .text_color(theme.accent)
+                                    .child(agent_name),
+                            )
+                        })
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_color(theme.foreground)
+                                .whitespace_normal()
+                                .child(line.text),
+                        )
+                })),
+        )
+    }
+}
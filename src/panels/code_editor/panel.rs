@@ -1,4 +1,8 @@
-use std::{path::PathBuf, rc::Rc, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+};
 
 use autocorrect::ignorer::Ignorer;
 use gpui::{prelude::FluentBuilder, *};
@@ -20,6 +24,8 @@ use super::lsp_providers::TextConvertor;
 use super::lsp_store::CodeEditorPanelLspStore;
 use super::types::build_file_items;
 use crate::AppState;
+use crate::core::agent::normalize_path;
+use crate::core::event_bus::FileChangeEvent;
 
 pub struct CodeEditorPanel {
     editor: Entity<InputState>,
@@ -107,9 +113,20 @@ impl CodeEditorPanel {
             working_dir.unwrap_or_else(|| AppState::global(cx).current_working_dir().clone());
         Self::load_files(tree_state.clone(), working_dir.clone(), cx);
 
-        let _subscriptions = vec![cx.subscribe(&editor, |this, _, _: &InputEvent, cx| {
+        let _subscriptions = vec![cx.subscribe(&editor, |this, _, event: &InputEvent, cx| {
+            if matches!(event, InputEvent::Change) {
+                this.sync_open_buffer(cx);
+            }
             this.lint_document(cx);
         })];
+        Self::subscribe_to_file_changes(window, cx);
+
+        cx.on_release(|this, cx| {
+            if let Some(path) = &this.current_file_path {
+                AppState::global(cx).open_buffers.remove(path);
+            }
+        })
+        .detach();
 
         Self {
             editor,
@@ -142,6 +159,60 @@ impl CodeEditorPanel {
         .detach();
     }
 
+    /// Reload the open file when an agent writes it through `fs/write_text_file`
+    fn subscribe_to_file_changes(window: &mut Window, cx: &mut Context<Self>) {
+        let file_change_bus = AppState::global(cx).file_change_bus.clone();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<FileChangeEvent>();
+        let subscription_id = file_change_bus.subscribe(move |event| {
+            let _ = tx.send(event.clone());
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            while let Some(event) = rx.recv().await {
+                let result = this.update_in(cx, |this, window, cx| {
+                    this.reload_if_current(&event.path, window, cx);
+                });
+                if result.is_err() {
+                    break;
+                }
+            }
+            file_change_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    fn reload_if_current(&mut self, path: &Path, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(current) = self.current_file_path.as_ref() else {
+            return;
+        };
+        if normalize_path(current) != path {
+            return;
+        }
+
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                log::info!("[CodeEditorPanel] Reloading {:?} after agent write", path);
+                self.editor.update(cx, |state, cx| {
+                    state.set_value(content, window, cx);
+                });
+                cx.notify();
+            }
+            Err(e) => {
+                log::warn!("[CodeEditorPanel] Failed to reload {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Share the current editor contents with agents
+    fn sync_open_buffer(&self, cx: &mut Context<Self>) {
+        if let Some(path) = &self.current_file_path {
+            let text = self.editor.read(cx).text().to_string();
+            AppState::global(cx).open_buffers.update(path.clone(), text);
+        }
+    }
+
     /// Get the workspace_id (if available)
     pub fn workspace_id(&self) -> Option<String> {
         self.workspace_id.clone()
@@ -290,8 +361,11 @@ impl CodeEditorPanel {
                     });
 
                     this.language = language;
-                    this.current_file_path = Some(path_clone);
+                    if let Some(previous) = this.current_file_path.replace(path_clone) {
+                        AppState::global(cx).open_buffers.remove(&previous);
+                    }
                     this.has_opened_file = true;
+                    this.sync_open_buffer(cx);
                     cx.notify();
                 });
             })
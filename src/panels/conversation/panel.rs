@@ -1,15 +1,22 @@
 use gpui::{
-    App, ClipboardEntry, Context, Entity, FocusHandle, Focusable, IntoElement, ParentElement,
-    Render, ScrollHandle, SharedString, Styled, Window, div, prelude::*, px,
+    AnyElement, App, ClipboardEntry, Context, Entity, FocusHandle, Focusable, IntoElement,
+    ParentElement, Render, ScrollHandle, SharedString, Styled, Window, div, prelude::*, px,
 };
 
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Sizable, StyledExt, h_flex, input::InputState, skeleton::Skeleton,
-    spinner::Spinner, v_flex,
+    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::InputState,
+    skeleton::Skeleton,
+    spinner::Spinner,
+    v_flex,
 };
 
 // Use the published ACP schema crate
-use agent_client_protocol::{ContentChunk, ImageContent, PlanEntryStatus, SessionUpdate, ToolCall};
+use agent_client_protocol::{
+    ContentChunk, ImageContent, PlanEntryStatus, SessionUpdate, ToolCall, ToolCallUpdate,
+};
 use chrono::{DateTime, Utc};
 use rust_i18n::t;
 use smol::Timer;
@@ -17,8 +24,15 @@ use std::time::Duration;
 
 use crate::components::ToolCallItem;
 use crate::{
-    AgentMessage, AgentTodoList, AppState, ChatInputBox, DiffSummary, DiffSummaryData,
-    SendMessageToSession, app::actions::AddCodeSelection, core::services::SessionStatus,
+    AgentMessage, AgentTodoList, AppState, ChatInputBox, DiffSummary, DiffSummaryData, ForkSession,
+    SendMessageToSession, SessionExportButton,
+    app::actions::AddCodeSelection,
+    core::event_bus::PermissionStatusEvent,
+    core::event_bus::workspace_bus::WorkspaceUpdateEvent,
+    core::services::{
+        HISTORY_PAGE_SIZE, HistoryPage, OpenSession, SessionExportOptions, SessionStatus,
+        UsageTotals, format_cost, format_tokens, is_imported_session,
+    },
     panels::dock_panel::DockPanel,
 };
 
@@ -51,6 +65,8 @@ pub struct ConversationPanel {
     next_index: usize,
     /// Optional session ID to filter updates (None = all sessions)
     session_id: Option<String>,
+    /// Keeps the retention policy away from the session while it is shown
+    _open_session: Option<OpenSession>,
     /// Scroll handle for auto-scrolling to bottom
     scroll_handle: ScrollHandle,
     /// Input state for the chat input box
@@ -61,10 +77,32 @@ pub struct ConversationPanel {
     code_selections: Vec<AddCodeSelection>,
     /// Session status information for display
     session_status: Option<SessionStatusInfo>,
+    /// What the agent reports it is doing, from a status extension notification
+    agent_status_text: Option<String>,
     /// Workspace information
     workspace_id: Option<String>,
     workspace_name: Option<String>,
     working_directory: Option<String>,
+    /// Task of the session, if it was started as one
+    task_id: Option<String>,
+    /// Whether the task runs in autopilot mode
+    autopilot: bool,
+    /// Rendered item of each history message, once the history is loaded
+    history_positions: Option<Vec<usize>>,
+    /// History message to scroll to once the history is loaded
+    pending_reveal: Option<usize>,
+    /// Rendered item marked as the target of a search
+    highlighted_item: Option<usize>,
+    /// Options of the export menu
+    export_options: SessionExportOptions,
+    /// Imported for review: history only, no input and no agent
+    read_only: bool,
+    /// Position in the session file of the oldest loaded history message
+    history_start: usize,
+    /// Whether an older page of history is being loaded
+    loading_older_history: bool,
+    /// Loaded tool call updates whose tool call is on a page not loaded yet
+    deferred_tool_call_updates: Vec<ToolCallUpdate>,
 }
 
 const MESSAGE_SERVICE_RETRY_DELAY_MS: u64 = 500;
@@ -78,12 +116,17 @@ impl ConversationPanel {
         Self::subscribe_to_updates(&entity, None, cx);
         Self::subscribe_to_permissions(&entity, None, cx);
         Self::subscribe_to_code_selections(&entity, cx);
+        Self::subscribe_to_terminal_output(&entity, None, cx);
         log::info!("✅ ConversationPanel view created and subscribed");
         entity
     }
 
     /// Create a new panel for a specific session (no mock data)
     pub fn view_for_session(session_id: String, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        if is_imported_session(&session_id) {
+            return Self::view_for_imported(session_id, window, cx);
+        }
+
         log::info!("🚀 Creating ConversationPanel for session: {}", session_id);
         let entity = cx.new(|cx| Self::new_for_session(session_id.clone(), window, cx));
 
@@ -94,10 +137,29 @@ impl ConversationPanel {
         Self::subscribe_to_permissions(&entity, Some(session_id.clone()), cx);
         Self::subscribe_to_code_selections(&entity, cx);
         Self::subscribe_to_status_updates(&entity, Some(session_id.clone()), cx);
+        Self::subscribe_to_terminal_output(&entity, Some(session_id.clone()), cx);
+        Self::subscribe_to_history_restores(&entity, session_id.clone(), cx);
+        Self::subscribe_to_agent_status_text(&entity, session_id.clone(), cx);
+        Self::load_task_autopilot(&entity, session_id.clone(), cx);
         log::info!("✅ ConversationPanel created for session: {}", session_id);
         entity
     }
 
+    /// Read-only panel for a session imported for review
+    ///
+    /// Renders the imported history with the same components as a live
+    /// session but subscribes to nothing and offers no input box.
+    fn view_for_imported(session_id: String, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        log::info!("Opening imported session {} read-only", session_id);
+        let entity = cx.new(|cx| {
+            let mut panel = Self::new_for_session(session_id.clone(), window, cx);
+            panel.read_only = true;
+            panel
+        });
+        Self::load_history_for_session(&entity, session_id, cx);
+        entity
+    }
+
     pub fn session_id(&self) -> Option<String> {
         self.session_id.clone()
     }
@@ -137,6 +199,14 @@ impl ConversationPanel {
         let rendered_items = Vec::new();
         let update_index = UpdateStateIndex::new();
         let next_index = rendered_items.len();
+        let open_session = session_id
+            .as_deref()
+            .filter(|session_id| !is_imported_session(session_id))
+            .and_then(|session_id| {
+                AppState::global(cx)
+                    .persistence_service()
+                    .map(|service| service.open_session(session_id))
+            });
 
         Self {
             focus_handle,
@@ -144,14 +214,26 @@ impl ConversationPanel {
             update_index,
             next_index,
             session_id,
+            _open_session: open_session,
             scroll_handle,
             input_state,
             pasted_images: Vec::new(),
             code_selections: Vec::new(),
             session_status: None,
+            agent_status_text: None,
             workspace_id: None,
             workspace_name: None,
             working_directory: None,
+            task_id: None,
+            autopilot: false,
+            history_positions: None,
+            pending_reveal: None,
+            highlighted_item: None,
+            export_options: SessionExportOptions::default(),
+            read_only: false,
+            history_start: 0,
+            loading_older_history: false,
+            deferred_tool_call_updates: Vec::new(),
         }
     }
 
@@ -164,7 +246,10 @@ impl ConversationPanel {
         })
     }
 
-    /// Load historical messages for a session
+    /// Load the latest page of historical messages for a session
+    ///
+    /// Older pages are loaded by [`Self::load_older_history`] when the user
+    /// scrolls to the top.
     pub fn load_history_for_session(entity: &Entity<Self>, session_id: String, cx: &mut App) {
         let persistence_service = match AppState::global(cx).persistence_service() {
             Some(service) => service.clone(),
@@ -174,16 +259,37 @@ impl ConversationPanel {
             }
         };
 
+        let workspace_service = AppState::global(cx).workspace_service().cloned();
         let weak_entity = entity.downgrade();
 
         log::info!("Loading history for session: {}", session_id);
 
-        cx.spawn(
-            async move |cx| match persistence_service.load_messages(&session_id).await {
-                Ok(messages) => {
+        cx.spawn(async move |cx| {
+            // History moved away by the retention policy comes back when opened
+            match persistence_service
+                .restore_archived_session(&session_id)
+                .await
+            {
+                Ok(true) => {
+                    if let Some(workspace_service) = &workspace_service
+                        && let Err(e) = workspace_service.mark_session_restored(&session_id).await
+                    {
+                        log::warn!("Failed to update task of session {}: {}", session_id, e);
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => log::error!("Failed to restore archived session {}: {}", session_id, e),
+            }
+
+            match persistence_service
+                .load_history_page(&session_id, None, HISTORY_PAGE_SIZE)
+                .await
+            {
+                Ok(page) => {
                     log::info!(
-                        "Loaded {} historical messages for session: {}",
-                        messages.len(),
+                        "Loaded {} of {} historical messages for session: {}",
+                        page.messages.len(),
+                        page.total,
                         session_id
                     );
 
@@ -191,28 +297,19 @@ impl ConversationPanel {
                     let _ = cx.update(|cx| {
                         if let Some(entity) = weak.upgrade() {
                             entity.update(cx, |this, cx| {
-                                let agent_name = AppState::global(cx)
-                                    .agent_service()
-                                    .and_then(|service| service.get_agent_for_session(&session_id));
-
-                                // Use optimized UpdateProcessor for batch loading
-                                for persisted_msg in messages.into_iter() {
-                                    log::debug!(
-                                        "Loading historical message {}: timestamp={}",
-                                        this.next_index,
-                                        persisted_msg.timestamp
-                                    );
-
-                                    let mut processor = UpdateProcessor::<ConversationPanel>::new(
-                                        &mut this.rendered_items,
-                                        &mut this.update_index,
-                                        Some(session_id.as_str()),
-                                        agent_name.as_deref(),
-                                        this.next_index,
-                                    );
-
-                                    processor.process_update(persisted_msg.update, cx);
-                                    this.next_index += 1;
+                                let corrupt_lines = page.corrupt_lines;
+                                // History items are keyed by their position in the file
+                                this.next_index = this.next_index.max(page.total);
+                                this.prepend_history_page(page, cx);
+
+                                if corrupt_lines > 0 {
+                                    this.rendered_items.push(RenderedItem::InfoUpdate(
+                                        t!(
+                                            "conversation.history.skipped_lines",
+                                            count = corrupt_lines
+                                        )
+                                        .to_string(),
+                                    ));
                                 }
 
                                 log::info!(
@@ -223,7 +320,10 @@ impl ConversationPanel {
                                 );
 
                                 this.add_diff_summary_if_needed(cx);
-                                this.scroll_handle.scroll_to_bottom();
+                                match this.pending_reveal.take() {
+                                    Some(message_index) => this.reveal_message(message_index, cx),
+                                    None => this.scroll_handle.scroll_to_bottom(),
+                                }
                                 cx.notify();
                             });
                         } else {
@@ -234,8 +334,345 @@ impl ConversationPanel {
                 Err(e) => {
                     log::error!("Failed to load history for session {}: {}", session_id, e);
                 }
-            },
+            }
+        })
+        .detach();
+    }
+
+    /// Load the page of history before the oldest loaded message
+    fn load_older_history(&mut self, cx: &mut Context<Self>) {
+        if self.loading_older_history || self.history_start == 0 {
+            return;
+        }
+        let Some(session_id) = self.session_id.clone() else {
+            return;
+        };
+        let Some(persistence_service) = AppState::global(cx).persistence_service().cloned() else {
+            log::error!("PersistenceService not initialized, cannot load history");
+            return;
+        };
+
+        let before = self.history_start;
+        // Reach a message that is about to be revealed in one go
+        let limit = match self.pending_reveal {
+            Some(message_index) if message_index < before => {
+                (before - message_index).max(HISTORY_PAGE_SIZE)
+            }
+            _ => HISTORY_PAGE_SIZE,
+        };
+        self.loading_older_history = true;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let page = persistence_service
+                .load_history_page(&session_id, Some(before), limit)
+                .await;
+            _ = this.update(cx, |this, cx| {
+                this.loading_older_history = false;
+                match page {
+                    Ok(page) => {
+                        let inserted = this.prepend_history_page(page, cx);
+                        match this.pending_reveal.take() {
+                            Some(message_index) => this.reveal_message(message_index, cx),
+                            // Keep the message that was at the top in view
+                            None => this
+                                .scroll_handle
+                                .scroll_to_item(this.child_index(inserted)),
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load older history of {}: {}", session_id, e);
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Render a page of history in front of the loaded items
+    ///
+    /// Tool call updates whose tool call is on an older page are held back
+    /// until that page is loaded. Returns the number of inserted items.
+    fn prepend_history_page(&mut self, page: HistoryPage, cx: &mut Context<Self>) -> usize {
+        let Some(session_id) = self.session_id.clone() else {
+            return 0;
+        };
+        let agent_name = AppState::global(cx)
+            .agent_service()
+            .and_then(|service| service.get_agent_for_session(&session_id));
+
+        let mut items = Vec::new();
+        let mut index = UpdateStateIndex::new();
+        let mut positions = Vec::with_capacity(page.messages.len());
+        let mut deferred = Vec::new();
+
+        for (offset, persisted_msg) in page.messages.into_iter().enumerate() {
+            // Tool call updates change the item of their tool call
+            let tool_call_id = match &persisted_msg.update {
+                SessionUpdate::ToolCallUpdate(update) => Some(update.tool_call_id.to_string()),
+                _ => None,
+            };
+            if let (Some(id), SessionUpdate::ToolCallUpdate(update)) =
+                (&tool_call_id, &persisted_msg.update)
+                && page.start > 0
+                && index.find_tool_call(id).is_none()
+            {
+                deferred.push(update.clone());
+                positions.push(items.len().saturating_sub(1));
+                continue;
+            }
+
+            let mut processor = UpdateProcessor::<ConversationPanel>::new(
+                &mut items,
+                &mut index,
+                Some(session_id.as_str()),
+                agent_name.as_deref(),
+                page.start + offset,
+            );
+            processor.process_update(persisted_msg.update, cx);
+
+            positions.push(
+                tool_call_id
+                    .and_then(|id| index.find_tool_call(&id))
+                    .unwrap_or(items.len().saturating_sub(1)),
+            );
+        }
+
+        // Updates held back by newer pages whose tool call is on this one
+        for update in std::mem::take(&mut self.deferred_tool_call_updates) {
+            let id = update.tool_call_id.to_string();
+            if page.start > 0 && index.find_tool_call(&id).is_none() {
+                deferred.push(update);
+                continue;
+            }
+            let mut processor = UpdateProcessor::<ConversationPanel>::new(
+                &mut items,
+                &mut index,
+                Some(session_id.as_str()),
+                agent_name.as_deref(),
+                page.start,
+            );
+            processor.process_update(SessionUpdate::ToolCallUpdate(update), cx);
+        }
+        self.deferred_tool_call_updates = deferred;
+
+        let inserted = items.len();
+        self.rendered_items.splice(0..0, items);
+        self.update_index.prepend(index, inserted);
+        self.highlighted_item = self.highlighted_item.map(|item| item + inserted);
+        self.history_positions = Some(
+            positions
+                .into_iter()
+                .chain(
+                    self.history_positions
+                        .take()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|item| item + inserted),
+                )
+                .collect(),
+        );
+        self.history_start = page.start;
+        inserted
+    }
+
+    /// Index among the scroll container's children of a rendered item
+    fn child_index(&self, item: usize) -> usize {
+        // The "load earlier messages" row comes first while there is one
+        item + usize::from(self.history_start > 0)
+    }
+
+    /// Scroll to a history message, given by its position in the session file
+    pub fn reveal_message(&mut self, message_index: usize, cx: &mut Context<Self>) {
+        let Some(positions) = &self.history_positions else {
+            self.pending_reveal = Some(message_index);
+            return;
+        };
+        if message_index < self.history_start {
+            self.pending_reveal = Some(message_index);
+            self.load_older_history(cx);
+            return;
+        }
+        let Some(&item) = positions
+            .get(message_index - self.history_start)
+            .or(positions.last())
+        else {
+            return;
+        };
+        self.highlighted_item = Some(item);
+        self.scroll_handle.scroll_to_item(self.child_index(item));
+        cx.notify();
+    }
+
+    /// Token total of the session, with its cost when the model has a price
+    fn usage_label(usage: &UsageTotals) -> String {
+        let tokens = t!(
+            "conversation.usage.tokens",
+            count = format_tokens(usage.usage.total())
         )
+        .to_string();
+        if usage.unpriced == usage.responses {
+            tokens
+        } else {
+            format!("{} · {}", tokens, format_cost(usage.cost_usd))
+        }
+    }
+
+    /// Fork the session into a new task, up to and including a conversation
+    /// message counted from the oldest loaded history message
+    fn fork_from(&mut self, message_count: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(session_id) = self.session_id.clone() else {
+            return;
+        };
+        window.dispatch_action(
+            Box::new(ForkSession {
+                session_id,
+                history_start: self.history_start,
+                message_count,
+            }),
+            cx,
+        );
+    }
+
+    /// Find the task of the session to show its autopilot toggle
+    fn load_task_autopilot(entity: &Entity<Self>, session_id: String, cx: &mut App) {
+        let Some(workspace_service) = AppState::global(cx).workspace_service().cloned() else {
+            return;
+        };
+        let weak_entity = entity.downgrade();
+
+        cx.spawn(async move |cx| {
+            let Some(task) = workspace_service.get_task_by_session(&session_id).await else {
+                return;
+            };
+            let Some(entity) = weak_entity.upgrade() else {
+                return;
+            };
+            _ = entity.update(cx, |this, cx| {
+                this.task_id = Some(task.id);
+                this.autopilot = task.autopilot;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn set_autopilot(&mut self, enabled: bool, cx: &mut Context<Self>) {
+        let Some(task_id) = self.task_id.clone() else {
+            return;
+        };
+        let Some(workspace_service) = AppState::global(cx).workspace_service().cloned() else {
+            log::error!("WorkspaceService not available in AppState");
+            return;
+        };
+        let previous = self.autopilot;
+        self.autopilot = enabled;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            if let Err(e) = workspace_service
+                .set_task_autopilot(&task_id, enabled)
+                .await
+            {
+                log::error!("Failed to set autopilot for task {}: {}", task_id, e);
+                _ = this.update(cx, |this, cx| {
+                    this.autopilot = previous;
+                    cx.notify();
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Reload the history when session/load restored messages missing locally
+    fn subscribe_to_history_restores(entity: &Entity<Self>, session_id: String, cx: &mut App) {
+        let weak_entity = entity.downgrade();
+        let workspace_bus = AppState::global(cx).workspace_bus.clone();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<usize>();
+        let filter_id = session_id.clone();
+        let subscription_id = workspace_bus.subscribe(move |event| {
+            if let WorkspaceUpdateEvent::SessionHistoryRestored { session_id, added } = event {
+                if session_id == &filter_id {
+                    let _ = tx.send(*added);
+                }
+            }
+        });
+
+        cx.spawn(async move |cx| {
+            while let Some(added) = rx.recv().await {
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                log::info!(
+                    "Reloading history for session {} after {} messages were restored",
+                    session_id,
+                    added
+                );
+                let _ = cx.update(|cx| {
+                    entity.update(cx, |this, _| {
+                        this.rendered_items.clear();
+                        this.update_index = UpdateStateIndex::new();
+                        this.next_index = 0;
+                        this.history_positions = None;
+                        this.highlighted_item = None;
+                        this.history_start = 0;
+                        this.deferred_tool_call_updates.clear();
+                    });
+                    Self::load_history_for_session(&entity, session_id.clone(), cx);
+                });
+            }
+            workspace_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Show status text the agent of this session sends through extension
+    /// notifications
+    fn subscribe_to_agent_status_text(entity: &Entity<Self>, session_id: String, cx: &mut App) {
+        let weak_entity = entity.downgrade();
+        let workspace_bus = AppState::global(cx).workspace_bus.clone();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) =
+            tokio::sync::mpsc::unbounded_channel::<(String, Option<String>, Option<String>)>();
+        let subscription_id = workspace_bus.subscribe(move |event| {
+            if let WorkspaceUpdateEvent::AgentStatusText {
+                agent_name,
+                session_id,
+                text,
+            } = event
+            {
+                let _ = tx.send((agent_name.clone(), session_id.clone(), text.clone()));
+            }
+        });
+
+        cx.spawn(async move |cx| {
+            while let Some((agent_name, target_session, text)) = rx.recv().await {
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                let _ = cx.update(|cx| {
+                    // Text without a session applies to every session of the agent
+                    let applies = match target_session {
+                        Some(target) => target == session_id,
+                        None => AppState::global(cx)
+                            .agent_service()
+                            .and_then(|service| service.get_agent_for_session(&session_id))
+                            .is_some_and(|name| name == agent_name),
+                    };
+                    if applies {
+                        entity.update(cx, |this, cx| {
+                            this.agent_status_text = text;
+                            cx.notify();
+                        });
+                    }
+                });
+            }
+            workspace_bus.unsubscribe(subscription_id);
+        })
         .detach();
     }
 
@@ -422,6 +859,7 @@ impl ConversationPanel {
                                         &event.tool_call,
                                         event.options.clone(),
                                     )
+                                    .escalation(event.escalation.clone())
                                 });
                                 crate::PermissionRequestView { item: inner }
                             });
@@ -458,6 +896,104 @@ impl ConversationPanel {
 
         let filter_log_str = filter_log.as_deref().unwrap_or("all sessions");
         log::info!("Subscribed to permission bus for: {}", filter_log_str);
+
+        Self::subscribe_to_permission_status(entity, filter_log, cx);
+    }
+
+    /// Mark permission requests answered elsewhere as responded
+    fn subscribe_to_permission_status(
+        entity: &Entity<Self>,
+        session_filter: Option<String>,
+        cx: &mut App,
+    ) {
+        let weak_entity = entity.downgrade();
+        let permission_bus = AppState::global(cx).permission_bus.clone();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let subscription_id = permission_bus.subscribe_status(move |event| {
+            let PermissionStatusEvent::Resolved { .. } = event else {
+                return;
+            };
+            if session_filter
+                .as_ref()
+                .is_some_and(|session_id| session_id != event.session_id())
+            {
+                return;
+            }
+            let _ = tx.send(event.permission_id().to_string());
+        });
+
+        cx.spawn(async move |cx| {
+            while let Some(permission_id) = rx.recv().await {
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                _ = entity.update(cx, |this, cx| {
+                    for item in &this.rendered_items {
+                        if let RenderedItem::PermissionRequest(view) = item {
+                            let request = view.read(cx).item.clone();
+                            if request.read(cx).permission_id() == permission_id {
+                                request.update(cx, |request, cx| request.mark_responded(cx));
+                            }
+                        }
+                    }
+                });
+            }
+            permission_bus.unsubscribe_status(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Subscribe to TerminalBus so tool calls re-render while their terminal produces output
+    pub fn subscribe_to_terminal_output(
+        entity: &Entity<Self>,
+        session_filter: Option<String>,
+        cx: &mut App,
+    ) {
+        let weak_entity = entity.downgrade();
+        let terminal_bus = AppState::global(cx).terminal_bus.clone();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let subscription_id = match session_filter {
+            Some(session_id) => terminal_bus.subscribe_session(session_id, move |event| {
+                let _ = tx.send(event.terminal_id.clone());
+            }),
+            None => terminal_bus.subscribe(move |event| {
+                let _ = tx.send(event.terminal_id.clone());
+            }),
+        };
+
+        cx.spawn(async move |cx| {
+            while let Some(terminal_id) = rx.recv().await {
+                let weak = weak_entity.clone();
+                let alive = cx.update(|cx| {
+                    let Some(entity) = weak.upgrade() else {
+                        return false;
+                    };
+                    let items: Vec<_> = entity
+                        .read(cx)
+                        .rendered_items
+                        .iter()
+                        .filter_map(|item| match item {
+                            RenderedItem::ToolCall(tool_call) => Some(tool_call.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    for item in items {
+                        if item.read(cx).has_terminal(&terminal_id) {
+                            item.update(cx, |_, cx| cx.notify());
+                        }
+                    }
+                    true
+                });
+                if !alive {
+                    break;
+                }
+            }
+            terminal_bus.unsubscribe(subscription_id);
+        })
+        .detach();
     }
 
     /// Subscribe to CodeSelectionBus to receive code selection events
@@ -541,8 +1077,9 @@ impl ConversationPanel {
                                     status
                                 );
 
-                                // Mark last message as complete when session completes or becomes idle
-                                if matches!(status, SessionStatus::Completed | SessionStatus::Idle) {
+                                // Mark last message as complete when the agent's reply ends
+                                if status.is_turn_end() {
+                                    this.agent_status_text = None;
                                     if let Some(last_item) = this.rendered_items.last_mut() {
                                         last_item.mark_complete();
                                         log::debug!("Marked last message as complete due to status change to {:?}", status);
@@ -722,6 +1259,65 @@ impl ConversationPanel {
         .detach();
     }
 
+    /// Render why the agent stopped when the turn ended short of completion
+    fn render_stop_notice(&self, cx: &mut Context<Self>) -> AnyElement {
+        let Some(status) = self.session_status.as_ref().map(|info| &info.status) else {
+            return v_flex().into_any_element();
+        };
+        let message = match status {
+            SessionStatus::Refused => t!("conversation.stop.refused"),
+            SessionStatus::MaxTokens => t!("conversation.stop.max_tokens"),
+            SessionStatus::MaxTurnRequests => t!("conversation.stop.max_turn_requests"),
+            SessionStatus::Cancelled => t!("conversation.stop.cancelled"),
+            SessionStatus::TimedOut => t!("conversation.stop.timed_out"),
+            _ => return v_flex().into_any_element(),
+        };
+        let color = match status {
+            SessionStatus::Refused | SessionStatus::TimedOut => cx.theme().red,
+            SessionStatus::Cancelled => cx.theme().muted_foreground,
+            _ => cx.theme().warning,
+        };
+
+        h_flex()
+            .w_full()
+            .justify_between()
+            .items_center()
+            .gap_3()
+            .px_3()
+            .py_2()
+            .rounded(cx.theme().radius)
+            .border_1()
+            .border_color(color.opacity(0.5))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        Icon::new(IconName::TriangleAlert)
+                            .size(px(14.))
+                            .text_color(color),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().foreground)
+                            .child(message.to_string()),
+                    ),
+            )
+            .when(status.can_continue(), |this| {
+                this.child(
+                    Button::new("continue-after-stop")
+                        .label(t!("conversation.stop.continue").to_string())
+                        .ghost()
+                        .small()
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.send_message("Continue".to_string(), vec![], window, cx);
+                        })),
+                )
+            })
+            .into_any_element()
+    }
+
     /// Render the loading skeleton and status info when session is in progress
     fn render_loading_skeleton(&self, cx: &mut Context<Self>) -> impl IntoElement {
         // Only show loading skeleton when session is actively processing
@@ -787,6 +1383,18 @@ impl ConversationPanel {
                             .items_center()
                             .gap_2p5()
                             .flex_1()
+                            .when_some(self.agent_status_text.clone(), |this, text| {
+                                this.child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .max_w(px(320.))
+                                        .overflow_hidden()
+                                        .text_ellipsis()
+                                        .whitespace_nowrap()
+                                        .child(text),
+                                )
+                            })
                             .when_some(current_todo, |this, todo| {
                                 // Current task indicator
                                 this.child(
@@ -915,62 +1523,138 @@ impl Focusable for ConversationPanel {
 
 impl Render for ConversationPanel {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let mut children = v_flex().p_4().gap_3().bg(cx.theme().background);
+        // Items are direct children of the scroll container so that
+        // `reveal_message` can scroll to one of them by index
+        let mut children: Vec<AnyElement> = Vec::with_capacity(self.rendered_items.len() + 2);
 
-        for item in &self.rendered_items {
-            match item {
-                RenderedItem::UserMessage(entity) => {
-                    children = children.child(entity.clone());
-                }
+        if self.history_start > 0 {
+            children.push(
+                h_flex()
+                    .id("load-older-history")
+                    .w_full()
+                    .justify_center()
+                    .gap_2()
+                    .py_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .when(self.loading_older_history, |this| {
+                        this.child(Spinner::new().xsmall())
+                            .child("Loading earlier messages...")
+                    })
+                    .when(!self.loading_older_history, |this| {
+                        this.cursor_pointer()
+                            .hover(|this| this.text_color(cx.theme().foreground))
+                            .child(format!("Load {} earlier messages", self.history_start))
+                            .on_click(cx.listener(|this, _, _, cx| this.load_older_history(cx)))
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        let can_fork = !self.read_only && self.session_id.is_some();
+        // Conversation messages so far, counted the way the session file stores them
+        let mut message_count = 0;
+        let mut previous_user = false;
+
+        for (ix, item) in self.rendered_items.iter().enumerate() {
+            let is_user = matches!(item, RenderedItem::UserMessage(_));
+            let is_message =
+                (is_user && !previous_user) || matches!(item, RenderedItem::AgentMessage(..));
+            previous_user = is_user;
+            if is_message {
+                message_count += 1;
+            }
+
+            let element = match item {
+                RenderedItem::UserMessage(entity) => entity.clone().into_any_element(),
                 RenderedItem::AgentMessage(id, data) => {
-                    let msg = AgentMessage::new(get_element_id(id), data.clone());
-                    children = children.child(msg);
-                }
-                RenderedItem::AgentThought(entity) => {
-                    children = children.child(entity.clone());
+                    AgentMessage::new(get_element_id(id), data.clone()).into_any_element()
                 }
+                RenderedItem::AgentThought(entity) => entity.clone().into_any_element(),
                 RenderedItem::Plan(plan) => {
                     let todo_list = AgentTodoList::from_plan(plan.clone());
-                    children = children.child(v_flex().pl_6().child(todo_list));
+                    v_flex().pl_6().child(todo_list).into_any_element()
                 }
                 RenderedItem::ToolCall(entity) => {
-                    children = children.child(v_flex().pl_6().child(entity.clone()));
+                    v_flex().pl_6().child(entity.clone()).into_any_element()
                 }
                 RenderedItem::PermissionRequest(entity) => {
-                    children = children.child(v_flex().pl_6().child(entity.clone()));
-                }
-                RenderedItem::DiffSummary(entity) => {
-                    // Render DiffSummary as part of message stream
-                    children = children.child(entity.clone());
-                }
-                RenderedItem::InfoUpdate(text) => {
-                    children = children.child(
-                        div().pl_6().child(
-                            div()
-                                .p_2()
-                                .rounded(cx.theme().radius)
-                                .bg(cx.theme().muted.opacity(0.5))
-                                .border_1()
-                                .border_color(cx.theme().border.opacity(0.3))
-                                .child(
-                                    div()
-                                        .text_xs()
-                                        .text_color(cx.theme().muted_foreground)
-                                        .child(text.clone()),
-                                ),
-                        ),
-                    );
+                    v_flex().pl_6().child(entity.clone()).into_any_element()
                 }
+                // Render DiffSummary as part of message stream
+                RenderedItem::DiffSummary(entity) => entity.clone().into_any_element(),
+                RenderedItem::InfoUpdate(text) => div()
+                    .pl_6()
+                    .child(
+                        div()
+                            .p_2()
+                            .rounded(cx.theme().radius)
+                            .bg(cx.theme().muted.opacity(0.5))
+                            .border_1()
+                            .border_color(cx.theme().border.opacity(0.3))
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(text.clone()),
+                            ),
+                    )
+                    .into_any_element(),
+            };
+
+            let element = if can_fork && is_message {
+                let fork_count = message_count;
+                let group = SharedString::from(format!("conversation-message-{}", ix));
+                div()
+                    .relative()
+                    .group(group.clone())
+                    .child(element)
+                    .child(
+                        div()
+                            .absolute()
+                            .top_0()
+                            .right_0()
+                            .opacity(0.)
+                            .group_hover(group, |this| this.opacity(1.))
+                            .child(
+                                Button::new(("fork-from-message", ix))
+                                    .icon(Icon::new(crate::assets::Icon::GitFork))
+                                    .ghost()
+                                    .xsmall()
+                                    .tooltip("Fork from here")
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.fork_from(fork_count, window, cx);
+                                    })),
+                            ),
+                    )
+                    .into_any_element()
+            } else {
+                element
+            };
+
+            if self.highlighted_item == Some(ix) {
+                children.push(
+                    div()
+                        .rounded(cx.theme().radius)
+                        .border_1()
+                        .border_color(cx.theme().primary.opacity(0.5))
+                        .child(element)
+                        .into_any_element(),
+                );
+            } else {
+                children.push(element);
             }
         }
 
         // Add loading skeleton when session is in progress (conditional rendering handled in function)
-        children = children.child(self.render_loading_skeleton(cx));
+        children.push(self.render_loading_skeleton(cx).into_any_element());
+        children.push(self.render_stop_notice(cx));
 
         // Main layout: vertical flex with scroll area on top and input box at bottom
         v_flex()
             .id("messages")
             .size_full()
+            .relative()
             .child(
                 // Scrollable message area - takes remaining space
                 div()
@@ -979,6 +1663,12 @@ impl Render for ConversationPanel {
                     .w_full()
                     .track_scroll(&self.scroll_handle)
                     .overflow_y_scroll()
+                    .on_scroll_wheel(cx.listener(|this, _, _, cx| {
+                        // Fetch older history once the top is reached
+                        if this.scroll_handle.offset().y >= px(0.) {
+                            this.load_older_history(cx);
+                        }
+                    }))
                     .size_full()
                     .when(self.rendered_items.is_empty(), |this| {
                         // Show empty state with centered text
@@ -998,69 +1688,143 @@ impl Render for ConversationPanel {
                     })
                     .when(!self.rendered_items.is_empty(), |this| {
                         // Show message list
-                        this.pb_3() // Add padding at bottom so messages don't get hidden behind input box
-                            .child(children)
+                        this.flex()
+                            .flex_col()
+                            .gap_3()
+                            .p_4()
+                            .pb_7() // Add padding at bottom so messages don't get hidden behind input box
+                            .bg(cx.theme().background)
+                            .children(children)
                     }),
             )
-            .child(
-                // Chat input box at bottom (fixed, not scrollable)
-                div()
-                    .flex_none() // Don't allow shrinking
-                    .w_full()
-                    .bg(cx.theme().background) // Solid background
-                    // .border_t_1()
-                    .p_1()
-                    // .border_color(cx.theme().border)
-                    .child({
-                        let entity = cx.entity().clone();
-                        ChatInputBox::new("chat-input", self.input_state.clone())
-                            .pasted_images(self.pasted_images.clone())
-                            .code_selections(self.code_selections.clone())
-                            .session_status(
-                                self.session_status.as_ref().map(|info| info.status.clone()),
-                            )
-                            .on_paste(move |window, cx| {
-                                entity.update(cx, |this, cx| {
-                                    this.handle_paste(window, cx);
-                                });
-                            })
-                            .on_remove_image(cx.listener(|this, idx, _, cx| {
-                                // Remove the image at the given index
-                                if *idx < this.pasted_images.len() {
-                                    this.pasted_images.remove(*idx);
-                                    cx.notify();
-                                }
-                            }))
-                            .on_remove_code_selection(cx.listener(|this, idx, _, cx| {
-                                // Remove the code selection at the given index
-                                if *idx < this.code_selections.len() {
-                                    this.code_selections.remove(*idx);
-                                    cx.notify();
-                                }
-                            }))
-                            .on_send(cx.listener(|this, _ev, window, cx| {
-                                let text = this.input_state.read(cx).value().to_string();
-                                if !text.trim().is_empty() || !this.pasted_images.is_empty() {
-                                    // Clear the input
-                                    this.input_state.update(cx, |state, cx| {
-                                        state.set_value(SharedString::from(""), window, cx);
+            .when(self.read_only, |this| {
+                this.child(
+                    div()
+                        .flex_none()
+                        .w_full()
+                        .px_4()
+                        .py_2()
+                        .border_t_1()
+                        .border_color(cx.theme().border)
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Imported session · read-only, not attached to an agent"),
+                )
+            })
+            .when(!self.read_only, |this| {
+                this.child(
+                    // Chat input box at bottom (fixed, not scrollable)
+                    div()
+                        .flex_none() // Don't allow shrinking
+                        .w_full()
+                        .bg(cx.theme().background) // Solid background
+                        // .border_t_1()
+                        .p_1()
+                        // .border_color(cx.theme().border)
+                        .child({
+                            let entity = cx.entity().clone();
+                            ChatInputBox::new("chat-input", self.input_state.clone())
+                                .pasted_images(self.pasted_images.clone())
+                                .code_selections(self.code_selections.clone())
+                                .session_status(
+                                    self.session_status.as_ref().map(|info| info.status.clone()),
+                                )
+                                .on_paste(move |window, cx| {
+                                    entity.update(cx, |this, cx| {
+                                        this.handle_paste(window, cx);
                                     });
-
-                                    // Send the message with images if any
-                                    let images = std::mem::take(&mut this.pasted_images);
-                                    this.send_message(text, images, window, cx);
-
-                                    // Clear pasted images and code selections after sending
-                                    this.code_selections.clear();
+                                })
+                                .on_remove_image(cx.listener(|this, idx, _, cx| {
+                                    // Remove the image at the given index
+                                    if *idx < this.pasted_images.len() {
+                                        this.pasted_images.remove(*idx);
+                                        cx.notify();
+                                    }
+                                }))
+                                .on_remove_code_selection(cx.listener(|this, idx, _, cx| {
+                                    // Remove the code selection at the given index
+                                    if *idx < this.code_selections.len() {
+                                        this.code_selections.remove(*idx);
+                                        cx.notify();
+                                    }
+                                }))
+                                .on_send(cx.listener(|this, _ev, window, cx| {
+                                    let text = this.input_state.read(cx).value().to_string();
+                                    if !text.trim().is_empty() || !this.pasted_images.is_empty() {
+                                        // Clear the input
+                                        this.input_state.update(cx, |state, cx| {
+                                            state.set_value(SharedString::from(""), window, cx);
+                                        });
+
+                                        // Send the message with images if any
+                                        let images = std::mem::take(&mut this.pasted_images);
+                                        this.send_message(text, images, window, cx);
+
+                                        // Clear pasted images and code selections after sending
+                                        this.code_selections.clear();
+                                        cx.notify();
+                                    }
+                                }))
+                                .on_cancel(cx.listener(|this, _ev, window, cx| {
+                                    log::info!("[ConversationPanel] on_cancel callback triggered");
+                                    this.send_cancel_message(window, cx);
                                     cx.notify();
-                                }
-                            }))
-                            .on_cancel(cx.listener(|this, _ev, window, cx| {
-                                log::info!("[ConversationPanel] on_cancel callback triggered");
-                                this.send_cancel_message(window, cx);
-                                cx.notify();
-                            }))
-                    }),
+                                }))
+                                .when(self.task_id.is_some(), |input| {
+                                    input.autopilot(self.autopilot).on_autopilot_toggle(
+                                        cx.listener(|this, enabled: &bool, _, cx| {
+                                            this.set_autopilot(*enabled, cx);
+                                        }),
+                                    )
+                                })
+                        }),
+                )
+            })
+            .when_some(
+                self.session_id
+                    .clone()
+                    .filter(|_| !self.rendered_items.is_empty()),
+                |this, session_id| {
+                    let usage = AppState::global(cx)
+                        .persistence_service()
+                        .map(|service| service.usage().session_totals(&session_id))
+                        .filter(|totals| !totals.is_empty());
+
+                    // Floating over the top right corner of the message list
+                    this.child(
+                        h_flex()
+                            .absolute()
+                            .top_2()
+                            .right_4()
+                            .gap_2()
+                            .items_center()
+                            .when_some(usage, |this, usage| {
+                                this.child(
+                                    div()
+                                        .id("conversation-usage")
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(Self::usage_label(&usage))
+                                        .tooltip(|window, cx| {
+                                            gpui_component::tooltip::Tooltip::new(
+                                                "Tokens and cost reported for this session",
+                                            )
+                                            .build(window, cx)
+                                        }),
+                                )
+                            })
+                            .child(
+                                SessionExportButton::new("conversation-export", session_id)
+                                    .options(self.export_options)
+                                    .on_options_change(cx.listener(
+                                        |this, options: &SessionExportOptions, _, cx| {
+                                            this.export_options = *options;
+                                            cx.notify();
+                                        },
+                                    )),
+                            ),
+                    )
+                },
             )
     }
 }
@@ -76,6 +76,23 @@ impl UpdateStateIndex {
         self.last_thought_index = None;
     }
 
+    /// Account for `older` items inserted in front of the indexed ones
+    ///
+    /// Positions of the newer items move back by the number of inserted
+    /// items; a tool call present on both sides keeps its newer position.
+    pub fn prepend(&mut self, older: UpdateStateIndex, inserted: usize) {
+        for position in self.tool_call_positions.values_mut() {
+            *position += inserted;
+        }
+        for (tool_call_id, position) in older.tool_call_positions {
+            self.tool_call_positions
+                .entry(tool_call_id)
+                .or_insert(position);
+        }
+        self.last_message_index = self.last_message_index.map(|ix| ix + inserted);
+        self.last_thought_index = self.last_thought_index.map(|ix| ix + inserted);
+    }
+
     /// Rebuild index from rendered items (call after bulk operations)
     pub fn rebuild(&mut self, items: &[RenderedItem], cx: &App) {
         self.tool_call_positions.clear();
@@ -15,7 +15,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::AppState;
 use crate::panels::{
-    CodeEditorPanel, ConversationPanel, SessionManagerPanel, SettingsPanel, TaskPanel,
+    AgentLogsPanel, CodeEditorPanel, ConversationPanel, PermissionAuditPanel, PermissionQueuePanel,
+    ProtocolInspectorPanel, SessionManagerPanel, SessionSearchPanel, SettingsPanel, TaskPanel,
     TerminalPanel, ToolCallDetailPanel, WelcomePanel,
 };
 use crate::{ShowPanelInfo, ToggleSearch};
@@ -427,6 +428,11 @@ impl DockPanelContainer {
             "SessionManagerPanel" => Self::panel::<SessionManagerPanel>(window, cx),
             "SettingsPanel" => Self::panel::<SettingsPanel>(window, cx),
             "ToolCallDetailPanel" => Self::panel::<ToolCallDetailPanel>(window, cx),
+            "AgentLogsPanel" => Self::panel::<AgentLogsPanel>(window, cx),
+            "ProtocolInspectorPanel" => Self::panel::<ProtocolInspectorPanel>(window, cx),
+            "PermissionQueuePanel" => Self::panel::<PermissionQueuePanel>(window, cx),
+            "PermissionAuditPanel" => Self::panel::<PermissionAuditPanel>(window, cx),
+            "SessionSearchPanel" => Self::panel::<SessionSearchPanel>(window, cx),
             "ConversationPanel" => {
                 if let Some(session_id) = agent_state
                     .session_id
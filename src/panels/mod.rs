@@ -1,9 +1,14 @@
 // Panel-related modules
 
+mod agent_logs_panel;
 pub mod code_editor;
 pub mod conversation;
 pub mod dock_panel;
+mod permission_audit_panel;
+mod permission_queue_panel;
+mod protocol_inspector_panel;
 mod session_manager;
+mod session_search_panel;
 mod settings_panel;
 mod task_panel;
 mod terminal_panel;
@@ -11,10 +16,15 @@ mod tool_call_detail_panel;
 mod welcome_panel;
 
 // Re-export panel types
+pub use agent_logs_panel::AgentLogsPanel;
 pub use code_editor::CodeEditorPanel;
 pub use conversation::ConversationPanel;
 pub use dock_panel::{DockPanel, DockPanelContainer, DockPanelState};
+pub use permission_audit_panel::PermissionAuditPanel;
+pub use permission_queue_panel::PermissionQueuePanel;
+pub use protocol_inspector_panel::ProtocolInspectorPanel;
 pub use session_manager::SessionManagerPanel;
+pub use session_search_panel::SessionSearchPanel;
 pub use settings_panel::{AppSettings, SettingsPanel};
 pub use task_panel::TaskPanel;
 pub use terminal_panel::TerminalPanel;
@@ -0,0 +1,591 @@
+use std::path::PathBuf;
+
+use gpui::{
+    AnyElement, App, AppContext, Context, Entity, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, ParentElement, Render, ScrollHandle, SharedString, StatefulInteractiveElement,
+    Styled, Subscription, Window, div, prelude::FluentBuilder, px,
+};
+use gpui_component::{
+    ActiveTheme, IconName, Selectable, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex,
+};
+use rust_i18n::t;
+
+use crate::{
+    AppState,
+    core::{
+        agent::{PermissionAuditEntry, export_csv, export_json},
+        event_bus::PermissionStatusEvent,
+    },
+    panels::dock_panel::DockPanel,
+};
+
+/// Rows rendered at most; older matching entries stay in the file
+const MAX_VISIBLE_ENTRIES: usize = 500;
+
+/// Resolver labels offered as filters, see `AuditResolver::label`
+const RESOLVER_FILTERS: [&str; 5] = ["user", "rule", "autopilot", "timeout", "cancelled"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DecisionFilter {
+    All,
+    Allowed,
+    Denied,
+}
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Json,
+    Csv,
+}
+
+/// Panel that lists recorded permission decisions of one workspace
+pub struct PermissionAuditPanel {
+    focus_handle: FocusHandle,
+    list_scroll_handle: ScrollHandle,
+    detail_scroll_handle: ScrollHandle,
+    search: Entity<InputState>,
+    /// Workspaces with an audit file; `None` is sessions without one
+    workspaces: Vec<Option<PathBuf>>,
+    selected_workspace: Option<PathBuf>,
+    /// Entries of the selected workspace, oldest first
+    entries: Vec<PermissionAuditEntry>,
+    decision_filter: DecisionFilter,
+    resolver_filter: Option<&'static str>,
+    /// Index into `entries` of the entry shown in the detail view
+    selected_entry: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DockPanel for PermissionAuditPanel {
+    fn title() -> &'static str {
+        "Permission Audit"
+    }
+
+    fn title_key() -> Option<&'static str> {
+        Some("permission_audit_panel.title")
+    }
+
+    fn description() -> &'static str {
+        "Review and export past permission decisions"
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render> {
+        Self::view(window, cx)
+    }
+
+    fn paddings() -> gpui::Pixels {
+        px(0.)
+    }
+}
+
+impl PermissionAuditPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let entity = cx.new(|cx| Self::new(window, cx));
+        Self::subscribe_to_decisions(&entity, cx);
+        entity.update(cx, |this, cx| this.reload(cx));
+        entity
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("permission_audit_panel.search.placeholder").to_string())
+        });
+        let subscription = cx.subscribe(&search, |_this, _input, _event: &InputEvent, cx| {
+            cx.notify();
+        });
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            list_scroll_handle: ScrollHandle::new(),
+            detail_scroll_handle: ScrollHandle::new(),
+            search,
+            workspaces: Vec::new(),
+            selected_workspace: None,
+            entries: Vec::new(),
+            decision_filter: DecisionFilter::All,
+            resolver_filter: None,
+            selected_entry: None,
+            _subscriptions: vec![subscription],
+        }
+    }
+
+    /// Reload when a request is answered; decisions made by rules show up
+    /// on the next refresh
+    fn subscribe_to_decisions(entity: &Entity<Self>, cx: &mut App) {
+        let permission_bus = AppState::global(cx).permission_bus.clone();
+        let weak_entity = entity.downgrade();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let subscription_id = permission_bus.subscribe_status(move |event| {
+            if matches!(event, PermissionStatusEvent::Resolved { .. }) {
+                let _ = tx.send(());
+            }
+        });
+
+        cx.spawn(async move |cx| {
+            while rx.recv().await.is_some() {
+                while rx.try_recv().is_ok() {}
+
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                _ = entity.update(cx, |this, cx| this.reload(cx));
+            }
+            permission_bus.unsubscribe_status(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Read the workspace list and the selected workspace's entries from disk
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let audit = AppState::global(cx).permission_audit.clone();
+        let selected = self.selected_workspace.clone();
+        let task = cx.background_spawn(async move {
+            let workspaces = audit.workspaces();
+            // Keep the selection; fall back to the first workspace
+            let workspace = if workspaces.contains(&selected) {
+                selected
+            } else {
+                workspaces.first().cloned().flatten()
+            };
+            let entries = audit.entries(workspace.as_deref());
+            (workspaces, workspace, entries)
+        });
+
+        cx.spawn(async move |this, cx| {
+            let (workspaces, workspace, entries) = task.await;
+            let entries = entries.unwrap_or_else(|e| {
+                log::error!("Failed to read permission audit log: {}", e);
+                Vec::new()
+            });
+            _ = this.update(cx, |this, cx| {
+                if this.selected_workspace != workspace {
+                    this.selected_entry = None;
+                }
+                this.workspaces = workspaces;
+                this.selected_workspace = workspace;
+                this.entries = entries;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn select_workspace(&mut self, workspace: Option<PathBuf>, cx: &mut Context<Self>) {
+        self.selected_workspace = workspace;
+        self.selected_entry = None;
+        self.reload(cx);
+    }
+
+    /// Indices of entries matching the filters, oldest first
+    fn filtered_indices(&self, cx: &App) -> Vec<usize> {
+        let query = self.search.read(cx).text().to_string().to_lowercase();
+        let query = query.trim();
+
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| match self.decision_filter {
+                DecisionFilter::All => true,
+                DecisionFilter::Allowed => entry.allowed(),
+                DecisionFilter::Denied => !entry.allowed(),
+            })
+            .filter(|(_, entry)| {
+                self.resolver_filter
+                    .is_none_or(|resolver| entry.resolver.label() == resolver)
+            })
+            .filter(|(_, entry)| query.is_empty() || entry_matches(entry, query))
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    /// Save the filtered entries to a file picked by the user
+    fn export(&mut self, format: ExportFormat, cx: &mut Context<Self>) {
+        let entries: Vec<PermissionAuditEntry> = self
+            .filtered_indices(cx)
+            .into_iter()
+            .map(|ix| self.entries[ix].clone())
+            .collect();
+        let (extension, contents) = match format {
+            ExportFormat::Json => match export_json(&entries) {
+                Ok(json) => ("json", json),
+                Err(e) => {
+                    log::error!("Failed to export permission audit log: {}", e);
+                    return;
+                }
+            },
+            ExportFormat::Csv => ("csv", export_csv(&entries)),
+        };
+
+        cx.spawn(async move |_this, _cx| {
+            let file = rfd::AsyncFileDialog::new()
+                .set_title(t!("permission_audit_panel.export.dialog_title").to_string())
+                .add_filter(extension.to_uppercase(), &[extension])
+                .set_file_name(format!("permission-audit.{}", extension))
+                .save_file()
+                .await;
+            let Some(file) = file else {
+                return;
+            };
+            let path = file.path().to_path_buf();
+            match std::fs::write(&path, contents) {
+                Ok(()) => log::info!("Exported permission audit log to {}", path.display()),
+                Err(e) => log::error!("Failed to write {}: {}", path.display(), e),
+            }
+        })
+        .detach();
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> AnyElement {
+        let theme = cx.theme();
+        let decision_button =
+            |id: &'static str, label_key: &'static str, filter: DecisionFilter| {
+                Button::new(id)
+                    .label(t!(label_key).to_string())
+                    .xsmall()
+                    .ghost()
+                    .selected(self.decision_filter == filter)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.decision_filter = filter;
+                        cx.notify();
+                    }))
+            };
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(theme.border)
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .flex_1()
+                            .child(Input::new(&self.search).small().cleanable(true)),
+                    )
+                    .child(
+                        Button::new("permission-audit-refresh")
+                            .icon(IconName::RefreshCw)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("permission_audit_panel.action.refresh").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.reload(cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("permission-audit-export-json")
+                            .label("JSON")
+                            .icon(IconName::ArrowDown)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("permission_audit_panel.action.export_json").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.export(ExportFormat::Json, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("permission-audit-export-csv")
+                            .label("CSV")
+                            .icon(IconName::ArrowDown)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("permission_audit_panel.action.export_csv").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.export(ExportFormat::Csv, cx);
+                            })),
+                    ),
+            )
+            .child(h_flex().w_full().gap_1().flex_wrap().children(
+                self.workspaces.iter().enumerate().map(|(ix, workspace)| {
+                    let label = workspace
+                        .as_ref()
+                        .map(|path| {
+                            path.file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_else(|| path.display().to_string())
+                        })
+                        .unwrap_or_else(|| {
+                            t!("permission_audit_panel.filter.no_workspace").to_string()
+                        });
+                    let workspace = workspace.clone();
+                    Button::new(("permission-audit-workspace", ix))
+                        .label(label)
+                        .xsmall()
+                        .ghost()
+                        .selected(self.selected_workspace == workspace)
+                        .when_some(workspace.clone(), |button, path| {
+                            button.tooltip(path.display().to_string())
+                        })
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.select_workspace(workspace.clone(), cx);
+                        }))
+                }),
+            ))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_1()
+                    .flex_wrap()
+                    .child(decision_button(
+                        "permission-audit-all",
+                        "permission_audit_panel.filter.all",
+                        DecisionFilter::All,
+                    ))
+                    .child(decision_button(
+                        "permission-audit-allowed",
+                        "permission_audit_panel.filter.allowed",
+                        DecisionFilter::Allowed,
+                    ))
+                    .child(decision_button(
+                        "permission-audit-denied",
+                        "permission_audit_panel.filter.denied",
+                        DecisionFilter::Denied,
+                    ))
+                    .child(div().w(px(12.)))
+                    .child(
+                        Button::new("permission-audit-any-resolver")
+                            .label(t!("permission_audit_panel.filter.anyone").to_string())
+                            .xsmall()
+                            .ghost()
+                            .selected(self.resolver_filter.is_none())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.resolver_filter = None;
+                                cx.notify();
+                            })),
+                    )
+                    .children(
+                        RESOLVER_FILTERS
+                            .into_iter()
+                            .enumerate()
+                            .map(|(ix, resolver)| {
+                                Button::new(("permission-audit-resolver", ix))
+                                    .label(resolver_label(resolver))
+                                    .xsmall()
+                                    .ghost()
+                                    .selected(self.resolver_filter == Some(resolver))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.resolver_filter = Some(resolver);
+                                        cx.notify();
+                                    }))
+                            }),
+                    ),
+            )
+            .into_any_element()
+    }
+
+    fn render_entry_row(
+        &self,
+        ix: usize,
+        entry: &PermissionAuditEntry,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let theme = cx.theme();
+        let selected = self.selected_entry == Some(ix);
+        let time: SharedString = entry
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+            .into();
+        let choice = entry
+            .choice
+            .as_ref()
+            .map(|choice| choice.name.clone())
+            .unwrap_or_else(|| t!("permission_audit_panel.decision.cancelled").to_string());
+        let choice_color = if entry.allowed() {
+            theme.green
+        } else {
+            theme.red
+        };
+
+        h_flex()
+            .id(("permission-audit-entry", ix))
+            .w_full()
+            .gap_2()
+            .px_3()
+            .py_0p5()
+            .text_size(px(12.))
+            .when(selected, |this| this.bg(theme.accent))
+            .hover(|this| this.bg(theme.secondary_hover))
+            .cursor_pointer()
+            .child(
+                div()
+                    .w(px(130.))
+                    .flex_shrink_0()
+                    .text_color(theme.muted_foreground)
+                    .child(time),
+            )
+            .child(
+                div()
+                    .w(px(100.))
+                    .flex_shrink_0()
+                    .overflow_hidden()
+                    .text_color(theme.muted_foreground)
+                    .child(entry.agent_name.clone()),
+            )
+            .child(
+                div()
+                    .w(px(64.))
+                    .flex_shrink_0()
+                    .text_color(theme.muted_foreground)
+                    .child(entry.tool_kind().unwrap_or_default()),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .whitespace_nowrap()
+                    .text_color(theme.foreground)
+                    .child(entry.tool_title().to_string()),
+            )
+            .child(
+                div()
+                    .w(px(110.))
+                    .flex_shrink_0()
+                    .overflow_hidden()
+                    .text_color(choice_color)
+                    .child(choice),
+            )
+            .child(
+                div()
+                    .w(px(64.))
+                    .flex_shrink_0()
+                    .text_color(theme.muted_foreground)
+                    .child(resolver_label(entry.resolver.label())),
+            )
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.selected_entry = Some(ix);
+                cx.notify();
+            }))
+            .into_any_element()
+    }
+}
+
+/// Display name of a resolver label, see `AuditResolver::label`
+fn resolver_label(resolver: &str) -> String {
+    t!(format!("permission_audit_panel.resolver.{}", resolver)).to_string()
+}
+
+/// Case-insensitive match of a lowercase query against the entry's tool,
+/// agent, session and chosen option
+fn entry_matches(entry: &PermissionAuditEntry, query: &str) -> bool {
+    let choice = entry.choice.as_ref().map(|choice| choice.name.as_str());
+    let kind = entry.tool_kind();
+    [
+        Some(entry.tool_title()),
+        kind.as_deref(),
+        Some(entry.agent_name.as_str()),
+        Some(entry.session_id.as_str()),
+        choice,
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| field.to_lowercase().contains(query))
+}
+
+impl Focusable for PermissionAuditPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for PermissionAuditPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let toolbar = self.render_toolbar(cx);
+        let indices = self.filtered_indices(cx);
+        let hidden = indices.len().saturating_sub(MAX_VISIBLE_ENTRIES);
+        let total = self.entries.len();
+        let shown = indices.len();
+
+        // Newest first
+        let rows: Vec<AnyElement> = indices
+            .iter()
+            .skip(hidden)
+            .rev()
+            .map(|&ix| self.render_entry_row(ix, &self.entries[ix], cx))
+            .collect();
+        let detail = self
+            .selected_entry
+            .and_then(|ix| self.entries.get(ix))
+            .map(|entry| serde_json::to_string_pretty(entry).unwrap_or_default());
+
+        let theme = cx.theme();
+        v_flex()
+            .size_full()
+            .child(toolbar)
+            .child(
+                div()
+                    .px_3()
+                    .py_1()
+                    .text_size(px(12.))
+                    .text_color(theme.muted_foreground)
+                    .child(
+                        t!("permission_audit_panel.count", shown = shown, total = total)
+                            .to_string(),
+                    )
+                    .when(hidden > 0, |this| {
+                        this.child(
+                            t!("permission_audit_panel.count_hidden", count = hidden).to_string(),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .id("permission-audit-list")
+                    .flex_1()
+                    .w_full()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.list_scroll_handle)
+                    .when(rows.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_3()
+                                .text_size(px(13.))
+                                .text_color(theme.muted_foreground)
+                                .child(t!("permission_audit_panel.empty").to_string()),
+                        )
+                    })
+                    .children(rows),
+            )
+            .when_some(detail, |this, detail| {
+                this.child(
+                    div()
+                        .id("permission-audit-detail")
+                        .h(px(240.))
+                        .w_full()
+                        .p_3()
+                        .border_t_1()
+                        .border_color(theme.border)
+                        .overflow_y_scroll()
+                        .track_scroll(&self.detail_scroll_handle)
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(theme.radius)
+                                .bg(theme.secondary)
+                                .border_1()
+                                .border_color(theme.border)
+                                .text_size(px(12.))
+                                .font_family("Monaco, 'Courier New', monospace")
+                                .line_height(px(18.))
+                                .text_color(theme.foreground)
+                                .whitespace_normal()
+                                .child(detail),
+                        ),
+                )
+            })
+    }
+}
@@ -0,0 +1,414 @@
+use agent_client_protocol as acp;
+use gpui::{
+    App, AppContext, Context, Entity, FocusHandle, Focusable, InteractiveElement, IntoElement,
+    ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window, div,
+    prelude::FluentBuilder, px,
+};
+use gpui_component::{
+    ActiveTheme, Icon, IconName, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex, v_flex,
+};
+use rust_i18n::t;
+
+use crate::{
+    AppState, PanelAction,
+    components::{permission_is_allow, permission_option_kind_to_icon},
+    core::{agent::QueuedPermission, event_bus::PermissionStatusEvent},
+    panels::dock_panel::DockPanel,
+};
+
+/// Pending permission requests of all agents and sessions, answerable from
+/// one place
+pub struct PermissionQueuePanel {
+    focus_handle: FocusHandle,
+    /// Oldest first
+    queue: Vec<QueuedPermission>,
+}
+
+/// Change applied to the queue on the UI thread
+enum QueueUpdate {
+    Added(QueuedPermission),
+    Overdue(String),
+    Resolved(String),
+}
+
+impl DockPanel for PermissionQueuePanel {
+    fn title() -> &'static str {
+        "Permissions"
+    }
+
+    fn title_key() -> Option<&'static str> {
+        Some("permission_queue_panel.title")
+    }
+
+    fn description() -> &'static str {
+        "Answer pending permission requests of all agents"
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render> {
+        Self::view(window, cx)
+    }
+
+    fn paddings() -> gpui::Pixels {
+        px(0.)
+    }
+}
+
+impl PermissionQueuePanel {
+    pub fn view(_window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let entity = cx.new(|cx| Self {
+            focus_handle: cx.focus_handle(),
+            queue: Vec::new(),
+        });
+        Self::subscribe_to_permissions(&entity, cx);
+        Self::load_pending(&entity, cx);
+        entity
+    }
+
+    /// Requests published before the panel was opened
+    fn load_pending(entity: &Entity<Self>, cx: &mut App) {
+        let Some(store) = AppState::global(cx).permission_store().cloned() else {
+            return;
+        };
+        let weak_entity = entity.downgrade();
+        cx.spawn(async move |cx| {
+            let pending = store.pending().await;
+            let Some(entity) = weak_entity.upgrade() else {
+                return;
+            };
+            _ = entity.update(cx, |this, cx| {
+                for queued in pending {
+                    this.apply(QueueUpdate::Added(queued));
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn subscribe_to_permissions(entity: &Entity<Self>, cx: &mut App) {
+        let permission_bus = AppState::global(cx).permission_bus.clone();
+        let weak_entity = entity.downgrade();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<QueueUpdate>();
+        let request_tx = tx.clone();
+        let request_subscription = permission_bus.subscribe(move |event| {
+            let _ = request_tx.send(QueueUpdate::Added(QueuedPermission {
+                request: event.clone(),
+                requested_at: chrono::Utc::now(),
+                overdue: false,
+            }));
+        });
+        let status_subscription = permission_bus.subscribe_status(move |event| {
+            let update = match event {
+                PermissionStatusEvent::Overdue { permission_id, .. } => {
+                    QueueUpdate::Overdue(permission_id.clone())
+                }
+                PermissionStatusEvent::Resolved { permission_id, .. } => {
+                    QueueUpdate::Resolved(permission_id.clone())
+                }
+            };
+            let _ = tx.send(update);
+        });
+
+        cx.spawn(async move |cx| {
+            while let Some(update) = rx.recv().await {
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                _ = entity.update(cx, |this, cx| {
+                    this.apply(update);
+                    cx.notify();
+                });
+            }
+            permission_bus.unsubscribe(request_subscription);
+            permission_bus.unsubscribe_status(status_subscription);
+        })
+        .detach();
+    }
+
+    fn apply(&mut self, update: QueueUpdate) {
+        match update {
+            QueueUpdate::Added(queued) => {
+                let id = &queued.request.permission_id;
+                if !self.queue.iter().any(|q| &q.request.permission_id == id) {
+                    self.queue.push(queued);
+                    self.queue.sort_by_key(|q| q.requested_at);
+                }
+            }
+            QueueUpdate::Overdue(permission_id) => {
+                if let Some(queued) = self
+                    .queue
+                    .iter_mut()
+                    .find(|q| q.request.permission_id == permission_id)
+                {
+                    queued.overdue = true;
+                }
+            }
+            QueueUpdate::Resolved(permission_id) => {
+                self.queue
+                    .retain(|q| q.request.permission_id != permission_id);
+            }
+        }
+    }
+
+    fn respond(
+        &mut self,
+        permission_id: String,
+        option_id: acp::PermissionOptionId,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(store) = AppState::global(cx).permission_store().cloned() else {
+            log::error!("PermissionStore not available in AppState");
+            return;
+        };
+        // Drop the row right away; the resolved event confirms it
+        self.apply(QueueUpdate::Resolved(permission_id.clone()));
+        cx.notify();
+
+        cx.spawn(async move |_entity, _cx| {
+            let response =
+                acp::RequestPermissionResponse::new(acp::RequestPermissionOutcome::Selected(
+                    acp::SelectedPermissionOutcome::new(option_id),
+                ));
+            if let Err(e) = store.respond(&permission_id, response).await {
+                log::error!("Failed to send permission response: {}", e);
+            }
+        })
+        .detach();
+    }
+
+    /// Answer every request with its "reject once" option where it has one
+    fn deny_all(&mut self, cx: &mut Context<Self>) {
+        let rejections: Vec<(String, acp::PermissionOptionId)> = self
+            .queue
+            .iter()
+            .filter_map(|queued| {
+                let option = queued
+                    .request
+                    .options
+                    .iter()
+                    .find(|option| option.kind == acp::PermissionOptionKind::RejectOnce)?;
+                Some((
+                    queued.request.permission_id.clone(),
+                    option.option_id.clone(),
+                ))
+            })
+            .collect();
+        for (permission_id, option_id) in rejections {
+            self.respond(permission_id, option_id, cx);
+        }
+    }
+
+    fn render_request(
+        &self,
+        ix: usize,
+        queued: &QueuedPermission,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+        let request = &queued.request;
+        let title = request
+            .tool_call
+            .fields
+            .title
+            .clone()
+            .unwrap_or_else(|| t!("permission_queue_panel.request.untitled").to_string());
+        let kind = request
+            .tool_call
+            .fields
+            .kind
+            .as_ref()
+            .map(|kind| format!("{:?}", kind));
+        let requested_at: SharedString = queued
+            .requested_at
+            .with_timezone(&chrono::Local)
+            .format("%H:%M:%S")
+            .to_string()
+            .into();
+        let session_id = request.session_id.clone();
+        let short_session: String = session_id.chars().take(8).collect();
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .p_3()
+            .rounded(theme.radius)
+            .border_1()
+            .border_color(if queued.overdue {
+                theme.red
+            } else {
+                theme.border
+            })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(Icon::new(IconName::TriangleAlert).size(px(14.)).text_color(
+                        if queued.overdue {
+                            theme.red
+                        } else {
+                            theme.accent
+                        },
+                    ))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(px(13.))
+                            .font_weight(gpui::FontWeight::SEMIBOLD)
+                            .text_color(theme.foreground)
+                            .child(title),
+                    )
+                    .when(queued.overdue, |this| {
+                        this.child(
+                            div()
+                                .text_size(px(11.))
+                                .text_color(theme.red)
+                                .child(t!("permission_queue_panel.request.overdue").to_string()),
+                        )
+                    })
+                    .child(
+                        Button::new(("permission-queue-open", ix))
+                            .icon(IconName::ArrowRight)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(
+                                t!("permission_queue_panel.request.open_conversation").to_string(),
+                            )
+                            .on_click(move |_, window, cx| {
+                                window.dispatch_action(
+                                    Box::new(PanelAction::show_conversation(Some(
+                                        session_id.clone(),
+                                    ))),
+                                    cx,
+                                );
+                            }),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .pl_6()
+                    .text_size(px(12.))
+                    .text_color(theme.muted_foreground)
+                    .child(request.agent_name.clone())
+                    .child(
+                        t!("permission_queue_panel.request.session", id = short_session)
+                            .to_string(),
+                    )
+                    .when_some(kind, |this, kind| this.child(kind))
+                    .child(requested_at),
+            )
+            .when_some(request.escalation.clone(), |this, reason| {
+                this.child(
+                    div()
+                        .pl_6()
+                        .text_size(px(12.))
+                        .text_color(theme.warning)
+                        .child(
+                            t!(
+                                "permission_queue_panel.request.autopilot_paused",
+                                reason = reason
+                            )
+                            .to_string(),
+                        ),
+                )
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .pl_6()
+                    .flex_wrap()
+                    .children(request.options.iter().map(|option| {
+                        let permission_id = request.permission_id.clone();
+                        let option_id = option.option_id.clone();
+                        let is_allow = permission_is_allow(option.kind);
+                        Button::new(SharedString::from(format!(
+                            "permission-queue-{}-{}",
+                            request.permission_id, option.option_id
+                        )))
+                        .label(option.name.clone())
+                        .icon(permission_option_kind_to_icon(option.kind))
+                        .when(is_allow, |btn| btn.primary())
+                        .when(!is_allow, |btn| btn.ghost())
+                        .small()
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.respond(permission_id.clone(), option_id.clone(), cx);
+                        }))
+                    })),
+            )
+    }
+}
+
+impl Focusable for PermissionQueuePanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for PermissionQueuePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let queue = self.queue.clone();
+        let theme = cx.theme();
+        let header = h_flex()
+            .w_full()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .items_center()
+            .border_b_1()
+            .border_color(theme.border)
+            .child(
+                div()
+                    .flex_1()
+                    .text_size(px(13.))
+                    .text_color(theme.muted_foreground)
+                    .child(t!("permission_queue_panel.pending", count = queue.len()).to_string()),
+            )
+            .child(
+                Button::new("permission-queue-deny-all")
+                    .label(t!("permission_queue_panel.deny_all").to_string())
+                    .icon(IconName::CircleX)
+                    .ghost()
+                    .xsmall()
+                    .disabled(queue.is_empty())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.deny_all(cx);
+                    })),
+            );
+
+        let empty = queue.is_empty();
+        let muted = theme.muted_foreground;
+        let requests: Vec<_> = queue
+            .iter()
+            .enumerate()
+            .map(|(ix, queued)| self.render_request(ix, queued, cx))
+            .collect();
+
+        v_flex().size_full().child(header).child(
+            div()
+                .id("permission-queue-scroll")
+                .flex_1()
+                .w_full()
+                .overflow_y_scroll()
+                .p_3()
+                .child(
+                    v_flex()
+                        .w_full()
+                        .gap_2()
+                        .when(empty, |this| {
+                            this.child(
+                                div()
+                                    .text_size(px(13.))
+                                    .text_color(muted)
+                                    .child(t!("permission_queue_panel.empty").to_string()),
+                            )
+                        })
+                        .children(requests),
+                ),
+        )
+    }
+}
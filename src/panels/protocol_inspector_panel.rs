@@ -0,0 +1,611 @@
+use gpui::{
+    AnyElement, App, AppContext, ClipboardItem, Context, Entity, FocusHandle, Focusable, Hsla,
+    InteractiveElement, IntoElement, ParentElement, Render, ScrollHandle, SharedString,
+    StatefulInteractiveElement, Styled, Subscription, Window, div, prelude::FluentBuilder, px,
+};
+use gpui_component::{
+    ActiveTheme, IconName, Selectable, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex,
+};
+use rust_i18n::t;
+
+use crate::{
+    AppState,
+    core::agent::{ExtensionKind, FrameKind, TraceDirection, TraceFrame, UnknownExtension},
+    panels::dock_panel::DockPanel,
+};
+
+/// Rows rendered at most; older matching frames stay in the buffer
+const MAX_VISIBLE_FRAMES: usize = 500;
+
+/// Panel that lists the JSON-RPC frames exchanged with agents
+pub struct ProtocolInspectorPanel {
+    focus_handle: FocusHandle,
+    list_scroll_handle: ScrollHandle,
+    detail_scroll_handle: ScrollHandle,
+    session_filter: Entity<InputState>,
+    method_filter: Entity<InputState>,
+    /// Sequence number of the selected frame
+    selected_seq: Option<u64>,
+    /// Keep the list scrolled to the newest frame
+    follow: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DockPanel for ProtocolInspectorPanel {
+    fn title() -> &'static str {
+        "Protocol Inspector"
+    }
+
+    fn title_key() -> Option<&'static str> {
+        Some("protocol_inspector_panel.title")
+    }
+
+    fn description() -> &'static str {
+        "Inspect ACP JSON-RPC traffic between the client and agents"
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render> {
+        Self::view(window, cx)
+    }
+
+    fn paddings() -> gpui::Pixels {
+        px(0.)
+    }
+}
+
+impl ProtocolInspectorPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let entity = cx.new(|cx| Self::new(window, cx));
+        Self::subscribe_to_frames(&entity, cx);
+        Self::subscribe_to_unknown_extensions(&entity, cx);
+        entity
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let session_filter = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("protocol_inspector_panel.filter.session").to_string())
+        });
+        let method_filter = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("protocol_inspector_panel.filter.method").to_string())
+        });
+
+        // Re-render when a filter changes
+        let subscriptions = [&session_filter, &method_filter]
+            .into_iter()
+            .map(|input| {
+                cx.subscribe(input, |_this, _input, _event: &InputEvent, cx| {
+                    cx.notify();
+                })
+            })
+            .collect();
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            list_scroll_handle: ScrollHandle::new(),
+            detail_scroll_handle: ScrollHandle::new(),
+            session_filter,
+            method_filter,
+            selected_seq: None,
+            follow: true,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// Re-render as frames are recorded
+    fn subscribe_to_frames(entity: &Entity<Self>, cx: &mut App) {
+        let trace_bus = AppState::global(cx).protocol_trace_bus.clone();
+        let weak_entity = entity.downgrade();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<u64>();
+        let subscription_id = trace_bus.subscribe(move |event| {
+            let _ = tx.send(event.frame.seq);
+        });
+
+        cx.spawn(async move |cx| {
+            while rx.recv().await.is_some() {
+                // Coalesce bursts of traffic into a single refresh
+                while rx.try_recv().is_ok() {}
+
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                _ = entity.update(cx, |this, cx| {
+                    if this.follow {
+                        this.list_scroll_handle.scroll_to_bottom();
+                    }
+                    cx.notify();
+                });
+            }
+            trace_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Re-render when an agent sends an extension nothing handles; these are
+    /// listed even while recording is off
+    fn subscribe_to_unknown_extensions(entity: &Entity<Self>, cx: &mut App) {
+        let extension_bus = AppState::global(cx).extension_bus.clone();
+        let weak_entity = entity.downgrade();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let subscription_id = extension_bus.subscribe(move |_event| {
+            let _ = tx.send(());
+        });
+
+        cx.spawn(async move |cx| {
+            while rx.recv().await.is_some() {
+                while rx.try_recv().is_ok() {}
+
+                let Some(entity) = weak_entity.upgrade() else {
+                    break;
+                };
+                _ = entity.update(cx, |_, cx| cx.notify());
+            }
+            extension_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Frames matching the session and method filters, oldest first
+    fn filtered_frames(&self, frames: &[TraceFrame], cx: &App) -> Vec<TraceFrame> {
+        let session = self.session_filter.read(cx).text().to_string();
+        let session = session.trim();
+        let method = self
+            .method_filter
+            .read(cx)
+            .text()
+            .to_string()
+            .to_lowercase();
+        let method = method.trim();
+
+        frames
+            .iter()
+            .filter(|frame| {
+                session.is_empty()
+                    || frame
+                        .session_id
+                        .as_deref()
+                        .is_some_and(|id| id.contains(session))
+            })
+            .filter(|frame| {
+                method.is_empty()
+                    || frame
+                        .method
+                        .as_deref()
+                        .is_some_and(|m| m.to_lowercase().contains(method))
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn toggle_recording(&mut self, cx: &mut Context<Self>) {
+        let store = &AppState::global(cx).protocol_trace;
+        store.set_enabled(!store.is_enabled());
+        cx.notify();
+    }
+
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        AppState::global(cx).protocol_trace.clear();
+        self.selected_seq = None;
+        cx.notify();
+    }
+
+    fn copy_selected(&mut self, cx: &mut Context<Self>) {
+        let frames = AppState::global(cx).protocol_trace.frames();
+        let Some(frame) = frames.iter().find(|f| Some(f.seq) == self.selected_seq) else {
+            return;
+        };
+        let text = serde_json::to_string_pretty(&frame.payload).unwrap_or_default();
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> AnyElement {
+        let recording = AppState::global(cx).protocol_trace.is_enabled();
+        let theme = cx.theme();
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .items_center()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(theme.border)
+            .child(
+                Button::new("protocol-inspector-record")
+                    .label(
+                        if recording {
+                            t!("protocol_inspector_panel.action.recording")
+                        } else {
+                            t!("protocol_inspector_panel.action.record")
+                        }
+                        .to_string(),
+                    )
+                    .xsmall()
+                    .when(recording, |button| button.danger())
+                    .when(!recording, |button| button.ghost())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.toggle_recording(cx);
+                    })),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .child(Input::new(&self.session_filter).small().cleanable(true)),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .child(Input::new(&self.method_filter).small().cleanable(true)),
+            )
+            .child(
+                Button::new("protocol-inspector-follow")
+                    .icon(IconName::ArrowDown)
+                    .ghost()
+                    .xsmall()
+                    .selected(self.follow)
+                    .tooltip(t!("protocol_inspector_panel.action.follow").to_string())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.follow = !this.follow;
+                        if this.follow {
+                            this.list_scroll_handle.scroll_to_bottom();
+                        }
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("protocol-inspector-copy")
+                    .icon(IconName::Copy)
+                    .ghost()
+                    .xsmall()
+                    .tooltip(t!("protocol_inspector_panel.action.copy").to_string())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.copy_selected(cx);
+                    })),
+            )
+            .child(
+                Button::new("protocol-inspector-clear")
+                    .icon(IconName::Delete)
+                    .ghost()
+                    .xsmall()
+                    .tooltip(t!("protocol_inspector_panel.action.clear").to_string())
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.clear(cx);
+                    })),
+            )
+            .into_any_element()
+    }
+
+    fn render_unknown_extensions(&self, unknown: &[UnknownExtension], cx: &App) -> AnyElement {
+        let theme = cx.theme();
+        let summary = unknown
+            .iter()
+            .map(|extension| {
+                let kind = match extension.kind {
+                    ExtensionKind::Method => t!("protocol_inspector_panel.kind.request"),
+                    ExtensionKind::Notification => t!("protocol_inspector_panel.kind.notification"),
+                };
+                format!(
+                    "{} ({}, {}) ×{}",
+                    extension.method, extension.agent_name, kind, extension.count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" · ");
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .border_b_1()
+            .border_color(theme.border)
+            .bg(theme.secondary)
+            .text_size(px(12.))
+            .child(
+                div()
+                    .flex_shrink_0()
+                    .text_color(theme.warning)
+                    .child(t!("protocol_inspector_panel.unhandled_extensions").to_string()),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .whitespace_nowrap()
+                    .text_color(theme.muted_foreground)
+                    .child(summary),
+            )
+            .into_any_element()
+    }
+
+    fn render_frame_row(
+        &self,
+        frame: &TraceFrame,
+        paired_seq: Option<u64>,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let theme = cx.theme();
+        let seq = frame.seq;
+        let selected = self.selected_seq == Some(seq);
+        // Highlight the other half of the selected request/response pair
+        let paired = paired_seq.is_some() && paired_seq == self.selected_seq;
+
+        let arrow = match frame.direction {
+            TraceDirection::Outgoing => "→",
+            TraceDirection::Incoming => "←",
+        };
+        let kind_color: Hsla = match frame.kind {
+            FrameKind::Request => theme.blue,
+            FrameKind::Response => theme.green,
+            FrameKind::Error | FrameKind::Invalid => theme.red,
+            FrameKind::Notification => theme.muted_foreground,
+        };
+        let kind = match frame.kind {
+            FrameKind::Request => t!("protocol_inspector_panel.kind.request"),
+            FrameKind::Response => t!("protocol_inspector_panel.kind.response"),
+            FrameKind::Error => t!("protocol_inspector_panel.kind.error"),
+            FrameKind::Notification => t!("protocol_inspector_panel.kind.notification"),
+            FrameKind::Invalid => t!("protocol_inspector_panel.kind.invalid"),
+        }
+        .to_string();
+        let time: SharedString = frame
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%H:%M:%S%.3f")
+            .to_string()
+            .into();
+        let latency = frame
+            .latency_ms
+            .map(|ms| format!("{:.1} ms", ms))
+            .unwrap_or_default();
+
+        h_flex()
+            .id(("protocol-frame", seq as usize))
+            .w_full()
+            .gap_2()
+            .px_3()
+            .py_0p5()
+            .text_size(px(12.))
+            .font_family("Monaco, 'Courier New', monospace")
+            .when(selected, |this| this.bg(theme.accent))
+            .when(paired && !selected, |this| this.bg(theme.secondary))
+            .hover(|this| this.bg(theme.secondary_hover))
+            .cursor_pointer()
+            .child(
+                div()
+                    .w(px(90.))
+                    .flex_shrink_0()
+                    .text_color(theme.muted_foreground)
+                    .child(time),
+            )
+            .child(div().w(px(16.)).flex_shrink_0().child(arrow))
+            .child(
+                div()
+                    .w(px(100.))
+                    .flex_shrink_0()
+                    .overflow_hidden()
+                    .text_color(theme.muted_foreground)
+                    .child(frame.agent_name.clone()),
+            )
+            .child(
+                div()
+                    .w(px(64.))
+                    .flex_shrink_0()
+                    .text_color(kind_color)
+                    .child(kind),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .text_color(theme.foreground)
+                    .child(frame.method.clone().unwrap_or_default()),
+            )
+            .child(
+                div()
+                    .w(px(48.))
+                    .flex_shrink_0()
+                    .text_color(theme.muted_foreground)
+                    .child(
+                        frame
+                            .id
+                            .clone()
+                            .map(|id| format!("#{}", id))
+                            .unwrap_or_default(),
+                    ),
+            )
+            .child(
+                div()
+                    .w(px(72.))
+                    .flex_shrink_0()
+                    .text_color(theme.muted_foreground)
+                    .child(latency),
+            )
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.selected_seq = Some(seq);
+                this.follow = false;
+                cx.notify();
+            }))
+            .into_any_element()
+    }
+
+    fn render_frame_detail(&self, title: &str, frame: &TraceFrame, cx: &App) -> AnyElement {
+        let theme = cx.theme();
+        let json = serde_json::to_string_pretty(&frame.payload).unwrap_or_default();
+        let mut summary = format!("{} · {} · #{}", title, frame.agent_name, frame.seq);
+        if let Some(session_id) = &frame.session_id {
+            summary.push_str(" · ");
+            summary.push_str(&t!(
+                "protocol_inspector_panel.detail.session",
+                id = session_id
+            ));
+        }
+        if let Some(latency) = frame.latency_ms {
+            summary.push_str(&format!(" · {:.1} ms", latency));
+        }
+
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .gap_1()
+            .child(
+                div()
+                    .text_size(px(12.))
+                    .text_color(theme.muted_foreground)
+                    .child(summary),
+            )
+            .child(
+                div()
+                    .p_2()
+                    .rounded(theme.radius)
+                    .bg(theme.secondary)
+                    .border_1()
+                    .border_color(theme.border)
+                    .text_size(px(12.))
+                    .font_family("Monaco, 'Courier New', monospace")
+                    .line_height(px(18.))
+                    .text_color(theme.foreground)
+                    .whitespace_normal()
+                    .child(json),
+            )
+            .into_any_element()
+    }
+}
+
+impl Focusable for ProtocolInspectorPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ProtocolInspectorPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let toolbar = self.render_toolbar(cx);
+        let unknown_extensions = AppState::global(cx).protocol_trace.unknown_extensions();
+        let unknown_extensions = (!unknown_extensions.is_empty())
+            .then(|| self.render_unknown_extensions(&unknown_extensions, cx));
+        let all_frames = AppState::global(cx).protocol_trace.frames();
+        let frames = self.filtered_frames(&all_frames, cx);
+        let hidden = frames.len().saturating_sub(MAX_VISIBLE_FRAMES);
+
+        // Pair the selected frame with its request or response
+        let selected = self
+            .selected_seq
+            .and_then(|seq| all_frames.iter().find(|frame| frame.seq == seq));
+        let counterpart = selected.and_then(|selected| match selected.request_seq {
+            Some(request_seq) => all_frames.iter().find(|frame| frame.seq == request_seq),
+            None => all_frames
+                .iter()
+                .find(|frame| frame.request_seq == Some(selected.seq)),
+        });
+
+        let rows: Vec<AnyElement> = frames
+            .iter()
+            .skip(hidden)
+            .map(|frame| {
+                let paired_seq = frame.request_seq.or_else(|| {
+                    selected
+                        .filter(|selected| selected.request_seq == Some(frame.seq))
+                        .map(|selected| selected.seq)
+                });
+                self.render_frame_row(frame, paired_seq, cx)
+            })
+            .collect();
+
+        let detail = selected.map(|selected| {
+            let (request, response) = if selected.request_seq.is_some() {
+                (counterpart, Some(selected))
+            } else {
+                (Some(selected), counterpart)
+            };
+            h_flex()
+                .w_full()
+                .gap_3()
+                .items_start()
+                .children(
+                    request.map(|frame| self.render_frame_detail(&frame_title(frame), frame, cx)),
+                )
+                .children(
+                    response.map(|frame| self.render_frame_detail(&frame_title(frame), frame, cx)),
+                )
+        });
+
+        let theme = cx.theme();
+        let empty_text = if AppState::global(cx).protocol_trace.is_enabled() {
+            t!("protocol_inspector_panel.empty.waiting")
+        } else {
+            t!("protocol_inspector_panel.empty.recording_off")
+        }
+        .to_string();
+
+        v_flex()
+            .size_full()
+            .child(toolbar)
+            .children(unknown_extensions)
+            .child(
+                div()
+                    .id("protocol-inspector-list")
+                    .flex_1()
+                    .w_full()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.list_scroll_handle)
+                    .when(hidden > 0, |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .text_size(px(12.))
+                                .text_color(theme.muted_foreground)
+                                .child(
+                                    t!("protocol_inspector_panel.hidden_frames", count = hidden)
+                                        .to_string(),
+                                ),
+                        )
+                    })
+                    .when(rows.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_3()
+                                .text_size(px(13.))
+                                .text_color(theme.muted_foreground)
+                                .child(empty_text),
+                        )
+                    })
+                    .children(rows),
+            )
+            .when_some(detail, |this, detail| {
+                this.child(
+                    div()
+                        .id("protocol-inspector-detail")
+                        .h(px(240.))
+                        .w_full()
+                        .p_3()
+                        .border_t_1()
+                        .border_color(theme.border)
+                        .overflow_y_scroll()
+                        .track_scroll(&self.detail_scroll_handle)
+                        .child(detail),
+                )
+            })
+    }
+}
+
+fn frame_title(frame: &TraceFrame) -> String {
+    match frame.kind {
+        FrameKind::Request => t!("protocol_inspector_panel.detail.request"),
+        FrameKind::Response => t!("protocol_inspector_panel.detail.response"),
+        FrameKind::Error => t!("protocol_inspector_panel.detail.error"),
+        FrameKind::Notification => t!("protocol_inspector_panel.detail.notification"),
+        FrameKind::Invalid => t!("protocol_inspector_panel.detail.invalid"),
+    }
+    .to_string()
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use gpui::{
     App, AppContext, Context, Entity, FocusHandle, Focusable, IntoElement, ParentElement, Pixels,
     Render, Styled, Window, prelude::FluentBuilder, px,
@@ -8,10 +10,14 @@ use gpui_component::{
     button::{Button, ButtonVariants},
     h_flex, v_flex,
 };
+use rust_i18n::t;
 
 use crate::{
-    AppState,
-    core::services::{AgentSessionInfo, SessionStatus},
+    AppState, SessionExportButton,
+    core::services::{
+        AgentSessionInfo, ImportedSession, SessionExportOptions, SessionStats, SessionStatus,
+        format_duration,
+    },
     panels::dock_panel::DockPanel,
 };
 
@@ -19,6 +25,14 @@ use crate::{
 pub struct SessionManagerPanel {
     focus_handle: FocusHandle,
     sessions_by_agent: Vec<(String, Vec<AgentSessionInfo>)>,
+    /// Sessions imported for read-only review
+    imported_sessions: Vec<ImportedSession>,
+    /// Options of the export menus
+    export_options: SessionExportOptions,
+    /// Sessions excluded from the retention policy
+    pinned_sessions: Vec<String>,
+    /// Message, tool call and timing statistics by session
+    session_stats: HashMap<String, SessionStats>,
 }
 
 impl DockPanel for SessionManagerPanel {
@@ -52,6 +66,10 @@ impl SessionManagerPanel {
         let mut panel = Self {
             focus_handle: cx.focus_handle(),
             sessions_by_agent: Vec::new(),
+            imported_sessions: Vec::new(),
+            export_options: SessionExportOptions::default(),
+            pinned_sessions: Vec::new(),
+            session_stats: HashMap::new(),
         };
 
         // Load initial session data
@@ -62,6 +80,9 @@ impl SessionManagerPanel {
 
     /// Refresh sessions from AgentService
     fn refresh_sessions(&mut self, cx: &mut Context<Self>) {
+        self.refresh_imported_sessions(cx);
+        self.refresh_pinned_sessions(cx);
+
         let agent_service = match AppState::global(cx).agent_service() {
             Some(service) => service.clone(),
             None => {
@@ -70,6 +91,10 @@ impl SessionManagerPanel {
             }
         };
 
+        let stats_collector = AppState::global(cx)
+            .persistence_service()
+            .map(|service| service.stats().clone());
+
         let weak_self = cx.entity().downgrade();
         cx.spawn(async move |_entity, cx| {
             // Get all agents
@@ -84,10 +109,32 @@ impl SessionManagerPanel {
                 }
             }
 
+            // Stats of sessions that have not run since start are read from disk
+            let session_ids: Vec<String> = sessions_by_agent
+                .iter()
+                .flat_map(|(_, sessions)| sessions.iter().map(|s| s.session_id.clone()))
+                .collect();
+            let session_stats = match stats_collector {
+                Some(collector) => {
+                    smol::unblock(move || {
+                        session_ids
+                            .into_iter()
+                            .map(|id| {
+                                let stats = collector.stats(&id);
+                                (id, stats)
+                            })
+                            .collect()
+                    })
+                    .await
+                }
+                None => HashMap::new(),
+            };
+
             _ = cx.update(|cx| {
                 if let Some(this) = weak_self.upgrade() {
                     this.update(cx, |this, cx| {
                         this.sessions_by_agent = sessions_by_agent;
+                        this.session_stats = session_stats;
                         cx.notify();
                     });
                 }
@@ -96,6 +143,140 @@ impl SessionManagerPanel {
         .detach();
     }
 
+    /// Reload the list of imported sessions
+    fn refresh_imported_sessions(&mut self, cx: &mut Context<Self>) {
+        let Some(persistence_service) = AppState::global(cx).persistence_service().cloned() else {
+            log::warn!("[SessionManagerPanel] PersistenceService not initialized");
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let imported_sessions = match persistence_service.imported_sessions().await {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    log::error!(
+                        "[SessionManagerPanel] Failed to list imported sessions: {}",
+                        e
+                    );
+                    return;
+                }
+            };
+            _ = this.update(cx, |this, cx| {
+                this.imported_sessions = imported_sessions;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Reload the sessions pinned against retention
+    fn refresh_pinned_sessions(&mut self, cx: &mut Context<Self>) {
+        let Some(agent_config_service) = AppState::global(cx).agent_config_service().cloned()
+        else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let pinned_sessions = agent_config_service
+                .retention_config()
+                .await
+                .pinned_sessions;
+            _ = this.update(cx, |this, cx| {
+                this.pinned_sessions = pinned_sessions;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Pin or unpin a session so the retention policy never expires it
+    fn toggle_pinned(&mut self, session_id: String, cx: &mut Context<Self>) {
+        let Some(agent_config_service) = AppState::global(cx).agent_config_service().cloned()
+        else {
+            log::warn!("[SessionManagerPanel] AgentConfigService not initialized");
+            return;
+        };
+
+        let pinned = !self.pinned_sessions.contains(&session_id);
+        if pinned {
+            self.pinned_sessions.push(session_id.clone());
+        } else {
+            self.pinned_sessions.retain(|id| *id != session_id);
+        }
+        cx.notify();
+
+        cx.spawn(async move |_this, _cx| {
+            if let Err(e) = agent_config_service
+                .set_session_pinned(&session_id, pinned)
+                .await
+            {
+                log::error!(
+                    "[SessionManagerPanel] Failed to pin session {}: {}",
+                    session_id,
+                    e
+                );
+            }
+        })
+        .detach();
+    }
+
+    /// Pick an exported bundle or session file and import it for review
+    fn import_session(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(persistence_service) = AppState::global(cx).persistence_service().cloned() else {
+            log::error!("[SessionManagerPanel] PersistenceService not initialized");
+            return;
+        };
+
+        cx.spawn_in(window, async move |this, window| {
+            let file = rfd::AsyncFileDialog::new()
+                .set_title("Import session")
+                .add_filter("Session", &["json", "jsonl"])
+                .pick_file()
+                .await;
+            let Some(file) = file else {
+                return;
+            };
+
+            match persistence_service.import_session(file.path()).await {
+                Ok(imported) => {
+                    _ = this.update_in(window, |this, window, cx| {
+                        this.refresh_imported_sessions(cx);
+                        this.open_session(imported.session_id, window, cx);
+                    });
+                }
+                Err(e) => {
+                    log::error!("[SessionManagerPanel] Failed to import session: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Delete an imported session; sessions of agents are never touched
+    fn remove_imported_session(&mut self, session_id: String, cx: &mut Context<Self>) {
+        let Some(persistence_service) = AppState::global(cx).persistence_service().cloned() else {
+            log::error!("[SessionManagerPanel] PersistenceService not initialized");
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            if let Err(e) = persistence_service
+                .remove_imported_session(&session_id)
+                .await
+            {
+                log::error!(
+                    "[SessionManagerPanel] Failed to remove imported session {}: {}",
+                    session_id,
+                    e
+                );
+            }
+            _ = this.update(cx, |this, cx| {
+                this.refresh_imported_sessions(cx);
+            });
+        })
+        .detach();
+    }
+
     /// Create a new session for the given agent
     fn create_new_session(
         &mut self,
@@ -116,13 +297,9 @@ impl SessionManagerPanel {
         let weak_self = cx.entity().downgrade();
         cx.spawn_in(window, async move |_this, window| {
             let mcp_servers = if let Some(service) = agent_config_service {
-                service
-                    .list_mcp_servers()
+                agent_service
+                    .mcp_servers_for_agent(&agent_name, service.list_mcp_servers().await)
                     .await
-                    .into_iter()
-                    .filter(|(_, config)| config.enabled)
-                    .map(|(name, config)| config.to_acp_mcp_server(name))
-                    .collect()
             } else {
                 Vec::new()
             };
@@ -205,6 +382,113 @@ impl SessionManagerPanel {
         );
     }
 
+    /// Group of the sessions imported for review, below the agents
+    fn render_imported_sessions(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .p_3()
+            .rounded(px(8.))
+            .bg(theme.secondary)
+            .border_1()
+            .border_color(theme.border)
+            .child(
+                gpui::div()
+                    .text_sm()
+                    .font_weight(gpui::FontWeight::SEMIBOLD)
+                    .text_color(theme.foreground)
+                    .child(format!(
+                        "Imported ({} sessions)",
+                        self.imported_sessions.len()
+                    )),
+            )
+            .children(
+                self.imported_sessions
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, session)| {
+                        let session_id_for_open = session.session_id.clone();
+                        let session_id_for_remove = session.session_id.clone();
+                        let source = session
+                            .source
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| session.source.display().to_string());
+                        let details = match &session.agent_name {
+                            Some(agent) => format!(
+                                "{} | {} messages | from {}",
+                                agent, session.message_count, source
+                            ),
+                            None => format!("{} messages | from {}", session.message_count, source),
+                        };
+
+                        h_flex()
+                            .w_full()
+                            .items_center()
+                            .justify_between()
+                            .gap_2()
+                            .p_2()
+                            .rounded(px(6.))
+                            .bg(theme.background)
+                            .border_1()
+                            .border_color(theme.border.opacity(0.5))
+                            .child(
+                                v_flex()
+                                    .flex_1()
+                                    .gap_1()
+                                    .overflow_hidden()
+                                    .child(
+                                        gpui::div()
+                                            .text_xs()
+                                            .font_weight(gpui::FontWeight::MEDIUM)
+                                            .text_color(theme.foreground)
+                                            .overflow_hidden()
+                                            .text_ellipsis()
+                                            .whitespace_nowrap()
+                                            .child(session.title.clone()),
+                                    )
+                                    .child(
+                                        gpui::div()
+                                            .text_xs()
+                                            .text_color(theme.muted_foreground)
+                                            .child(details),
+                                    ),
+                            )
+                            .child(
+                                h_flex()
+                                    .gap_1()
+                                    .child(
+                                        Button::new(("open-imported", ix))
+                                            .label("Open")
+                                            .ghost()
+                                            .small()
+                                            .on_click(cx.listener(move |this, _, window, cx| {
+                                                this.open_session(
+                                                    session_id_for_open.clone(),
+                                                    window,
+                                                    cx,
+                                                );
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new(("remove-imported", ix))
+                                            .label("Remove")
+                                            .ghost()
+                                            .small()
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                this.remove_imported_session(
+                                                    session_id_for_remove.clone(),
+                                                    cx,
+                                                );
+                                            })),
+                                    ),
+                            )
+                    }),
+            )
+    }
+
     /// Get status badge color
     fn status_color(&self, status: &SessionStatus, cx: &App) -> gpui::Hsla {
         let theme = cx.theme();
@@ -216,6 +500,11 @@ impl SessionManagerPanel {
             SessionStatus::Failed => theme.muted,
             SessionStatus::InProgress => theme.info,
             SessionStatus::Pending => theme.info,
+            SessionStatus::Refused => theme.red,
+            SessionStatus::MaxTokens => theme.warning,
+            SessionStatus::MaxTurnRequests => theme.warning,
+            SessionStatus::Cancelled => theme.muted,
+            SessionStatus::TimedOut => theme.red,
         }
     }
 
@@ -229,7 +518,62 @@ impl SessionManagerPanel {
             SessionStatus::Completed => "Completed",
             SessionStatus::Failed => "Failed",
             SessionStatus::Pending => "Pending",
+            SessionStatus::Refused => "Refused",
+            SessionStatus::MaxTokens => "Token limit reached",
+            SessionStatus::MaxTurnRequests => "Request limit reached",
+            SessionStatus::Cancelled => "Cancelled",
+            SessionStatus::TimedOut => "Timed out",
+        }
+    }
+
+    /// One line summary of a session's statistics
+    fn stats_summary(stats: &SessionStats) -> String {
+        let mut parts = vec![
+            t!(
+                "session_manager.stats.messages",
+                count = stats.message_count()
+            )
+            .to_string(),
+            t!(
+                "session_manager.stats.tool_calls",
+                count = stats.tool_call_count()
+            )
+            .to_string(),
+        ];
+        let failed = stats.failed_tool_calls();
+        if failed > 0 {
+            parts.push(t!("session_manager.stats.failed", count = failed).to_string());
+        }
+        if let Some(average) = stats.average_turn() {
+            parts.push(
+                t!(
+                    "session_manager.stats.turns",
+                    count = stats.prompt_turns,
+                    average = format_duration(average)
+                )
+                .to_string(),
+            );
         }
+        if stats.permission_requests > 0 {
+            parts.push(
+                t!(
+                    "session_manager.stats.permission_wait",
+                    wait = format_duration(stats.permission_wait())
+                )
+                .to_string(),
+            );
+        }
+        if stats.lines_added + stats.lines_removed > 0 {
+            parts.push(
+                t!(
+                    "session_manager.stats.lines",
+                    added = stats.lines_added,
+                    removed = stats.lines_removed
+                )
+                .to_string(),
+            );
+        }
+        parts.join(" · ")
     }
 }
 
@@ -261,13 +605,28 @@ impl Render for SessionManagerPanel {
                             .child("Sessions"),
                     )
                     .child(
-                        Button::new("refresh")
-                            .icon(Icon::new(IconName::LoaderCircle))
-                            .ghost()
-                            .small()
-                            .on_click(cx.listener(|this, _, _window, cx| {
-                                this.refresh_sessions(cx);
-                            })),
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Button::new("import")
+                                    .label("Import")
+                                    .icon(Icon::new(IconName::FolderOpen))
+                                    .ghost()
+                                    .small()
+                                    .tooltip("Open an exported session for read-only review")
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.import_session(window, cx);
+                                    })),
+                            )
+                            .child(
+                                Button::new("refresh")
+                                    .icon(Icon::new(IconName::LoaderCircle))
+                                    .ghost()
+                                    .small()
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.refresh_sessions(cx);
+                                    })),
+                            ),
                     ),
             )
             .child(
@@ -324,6 +683,14 @@ impl Render for SessionManagerPanel {
                                                 let agent_name_for_close = agent_name_clone.clone();
                                                 let session_id_for_close = session_id.clone();
                                                 let session_id_for_open = session_id.clone();
+                                                let session_id_for_export = session_id.clone();
+                                                let session_id_for_pin = session_id.clone();
+                                                let pinned = self.pinned_sessions.contains(&session_id);
+                                                let stats_summary = self
+                                                    .session_stats
+                                                    .get(&session_id)
+                                                    .filter(|stats| !stats.is_empty())
+                                                    .map(Self::stats_summary);
                                                 let status_color = self.status_color(&session.status, cx);
                                                 let short_id = if session_id.len() > 12 {
                                                     &session_id[..12]
@@ -372,7 +739,15 @@ impl Render for SessionManagerPanel {
                                                                                 Self::status_text(&session.status),
                                                                                 session.last_active.format("%H:%M:%S")
                                                                             )),
-                                                                    ),
+                                                                    )
+                                                                    .when_some(stats_summary, |this, summary| {
+                                                                        this.child(
+                                                                            gpui::div()
+                                                                                .text_xs()
+                                                                                .text_color(theme.muted_foreground)
+                                                                                .child(summary),
+                                                                        )
+                                                                    }),
                                                             ),
                                                     )
                                                     .child(
@@ -387,6 +762,25 @@ impl Render for SessionManagerPanel {
                                                                         this.open_session(session_id_for_open.clone(), window, cx);
                                                                     })),
                                                             )
+                                                            .child(
+                                                                Button::new(("pin", btn_id))
+                                                                    .label(if pinned { "Unpin" } else { "Pin" })
+                                                                    .when(pinned, |this| this.icon(IconName::Check))
+                                                                    .ghost()
+                                                                    .small()
+                                                                    .tooltip("Pinned sessions never expire")
+                                                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                                                        this.toggle_pinned(session_id_for_pin.clone(), cx);
+                                                                    })),
+                                                            )
+                                                            .child(
+                                                                SessionExportButton::new(("export", btn_id), session_id_for_export)
+                                                                    .options(self.export_options)
+                                                                    .on_options_change(cx.listener(|this, options: &SessionExportOptions, _, cx| {
+                                                                        this.export_options = *options;
+                                                                        cx.notify();
+                                                                    })),
+                                                            )
                                                             .when(session.status != SessionStatus::Closed, |this| {
                                                                 this.child(
                                                                     Button::new(("close", btn_id))
@@ -405,7 +799,10 @@ impl Render for SessionManagerPanel {
                                                     )
                                             })),
                                     )
-                            })),
+                            }))
+                            .when(!self.imported_sessions.is_empty(), |this| {
+                                this.child(self.render_imported_sessions(cx))
+                            }),
                     ),
             )
     }
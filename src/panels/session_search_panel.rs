@@ -0,0 +1,383 @@
+use gpui::{
+    AnyElement, App, AppContext, Context, Entity, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, ParentElement, Render, ScrollHandle, SharedString, StatefulInteractiveElement,
+    Styled, Subscription, Window, div, prelude::FluentBuilder, px,
+};
+use gpui_component::{
+    ActiveTheme, IconName, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex,
+};
+use rust_i18n::t;
+
+use crate::{
+    AppState, PanelAction,
+    core::services::{SearchHit, SessionIndex, SessionMetadata},
+    panels::dock_panel::DockPanel,
+};
+
+/// Matching messages shown at most
+const MAX_RESULTS: usize = 200;
+
+/// Panel that searches the messages of all persisted sessions
+pub struct SessionSearchPanel {
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    search: Entity<InputState>,
+    /// Query the current results belong to
+    query: String,
+    hits: Vec<SearchHit>,
+    /// Sessions listed while the query is empty, most recent first
+    recent_sessions: Vec<SessionMetadata>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DockPanel for SessionSearchPanel {
+    fn title() -> &'static str {
+        "Search Sessions"
+    }
+
+    fn title_key() -> Option<&'static str> {
+        Some("session_search_panel.title")
+    }
+
+    fn description() -> &'static str {
+        "Search messages across all conversation history"
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render> {
+        Self::view(window, cx)
+    }
+
+    fn paddings() -> gpui::Pixels {
+        px(0.)
+    }
+}
+
+impl SessionSearchPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let entity = cx.new(|cx| Self::new(window, cx));
+        entity.update(cx, |this, cx| this.run_search(cx));
+        entity
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("session_search_panel.search.placeholder").to_string())
+        });
+        let subscription = cx.subscribe(&search, |this, _input, event: &InputEvent, cx| {
+            if matches!(event, InputEvent::Change) {
+                this.run_search(cx);
+            }
+        });
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            search,
+            query: String::new(),
+            hits: Vec::new(),
+            recent_sessions: Vec::new(),
+            _subscriptions: vec![subscription],
+        }
+    }
+
+    fn index(cx: &App) -> Option<SessionIndex> {
+        AppState::global(cx)
+            .persistence_service()
+            .map(|service| service.index().clone())
+    }
+
+    /// Search the index for the current query in the background
+    fn run_search(&mut self, cx: &mut Context<Self>) {
+        let Some(index) = Self::index(cx) else {
+            log::error!("PersistenceService not initialized, cannot search sessions");
+            return;
+        };
+        let query = self.search.read(cx).text().to_string().trim().to_string();
+        let task = cx.background_spawn({
+            let query = query.clone();
+            async move {
+                if query.is_empty() {
+                    (Vec::new(), index.sessions())
+                } else {
+                    (index.search(&query, MAX_RESULTS), Vec::new())
+                }
+            }
+        });
+
+        cx.spawn(async move |this, cx| {
+            let (hits, recent_sessions) = task.await;
+            _ = this.update(cx, |this, cx| {
+                // Drop results of a query that has been edited since
+                if this.search.read(cx).text().to_string().trim() != query {
+                    return;
+                }
+                this.query = query;
+                this.hits = hits;
+                this.recent_sessions = recent_sessions;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn render_hit(&self, ix: usize, hit: &SearchHit, cx: &mut Context<Self>) -> AnyElement {
+        let theme = cx.theme();
+        let session_id = hit.session.session_id.clone();
+        let message_index = hit.message_index;
+
+        v_flex()
+            .id(("session-search-hit", ix))
+            .w_full()
+            .gap_0p5()
+            .px_3()
+            .py_1p5()
+            .border_b_1()
+            .border_color(theme.border.opacity(0.5))
+            .hover(|this| this.bg(theme.secondary_hover))
+            .cursor_pointer()
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .text_size(px(12.))
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .whitespace_nowrap()
+                            .text_color(theme.foreground)
+                            .child(hit.session.display_title()),
+                    )
+                    .child(
+                        div()
+                            .flex_shrink_0()
+                            .text_color(theme.muted_foreground)
+                            .child(format_timestamp(&hit.timestamp)),
+                    ),
+            )
+            .child(
+                div()
+                    .text_size(px(12.))
+                    .text_color(theme.foreground.opacity(0.85))
+                    .child(hit.snippet.clone()),
+            )
+            .child(session_details(
+                Some(hit.kind.label()),
+                &hit.session,
+                theme.muted_foreground,
+            ))
+            .on_click(move |_, window, cx| {
+                window.dispatch_action(
+                    Box::new(PanelAction::show_conversation_message(
+                        session_id.clone(),
+                        message_index,
+                    )),
+                    cx,
+                );
+            })
+            .into_any_element()
+    }
+
+    fn render_session(
+        &self,
+        ix: usize,
+        session: &SessionMetadata,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let theme = cx.theme();
+        let session_id = session.session_id.clone();
+
+        v_flex()
+            .id(("session-search-session", ix))
+            .w_full()
+            .gap_0p5()
+            .px_3()
+            .py_1p5()
+            .border_b_1()
+            .border_color(theme.border.opacity(0.5))
+            .hover(|this| this.bg(theme.secondary_hover))
+            .cursor_pointer()
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .text_size(px(12.))
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .whitespace_nowrap()
+                            .text_color(theme.foreground)
+                            .child(session.display_title()),
+                    )
+                    .when_some(session.updated_at.as_deref(), |this, updated_at| {
+                        this.child(
+                            div()
+                                .flex_shrink_0()
+                                .text_color(theme.muted_foreground)
+                                .child(format_timestamp(updated_at)),
+                        )
+                    }),
+            )
+            .child(session_details(None, session, theme.muted_foreground))
+            .on_click(move |_, window, cx| {
+                window.dispatch_action(
+                    Box::new(PanelAction::show_conversation(Some(session_id.clone()))),
+                    cx,
+                );
+            })
+            .into_any_element()
+    }
+}
+
+/// Kind, agent, workspace, size and archive state of a session on one muted line
+fn session_details(
+    kind: Option<&'static str>,
+    session: &SessionMetadata,
+    color: gpui::Hsla,
+) -> impl IntoElement {
+    let kind = kind.map(|kind| t!(format!("session_search_panel.kind.{}", kind)).to_string());
+    let workspace = session.workspace.as_ref().map(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string())
+    });
+
+    h_flex()
+        .gap_2()
+        .text_size(px(11.))
+        .text_color(color)
+        .when_some(kind, |this, kind| this.child(kind))
+        .when_some(session.agent_name.clone(), |this, agent| this.child(agent))
+        .when_some(workspace, |this, workspace| this.child(workspace))
+        .child(
+            t!(
+                "session_search_panel.session.messages",
+                count = session.message_count
+            )
+            .to_string(),
+        )
+        .when(session.archived, |this| {
+            this.child(t!("session_search_panel.session.archived").to_string())
+        })
+}
+
+/// Local time of an ISO 8601 timestamp, or the timestamp as is
+fn format_timestamp(timestamp: &str) -> SharedString {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+        .into()
+}
+
+impl Focusable for SessionSearchPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for SessionSearchPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let searching = !self.query.is_empty();
+        let rows: Vec<AnyElement> = if searching {
+            self.hits
+                .iter()
+                .enumerate()
+                .map(|(ix, hit)| self.render_hit(ix, hit, cx))
+                .collect()
+        } else {
+            self.recent_sessions
+                .iter()
+                .enumerate()
+                .map(|(ix, session)| self.render_session(ix, session, cx))
+                .collect()
+        };
+        let summary = if searching {
+            if self.hits.len() >= MAX_RESULTS {
+                t!(
+                    "session_search_panel.summary.first_hits",
+                    count = MAX_RESULTS
+                )
+            } else {
+                t!("session_search_panel.summary.hits", count = self.hits.len())
+            }
+        } else {
+            t!(
+                "session_search_panel.summary.sessions",
+                count = self.recent_sessions.len()
+            )
+        }
+        .to_string();
+        let empty_text = if searching {
+            t!("session_search_panel.empty.no_hits")
+        } else {
+            t!("session_search_panel.empty.no_history")
+        }
+        .to_string();
+
+        let theme = cx.theme();
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        div()
+                            .flex_1()
+                            .child(Input::new(&self.search).small().cleanable(true)),
+                    )
+                    .child(
+                        Button::new("session-search-refresh")
+                            .icon(IconName::RefreshCw)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("session_search_panel.action.refresh").to_string())
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.run_search(cx);
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .px_3()
+                    .py_1()
+                    .text_size(px(12.))
+                    .text_color(theme.muted_foreground)
+                    .child(summary),
+            )
+            .child(
+                div()
+                    .id("session-search-results")
+                    .flex_1()
+                    .w_full()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll_handle)
+                    .when(rows.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_3()
+                                .text_size(px(13.))
+                                .text_color(theme.muted_foreground)
+                                .child(empty_text),
+                        )
+                    })
+                    .children(rows),
+            )
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
     app::actions::{
         AddAgent, ChangeConfigPath, ReloadAgentConfig, RemoveAgent, RestartAgent, UpdateAgent,
     },
+    core::config::AgentTransport,
 };
 
 impl SettingsPanel {
@@ -188,13 +189,18 @@ impl SettingsPanel {
                                                 .font_weight(gpui::FontWeight::SEMIBOLD)
                                         )
                                         .child(
-                                            Label::new(
-                                                t!(
+                                            Label::new(match config.transport.endpoint() {
+                                                Some(endpoint) => t!(
+                                                    "settings.agents.field.endpoint",
+                                                    endpoint = endpoint
+                                                )
+                                                .to_string(),
+                                                None => t!(
                                                     "settings.agents.field.command",
                                                     command = config.command
                                                 )
                                                 .to_string(),
-                                            )
+                                            })
                                                 .text_xs()
                                                 .text_color(cx.theme().muted_foreground)
                                         );
@@ -356,6 +362,18 @@ impl SettingsPanel {
             state
         });
 
+        let endpoint_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx)
+                .placeholder(t!("settings.agents.input.endpoint.placeholder").to_string());
+            if let Some(endpoint) = existing_config
+                .as_ref()
+                .and_then(|config| config.transport.endpoint())
+            {
+                state.set_value(endpoint, window, cx);
+            }
+            state
+        });
+
         let args_input = cx.new(|cx| {
             let mut state = InputState::new(window, cx)
                 .placeholder(t!("settings.agents.input.args.placeholder").to_string());
@@ -396,7 +414,11 @@ impl SettingsPanel {
                 .on_ok({
                     let name_input = name_input.clone();
                     let command_input = command_input.clone();
+                    let endpoint_input = endpoint_input.clone();
                     let args_input = args_input.clone();
+                    let existing_transport = existing_config
+                        .as_ref()
+                        .map(|config| config.transport.clone());
                     let env_input = env_input.clone();
                     let _agent_name = agent_name.clone();
 
@@ -408,8 +430,31 @@ impl SettingsPanel {
                         let args_text = args_input.read(cx).text().to_string();
                         let env_text = env_input.read(cx).text().to_string();
 
+                        let endpoint = endpoint_input.read(cx).text().to_string();
+                        let mut transport = match AgentTransport::parse_endpoint(&endpoint) {
+                            Ok(transport) => transport,
+                            Err(e) => {
+                                log::warn!("Invalid agent endpoint: {}", e);
+                                return false;
+                            }
+                        };
+                        // Headers can only be edited in config.json; keep them
+                        // while the URL stays the same
+                        if let (
+                            AgentTransport::WebSocket { url, headers },
+                            Some(AgentTransport::WebSocket {
+                                url: existing_url,
+                                headers: existing_headers,
+                            }),
+                        ) = (&mut transport, &existing_transport)
+                        {
+                            if url == existing_url {
+                                *headers = existing_headers.clone();
+                            }
+                        }
+
                         // Validate inputs
-                        if name.is_empty() || command.is_empty() {
+                        if name.is_empty() || (command.is_empty() && transport.is_stdio()) {
                             log::warn!("Agent name and command cannot be empty");
                             return false;
                         }
@@ -436,6 +481,7 @@ impl SettingsPanel {
                                     command: command.to_string(),
                                     args,
                                     env,
+                                    transport: transport.clone(),
                                 }),
                                 cx,
                             );
@@ -446,6 +492,7 @@ impl SettingsPanel {
                                     command: command.to_string(),
                                     args,
                                     env,
+                                    transport: transport.clone(),
                                 }),
                                 cx,
                             );
@@ -490,6 +537,25 @@ impl SettingsPanel {
                                     .text_color(cx.theme().muted_foreground),
                                 ),
                         )
+                        .child(
+                            v_flex()
+                                .gap_2()
+                                .child(
+                                    Label::new(
+                                        t!("settings.agents.field.endpoint_label").to_string(),
+                                    )
+                                    .text_sm()
+                                    .font_weight(gpui::FontWeight::SEMIBOLD),
+                                )
+                                .child(Input::new(&endpoint_input))
+                                .child(
+                                    Label::new(
+                                        t!("settings.agents.field.endpoint_help").to_string(),
+                                    )
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                                ),
+                        )
                         .child(
                             v_flex()
                                 .gap_2()
@@ -98,11 +98,27 @@ impl SettingsPanel {
             for (idx, (name, config)) in mcp_configs.iter().enumerate() {
                 let name_for_delete = name.clone();
 
-                let mcp_info = v_flex().flex_1().gap_1().child(
-                    Label::new(name.clone())
-                        .text_sm()
-                        .font_weight(gpui::FontWeight::SEMIBOLD),
-                );
+                let mcp_info = v_flex()
+                    .flex_1()
+                    .gap_1()
+                    .child(
+                        Label::new(name.clone())
+                            .text_sm()
+                            .font_weight(gpui::FontWeight::SEMIBOLD),
+                    )
+                    .child(
+                        Label::new(format!(
+                            "{}: {}",
+                            match config {
+                                McpServerConfig::Stdio { .. } => t!("settings.mcp.type.stdio"),
+                                McpServerConfig::Http { .. } => t!("settings.mcp.type.http"),
+                                McpServerConfig::Sse { .. } => t!("settings.mcp.type.sse"),
+                            },
+                            config.target()
+                        ))
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                    );
 
                 content = content.child(
                     h_flex()
@@ -121,7 +137,7 @@ impl SettingsPanel {
                                 .gap_2()
                                 .items_center()
                                 .child(
-                                    Label::new(if config.enabled {
+                                    Label::new(if config.is_enabled() {
                                         t!("settings.mcp.status.enabled").to_string()
                                     } else {
                                         t!("settings.mcp.status.disabled").to_string()
@@ -252,6 +268,13 @@ impl SettingsPanel {
       "env": {
         "DEBUG": "true"
       }
+    },
+    "remote": {
+      "type": "http",
+      "url": "https://example.com/mcp",
+      "headers": {
+        "Authorization": "Bearer <token>"
+      }
     }
   }
 }"#,
@@ -299,17 +322,8 @@ impl SettingsPanel {
                             return false;
                         }
 
-                        // Parse config JSON (without enabled field, we'll add it)
-                        #[derive(serde::Deserialize)]
-                        struct TempMcpConfig {
-                            command: String,
-                            #[serde(default)]
-                            args: Vec<String>,
-                            #[serde(default)]
-                            env: std::collections::HashMap<String, String>,
-                        }
-
-                        let temp_config: TempMcpConfig = if !config_str.is_empty() {
+                        // Parse config JSON (stdio, http or sse; enabled by default)
+                        let config: McpServerConfig = if !config_str.is_empty() {
                             match serde_json::from_str(&config_str) {
                                 Ok(config) => config,
                                 Err(e) => {
@@ -325,12 +339,6 @@ impl SettingsPanel {
                         // Save to config file
                         if let Some(service) = AppState::global(cx).agent_config_service() {
                             let service = service.clone();
-                            let config = crate::core::config::McpServerConfig {
-                                enabled: true,
-                                command: temp_config.command,
-                                args: temp_config.args,
-                                env: temp_config.env,
-                            };
 
                             cx.spawn(async move |cx| {
                                 match service.add_mcp_server(name.clone(), config).await {
@@ -434,19 +442,37 @@ impl SettingsPanel {
             .and_then(|obj| obj.get("mcpServers").or_else(|| obj.get("mcp_servers")))
             .ok_or_else(|| t!("settings.mcp.json.error.missing_field").to_string())?;
 
-        serde_json::from_value::<HashMap<String, McpServerConfig>>(mcp_servers.clone())
-            .map_err(|e| t!("settings.mcp.json.error.invalid_config", error = e).to_string())
+        let entries = mcp_servers.as_object().ok_or_else(|| {
+            t!(
+                "settings.mcp.json.error.invalid_config",
+                error = "expected an object of servers"
+            )
+            .to_string()
+        })?;
+
+        let mut servers = HashMap::new();
+        for (name, entry) in entries {
+            // `"_comment": "..."` entries, as used in config.example.json
+            if name.starts_with('_') && entry.is_string() {
+                continue;
+            }
+            let config = serde_json::from_value::<McpServerConfig>(entry.clone()).map_err(|e| {
+                t!(
+                    "settings.mcp.json.error.invalid_server",
+                    name = name,
+                    error = e
+                )
+                .to_string()
+            })?;
+            servers.insert(name.clone(), config);
+        }
+        Ok(servers)
     }
 
     pub fn load_mcp_servers_to_json(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let json = serde_json::json!({
             "mcpServers": self.cached_mcp_servers.iter().map(|(name, config)| {
-                (name.clone(), serde_json::json!({
-                    "enabled": config.enabled,
-                    "command": config.command,
-                    "args": config.args,
-                    "env": config.env
-                }))
+                (name.clone(), serde_json::to_value(config).unwrap_or_default())
             }).collect::<serde_json::Map<String, serde_json::Value>>()
         });
 
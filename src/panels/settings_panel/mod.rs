@@ -6,9 +6,12 @@ mod mcp_page;
 mod model_page;
 mod network_page;
 mod panel;
+mod permission_page;
 mod prompt_page;
+mod storage_page;
 mod types;
 mod update_page;
+mod usage_page;
 
 pub use panel::SettingsPanel;
 pub use types::AppSettings;
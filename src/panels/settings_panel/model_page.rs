@@ -15,7 +15,7 @@ use gpui_component::{
 use rust_i18n::t;
 
 use super::panel::SettingsPanel;
-use crate::AppState;
+use crate::{AppState, core::config::ModelPrice};
 
 impl SettingsPanel {
     pub fn model_page(&self, view: &Entity<Self>) -> SettingPage {
@@ -162,6 +162,21 @@ impl SettingsPanel {
                                         );
                                     }
 
+                                    if let Some(price) = config.price {
+                                        model_info = model_info.child(
+                                            Label::new(
+                                                t!(
+                                                    "settings.models.field.price",
+                                                    input = price.input_per_million,
+                                                    output = price.output_per_million
+                                                )
+                                                .to_string(),
+                                            )
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground),
+                                        );
+                                    }
+
                                     content = content.child(
                                         h_flex()
                                             .w_full()
@@ -265,6 +280,14 @@ impl SettingsPanel {
             InputState::new(window, cx)
                 .placeholder(t!("settings.models.input.model_name.placeholder").to_string())
         });
+        let input_price_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("settings.models.input.price.placeholder").to_string())
+        });
+        let output_price_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("settings.models.input.price.placeholder").to_string())
+        });
         let entity = cx.entity().downgrade();
 
         window.open_dialog(cx, move |dialog, _window, _cx| {
@@ -282,6 +305,8 @@ impl SettingsPanel {
                     let url_input = url_input.clone();
                     let key_input = key_input.clone();
                     let model_input = model_input.clone();
+                    let input_price_input = input_price_input.clone();
+                    let output_price_input = output_price_input.clone();
                     let entity = entity.clone();
 
                     move |_, _window, cx| {
@@ -300,6 +325,14 @@ impl SettingsPanel {
                             log::warn!("Name, provider, and URL cannot be empty");
                             return false;
                         }
+                        let Ok(price) = parse_price(
+                            &input_price_input.read(cx).text().to_string(),
+                            &output_price_input.read(cx).text().to_string(),
+                            None,
+                        ) else {
+                            log::warn!("Prices must be numbers");
+                            return false;
+                        };
 
                         // Save to config file
                         if let Some(service) = AppState::global(cx).agent_config_service() {
@@ -310,6 +343,7 @@ impl SettingsPanel {
                                 base_url: url,
                                 api_key: key,
                                 model_name: model,
+                                price,
                             };
                             let name_clone = name.clone();
                             let entity = entity.clone();
@@ -379,6 +413,22 @@ impl SettingsPanel {
                                     t!("settings.models.field.model_name_label").to_string(),
                                 ))
                                 .child(Input::new(&model_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_2()
+                                .child(Label::new(
+                                    t!("settings.models.field.input_price_label").to_string(),
+                                ))
+                                .child(Input::new(&input_price_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_2()
+                                .child(Label::new(
+                                    t!("settings.models.field.output_price_label").to_string(),
+                                ))
+                                .child(Input::new(&output_price_input)),
                         ),
                 )
         });
@@ -415,6 +465,25 @@ impl SettingsPanel {
             state.set_value(config.model_name.clone(), window, cx);
             state
         });
+        let input_price_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx)
+                .placeholder(t!("settings.models.input.price.placeholder").to_string());
+            if let Some(price) = config.price {
+                state.set_value(price.input_per_million.to_string(), window, cx);
+            }
+            state
+        });
+        let output_price_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx)
+                .placeholder(t!("settings.models.input.price.placeholder").to_string());
+            if let Some(price) = config.price {
+                state.set_value(price.output_per_million.to_string(), window, cx);
+            }
+            state
+        });
+        let cached_input_price = config
+            .price
+            .and_then(|price| price.cached_input_per_million);
 
         let enabled = config.enabled;
 
@@ -432,6 +501,8 @@ impl SettingsPanel {
                     let url_input = url_input.clone();
                     let key_input = key_input.clone();
                     let model_input = model_input.clone();
+                    let input_price_input = input_price_input.clone();
+                    let output_price_input = output_price_input.clone();
                     let model_name = model_name.clone();
 
                     move |_, _window, cx| {
@@ -448,6 +519,14 @@ impl SettingsPanel {
                             log::warn!("Provider and URL cannot be empty");
                             return false;
                         }
+                        let Ok(price) = parse_price(
+                            &input_price_input.read(cx).text().to_string(),
+                            &output_price_input.read(cx).text().to_string(),
+                            cached_input_price,
+                        ) else {
+                            log::warn!("Prices must be numbers");
+                            return false;
+                        };
 
                         if let Some(service) = AppState::global(cx).agent_config_service() {
                             let service = service.clone();
@@ -458,6 +537,7 @@ impl SettingsPanel {
                                 base_url: url.to_string(),
                                 api_key: key.to_string(),
                                 model_name: model.to_string(),
+                                price,
                             };
 
                             cx.spawn(async move |cx| {
@@ -508,6 +588,22 @@ impl SettingsPanel {
                                     t!("settings.models.field.model_name_label").to_string(),
                                 ))
                                 .child(Input::new(&model_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_2()
+                                .child(Label::new(
+                                    t!("settings.models.field.input_price_label").to_string(),
+                                ))
+                                .child(Input::new(&input_price_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_2()
+                                .child(Label::new(
+                                    t!("settings.models.field.output_price_label").to_string(),
+                                ))
+                                .child(Input::new(&output_price_input)),
                         ),
                 )
         });
@@ -571,3 +667,27 @@ impl SettingsPanel {
         cx.notify();
     }
 }
+
+/// Price from the dialog inputs; None when both are empty
+fn parse_price(
+    input: &str,
+    output: &str,
+    cached_input_per_million: Option<f64>,
+) -> Result<Option<ModelPrice>, std::num::ParseFloatError> {
+    let (input, output) = (input.trim(), output.trim());
+    if input.is_empty() && output.is_empty() {
+        return Ok(None);
+    }
+    let parse = |value: &str| {
+        if value.is_empty() {
+            Ok(0.0)
+        } else {
+            value.parse::<f64>()
+        }
+    };
+    Ok(Some(ModelPrice {
+        input_per_million: parse(input)?,
+        output_per_million: parse(output)?,
+        cached_input_per_million,
+    }))
+}
@@ -11,7 +11,10 @@ use std::{collections::HashMap, path::PathBuf};
 use crate::{
     AppState,
     core::{
-        config::{AgentProcessConfig, CommandConfig, McpServerConfig, ModelConfig},
+        config::{
+            AgentProcessConfig, CommandConfig, McpServerConfig, ModelConfig, RetentionConfig,
+        },
+        services::{SessionOwner, StorageUsage, UsageGrouping, UsageRecord},
         updater::UpdateManager,
     },
 };
@@ -37,6 +40,13 @@ pub struct SettingsPanel {
     pub(super) inline_comment_input: Entity<InputState>,
     pub(super) explain_input: Entity<InputState>,
     pub(super) improve_input: Entity<InputState>,
+    // Session storage state
+    pub(super) cached_retention: RetentionConfig,
+    pub(super) storage_usage: Option<StorageUsage>,
+    // Token usage state
+    pub(super) usage_grouping: UsageGrouping,
+    pub(super) usage_records: Option<Vec<UsageRecord>>,
+    pub(super) usage_owners: HashMap<String, SessionOwner>,
 }
 
 impl crate::panels::dock_panel::DockPanel for SettingsPanel {
@@ -88,7 +98,7 @@ impl SettingsPanel {
         let explain_input = cx.new(|cx| InputState::new(window, cx));
         let improve_input = cx.new(|cx| InputState::new(window, cx));
 
-        let panel = Self {
+        let mut panel = Self {
             focus_handle: cx.focus_handle(),
             update_status: UpdateStatus::Idle,
             update_manager: UpdateManager::default(),
@@ -104,7 +114,14 @@ impl SettingsPanel {
             inline_comment_input,
             explain_input,
             improve_input,
+            cached_retention: RetentionConfig::default(),
+            storage_usage: None,
+            usage_grouping: UsageGrouping::default(),
+            usage_records: None,
+            usage_owners: HashMap::new(),
         };
+        panel.load_storage_usage(cx);
+        panel.load_usage(cx);
 
         // Load all configuration from service asynchronously
         let weak_entity = cx.entity().downgrade();
@@ -116,6 +133,7 @@ impl SettingsPanel {
                 let mcp_servers = service.list_mcp_servers().await;
                 let commands = service.list_commands().await;
                 let upload_dir = service.get_upload_dir().await;
+                let retention = service.retention_config().await;
 
                 _ = window.update(|window, cx| {
                     if let Some(entity) = weak_entity.upgrade() {
@@ -125,6 +143,7 @@ impl SettingsPanel {
                             this.cached_mcp_servers = mcp_servers.into_iter().collect();
                             this.cached_commands = commands.into_iter().collect();
                             this.cached_upload_dir = upload_dir;
+                            this.cached_retention = retention;
                             // Load system prompts into input fields
                             this.load_system_prompts(window, cx);
                             cx.notify();
@@ -244,6 +263,9 @@ impl SettingsPanel {
             self.model_page(&view),
             self.prompt_page(&view),
             self.mcp_page(&view),
+            self.permission_page(&view),
+            self.storage_page(&view),
+            self.usage_page(&view),
             self.command_page(&view),
             super::about_page::about_page(resettable),
         ]
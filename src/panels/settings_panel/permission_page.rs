@@ -0,0 +1,513 @@
+use gpui::{AppContext as _, Context, Entity, ParentElement as _, Styled, Window, px};
+use gpui_component::{
+    ActiveTheme, IconName, Sizable, WindowExt as _,
+    button::Button,
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    setting::{SettingGroup, SettingItem, SettingPage},
+    tab::{Tab, TabBar},
+    v_flex,
+};
+use rust_i18n::t;
+
+use super::panel::SettingsPanel;
+use crate::{
+    AppState,
+    core::agent::{PermissionRule, RuleDecision, RuleScope},
+};
+
+impl SettingsPanel {
+    pub fn permission_page(&self, view: &Entity<Self>) -> SettingPage {
+        SettingPage::new(t!("settings.permissions.title").to_string())
+            .resettable(false)
+            .groups(vec![
+                SettingGroup::new()
+                    .title(t!("settings.permissions.group.rules").to_string())
+                    .description(t!("settings.permissions.group.rules.description").to_string())
+                    .item(SettingItem::render({
+                        let view = view.clone();
+                        move |_options, _window, cx| {
+                            let rules = AppState::global(cx).permission_rules.rules();
+
+                            let mut content = v_flex().w_full().gap_3().child(
+                                h_flex().w_full().justify_end().child(
+                                    Button::new("add-permission-rule-btn")
+                                        .label(t!("settings.permissions.button.add").to_string())
+                                        .icon(IconName::Plus)
+                                        .small()
+                                        .on_click({
+                                            let view = view.clone();
+                                            move |_, window, cx| {
+                                                view.update(cx, |this, cx| {
+                                                    this.show_permission_rule_dialog(
+                                                        window, cx, None,
+                                                    );
+                                                });
+                                            }
+                                        }),
+                                ),
+                            );
+
+                            if rules.is_empty() {
+                                content = content.child(
+                                    h_flex().w_full().p_4().justify_center().child(
+                                        Label::new(t!("settings.permissions.empty").to_string())
+                                            .text_sm()
+                                            .text_color(cx.theme().muted_foreground),
+                                    ),
+                                );
+                            }
+
+                            for (idx, rule) in rules.into_iter().enumerate() {
+                                let decision_color = match rule.decision {
+                                    RuleDecision::Allow => cx.theme().green,
+                                    RuleDecision::Deny => cx.theme().red,
+                                };
+
+                                let rule_info = v_flex()
+                                    .flex_1()
+                                    .gap_1()
+                                    .child(
+                                        h_flex()
+                                            .gap_2()
+                                            .child(
+                                                Label::new(decision_label(rule.decision))
+                                                    .text_sm()
+                                                    .font_weight(gpui::FontWeight::SEMIBOLD)
+                                                    .text_color(decision_color),
+                                            )
+                                            .child(
+                                                Label::new(criteria_summary(&rule))
+                                                    .text_sm(),
+                                            ),
+                                    )
+                                    .child(
+                                        Label::new(scope_label(&rule.scope))
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground),
+                                    );
+
+                                let rule_for_edit = rule.clone();
+                                let id_for_revoke = rule.id.clone();
+
+                                content = content.child(
+                                    h_flex()
+                                        .w_full()
+                                        .items_start()
+                                        .justify_between()
+                                        .p_3()
+                                        .gap_3()
+                                        .rounded(px(6.))
+                                        .bg(cx.theme().secondary)
+                                        .border_1()
+                                        .border_color(cx.theme().border)
+                                        .child(rule_info)
+                                        .child(
+                                            h_flex()
+                                                .gap_2()
+                                                .items_center()
+                                                .child(
+                                                    Button::new(("edit-permission-rule-btn", idx))
+                                                        .label(
+                                                            t!("settings.permissions.button.edit")
+                                                                .to_string(),
+                                                        )
+                                                        .icon(IconName::Settings)
+                                                        .outline()
+                                                        .small()
+                                                        .on_click({
+                                                            let view = view.clone();
+                                                            move |_, window, cx| {
+                                                                view.update(cx, |this, cx| {
+                                                                    this.show_permission_rule_dialog(
+                                                                        window,
+                                                                        cx,
+                                                                        Some(rule_for_edit.clone()),
+                                                                    );
+                                                                });
+                                                            }
+                                                        }),
+                                                )
+                                                .child(
+                                                    Button::new((
+                                                        "revoke-permission-rule-btn",
+                                                        idx,
+                                                    ))
+                                                    .label(
+                                                        t!("settings.permissions.button.revoke")
+                                                            .to_string(),
+                                                    )
+                                                    .icon(IconName::Delete)
+                                                    .outline()
+                                                    .small()
+                                                    .on_click({
+                                                        let view = view.clone();
+                                                        move |_, window, cx| {
+                                                            view.update(cx, |this, cx| {
+                                                                this.show_revoke_permission_rule_dialog(
+                                                                    window,
+                                                                    cx,
+                                                                    id_for_revoke.clone(),
+                                                                );
+                                                            });
+                                                        }
+                                                    }),
+                                                ),
+                                        ),
+                                );
+                            }
+
+                            content
+                        }
+                    })),
+            ])
+    }
+
+    /// Add a rule, or edit `existing`
+    pub fn show_permission_rule_dialog(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        existing: Option<PermissionRule>,
+    ) {
+        let entity = cx.entity().downgrade();
+        let rule = existing
+            .clone()
+            .unwrap_or_else(|| PermissionRule::new(RuleDecision::Allow, RuleScope::Global));
+        let is_new = existing.is_none();
+
+        let text_input =
+            |value: String, placeholder: &str, window: &mut Window, cx: &mut Context<Self>| {
+                let placeholder = placeholder.to_string();
+                cx.new(|cx| {
+                    let mut state = InputState::new(window, cx).placeholder(placeholder);
+                    state.set_value(value, window, cx);
+                    state
+                })
+            };
+        let kind_input = text_input(
+            rule.tool_kind.clone().unwrap_or_default(),
+            &t!("settings.permissions.input.tool_kind.placeholder"),
+            window,
+            cx,
+        );
+        let title_input = text_input(
+            rule.title.clone().unwrap_or_default(),
+            &t!("settings.permissions.input.title.placeholder"),
+            window,
+            cx,
+        );
+        let paths_input = text_input(
+            rule.path_globs.join(", "),
+            &t!("settings.permissions.input.path_globs.placeholder"),
+            window,
+            cx,
+        );
+        let command_input = text_input(
+            rule.command_regex.clone().unwrap_or_default(),
+            &t!("settings.permissions.input.command_regex.placeholder"),
+            window,
+            cx,
+        );
+
+        // Workspace rules default to the current working directory
+        let workspace_path = match &rule.scope {
+            RuleScope::Workspace { path } => path.clone(),
+            _ => AppState::global(cx).current_working_dir().clone(),
+        };
+        let workspace_input = text_input(workspace_path.display().to_string(), "", window, cx);
+
+        // Session rules can only be kept as they are, not created here
+        let session_scope =
+            matches!(rule.scope, RuleScope::Session { .. }).then(|| rule.scope.clone());
+        let decision_ix = cx.new(|_| match rule.decision {
+            RuleDecision::Allow => 0usize,
+            RuleDecision::Deny => 1,
+        });
+        let scope_ix = cx.new(|_| match rule.scope {
+            RuleScope::Global => 0usize,
+            RuleScope::Workspace { .. } => 1,
+            RuleScope::Session { .. } => 2,
+        });
+
+        window.open_dialog(cx, move |dialog, _window, cx| {
+            let title = if is_new {
+                t!("settings.permissions.dialog.add.title").to_string()
+            } else {
+                t!("settings.permissions.dialog.edit.title").to_string()
+            };
+            let current_decision = *decision_ix.read(cx);
+            let current_scope = *scope_ix.read(cx);
+
+            let mut scope_tabs = TabBar::new("permission-rule-scope")
+                .w_full()
+                .segmented()
+                .selected_index(current_scope)
+                .on_click({
+                    let scope_ix = scope_ix.clone();
+                    move |ix: &usize, window, cx| {
+                        scope_ix.update(cx, |scope, _| *scope = *ix);
+                        window.refresh();
+                    }
+                })
+                .child(
+                    Tab::new()
+                        .flex_1()
+                        .label(t!("settings.permissions.scope.global").to_string()),
+                )
+                .child(
+                    Tab::new()
+                        .flex_1()
+                        .label(t!("settings.permissions.scope.workspace_tab").to_string()),
+                );
+            if session_scope.is_some() {
+                scope_tabs = scope_tabs.child(
+                    Tab::new()
+                        .flex_1()
+                        .label(t!("settings.permissions.scope.session_tab").to_string()),
+                );
+            }
+
+            let mut form =
+                v_flex()
+                    .w_full()
+                    .gap_3()
+                    .p_4()
+                    .child(
+                        v_flex()
+                            .gap_2()
+                            .child(Label::new(
+                                t!("settings.permissions.field.decision").to_string(),
+                            ))
+                            .child(
+                                TabBar::new("permission-rule-decision")
+                                    .w_full()
+                                    .segmented()
+                                    .selected_index(current_decision)
+                                    .on_click({
+                                        let decision_ix = decision_ix.clone();
+                                        move |ix: &usize, window, cx| {
+                                            decision_ix.update(cx, |decision, _| *decision = *ix);
+                                            window.refresh();
+                                        }
+                                    })
+                                    .child(Tab::new().flex_1().label(
+                                        t!("settings.permissions.decision.allow").to_string(),
+                                    ))
+                                    .child(Tab::new().flex_1().label(
+                                        t!("settings.permissions.decision.deny").to_string(),
+                                    )),
+                            ),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_2()
+                            .child(Label::new(
+                                t!("settings.permissions.field.scope").to_string(),
+                            ))
+                            .child(scope_tabs),
+                    );
+            if current_scope == 1 {
+                form = form.child(Input::new(&workspace_input));
+            }
+
+            let field = |label: String, input: &Entity<InputState>| {
+                v_flex()
+                    .gap_2()
+                    .child(Label::new(label))
+                    .child(Input::new(input))
+            };
+            form = form
+                .child(field(
+                    t!("settings.permissions.field.tool_kind").to_string(),
+                    &kind_input,
+                ))
+                .child(field(
+                    t!("settings.permissions.field.title").to_string(),
+                    &title_input,
+                ))
+                .child(field(
+                    t!("settings.permissions.field.path_globs").to_string(),
+                    &paths_input,
+                ))
+                .child(field(
+                    t!("settings.permissions.field.command_regex").to_string(),
+                    &command_input,
+                ));
+
+            dialog
+                .title(title)
+                .confirm()
+                .button_props(
+                    DialogButtonProps::default()
+                        .ok_text(t!("settings.permissions.dialog.ok").to_string())
+                        .cancel_text(t!("settings.permissions.dialog.cancel").to_string()),
+                )
+                .on_ok({
+                    let rule = rule.clone();
+                    let decision_ix = decision_ix.clone();
+                    let scope_ix = scope_ix.clone();
+                    let session_scope = session_scope.clone();
+                    let kind_input = kind_input.clone();
+                    let title_input = title_input.clone();
+                    let paths_input = paths_input.clone();
+                    let command_input = command_input.clone();
+                    let workspace_input = workspace_input.clone();
+                    let entity = entity.clone();
+
+                    move |_, _window, cx| {
+                        let text = |input: &Entity<InputState>, cx: &gpui::App| {
+                            let value = input.read(cx).text().to_string().trim().to_string();
+                            (!value.is_empty()).then_some(value)
+                        };
+
+                        let mut rule = rule.clone();
+                        rule.decision = match *decision_ix.read(cx) {
+                            0 => RuleDecision::Allow,
+                            _ => RuleDecision::Deny,
+                        };
+                        rule.scope = match *scope_ix.read(cx) {
+                            0 => RuleScope::Global,
+                            1 => match text(&workspace_input, cx) {
+                                Some(path) => RuleScope::Workspace { path: path.into() },
+                                None => {
+                                    log::warn!("Workspace rules need a directory");
+                                    return false;
+                                }
+                            },
+                            _ => session_scope.clone().unwrap_or(RuleScope::Global),
+                        };
+                        rule.tool_kind = text(&kind_input, cx);
+                        rule.title = text(&title_input, cx);
+                        rule.path_globs = text(&paths_input, cx)
+                            .map(|paths| {
+                                paths
+                                    .split(',')
+                                    .map(str::trim)
+                                    .filter(|glob| !glob.is_empty())
+                                    .map(str::to_string)
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        rule.command_regex = text(&command_input, cx);
+
+                        let rules = AppState::global(cx).permission_rules.clone();
+                        let result = if is_new {
+                            rules.add(rule)
+                        } else {
+                            rules.update(rule)
+                        };
+                        if let Err(e) = result {
+                            log::error!("Failed to save permission rule: {}", e);
+                            return false;
+                        }
+
+                        if let Some(panel) = entity.upgrade() {
+                            panel.update(cx, |_, cx| cx.notify());
+                        }
+                        true
+                    }
+                })
+                .child(form)
+        });
+    }
+
+    pub fn show_revoke_permission_rule_dialog(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        rule_id: String,
+    ) {
+        let entity = cx.entity().downgrade();
+        let Some(rule) = AppState::global(cx).permission_rules.get(&rule_id) else {
+            log::warn!("Permission rule not found: {}", rule_id);
+            return;
+        };
+        let summary = format!(
+            "{} {}",
+            decision_label(rule.decision),
+            criteria_summary(&rule)
+        );
+
+        window.open_dialog(cx, move |dialog, _window, _cx| {
+            let rule_id = rule_id.clone();
+            let entity = entity.clone();
+            dialog
+                .title(t!("settings.permissions.dialog.revoke.title").to_string())
+                .confirm()
+                .button_props(
+                    DialogButtonProps::default()
+                        .ok_text(t!("settings.permissions.dialog.revoke.ok").to_string())
+                        .ok_variant(gpui_component::button::ButtonVariant::Danger)
+                        .cancel_text(t!("settings.permissions.dialog.cancel").to_string()),
+                )
+                .on_ok(move |_, _window, cx| {
+                    match AppState::global(cx).permission_rules.remove(&rule_id) {
+                        Ok(_) => log::info!("Revoked permission rule: {}", rule_id),
+                        Err(e) => log::error!("Failed to revoke permission rule: {}", e),
+                    }
+                    if let Some(panel) = entity.upgrade() {
+                        panel.update(cx, |_, cx| cx.notify());
+                    }
+                    true
+                })
+                .child(
+                    v_flex().w_full().gap_2().p_4().child(
+                        Label::new(
+                            t!("settings.permissions.dialog.revoke.message", rule = summary)
+                                .to_string(),
+                        )
+                        .text_sm(),
+                    ),
+                )
+        });
+    }
+}
+
+fn decision_label(decision: RuleDecision) -> String {
+    match decision {
+        RuleDecision::Allow => t!("settings.permissions.decision.allow").to_string(),
+        RuleDecision::Deny => t!("settings.permissions.decision.deny").to_string(),
+    }
+}
+
+fn scope_label(scope: &RuleScope) -> String {
+    match scope {
+        RuleScope::Global => t!("settings.permissions.scope.global").to_string(),
+        RuleScope::Workspace { path } => t!(
+            "settings.permissions.scope.workspace",
+            path = path.display()
+        )
+        .to_string(),
+        RuleScope::Session { session_id } => {
+            t!("settings.permissions.scope.session", id = session_id).to_string()
+        }
+    }
+}
+
+/// One-line description of what a rule matches
+fn criteria_summary(rule: &PermissionRule) -> String {
+    let mut parts = Vec::new();
+    if let Some(kind) = &rule.tool_kind {
+        parts.push(kind.clone());
+    }
+    if let Some(title) = &rule.title {
+        parts.push(format!("\"{}\"", title));
+    }
+    if let Some(title) = &rule.exact_title {
+        parts.push(format!("= \"{}\"", title));
+    }
+    if !rule.path_globs.is_empty() {
+        parts.push(rule.path_globs.join(", "));
+    }
+    if let Some(command) = &rule.command_regex {
+        parts.push(format!("/{}/", command));
+    }
+    if parts.is_empty() {
+        t!("settings.permissions.criteria.any").to_string()
+    } else {
+        parts.join(" · ")
+    }
+}
@@ -0,0 +1,303 @@
+use gpui::{App, Context, Entity, ParentElement as _, SharedString, Styled};
+use gpui_component::{
+    ActiveTheme, IconName, Sizable,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    setting::{NumberFieldOptions, SettingField, SettingGroup, SettingItem, SettingPage},
+    v_flex,
+};
+use rust_i18n::t;
+
+use super::panel::SettingsPanel;
+use crate::{
+    AppState,
+    core::config::{RetentionAction, RetentionConfig},
+};
+
+impl SettingsPanel {
+    pub fn storage_page(&self, view: &Entity<Self>) -> SettingPage {
+        let default_retention = RetentionConfig::default();
+
+        SettingPage::new(t!("settings.storage.title").to_string())
+            .resettable(false)
+            .groups(vec![
+                SettingGroup::new()
+                    .title(t!("settings.storage.group.retention").to_string())
+                    .description(t!("settings.storage.group.retention.description").to_string())
+                    .items(vec![
+                        SettingItem::new(
+                            t!("settings.storage.max_age.label").to_string(),
+                            SettingField::number_input(
+                                NumberFieldOptions {
+                                    min: 0.0,
+                                    max: 3650.0,
+                                    ..Default::default()
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |cx: &App| {
+                                        view.read(cx).cached_retention.max_age_days.unwrap_or(0)
+                                            as f64
+                                    }
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |val: f64, cx: &mut App| {
+                                        Self::update_retention(&view, cx, |retention| {
+                                            retention.max_age_days =
+                                                Some(val as u64).filter(|days| *days > 0);
+                                        });
+                                    }
+                                },
+                            )
+                            .default_value(0.0),
+                        )
+                        .description(t!("settings.storage.max_age.description").to_string()),
+                        SettingItem::new(
+                            t!("settings.storage.max_total.label").to_string(),
+                            SettingField::number_input(
+                                NumberFieldOptions {
+                                    min: 0.0,
+                                    max: 1024.0 * 1024.0,
+                                    ..Default::default()
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |cx: &App| {
+                                        view.read(cx).cached_retention.max_total_mb.unwrap_or(0)
+                                            as f64
+                                    }
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |val: f64, cx: &mut App| {
+                                        Self::update_retention(&view, cx, |retention| {
+                                            retention.max_total_mb =
+                                                Some(val as u64).filter(|mb| *mb > 0);
+                                        });
+                                    }
+                                },
+                            )
+                            .default_value(0.0),
+                        )
+                        .description(t!("settings.storage.max_total.description").to_string()),
+                        SettingItem::new(
+                            t!("settings.storage.action.label").to_string(),
+                            SettingField::dropdown(
+                                vec![
+                                    (
+                                        SharedString::from("archive"),
+                                        SharedString::from(t!("settings.storage.action.archive")),
+                                    ),
+                                    (
+                                        SharedString::from("delete"),
+                                        SharedString::from(t!("settings.storage.action.delete")),
+                                    ),
+                                ],
+                                {
+                                    let view = view.clone();
+                                    move |cx: &App| match view.read(cx).cached_retention.action {
+                                        RetentionAction::Archive => "archive".into(),
+                                        RetentionAction::Delete => "delete".into(),
+                                    }
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |val: SharedString, cx: &mut App| {
+                                        Self::update_retention(&view, cx, |retention| {
+                                            retention.action = if val == "delete" {
+                                                RetentionAction::Delete
+                                            } else {
+                                                RetentionAction::Archive
+                                            };
+                                        });
+                                    }
+                                },
+                            )
+                            .default_value(SharedString::from("archive")),
+                        )
+                        .description(t!("settings.storage.action.description").to_string()),
+                        SettingItem::new(
+                            t!("settings.storage.interval.label").to_string(),
+                            SettingField::number_input(
+                                NumberFieldOptions {
+                                    min: 1.0,
+                                    max: 24.0 * 7.0,
+                                    ..Default::default()
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |cx: &App| {
+                                        view.read(cx).cached_retention.interval_hours as f64
+                                    }
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |val: f64, cx: &mut App| {
+                                        Self::update_retention(&view, cx, |retention| {
+                                            retention.interval_hours = (val as u64).max(1);
+                                        });
+                                    }
+                                },
+                            )
+                            .default_value(default_retention.interval_hours as f64),
+                        )
+                        .description(t!("settings.storage.interval.description").to_string()),
+                    ]),
+                SettingGroup::new()
+                    .title(t!("settings.storage.group.usage").to_string())
+                    .item(SettingItem::render({
+                        let view = view.clone();
+                        move |_options, _window, cx| {
+                            let this = view.read(cx);
+                            let pinned = this.cached_retention.pinned_sessions.len();
+                            let mut content = v_flex().w_full().gap_2().child(
+                                h_flex()
+                                    .w_full()
+                                    .justify_between()
+                                    .items_center()
+                                    .child(
+                                        Label::new(
+                                            t!("settings.storage.usage.pinned", count = pinned)
+                                                .to_string(),
+                                        )
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground),
+                                    )
+                                    .child(
+                                        Button::new("refresh-storage-usage-btn")
+                                            .label(
+                                                t!("settings.storage.button.refresh").to_string(),
+                                            )
+                                            .icon(IconName::RefreshCw)
+                                            .ghost()
+                                            .small()
+                                            .on_click({
+                                                let view = view.clone();
+                                                move |_, _, cx| {
+                                                    view.update(cx, |this, cx| {
+                                                        this.load_storage_usage(cx);
+                                                    });
+                                                }
+                                            }),
+                                    ),
+                            );
+
+                            let Some(usage) = &this.storage_usage else {
+                                return content.child(
+                                    Label::new(t!("settings.storage.usage.loading").to_string())
+                                        .text_sm()
+                                        .text_color(cx.theme().muted_foreground),
+                                );
+                            };
+
+                            let row = |name: String, detail: String| {
+                                h_flex()
+                                    .w_full()
+                                    .justify_between()
+                                    .gap_3()
+                                    .child(Label::new(name).text_sm())
+                                    .child(
+                                        Label::new(detail)
+                                            .text_sm()
+                                            .text_color(cx.theme().muted_foreground),
+                                    )
+                            };
+                            for storage in &usage.workspaces {
+                                let name = match &storage.workspace {
+                                    Some(path) => path.display().to_string(),
+                                    None => t!("settings.storage.usage.no_workspace").to_string(),
+                                };
+                                content = content.child(row(
+                                    name,
+                                    t!(
+                                        "settings.storage.usage.sessions",
+                                        count = storage.sessions,
+                                        size = format_bytes(storage.bytes)
+                                    )
+                                    .to_string(),
+                                ));
+                            }
+                            content
+                                .child(row(
+                                    t!("settings.storage.usage.archive").to_string(),
+                                    t!(
+                                        "settings.storage.usage.sessions",
+                                        count = usage.archived_sessions,
+                                        size = format_bytes(usage.archived_bytes)
+                                    )
+                                    .to_string(),
+                                ))
+                                .child(
+                                    row(
+                                        t!("settings.storage.usage.total").to_string(),
+                                        format_bytes(usage.session_bytes() + usage.archived_bytes),
+                                    )
+                                    .pt_2()
+                                    .border_t_1()
+                                    .border_color(cx.theme().border),
+                                )
+                        }
+                    })),
+            ])
+    }
+
+    /// Change the retention settings and save them to the config file
+    fn update_retention(
+        view: &Entity<Self>,
+        cx: &mut App,
+        update: impl FnOnce(&mut RetentionConfig),
+    ) {
+        let retention = view.update(cx, |this, cx| {
+            update(&mut this.cached_retention);
+            cx.notify();
+            this.cached_retention.clone()
+        });
+
+        let Some(service) = AppState::global(cx).agent_config_service().cloned() else {
+            log::error!("AgentConfigService not available, cannot save retention settings");
+            return;
+        };
+        cx.spawn(async move |_cx| {
+            if let Err(e) = service.update_retention(retention).await {
+                log::error!("Failed to save retention settings: {}", e);
+            }
+        })
+        .detach();
+    }
+
+    /// Measure the sessions directory again
+    pub(super) fn load_storage_usage(&mut self, cx: &mut Context<Self>) {
+        let Some(persistence_service) = AppState::global(cx).persistence_service().cloned() else {
+            return;
+        };
+        cx.spawn(
+            async move |this, cx| match persistence_service.storage_usage().await {
+                Ok(usage) => {
+                    _ = this.update(cx, |this, cx| {
+                        this.storage_usage = Some(usage);
+                        cx.notify();
+                    });
+                }
+                Err(e) => log::error!("Failed to measure session storage: {}", e),
+            },
+        )
+        .detach();
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use gpui::{App, Context, Entity, ParentElement as _, SharedString, Styled};
+use gpui_component::{
+    ActiveTheme, IconName, Sizable,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    setting::{SettingField, SettingGroup, SettingItem, SettingPage},
+    v_flex,
+};
+use rust_i18n::t;
+
+use super::panel::SettingsPanel;
+use crate::{
+    AppState,
+    core::services::{
+        SessionOwner, UsageGrouping, UsageTotals, format_cost, format_tokens, summarize_usage,
+        usage_csv,
+    },
+    schemas::workspace_schema::WorkspaceConfig,
+};
+
+impl SettingsPanel {
+    pub fn usage_page(&self, view: &Entity<Self>) -> SettingPage {
+        SettingPage::new(t!("settings.usage.title").to_string())
+            .resettable(false)
+            .groups(vec![
+                SettingGroup::new()
+                    .title(t!("settings.usage.group.totals").to_string())
+                    .description(t!("settings.usage.group.totals.description").to_string())
+                    .items(vec![
+                        SettingItem::new(
+                            t!("settings.usage.grouping.label").to_string(),
+                            SettingField::dropdown(
+                                UsageGrouping::ALL
+                                    .into_iter()
+                                    .map(|grouping| {
+                                        (
+                                            SharedString::from(grouping.id()),
+                                            SharedString::from(grouping_label(grouping)),
+                                        )
+                                    })
+                                    .collect(),
+                                {
+                                    let view = view.clone();
+                                    move |cx: &App| view.read(cx).usage_grouping.id().into()
+                                },
+                                {
+                                    let view = view.clone();
+                                    move |val: SharedString, cx: &mut App| {
+                                        view.update(cx, |this, cx| {
+                                            this.usage_grouping =
+                                                UsageGrouping::from_id(&val).unwrap_or_default();
+                                            cx.notify();
+                                        });
+                                    }
+                                },
+                            )
+                            .default_value(SharedString::from(UsageGrouping::default().id())),
+                        ),
+                        SettingItem::render({
+                            let view = view.clone();
+                            move |_options, _window, cx| {
+                                let this = view.read(cx);
+                                let mut content = v_flex().w_full().gap_2().child(
+                                    h_flex()
+                                        .w_full()
+                                        .justify_end()
+                                        .gap_2()
+                                        .child(
+                                            Button::new("export-usage-csv-btn")
+                                                .label(
+                                                    t!("settings.usage.button.export").to_string(),
+                                                )
+                                                .icon(IconName::File)
+                                                .ghost()
+                                                .small()
+                                                .on_click({
+                                                    let view = view.clone();
+                                                    move |_, _, cx| {
+                                                        view.update(cx, |this, cx| {
+                                                            this.export_usage_csv(cx);
+                                                        });
+                                                    }
+                                                }),
+                                        )
+                                        .child(
+                                            Button::new("refresh-usage-btn")
+                                                .label(
+                                                    t!("settings.usage.button.refresh").to_string(),
+                                                )
+                                                .icon(IconName::RefreshCw)
+                                                .ghost()
+                                                .small()
+                                                .on_click({
+                                                    let view = view.clone();
+                                                    move |_, _, cx| {
+                                                        view.update(cx, |this, cx| {
+                                                            this.load_usage(cx);
+                                                        });
+                                                    }
+                                                }),
+                                        ),
+                                );
+
+                                let Some(records) = &this.usage_records else {
+                                    return content.child(
+                                        Label::new(t!("settings.usage.loading").to_string())
+                                            .text_sm()
+                                            .text_color(cx.theme().muted_foreground),
+                                    );
+                                };
+                                if records.is_empty() {
+                                    return content.child(
+                                        Label::new(t!("settings.usage.empty").to_string())
+                                            .text_sm()
+                                            .text_color(cx.theme().muted_foreground),
+                                    );
+                                }
+
+                                let row = |name: String, totals: &UsageTotals| {
+                                    h_flex()
+                                        .w_full()
+                                        .justify_between()
+                                        .gap_3()
+                                        .child(Label::new(name).text_sm())
+                                        .child(
+                                            Label::new(usage_detail(totals))
+                                                .text_sm()
+                                                .text_color(cx.theme().muted_foreground),
+                                        )
+                                };
+                                let mut total = UsageTotals::default();
+                                for record in records {
+                                    total.add(record);
+                                }
+                                for summary in summarize_usage(
+                                    records,
+                                    this.usage_grouping,
+                                    &this.usage_owners,
+                                ) {
+                                    let name = summary.key.unwrap_or_else(|| {
+                                        t!("settings.usage.ungrouped").to_string()
+                                    });
+                                    content = content.child(row(name, &summary.totals));
+                                }
+                                content.child(
+                                    row(t!("settings.usage.total").to_string(), &total)
+                                        .pt_2()
+                                        .border_t_1()
+                                        .border_color(cx.theme().border),
+                                )
+                            }
+                        }),
+                    ]),
+            ])
+    }
+
+    /// Read the usage records and the tasks their sessions belong to
+    pub(super) fn load_usage(&mut self, cx: &mut Context<Self>) {
+        let app_state = AppState::global(cx);
+        let Some(persistence_service) = app_state.persistence_service().cloned() else {
+            return;
+        };
+        let workspace_service = app_state.workspace_service().cloned();
+
+        cx.spawn(async move |this, cx| {
+            let ledger = persistence_service.usage().clone();
+            let records = smol::unblock(move || ledger.records()).await;
+            let owners = match workspace_service {
+                Some(service) => session_owners(&service.get_config().await),
+                None => HashMap::new(),
+            };
+            _ = this.update(cx, |this, cx| {
+                this.usage_records = Some(records);
+                this.usage_owners = owners;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Save all usage records as CSV where the user picks
+    fn export_usage_csv(&mut self, cx: &mut Context<Self>) {
+        let Some(records) = self.usage_records.clone() else {
+            return;
+        };
+        let csv = usage_csv(&records, &self.usage_owners);
+
+        cx.spawn(async move |_this, _cx| {
+            let file = rfd::AsyncFileDialog::new()
+                .set_title(t!("settings.usage.button.export").to_string())
+                .add_filter("CSV", &["csv"])
+                .set_file_name("usage.csv")
+                .save_file()
+                .await;
+            let Some(file) = file else {
+                return;
+            };
+            let path = file.path().to_path_buf();
+            match smol::unblock({
+                let path = path.clone();
+                move || std::fs::write(path, csv)
+            })
+            .await
+            {
+                Ok(()) => log::info!("Exported usage to {}", path.display()),
+                Err(e) => log::error!("Failed to write {}: {}", path.display(), e),
+            }
+        })
+        .detach();
+    }
+}
+
+/// Task and workspace names of the sessions that have a task
+fn session_owners(config: &WorkspaceConfig) -> HashMap<String, SessionOwner> {
+    config
+        .tasks
+        .iter()
+        .filter_map(|task| {
+            let session_id = task.session_id.clone()?;
+            let workspace = config
+                .workspaces
+                .iter()
+                .find(|workspace| workspace.id == task.workspace_id)
+                .map(|workspace| workspace.name.clone())
+                .unwrap_or_else(|| task.workspace_id.clone());
+            Some((
+                session_id,
+                SessionOwner {
+                    task: task.name.clone(),
+                    workspace,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn grouping_label(grouping: UsageGrouping) -> String {
+    match grouping {
+        UsageGrouping::Session => t!("settings.usage.grouping.session"),
+        UsageGrouping::Task => t!("settings.usage.grouping.task"),
+        UsageGrouping::Workspace => t!("settings.usage.grouping.workspace"),
+        UsageGrouping::Agent => t!("settings.usage.grouping.agent"),
+        UsageGrouping::Model => t!("settings.usage.grouping.model"),
+        UsageGrouping::Day => t!("settings.usage.grouping.day"),
+    }
+    .to_string()
+}
+
+fn usage_detail(totals: &UsageTotals) -> String {
+    let mut detail = t!(
+        "settings.usage.detail",
+        responses = totals.responses,
+        input = format_tokens(totals.usage.input_tokens),
+        output = format_tokens(totals.usage.output_tokens + totals.usage.thought_tokens),
+        cost = format_cost(totals.cost_usd)
+    )
+    .to_string();
+    if totals.unpriced > 0 {
+        detail.push_str(&t!("settings.usage.unpriced", count = totals.unpriced));
+    }
+    detail
+}
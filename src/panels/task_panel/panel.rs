@@ -26,7 +26,7 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Duration;
 
-use crate::core::services::WorkspaceService;
+use crate::core::services::{WorkspaceService, format_duration};
 use crate::core::{event_bus::WorkspaceUpdateEvent, services::SessionStatus};
 use crate::panels::dock_panel::DockPanel;
 use crate::schemas::workspace_schema::WorkspaceTask;
@@ -52,6 +52,51 @@ pub struct WorkspaceGroup {
     pub is_expanded: bool,
 }
 
+/// Tasks in tree order with their fork depth
+///
+/// Top-level tasks come newest first; forks follow their parent, oldest
+/// first. A fork whose parent is gone is shown at the top level.
+fn fork_tree(tasks: &[Rc<WorkspaceTask>]) -> Vec<(Rc<WorkspaceTask>, usize)> {
+    fn push_forks(
+        parent: &Rc<WorkspaceTask>,
+        depth: usize,
+        forks: &HashMap<&str, Vec<&Rc<WorkspaceTask>>>,
+        ordered: &mut Vec<(Rc<WorkspaceTask>, usize)>,
+    ) {
+        ordered.push((parent.clone(), depth));
+        for fork in forks.get(parent.id.as_str()).into_iter().flatten() {
+            push_forks(fork, depth + 1, forks, ordered);
+        }
+    }
+
+    let has_parent = |task: &WorkspaceTask| {
+        task.parent_task_id
+            .as_ref()
+            .is_some_and(|parent| tasks.iter().any(|t| &t.id == parent))
+    };
+
+    let mut forks: HashMap<&str, Vec<&Rc<WorkspaceTask>>> = HashMap::new();
+    let mut roots = Vec::new();
+    for task in tasks {
+        match &task.parent_task_id {
+            Some(parent) if has_parent(task) => {
+                forks.entry(parent.as_str()).or_default().push(task)
+            }
+            _ => roots.push(task),
+        }
+    }
+    roots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    for children in forks.values_mut() {
+        children.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    }
+
+    let mut ordered = Vec::with_capacity(tasks.len());
+    for root in roots {
+        push_forks(root, 0, &forks, &mut ordered);
+    }
+    ordered
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ViewMode {
     Tree,
@@ -516,6 +561,18 @@ impl TaskPanel {
                             });
                         }
                     }
+                    // Agent lifecycle changes reach tasks through session status updates
+                    WorkspaceUpdateEvent::AgentCrashed { .. }
+                    | WorkspaceUpdateEvent::AgentRestarted { .. }
+                    | WorkspaceUpdateEvent::AgentRestartFailed { .. }
+                    | WorkspaceUpdateEvent::AgentInitFailed { .. }
+                    | WorkspaceUpdateEvent::PromptTimedOut { .. } => {}
+                    // The workspace moves the task over to the new session
+                    WorkspaceUpdateEvent::SessionReplaced { .. } => {}
+                    // Only the conversation of the session shows restored history
+                    // and agent status text
+                    WorkspaceUpdateEvent::SessionHistoryRestored { .. }
+                    | WorkspaceUpdateEvent::AgentStatusText { .. } => {}
                 }
             }
         })
@@ -1049,8 +1106,7 @@ impl TaskPanel {
         let workspace_name = workspace.name.clone();
 
         // Sort tasks by created_at descending (newest first)
-        let mut sorted_tasks = workspace.tasks.clone();
-        sorted_tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        let sorted_tasks = fork_tree(&workspace.tasks);
 
         v_flex()
             .w_full()
@@ -1210,11 +1266,9 @@ impl TaskPanel {
             // Expanded children
             .when(is_expanded, |this| {
                 this.child(self.render_new_task_button(&workspace.id, cx))
-                    .children(
-                        sorted_tasks
-                            .iter()
-                            .map(|task| self.render_task_item(task, entity.clone(), cx)),
-                    )
+                    .children(sorted_tasks.iter().map(|(task, depth)| {
+                        self.render_task_item(task, *depth, entity.clone(), cx)
+                    }))
             })
     }
 
@@ -1257,9 +1311,66 @@ impl TaskPanel {
             )
     }
 
+    /// Statistics of a task's session, shown when hovering the task
+    fn stats_tooltip(session_id: &str, cx: &App) -> String {
+        let Some(persistence_service) = AppState::global(cx).persistence_service() else {
+            return t!("task_panel.task.stats.none").to_string();
+        };
+        let stats = persistence_service.stats().stats(session_id);
+        if stats.is_empty() {
+            return t!("task_panel.task.stats.none").to_string();
+        }
+
+        let mut lines = vec![
+            t!(
+                "task_panel.task.stats.messages",
+                user = stats.user_messages,
+                agent = stats.agent_messages
+            )
+            .to_string(),
+            t!(
+                "task_panel.task.stats.tool_calls",
+                count = stats.tool_call_count(),
+                failed = stats.failed_tool_calls()
+            )
+            .to_string(),
+        ];
+        if let Some(average) = stats.average_turn() {
+            lines.push(
+                t!(
+                    "task_panel.task.stats.turns",
+                    count = stats.prompt_turns,
+                    average = format_duration(average),
+                    longest = format_duration(Duration::from_millis(stats.longest_turn_ms))
+                )
+                .to_string(),
+            );
+        }
+        if stats.permission_requests > 0 {
+            lines.push(
+                t!(
+                    "task_panel.task.stats.permission_wait",
+                    count = stats.permission_requests,
+                    wait = format_duration(stats.permission_wait())
+                )
+                .to_string(),
+            );
+        }
+        lines.push(
+            t!(
+                "task_panel.task.stats.lines",
+                added = stats.lines_added,
+                removed = stats.lines_removed
+            )
+            .to_string(),
+        );
+        lines.join("\n")
+    }
+
     fn render_task_item(
         &self,
         task: &Rc<WorkspaceTask>,
+        depth: usize,
         entity: Entity<Self>,
         cx: &Context<Self>,
     ) -> impl IntoElement {
@@ -1272,13 +1383,19 @@ impl TaskPanel {
             .w_full()
             .gap_0p5()
             .px_3()
-            .pl(px(CHILD_INDENT + 12.0)) // Align with new task button
+            .pl(px(CHILD_INDENT * (depth + 1) as f32 + 12.0)) // Align with new task button, forks under their parent
             .py_2()
             .cursor_pointer()
             .when(is_selected, |s| s.bg(theme.accent))
             .when(!is_selected, |s| {
                 s.hover(|s| s.bg(theme.accent.opacity(0.5)))
             })
+            .when_some(task.session_id.clone(), |this, session_id| {
+                this.tooltip(move |window, cx| {
+                    gpui_component::tooltip::Tooltip::new(Self::stats_tooltip(&session_id, cx))
+                        .build(window, cx)
+                })
+            })
             .on_click(cx.listener({
                 let task_id = task_id.clone();
                 move |this, event: &ClickEvent, window, cx| {
@@ -1306,6 +1423,13 @@ impl TaskPanel {
                             .min_w_0()
                             .flex_1()
                             .child(StatusIndicator::new(task.status.clone()).size(8.0))
+                            .when(task.parent_task_id.is_some(), |this| {
+                                this.child(
+                                    Icon::new(crate::assets::Icon::GitFork)
+                                        .size_3()
+                                        .text_color(theme.muted_foreground),
+                                )
+                            })
                             .child(
                                 div()
                                     .text_sm()
@@ -1652,6 +1776,26 @@ impl TaskPanel {
                 t!("task_panel.status.closed").to_string(),
                 gpui::rgb(0xef4444).into(),
             ),
+            SessionStatus::Refused => (
+                t!("task_panel.status.refused").to_string(),
+                gpui::rgb(0xef4444).into(),
+            ),
+            SessionStatus::MaxTokens => (
+                t!("task_panel.status.max_tokens").to_string(),
+                gpui::rgb(0xf59e0b).into(),
+            ),
+            SessionStatus::MaxTurnRequests => (
+                t!("task_panel.status.max_turn_requests").to_string(),
+                gpui::rgb(0xf59e0b).into(),
+            ),
+            SessionStatus::Cancelled => (
+                t!("task_panel.status.cancelled").to_string(),
+                theme.muted_foreground,
+            ),
+            SessionStatus::TimedOut => (
+                t!("task_panel.status.timed_out").to_string(),
+                gpui::rgb(0xef4444).into(),
+            ),
         };
 
         div().text_xs().text_color(color).child(label)
@@ -1666,6 +1810,11 @@ impl TaskPanel {
             SessionStatus::Completed => IconName::CircleCheck,
             SessionStatus::Failed => IconName::CircleX,
             SessionStatus::Closed => IconName::CircleX,
+            SessionStatus::Refused => IconName::CircleX,
+            SessionStatus::MaxTokens => IconName::TriangleAlert,
+            SessionStatus::MaxTurnRequests => IconName::TriangleAlert,
+            SessionStatus::Cancelled => IconName::CircleX,
+            SessionStatus::TimedOut => IconName::TriangleAlert,
         }
     }
 
@@ -1678,6 +1827,11 @@ impl TaskPanel {
             SessionStatus::Completed => gpui::rgb(0x22c55e).into(),
             SessionStatus::Failed => gpui::rgb(0xef4444).into(),
             SessionStatus::Closed => gpui::rgb(0xef4444).into(),
+            SessionStatus::Refused => gpui::rgb(0xef4444).into(),
+            SessionStatus::MaxTokens => gpui::rgb(0xf59e0b).into(),
+            SessionStatus::MaxTurnRequests => gpui::rgb(0xf59e0b).into(),
+            SessionStatus::Cancelled => gpui::rgb(0x6b7280).into(),
+            SessionStatus::TimedOut => gpui::rgb(0xef4444).into(),
         }
     }
 }
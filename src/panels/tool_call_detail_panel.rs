@@ -7,8 +7,9 @@ use gpui_component::{ActiveTheme, Icon, IconName, h_flex, text::TextView, v_flex
 
 use agent_client_protocol::{ContentBlock, ToolCall, ToolCallContent};
 
-use crate::components::DiffView;
+use crate::components::{DiffView, terminal_status_text};
 use crate::panels::dock_panel::DockPanel;
+use crate::utils::tool_call::{extract_terminal_output, tool_call_has_terminal};
 
 /// Panel that displays detailed tool call content
 pub struct ToolCallDetailPanel {
@@ -34,6 +35,7 @@ impl ToolCallDetailPanel {
         cx.new(|cx| {
             let panel = Self::new(window, cx);
             Self::subscribe_to_tool_call_updates(cx);
+            Self::subscribe_to_terminal_output(cx);
             panel
         })
     }
@@ -90,6 +92,102 @@ impl ToolCallDetailPanel {
         .detach();
     }
 
+    /// Re-render while a terminal shown by the current tool call produces output
+    fn subscribe_to_terminal_output(cx: &mut Context<Self>) {
+        let terminal_bus = crate::AppState::global(cx).terminal_bus.clone();
+
+        // Create unbounded channel for cross-thread communication
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let subscription_id = terminal_bus.subscribe(move |event| {
+            let _ = tx.send(event.terminal_id.clone());
+        });
+
+        cx.spawn(async move |this, cx| {
+            while let Some(terminal_id) = rx.recv().await {
+                let result = this.update(cx, |this, cx| {
+                    let shows_terminal = this
+                        .tool_call
+                        .as_ref()
+                        .is_some_and(|tc| tool_call_has_terminal(tc, &terminal_id));
+                    if shows_terminal {
+                        cx.notify();
+                    }
+                });
+                if result.is_err() {
+                    break;
+                }
+            }
+            terminal_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Render an agent terminal with its full captured output
+    fn render_terminal(&self, terminal: &agent_client_protocol::Terminal, cx: &App) -> AnyElement {
+        let terminal_id = terminal.terminal_id.to_string();
+        let snapshot = crate::AppState::global(cx).terminals.snapshot(&terminal_id);
+
+        let title = match &snapshot {
+            Some(snapshot) => format!("$ {}", snapshot.command),
+            None => format!("Terminal: {}", terminal.terminal_id),
+        };
+        let output = snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.output.clone())
+            .or_else(|| extract_terminal_output(terminal))
+            .unwrap_or_default();
+        let status_text = snapshot.as_ref().map(terminal_status_text);
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::SquareTerminal)
+                            .size(px(16.))
+                            .text_color(cx.theme().accent),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(px(13.))
+                            .font_weight(gpui::FontWeight::MEDIUM)
+                            .text_color(cx.theme().foreground)
+                            .child(title),
+                    )
+                    .when_some(status_text, |this, status| {
+                        this.child(
+                            div()
+                                .text_size(px(12.))
+                                .text_color(cx.theme().muted_foreground)
+                                .child(status),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .w_full()
+                    .p_3()
+                    .rounded(cx.theme().radius)
+                    .bg(cx.theme().secondary)
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .child(
+                        div()
+                            .text_size(px(12.))
+                            .font_family("Monaco, 'Courier New', monospace")
+                            .text_color(cx.theme().foreground)
+                            .line_height(px(18.))
+                            .whitespace_normal()
+                            .child(output),
+                    ),
+            )
+            .into_any_element()
+    }
+
     /// Render content based on ToolCallContent type
     fn render_content(
         &self,
@@ -135,44 +233,7 @@ impl ToolCallDetailPanel {
                     .into_any_element(),
             },
             ToolCallContent::Diff(diff) => self.render_diff_view(diff, window, cx),
-            ToolCallContent::Terminal(terminal) => v_flex()
-                .w_full()
-                .gap_2()
-                .child(
-                    h_flex()
-                        .items_center()
-                        .gap_2()
-                        .child(
-                            Icon::new(IconName::SquareTerminal)
-                                .size(px(16.))
-                                .text_color(cx.theme().accent),
-                        )
-                        .child(
-                            div()
-                                .text_size(px(13.))
-                                .font_weight(gpui::FontWeight::MEDIUM)
-                                .text_color(cx.theme().foreground)
-                                .child(format!("Terminal: {}", terminal.terminal_id)),
-                        ),
-                )
-                .child(
-                    div()
-                        .w_full()
-                        .p_3()
-                        .rounded(cx.theme().radius)
-                        .bg(cx.theme().secondary)
-                        .border_1()
-                        .border_color(cx.theme().border)
-                        .child(
-                            div()
-                                .text_size(px(12.))
-                                .font_family("Monaco, 'Courier New', monospace")
-                                .text_color(cx.theme().foreground)
-                                .line_height(px(18.))
-                                .child("Terminal output display"),
-                        ),
-                )
-                .into_any_element(),
+            ToolCallContent::Terminal(terminal) => self.render_terminal(terminal, cx),
             _ => div()
                 .text_size(px(13.))
                 .text_color(cx.theme().muted_foreground)
@@ -14,7 +14,7 @@ use gpui_component::{
     v_flex,
 };
 
-use agent_client_protocol::{self as acp, AvailableCommand, ImageContent};
+use agent_client_protocol::{AvailableCommand, ImageContent};
 
 use crate::{
     AppState, CreateTaskFromWelcome, WelcomeSession,
@@ -458,7 +458,7 @@ impl WelcomePanel {
         let enabled_mcps = self
             .available_mcps
             .iter()
-            .filter(|(_, config)| config.enabled)
+            .filter(|(_, config)| config.is_enabled())
             .map(|(name, _)| name.clone())
             .collect::<HashSet<_>>();
 
@@ -502,12 +502,12 @@ impl WelcomePanel {
     fn collect_mcp_servers_from_selection(
         available_mcps: &[(String, McpServerConfig)],
         selected_mcps: &[String],
-    ) -> Vec<acp::McpServer> {
+    ) -> Vec<(String, McpServerConfig)> {
         let selected_set: HashSet<&String> = selected_mcps.iter().collect();
         available_mcps
             .iter()
-            .filter(|(name, config)| config.enabled && selected_set.contains(name))
-            .map(|(name, config)| config.to_acp_mcp_server(name.clone()))
+            .filter(|(name, _)| selected_set.contains(name))
+            .cloned()
             .collect()
     }
 
@@ -1220,19 +1220,17 @@ impl WelcomePanel {
         let weak_self = cx.entity().downgrade();
         let agent_name_for_session = agent_name.clone();
         cx.spawn_in(window, async move |_this, window| {
-            let mut mcp_servers =
+            let mut mcp_configs =
                 Self::collect_mcp_servers_from_selection(&available_mcps, &selected_mcps);
 
             if !mcp_selection_initialized {
                 if let Some(service) = agent_config_service {
-                    let defaults = service.list_mcp_servers().await;
-                    mcp_servers = defaults
-                        .into_iter()
-                        .filter(|(_, config)| config.enabled)
-                        .map(|(name, config)| config.to_acp_mcp_server(name))
-                        .collect();
+                    mcp_configs = service.list_mcp_servers().await;
                 }
             }
+            let mcp_servers = agent_service
+                .mcp_servers_for_agent(&agent_name_for_session, mcp_configs)
+                .await;
 
             log::info!(
                 "[WelcomePanel] Creating session for agent '{}' with cwd: {:?}",
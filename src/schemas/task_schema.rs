@@ -1,7 +1,7 @@
 use gpui::SharedString;
 use serde::Deserialize;
 
-use crate::core::services::SessionStatus;
+use crate::core::services::{SessionStats, SessionStatus};
 
 #[derive(Clone, Default, Deserialize)]
 pub struct AgentTask {
@@ -53,6 +53,14 @@ impl AgentTask {
         }
     }
 
+    /// Take the changed line counts from the session statistics
+    pub fn apply_stats(&mut self, stats: &SessionStats) {
+        self.add_new_code_lines = stats.lines_added.min(i16::MAX as usize) as i16;
+        self.delete_code_lines = stats.lines_removed.min(i16::MAX as usize) as i16;
+        self.add_new_code_lines_str = format!("+{}", self.add_new_code_lines).into();
+        self.delete_code_lines_str = format!("-{}", self.delete_code_lines).into();
+    }
+
     /// Update the subtitle with a message preview
     pub fn update_subtitle(&mut self, text: impl Into<SharedString>) {
         self.subtitle = Some(text.into());
@@ -68,6 +68,17 @@ pub struct WorkspaceTask {
     pub session_id: Option<String>,
     /// Task status
     pub status: SessionStatus,
+    /// Approve permission requests without asking, within the autopilot
+    /// guardrails
+    #[serde(default)]
+    pub autopilot: bool,
+    /// The session's history was moved into the archive by the retention
+    /// policy; it is restored when the session is opened
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub history_archived: bool,
+    /// Task this one was forked from, if its session is a fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_task_id: Option<String>,
     /// When the task was created
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -87,15 +98,34 @@ impl WorkspaceTask {
             mode,
             session_id: None,
             status: SessionStatus::Pending,
+            autopilot: false,
+            history_archived: false,
+            parent_task_id: None,
             created_at: chrono::Utc::now(),
             last_message: None,
         }
     }
 
+    /// Create a task for a session forked from `parent`'s session
+    ///
+    /// The fork keeps the parent's workspace, agent and mode.
+    pub fn new_fork(parent: &WorkspaceTask, name: String) -> Self {
+        Self {
+            parent_task_id: Some(parent.id.clone()),
+            ..Self::new(
+                parent.workspace_id.clone(),
+                name,
+                parent.agent_name.clone(),
+                parent.mode.clone(),
+            )
+        }
+    }
+
     /// Associate a session with this task
     pub fn set_session(&mut self, session_id: String) {
         self.session_id = Some(session_id);
         self.status = SessionStatus::InProgress;
+        self.history_archived = false;
     }
 
     /// Update the last message preview
@@ -231,6 +261,27 @@ mod tests {
         assert_eq!(task.mode, "Auto");
         assert!(task.session_id.is_none());
         assert!(matches!(task.status, SessionStatus::Pending));
+        assert!(task.parent_task_id.is_none());
+    }
+
+    #[test]
+    fn test_workspace_task_new_fork() {
+        let mut parent = WorkspaceTask::new(
+            "workspace-1".to_string(),
+            "Fix bug".to_string(),
+            "claude".to_string(),
+            "Plan".to_string(),
+        );
+        parent.set_session("session-1".to_string());
+
+        let fork = WorkspaceTask::new_fork(&parent, "Fix bug (fork)".to_string());
+
+        assert_ne!(fork.id, parent.id);
+        assert_eq!(fork.parent_task_id.as_deref(), Some(parent.id.as_str()));
+        assert_eq!(fork.workspace_id, "workspace-1");
+        assert_eq!(fork.agent_name, "claude");
+        assert_eq!(fork.mode, "Plan");
+        assert!(fork.session_id.is_none());
     }
 
     #[test]
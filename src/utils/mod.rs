@@ -1,4 +1,5 @@
 pub mod clipboard;
+pub mod csv;
 pub mod external_editor;
 pub mod file;
 pub mod time;
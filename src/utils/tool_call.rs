@@ -17,6 +17,25 @@ pub fn truncate_lines(text: &str, max_lines: usize) -> String {
     result
 }
 
+/// Keep only the last `max_lines` lines, for output that grows at the bottom
+pub fn tail_lines(text: &str, max_lines: usize) -> String {
+    if max_lines == 0 {
+        return text.to_string();
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].join("\n")
+}
+
+/// Whether the tool call embeds the given terminal
+pub fn tool_call_has_terminal(tool_call: &acp::ToolCall, terminal_id: &str) -> bool {
+    tool_call.content.iter().any(|content| {
+        matches!(content, acp::ToolCallContent::Terminal(terminal)
+            if terminal.terminal_id.to_string() == terminal_id)
+    })
+}
+
 pub fn extract_terminal_output(terminal: &acp::Terminal) -> Option<String> {
     let meta = terminal.meta.as_ref()?;
     extract_terminal_output_from_meta(meta)
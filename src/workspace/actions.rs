@@ -8,8 +8,10 @@ use gpui_component::{
 use std::sync::Arc;
 
 use crate::{
-    AppState, ConversationPanel, CreateTaskFromWelcome, NewSessionConversationPanel, PanelAction,
-    SendMessageToSession, SettingsPanel, ToggleDockToggleButton, TogglePanelVisible, WelcomePanel,
+    AgentLogsPanel, AppState, ConversationPanel, CreateTaskFromWelcome, ForkSession,
+    NewSessionConversationPanel, PanelAction, PermissionAuditPanel, PermissionQueuePanel,
+    ProtocolInspectorPanel, SendMessageToSession, SessionSearchPanel, SettingsPanel,
+    ToggleDockToggleButton, TogglePanelVisible, WelcomePanel,
     app::actions::{
         AddAgent, CancelSession, ChangeConfigPath, PanelCommand, PanelKind, ReloadAgentConfig,
         RemoveAgent, RestartAgent, SetUploadDir, Submit, UpdateAgent,
@@ -105,36 +107,80 @@ impl DockWorkspace {
     }
 
     fn resume_session_if_needed(session_id: &str, cx: &mut Context<Self>) {
-        let agent_service = AppState::global(cx).agent_service().cloned();
-        if let Some(agent_service) = agent_service {
-            let session_id_clone = session_id.to_string();
-            cx.spawn(async move |_this, _cx| {
-                if let Some(agent_name) = agent_service.get_agent_for_session(&session_id_clone) {
-                    log::info!(
-                        "Resuming session {} for agent {}",
-                        session_id_clone,
-                        agent_name
-                    );
-                    match agent_service
-                        .resume_session(&agent_name, &session_id_clone)
-                        .await
-                    {
-                        Ok(_) => {
-                            log::info!("Successfully resumed session {}", session_id_clone);
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to resume session {}: {}", session_id_clone, e);
-                        }
+        let app_state = AppState::global(cx);
+        let Some(agent_service) = app_state.agent_service().cloned() else {
+            return;
+        };
+        let message_service = app_state.message_service().cloned();
+        let workspace_service = app_state.workspace_service().cloned();
+        let agent_config_service = app_state.agent_config_service().cloned();
+
+        let session_id_clone = session_id.to_string();
+        cx.spawn(async move |_this, _cx| {
+            if let Some(agent_name) = agent_service.get_agent_for_session(&session_id_clone) {
+                log::info!(
+                    "Resuming session {} for agent {}",
+                    session_id_clone,
+                    agent_name
+                );
+                match agent_service
+                    .resume_session(&agent_name, &session_id_clone)
+                    .await
+                {
+                    Ok(_) => {
+                        log::info!("Successfully resumed session {}", session_id_clone);
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to resume session {}: {}", session_id_clone, e);
                     }
-                } else {
-                    log::warn!(
-                        "No agent found for session {}, skipping resume",
-                        session_id_clone
-                    );
                 }
-            })
-            .detach();
-        }
+                return;
+            }
+
+            // Not known to the running agents: a persisted task reopened after a restart
+            let (Some(message_service), Some(workspace_service)) =
+                (message_service, workspace_service)
+            else {
+                return;
+            };
+            let Some(task) = workspace_service
+                .get_task_by_session(&session_id_clone)
+                .await
+            else {
+                log::warn!(
+                    "No agent found for session {}, skipping resume",
+                    session_id_clone
+                );
+                return;
+            };
+
+            let cwd = match workspace_service.get_workspace(&task.workspace_id).await {
+                Some(workspace) => workspace.path,
+                None => std::env::current_dir().unwrap_or_default(),
+            };
+            let mcp_servers = match agent_config_service {
+                Some(service) => {
+                    agent_service
+                        .mcp_servers_for_agent(&task.agent_name, service.list_mcp_servers().await)
+                        .await
+                }
+                None => Vec::new(),
+            };
+
+            log::info!(
+                "Restoring session {} of task {} with agent {}",
+                session_id_clone,
+                task.id,
+                task.agent_name
+            );
+            if let Err(e) = message_service
+                .restore_session(&task.agent_name, &session_id_clone, mcp_servers, cwd)
+                .await
+            {
+                log::warn!("Failed to restore session {}: {}", session_id_clone, e);
+            }
+        })
+        .detach();
     }
 
     fn find_focused_tab_panel(
@@ -269,6 +315,52 @@ impl DockWorkspace {
             .any(|child| Self::panel_state_contains_session(child, session_id))
     }
 
+    /// Scroll the conversation panel of a session to a history message;
+    /// the panel is active after `show_conversation_panel`
+    fn reveal_session_message(
+        &mut self,
+        session_id: &str,
+        message_index: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let items = self.dock_area.read(cx).center().clone();
+        match Self::find_active_session_conversation(&items, session_id, cx) {
+            Some(conversation) => conversation.update(cx, |conversation, cx| {
+                conversation.reveal_message(message_index, cx);
+            }),
+            None => log::debug!(
+                "No conversation panel to reveal message {} of session {}",
+                message_index,
+                session_id
+            ),
+        }
+    }
+
+    fn find_active_session_conversation(
+        item: &DockItem,
+        session_id: &str,
+        cx: &App,
+    ) -> Option<Entity<ConversationPanel>> {
+        match item {
+            DockItem::Tabs { view, .. } => {
+                let panel = view.read(cx).active_panel(cx)?;
+                let container = panel.view().downcast::<DockPanelContainer>().ok()?;
+                let conversation = container
+                    .read(cx)
+                    .agent_studio
+                    .clone()?
+                    .downcast::<ConversationPanel>()
+                    .ok()?;
+                (conversation.read(cx).session_id().as_deref() == Some(session_id))
+                    .then_some(conversation)
+            }
+            DockItem::Split { items, .. } => items
+                .iter()
+                .find_map(|item| Self::find_active_session_conversation(item, session_id, cx)),
+            DockItem::Tiles { .. } | DockItem::Panel { .. } => None,
+        }
+    }
+
     fn panel_matches_session(panel: &Arc<dyn PanelView>, session_id: &str, cx: &App) -> bool {
         let panel_id = panel.panel_id(cx);
         let Ok(container) = panel.view().downcast::<DockPanelContainer>() else {
@@ -340,7 +432,7 @@ impl DockWorkspace {
     ) {
         match &action.0 {
             PanelCommand::Add { panel, placement } => match panel {
-                PanelKind::Conversation { session_id } => {
+                PanelKind::Conversation { session_id, .. } => {
                     self.add_conversation_panel_to(session_id.clone(), *placement, window, cx);
                 }
                 PanelKind::Terminal { working_directory } => {
@@ -363,10 +455,31 @@ impl DockWorkspace {
                 } => {
                     self.show_tool_call_detail_panel((**tool_call).clone(), window, cx);
                 }
+                PanelKind::AgentLogs => {
+                    self.add_agent_logs_panel_to(*placement, window, cx);
+                }
+                PanelKind::ProtocolInspector => {
+                    self.add_protocol_inspector_panel_to(*placement, window, cx);
+                }
+                PanelKind::PermissionQueue => {
+                    self.add_permission_queue_panel_to(*placement, window, cx);
+                }
+                PanelKind::PermissionAudit => {
+                    self.add_permission_audit_panel_to(*placement, window, cx);
+                }
+                PanelKind::SessionSearch => {
+                    self.add_session_search_panel_to(*placement, window, cx);
+                }
             },
             PanelCommand::Show(panel) => match panel {
-                PanelKind::Conversation { session_id } => {
+                PanelKind::Conversation {
+                    session_id,
+                    message_index,
+                } => {
                     self.show_conversation_panel(session_id.clone(), window, cx);
+                    if let (Some(session_id), Some(message_index)) = (session_id, message_index) {
+                        self.reveal_session_message(session_id, *message_index, cx);
+                    }
                 }
                 PanelKind::Terminal { working_directory } => {
                     self.add_terminal_panel_to(
@@ -393,6 +506,21 @@ impl DockWorkspace {
                 } => {
                     self.show_tool_call_detail_panel((**tool_call).clone(), window, cx);
                 }
+                PanelKind::AgentLogs => {
+                    self.add_agent_logs_panel_to(DockPlacement::Bottom, window, cx);
+                }
+                PanelKind::ProtocolInspector => {
+                    self.add_protocol_inspector_panel_to(DockPlacement::Bottom, window, cx);
+                }
+                PanelKind::PermissionQueue => {
+                    self.add_permission_queue_panel_to(DockPlacement::Right, window, cx);
+                }
+                PanelKind::PermissionAudit => {
+                    self.add_permission_audit_panel_to(DockPlacement::Bottom, window, cx);
+                }
+                PanelKind::SessionSearch => {
+                    self.add_session_search_panel_to(DockPlacement::Right, window, cx);
+                }
             },
         }
     }
@@ -685,6 +813,75 @@ impl DockWorkspace {
         });
     }
 
+    fn add_agent_logs_panel_to(
+        &mut self,
+        placement: DockPlacement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = DockPanelContainer::panel::<AgentLogsPanel>(window, cx);
+        self.add_tool_panel_to(panel, placement, window, cx);
+    }
+
+    fn add_protocol_inspector_panel_to(
+        &mut self,
+        placement: DockPlacement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = DockPanelContainer::panel::<ProtocolInspectorPanel>(window, cx);
+        self.add_tool_panel_to(panel, placement, window, cx);
+    }
+
+    fn add_permission_queue_panel_to(
+        &mut self,
+        placement: DockPlacement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = DockPanelContainer::panel::<PermissionQueuePanel>(window, cx);
+        self.add_tool_panel_to(panel, placement, window, cx);
+    }
+
+    fn add_permission_audit_panel_to(
+        &mut self,
+        placement: DockPlacement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = DockPanelContainer::panel::<PermissionAuditPanel>(window, cx);
+        self.add_tool_panel_to(panel, placement, window, cx);
+    }
+
+    fn add_session_search_panel_to(
+        &mut self,
+        placement: DockPlacement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = DockPanelContainer::panel::<SessionSearchPanel>(window, cx);
+        self.add_tool_panel_to(panel, placement, window, cx);
+    }
+
+    /// Add a diagnostics panel to a dock and make sure the dock is open
+    fn add_tool_panel_to(
+        &mut self,
+        panel: Entity<DockPanelContainer>,
+        placement: DockPlacement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = Arc::new(panel);
+
+        self.dock_area.update(cx, |dock_area, cx| {
+            let was_dock_open = dock_area.is_dock_open(placement, cx);
+            dock_area.add_panel(panel, placement, None, window, cx);
+            if placement != DockPlacement::Center && !was_dock_open {
+                dock_area.toggle_dock(placement, window, cx);
+            }
+        });
+    }
+
     /// Handle NewSessionConversationPanel action - add a new conversation panel
     pub(super) fn on_action_new_session_conversation_panel(
         &mut self,
@@ -817,13 +1014,9 @@ impl DockWorkspace {
             } else {
                 // No welcome session, create new one with workspace cwd
                 let mcp_servers = if let Some(service) = agent_config_service {
-                    service
-                        .list_mcp_servers()
+                    agent_service
+                        .mcp_servers_for_agent(&agent_name, service.list_mcp_servers().await)
                         .await
-                        .into_iter()
-                        .filter(|(_, config)| config.enabled)
-                        .map(|(name, config)| config.to_acp_mcp_server(name))
-                        .collect()
                 } else {
                     Vec::new()
                 };
@@ -1156,6 +1349,137 @@ impl DockWorkspace {
         })
         .detach();
     }
+
+    /// Handle ForkSession action - fork a session into a child task and open it
+    pub(super) fn on_action_fork_session(
+        &mut self,
+        action: &ForkSession,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let parent_session_id = action.session_id.clone();
+        let history_start = action.history_start;
+        let message_count = action.message_count;
+
+        let app_state = AppState::global(cx);
+        let (Some(agent_service), Some(message_service), Some(workspace_service)) = (
+            app_state.agent_service().cloned(),
+            app_state.message_service().cloned(),
+            app_state.workspace_service().cloned(),
+        ) else {
+            log::error!("Services not initialized, cannot fork session");
+            return;
+        };
+        let agent_config_service = app_state.agent_config_service().cloned();
+
+        cx.spawn_in(window, async move |this, window| {
+            let notify_error = |window: &mut AsyncWindowContext, message: String| {
+                _ = window.update(|window, cx| {
+                    struct ForkSessionError;
+                    let note = Notification::error(message).id::<ForkSessionError>();
+                    window.push_notification(note, cx);
+                });
+            };
+
+            let parent_task = workspace_service
+                .get_task_by_session(&parent_session_id)
+                .await;
+            let Some(agent_name) = agent_service
+                .get_agent_for_session(&parent_session_id)
+                .or_else(|| parent_task.as_ref().map(|task| task.agent_name.clone()))
+            else {
+                log::error!("No agent found for session {}", parent_session_id);
+                notify_error(window, "Cannot fork: the session has no agent".to_string());
+                return;
+            };
+            let workspace = match &parent_task {
+                Some(task) => workspace_service.get_workspace(&task.workspace_id).await,
+                None => workspace_service.get_active_workspace().await,
+            };
+            let Some(workspace) = workspace else {
+                notify_error(
+                    window,
+                    "Cannot fork: no workspace available for the new session".to_string(),
+                );
+                return;
+            };
+
+            let mcp_servers = if let Some(service) = &agent_config_service {
+                agent_service
+                    .mcp_servers_for_agent(&agent_name, service.list_mcp_servers().await)
+                    .await
+            } else {
+                Vec::new()
+            };
+
+            let (session_id, seeded) = match message_service
+                .fork_session(
+                    &agent_name,
+                    &parent_session_id,
+                    history_start,
+                    message_count,
+                    mcp_servers,
+                    workspace.path.clone(),
+                )
+                .await
+            {
+                Ok(forked) => forked,
+                Err(e) => {
+                    log::error!("Failed to fork session {}: {}", parent_session_id, e);
+                    notify_error(window, format!("Failed to fork session: {}", e));
+                    return;
+                }
+            };
+
+            // The fork is a child of the parent's task, or a task of its own
+            let task = match &parent_task {
+                Some(parent) => {
+                    workspace_service
+                        .create_fork_task(
+                            &parent.id,
+                            format!("{} (fork)", parent.name),
+                            session_id.clone(),
+                        )
+                        .await
+                }
+                None => match workspace_service
+                    .create_task(
+                        &workspace.id,
+                        "Forked session".to_string(),
+                        agent_name.clone(),
+                        "Auto".to_string(),
+                    )
+                    .await
+                {
+                    Ok(task) => workspace_service
+                        .set_task_session(&task.id, session_id.clone())
+                        .await
+                        .map(|_| task),
+                    Err(e) => Err(e),
+                },
+            };
+            if let Err(e) = task {
+                log::error!(
+                    "Failed to create the task of forked session {}: {}",
+                    session_id,
+                    e
+                );
+            }
+
+            _ = this.update_in(window, |this, window, cx| {
+                this.show_conversation_panel(Some(session_id.clone()), window, cx);
+            });
+
+            if let Err(e) = seeded.await {
+                log::error!("Failed to seed forked session {}: {}", session_id, e);
+                notify_error(
+                    window,
+                    format!("The fork did not receive the earlier conversation: {}", e),
+                );
+            }
+        })
+        .detach();
+    }
 }
 
 // ============================================================================
@@ -1176,6 +1500,9 @@ pub fn add_agent(action: &AddAgent, cx: &mut App) {
         command: action.command.clone(),
         args: action.args.clone(),
         env: action.env.clone(),
+        transport: action.transport.clone(),
+        client_capabilities: Default::default(),
+        timeouts: Default::default(),
         nodejs_path: None,
     };
 
@@ -1207,20 +1534,30 @@ pub fn update_agent(action: &UpdateAgent, cx: &mut App) {
         command: action.command.clone(),
         args: action.args.clone(),
         env: action.env.clone(),
+        transport: action.transport.clone(),
+        client_capabilities: Default::default(),
+        timeouts: Default::default(),
         nodejs_path: None,
     };
 
     let _ = cx
-        .spawn(
-            async move |_cx| match agent_config_service.update_agent(&name, config).await {
+        .spawn(async move |_cx| {
+            // The settings form does not edit capability overrides or timeouts;
+            // keep the configured ones
+            let mut config = config;
+            if let Some(existing) = agent_config_service.get_agent(&name).await {
+                config.client_capabilities = existing.client_capabilities;
+                config.timeouts = existing.timeouts;
+            }
+            match agent_config_service.update_agent(&name, config).await {
                 Ok(()) => {
                     log::info!("Successfully updated agent: {}", name);
                 }
                 Err(e) => {
                     log::error!("Failed to update agent '{}': {}", name, e);
                 }
-            },
-        )
+            }
+        })
         .detach();
 }
 
@@ -1,13 +1,13 @@
 use anyhow::{Context as _, Result};
 use gpui::*;
-use gpui_component::Root;
 use gpui_component::dock::{DockArea, DockAreaState, DockEvent, DockItem, DockPlacement};
+use gpui_component::{Root, WindowExt, notification::Notification};
 use smol::Timer;
 use std::{sync::Arc, time::Duration};
 
 use crate::{
     AppTitleBar, CodeEditorPanel, ConversationPanel, SessionManagerPanel, TaskPanel, TerminalPanel,
-    panels::dock_panel::DockPanelContainer,
+    core::event_bus::WorkspaceUpdateEvent, panels::dock_panel::DockPanelContainer,
 };
 
 use self::startup::StartupState;
@@ -152,6 +152,10 @@ impl DockWorkspace {
             // })
         });
 
+        Self::subscribe_to_agent_init_failures(window, cx);
+        Self::subscribe_to_prompt_timeouts(window, cx);
+        Self::subscribe_to_session_replacements(window, cx);
+
         Self {
             dock_area,
             title_bar,
@@ -163,6 +167,140 @@ impl DockWorkspace {
         }
     }
 
+    /// Show an error notification with the agent's last stderr lines when it fails to start
+    fn subscribe_to_agent_init_failures(window: &mut Window, cx: &mut Context<Self>) {
+        let workspace_bus = crate::AppState::global(cx).workspace_bus.clone();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(String, String)>();
+        let subscription_id = workspace_bus.subscribe(move |event| {
+            if let WorkspaceUpdateEvent::AgentInitFailed {
+                agent_name,
+                error,
+                stderr_tail,
+            } = event
+            {
+                let mut message = error.clone();
+                if !stderr_tail.is_empty() {
+                    message.push_str("\n\nLast stderr output:\n");
+                    message.push_str(&stderr_tail.join("\n"));
+                }
+                let _ = tx.send((agent_name.clone(), message));
+            }
+        });
+
+        cx.spawn_in(window, async move |_this, window| {
+            while let Some((agent_name, message)) = rx.recv().await {
+                let result = window.update(|window, cx| {
+                    struct AgentInitFailed;
+                    let note = Notification::error(message)
+                        .title(format!("Agent '{}' failed to start", agent_name))
+                        .id1::<AgentInitFailed>(agent_name);
+                    window.push_notification(note, cx);
+                });
+                if result.is_err() {
+                    break;
+                }
+            }
+            workspace_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Warn when the watchdog cancels a prompt turn the agent never finished
+    fn subscribe_to_prompt_timeouts(window: &mut Window, cx: &mut Context<Self>) {
+        let workspace_bus = crate::AppState::global(cx).workspace_bus.clone();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(String, String, String)>();
+        let subscription_id = workspace_bus.subscribe(move |event| {
+            if let WorkspaceUpdateEvent::PromptTimedOut {
+                session_id,
+                agent_name,
+                reason,
+            } = event
+            {
+                let _ = tx.send((session_id.clone(), agent_name.clone(), reason.clone()));
+            }
+        });
+
+        cx.spawn_in(window, async move |_this, window| {
+            while let Some((session_id, agent_name, reason)) = rx.recv().await {
+                let result = window.update(|window, cx| {
+                    struct PromptTimedOut;
+                    let note = Notification::warning(format!(
+                        "The turn of session {} was cancelled: {}.",
+                        session_id, reason
+                    ))
+                    .title(format!("Agent '{}' stopped responding", agent_name))
+                    .id1::<PromptTimedOut>(session_id);
+                    window.push_notification(note, cx);
+                });
+                if result.is_err() {
+                    break;
+                }
+            }
+            workspace_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
+    /// Move tasks over to the sessions that replaced the ones an agent crash
+    /// interrupted, and tell the user the conversation starts afresh
+    fn subscribe_to_session_replacements(window: &mut Window, cx: &mut Context<Self>) {
+        let workspace_bus = crate::AppState::global(cx).workspace_bus.clone();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(String, String, String)>();
+        let subscription_id = workspace_bus.subscribe(move |event| {
+            if let WorkspaceUpdateEvent::SessionReplaced {
+                agent_name,
+                old_session_id,
+                new_session_id,
+            } = event
+            {
+                let _ = tx.send((
+                    agent_name.clone(),
+                    old_session_id.clone(),
+                    new_session_id.clone(),
+                ));
+            }
+        });
+
+        cx.spawn_in(window, async move |_this, window| {
+            while let Some((agent_name, old_session_id, new_session_id)) = rx.recv().await {
+                let result = window.update(|window, cx| {
+                    struct SessionReplaced;
+                    let note = Notification::warning(format!(
+                        "Session {} could not be reopened after the agent restarted; \
+                         its task continues in new session {} without the earlier context.",
+                        old_session_id, new_session_id
+                    ))
+                    .title(format!("Agent '{}' started a new session", agent_name))
+                    .id1::<SessionReplaced>(old_session_id.clone());
+                    window.push_notification(note, cx);
+                    crate::AppState::global(cx).workspace_service().cloned()
+                });
+                let Ok(workspace_service) = result else {
+                    break;
+                };
+
+                if let Some(workspace_service) = workspace_service
+                    && let Some(task) = workspace_service.get_task_by_session(&old_session_id).await
+                    && let Err(e) = workspace_service
+                        .set_task_session(&task.id, new_session_id.clone())
+                        .await
+                {
+                    log::error!(
+                        "Failed to move task {} to session {}: {}",
+                        task.id,
+                        new_session_id,
+                        e
+                    );
+                }
+            }
+            workspace_bus.unsubscribe(subscription_id);
+        })
+        .detach();
+    }
+
     fn save_layout(
         &mut self,
         dock_area: &Entity<DockArea>,
@@ -431,6 +569,7 @@ impl Render for DockWorkspace {
             .on_action(cx.listener(Self::on_action_create_task_from_welcome))
             .on_action(cx.listener(Self::on_action_send_message_to_session))
             .on_action(cx.listener(Self::on_action_cancel_session))
+            .on_action(cx.listener(Self::on_action_fork_session))
             .on_action(cx.listener(Self::on_action_open))
             .relative()
             .size_full()